@@ -6,16 +6,30 @@
 //!
 //! This contract implements a non-custodial vault where users deposit USDC and an AI agent
 //! automatically deploys those funds across various yield-generating protocols on the Stellar
-//! blockchain. The vault maintains 1:1 parity between deposited assets and user balances.
+//! blockchain. Deposits mint vault shares representing a proportional claim on total assets.
 //!
 //! ## Share Accounting Model
 //!
-//! Currently, this implementation uses a simple 1:1 asset accounting model where:
-//! - 1 deposited USDC = 1 vault share
-//! - Withdrawals return the exact USDC amount deposited (plus any yield earned externally)
+//! The vault uses ERC-4626 style share accounting:
+//! - `shares = assets * total_shares / total_assets` (rounded down on deposit)
+//! - `assets = shares * total_assets / total_shares` (rounded down on read)
+//! - The first deposit into an empty vault mints shares 1:1
+//! - Withdrawals burn shares rounded up so the vault is never shortchanged
+//! - Deposits in the same transaction or ledger are priced sequentially, each
+//!   against the totals left by the previous one
+//! - Conversions whose intermediate product exceeds `i128` panic with
+//!   "Arithmetic overflow" rather than wrapping
+//! - Conversions add `VIRTUAL_ASSETS` and the matching virtual shares to the
+//!   totals, as if a small position nobody can redeem were always held. This
+//!   blunts the first-depositor inflation attack: pumping the price of a
+//!   tiny share supply hands most of the gain to the virtual shares, and a
+//!   depositor's rounding loss stays a sliver of the deposit. The whole
+//!   supply still redeems every asset the vault holds. Tokens sent
+//!   straight to the vault do not move the price, since total assets change
+//!   only through deposits, exits and reported updates.
 //!
-//! In Phase 2, this will be upgraded to a proper share-based system using the ERC-4626
-//! standard, where vault shares represent a proportional claim on total vault assets.
+//! Yield reported through `update_total_assets()` raises the share price, so every
+//! holder's balance grows in proportion to their shares.
 //!
 //! ## Asset Flow
 //!
@@ -43,15 +57,95 @@
 //! ### Instance Storage (Contract-Wide, Expensive to Read/Write)
 //! - `Agent`: The authorized AI agent address that can call rebalance()
 //! - `UsdcToken`: The USDC token contract address
-//! - `TotalDeposits`: Total USDC held in vault (excluding yield deployed externally)
+//! - `TotalDeposits`: Total assets under management (deposits plus reported yield)
+//! - `TotalShares`: Total vault shares outstanding
 //! - `Paused`: Boolean flag for emergency pause state
 //! - `Owner`: Contract owner address for administrative functions
 //! - `TvlCap`: Maximum total value locked in the vault
 //! - `UserDepositCap`: Maximum deposit per user
 //! - `Version`: Contract version for upgrade tracking
+//! - `MinSharePrice` / `MaxSharePrice`: Optional share price sanity band
+//! - `SnapshotCount`: Number of snapshots taken (latest snapshot id)
+//! - `DepositorCount` / `MaxDepositors`: Live depositor count and optional cap
+//! - `WindDown`: Irreversible wind-down flag blocking new deposits
+//! - `PriceOracle` / `MaxOracleAge`: Oracle valuing non-USDC deposits and its staleness limit
+//! - `Locked`: Reentrancy guard held across oracle calls
+//! - `WithdrawalCooldown` / `NextRequestId` / `TotalPendingShares`: Withdrawal queue config and totals
+//! - `DepositCooldownSeconds`: Optional minimum time between a user's actions and their next deposit
+//! - `FeeHolidayUntil`: Timestamp until which fees are waived
+//! - `Strategies` / `TotalDeployed`: Registered strategies and USDC deployed to them
+//! - `TotalCostBasis`: Sum of all users' cost bases
+//! - `Underwater`: Set while total assets are below the aggregate cost basis
+//! - `AssetsUpdateCooldown` / `MaxAssetsDeltaBps` / `LastAssetsUpdate`: Rate limits on total asset reports
+//! - `DualEmit`: Publish legacy event shapes alongside current ones during indexer migration
+//! - `MaxSlippageBps`: Tolerance for quoted-price deposits and withdrawals
+//! - `SwapRouter` / `NativeToken`: Router and XLM asset for native deposits
+//! - `InitialShareRatio`: Shares per USDC minted by the first deposit
+//! - `MaxWithdrawPerTx`: Optional cap on USDC removed by a single withdrawal
+//! - `AgentPermissions`: Bitmask of operations the agent may perform
+//! - `ShareDecimals`: Decimal places of the share unit
+//! - `Owners` / `Threshold`: Multi-sig signers and approvals required for critical actions
+//! - `AssetsOracle` / `AssetsOracleToleranceBps`: Independent check on total asset reports
+//! - `FeatureFlags`: Bitmask of optional features currently enabled
+//! - `ReserveStrategy` / `ReserveBufferBps` / `ReserveDeployed`: Money market parking idle reserves
+//! - `ReserveTargetBps`: On-chain liquidity below which withdrawals signal the agent to top up
+//! - `AccrualDelay`: Window in which fresh deposits do not share in reported gains
+//! - `Pauser`: Optional address that may pause, but not unpause, the vault
+//! - `DustShareThreshold`: Remainders below this many shares are swept into withdrawals
+//! - `MaxDepositPerLedger` / `LedgerDeposits`: Optional cap on USDC deposited per ledger
+//! - `PriceStepBps` / `LastPriceMilestone`: Share price step that triggers milestone events
+//! - `AutoUnpauseWindow` / `StableSince`: Loss-free period after which a pause lifts itself
+//! - `PendingOwner`: Address nominated by `transfer_ownership()` that has yet to accept
+//! - `Guardian` / `RecoveryAddress` / `RecoveryDelay` / `PendingRecovery`: Init-time
+//!   emergency recovery of vault funds to a fixed safe address
+//! - `PauseGraceSeconds`: Window after a deposit in which the user may still withdraw while paused
+//! - `FavorableRoundingThreshold` / `FavorableRoundingBudget`: Small withdrawals rounded in
+//!   the user's favor, up to a remaining budget of shares
+//! - `DriftCheck`: Cross-check total shares against a running checksum on read
+//! - `MaxStrategies`: Optional cap on the number of registered strategies
+//! - `RebalancePaused`: Freezes strategy movement and asset reports while user flows stay open
+//! - `ConfigHistory`: The last `MAX_CONFIG_HISTORY` configuration changes (persistent)
+//! - `ManagementFeeBps` / `LastFeeAccrual`: Annual fee on assets, paid to the fee recipient in shares
+//! - `PerformanceFeeBps` / `HighWaterMark`: Fee on share price gains above the previous peak
+//! - `FeeRecipient`: Address fees are minted to, defaulting to the owner
+//! - `WithdrawalFeeBps`: Fee on withdrawals, left in the vault for remaining holders
+//! - `FeesCollected`: Cumulative fees collected, by fee type
+//! - `ReserveAttestation`: Agent's latest report of USDC deployed outside the vault
+//!
+//! Owner-set configuration keys live in `ConfigKey`, per-user position
+//! metadata added after `DataKey` filled up in `PositionKey`, and all other
+//! keys in `DataKey`.
 //!
 //! ### Persistent Storage (Per-User, Cheaper)
-//! - `Balance(user)`: USDC balance for each user address
+//! - `Shares(user)`: Vault shares held by each user address
+//! - `Balance(user)`: Deprecated 1:1 USDC balance, superseded by `Shares(user)`
+//! - `ShareCheckpoints(user)`: Share balance history used for snapshot lookups
+//! - `Snapshot(id)`: Vault totals recorded at each snapshot
+//! - `Airdrops(token)` / `AirdropClaimed(token, user)` / `AirdropReserved(token)`:
+//!   Reward token airdrop rounds and claim tracking
+//! - `WithdrawalRequest(id)` / `PendingRequests`: Queued withdrawals and their ids
+//! - `PendingShares(user)`: Shares the user has escrowed in pending withdrawal requests
+//! - `AutoCompound(user)`: Opt-in to have withdrawal fees re-minted to the user as shares
+//! - `LastAction(user)`: Timestamp of the user's last deposit or withdrawal
+//! - `Advisor(client, advisor)`: Client-granted read access for an advisor
+//! - `StrategyAllocation(strategy)`: USDC deployed to a single strategy
+//! - `StrategyRealizedYield(strategy)`: Reported gains attributed to a single strategy
+//! - `LastDeposit(user)`: Timestamp of the user's last deposit, kept while a pause grace is set
+//! - `Boosts`: Time-locked deposits earning a multiplied share of yield
+//! - `ShareChecksum`: Running sum of user share balances, kept while the drift check is on
+//! - `HoldingSince(user)`: Cost-basis-weighted average time the user's USDC went in
+//! - `Drawdown(user)`: Peak share price seen while holding and the worst fall from it
+//! - `CostBasis(user)`: USDC paid in for the user's current shares
+//! - `ProtectedPrincipal(user)`: USDC deposited minus USDC withdrawn, floored at zero
+//! - `ShareVelocity(user)`: Lifetime shares minted to and burned from a user
+//! - `RealizedPnl(user)`: Gain or loss realized on the user's withdrawals
+//! - `PositionLayout` / `DepositorIndex(version)`: Layout version of the position index and
+//!   the addresses currently holding shares under it (`Depositors` before versioning)
+//! - `PreRegistrations`: Depositor slots reserved ahead of a first deposit
+//! - `RecentDeposits`: Shares minted inside the accrual delay window
+//! - `LockedShares(user, locker)` / `UserLockedShares(user)`: Shares pledged as collateral
+//! - `ActionApprovals(hash)`: Signers that approved a pending critical action
+//! - `AssetsHistory`: The last `MAX_HISTORY_SAMPLES` total asset reports
 //!
 //! ## Event Design Philosophy
 //!
@@ -88,7 +182,8 @@
 #![no_std]
 
 use soroban_sdk::{
-    contract, contractimpl, contracttype, symbol_short, token, Address, Env, Symbol,
+    contract, contractclient, contractimpl, contracttype, symbol_short, token, xdr::ToXdr, Address,
+    Bytes, BytesN, Env, IntoVal, Symbol, Val, Vec,
 };
 
 // ============================================================================
@@ -102,10 +197,10 @@ use soroban_sdk::{
 /// storage is used for per-user data that requires efficient access.
 #[contracttype]
 pub enum DataKey {
-    /// User's USDC balance (key: user Address)
-    /// Stored in persistent storage for efficient per-user access
+    /// Deprecated: user's 1:1 USDC balance from the pre-share accounting model
+    /// Superseded by `Shares(Address)`; no longer written
     Balance(Address),
-    /// Total USDC deposits in the vault
+    /// Total assets under management (deposits plus reported yield)
     /// Stored in instance storage (single value, frequently read)
     TotalDeposits,
     /// Authorized AI agent address
@@ -128,8 +223,722 @@ pub enum DataKey {
     UserDepositCap,
     /// Contract version for upgrade tracking
     Version,
+    /// User's vault shares (key: user Address)
+    /// Stored in persistent storage for efficient per-user access
+    Shares(Address),
+    /// Total vault shares outstanding
+    TotalShares,
+    /// Number of snapshots taken so far (latest snapshot id)
+    SnapshotCount,
+    /// Vault totals recorded at a snapshot (key: snapshot id)
+    Snapshot(u32),
+    /// History of a user's share balance, tagged by snapshot epoch
+    ShareCheckpoints(Address),
+    /// Airdrop rounds distributed for a reward token (key: token Address)
+    Airdrops(Address),
+    /// Number of airdrop rounds a user has claimed (key: token, user)
+    AirdropClaimed(Address, Address),
+    /// Reward token amount distributed but not yet claimed (key: token Address)
+    AirdropReserved(Address),
+    /// Number of users currently holding a non-zero share balance
+    DepositorCount,
+    /// Wind-down state; when true, new deposits are rejected
+    /// and the owner can force-withdraw remaining positions
+    WindDown,
+    /// Reentrancy guard held during cross-contract oracle calls
+    Locked,
+    /// Id assigned to the next queued withdrawal request
+    NextRequestId,
+    /// A queued withdrawal request (key: request id)
+    WithdrawalRequest(u64),
+    /// Ids of all pending withdrawal requests, in request order
+    PendingRequests,
+    /// Total shares escrowed in pending withdrawal requests
+    TotalPendingShares,
+    /// Ledger timestamp of a user's last deposit or withdrawal (key: user Address)
+    LastAction(Address),
+    /// Whether an advisor may read a client's position (key: client, advisor)
+    Advisor(Address, Address),
+    /// Strategy addresses the agent may deploy funds to
+    Strategies,
+    /// USDC deployed to a strategy (key: strategy Address)
+    StrategyAllocation(Address),
+    /// Total USDC deployed across all strategies
+    TotalDeployed,
+    /// USDC a user has paid in for their current shares (key: user Address)
+    CostBasis(Address),
+    /// Sum of all users' cost bases
+    TotalCostBasis,
+    /// Whether total assets are below the aggregate cost basis
+    Underwater,
+    /// Ledger timestamp of the last `update_total_assets` call
+    LastAssetsUpdate,
+    /// Lifetime `(minted, burned)` shares of a user (key: user Address)
+    ShareVelocity(Address),
+    /// Agent's latest attestation of USDC held in external strategies
+    ReserveAttestation,
+    /// Addresses currently holding a non-zero share balance
+    Depositors,
+    /// Signers that approved a pending critical action (key: action hash)
+    ActionApprovals(BytesN<32>),
+    /// Recent `update_total_assets` samples, oldest first
+    AssetsHistory,
+    /// Shares a user has locked to a locker (key: user, locker)
+    LockedShares(Address, Address),
+    /// Total shares a user has locked across all lockers (key: user Address)
+    UserLockedShares(Address),
+    /// Cumulative gain or loss a user has realized on withdrawals (key: user Address)
+    RealizedPnl(Address),
+    /// Depositor slots reserved for users who have not deposited yet
+    PreRegistrations,
+    /// USDC currently parked in the reserve strategy
+    ReserveDeployed,
+    /// Deposits still inside the accrual delay window
+    RecentDeposits,
+    /// Ledger sequence and USDC deposited in it so far
+    LedgerDeposits,
+    /// Share price at the last emitted price milestone
+    LastPriceMilestone,
+    /// USDC a user has deposited net of withdrawals, floored at zero (key: user Address)
+    ProtectedPrincipal(Address),
+    /// Ledger timestamp at which a guardian recovery in progress may execute
+    PendingRecovery,
+    /// Cumulative reported gains attributed to a strategy (key: strategy Address)
+    StrategyRealizedYield(Address),
+    /// Ledger timestamp since which a pause has seen no loss; absent unless
+    /// the pause may lift automatically
+    StableSince,
+}
+
+/// Storage keys for owner-set vault configuration.
+///
+/// Split out of `DataKey` to stay within the contract spec's limit on enum
+/// cases. All values live in instance storage. Keys are encoded by variant
+/// name only, so moving a variant between these enums keeps its stored value.
+#[contracttype]
+pub enum ConfigKey {
+    /// Minimum sane share price (scaled by `SHARE_PRICE_SCALE`), 0 disables
+    MinSharePrice,
+    /// Maximum sane share price (scaled by `SHARE_PRICE_SCALE`), 0 disables
+    MaxSharePrice,
+    /// Maximum number of distinct depositors, 0 disables
+    MaxDepositors,
+    /// Price oracle used to value non-USDC deposits
+    PriceOracle,
+    /// Maximum accepted age of an oracle price in seconds
+    MaxOracleAge,
+    /// Seconds a queued withdrawal must wait before it can be claimed
+    WithdrawalCooldown,
+    /// Minimum seconds between a user's last action and their next deposit, 0 disables
+    DepositCooldownSeconds,
+    /// Timestamp until which all vault fees are waived
+    FeeHolidayUntil,
+    /// Minimum seconds between `update_total_assets` calls, 0 disables
+    AssetsUpdateCooldown,
+    /// Maximum change per `update_total_assets` call in basis points, 0 disables
+    MaxAssetsDeltaBps,
+    /// Whether legacy deposit/withdraw event shapes are also published
+    DualEmit,
+    /// Maximum share price deviation from a caller's quote, in basis points
+    MaxSlippageBps,
+    /// DEX router used to swap native XLM deposits into USDC
+    SwapRouter,
+    /// Stellar asset contract address of native XLM
+    NativeToken,
+    /// Shares minted per USDC on the first deposit (scaled by `SHARE_PRICE_SCALE`)
+    InitialShareRatio,
+    /// Maximum USDC a single `withdraw` may remove, 0 disables
+    MaxWithdrawPerTx,
+    /// Bitmask of `PERM_*` operations the agent may perform
+    AgentPermissions,
+    /// Decimal places of the share unit, at least `USDC_DECIMALS`
+    ShareDecimals,
+    /// Signers whose approvals authorize critical owner actions
+    Owners,
+    /// Approvals required to execute a critical action, 0 disables multi-sig
+    Threshold,
+    /// Oracle reporting an independent total assets figure for the vault
+    AssetsOracle,
+    /// Maximum deviation of a total assets report from the oracle, in basis points
+    AssetsOracleToleranceBps,
+    /// Bitmask of `FEATURE_*` optional behaviors currently enabled
+    FeatureFlags,
+    /// Money market that idle on-chain reserves are parked in
+    ReserveStrategy,
+    /// Share of the on-chain reserve kept liquid in the vault, in basis points
+    ReserveBufferBps,
+    /// Seconds after a deposit during which it is excluded from yield gains, 0 disables
+    AccrualDelay,
+    /// Address allowed to pause the vault alongside the owner
+    Pauser,
+    /// Share balance below which a withdrawal's remainder is swept into it, 0 disables
+    DustShareThreshold,
+    /// Maximum USDC deposited within a single ledger, 0 disables
+    MaxDepositPerLedger,
+    /// Share price move, in basis points of the last milestone, that emits a milestone event
+    PriceStepBps,
+    /// Loss-free seconds after a `pause()` before anyone may lift it, 0 disables
+    AutoUnpauseWindow,
+    /// Address allowed to start an emergency recovery, fixed at initialization
+    Guardian,
+    /// Safe address that recovered funds are sent to, fixed at initialization
+    RecoveryAddress,
+    /// Seconds between starting and executing a recovery, fixed at initialization
+    RecoveryDelay,
+    /// Seconds after a deposit during which the user may withdraw while paused, 0 disables
+    PauseGraceSeconds,
+    /// USDC amount below which withdrawals round shares in the user's favor, 0 disables
+    FavorableRoundingThreshold,
+    /// Shares the vault may still forgo through favorable rounding
+    FavorableRoundingBudget,
+    /// Whether `get_total_shares` cross-checks the running share checksum
+    DriftCheck,
+    /// Maximum number of registered strategies, 0 means no cap
+    MaxStrategies,
+    /// Whether rebalancing and asset reports are paused
+    RebalancePaused,
+    /// Recent configuration changes, oldest first (persistent storage)
+    ConfigHistory,
+    /// Annual management fee on total assets in basis points, 0 disables
+    ManagementFeeBps,
+    /// Ledger timestamp up to which the management fee has been accrued
+    LastFeeAccrual,
+    /// Cumulative fees collected, by fee type
+    FeesCollected,
+    /// Share of total assets the agent should keep on-chain, in basis
+    /// points; 0 disables `ReserveLowEvent`
+    ReserveTargetBps,
+    /// Performance fee on share price gains above the high-water mark, in
+    /// basis points
+    PerformanceFeeBps,
+    /// Highest share price (scaled by `SHARE_PRICE_SCALE`) fees have been
+    /// settled at
+    HighWaterMark,
+    /// Address management and performance fee shares are minted to
+    FeeRecipient,
+    /// Fee deducted from voluntary exit payouts and kept in the vault, in
+    /// basis points
+    WithdrawalFeeBps,
+    /// Address nominated as the next owner, pending its `accept_ownership()`
+    PendingOwner,
+}
+
+/// Storage keys for position metadata.
+///
+/// Split out of `DataKey` for the same enum case limit as `ConfigKey`.
+/// All values live in persistent storage.
+#[contracttype]
+pub enum PositionKey {
+    /// Ledger timestamp of a user's last deposit, recorded while
+    /// `PauseGraceSeconds` is set (key: user Address)
+    LastDeposit(Address),
+    /// Boosted deposits whose lock may still be running
+    Boosts,
+    /// Sum of all users' share balances, maintained while `DriftCheck` is on
+    ShareChecksum,
+    /// Average ledger timestamp of a user's deposits, weighted by the cost
+    /// basis each added (key: user Address)
+    HoldingSince(Address),
+    /// Peak share price seen while a user held shares and their worst
+    /// drawdown from it (key: user Address)
+    Drawdown(Address),
+    /// ed25519 public key that signs a user's deposit permits (key: user Address)
+    PermitKey(Address),
+    /// Number of deposit permits a user has consumed (key: user Address)
+    PermitNonce(Address),
+    /// Address allowed to withdraw to the user on their behalf (key: user Address)
+    WithdrawManager(Address),
+    /// Shares a user has escrowed in pending withdrawal requests (key: user Address)
+    PendingShares(Address),
+    /// Set while a user has opted to have withdrawal fees re-minted to them
+    /// as shares (key: user Address)
+    AutoCompound(Address),
+    /// Layout version of the position index in storage; absent for vaults
+    /// that predate it, which use the unversioned `DataKey::Depositors`
+    PositionLayout,
+    /// Addresses currently holding a non-zero share balance, written by
+    /// layout `version` (key: layout version)
+    DepositorIndex(u32),
+}
+
+/// How `process_deposit` takes delivery of the tokens backing a deposit.
+enum DepositFunding {
+    /// USDC transferred by the authorizing user
+    Transfer,
+    /// USDC pulled against the vault's allowance from the user
+    Allowance,
+    /// A non-USDC token and amount transferred by the authorizing user
+    Asset(Address, i128),
+    /// USDC the vault already holds, e.g. swap proceeds
+    Received,
+}
+
+/// Vault totals recorded when a snapshot is taken.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VaultSnapshot {
+    /// Total assets under management at the snapshot
+    pub total_assets: i128,
+    /// Total shares outstanding at the snapshot
+    pub total_shares: i128,
+    /// Ledger timestamp of the snapshot
+    pub timestamp: u64,
+}
+
+/// Vault totals recorded after an `update_total_assets` call.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AssetsSample {
+    pub timestamp: u64,
+    pub total_assets: i128,
+    pub total_shares: i128,
+    /// Share price after the update, scaled by `SHARE_PRICE_SCALE`
+    pub share_price: i128,
+}
+
+/// A configuration change recorded in the on-chain changelog.
+///
+/// Address-valued changes such as `update_agent` record 0 for both values
+/// and carry the addresses in `old_address` and `new_address` instead.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConfigChange {
+    /// The configuration key that changed (e.g. "max_dep")
+    pub key: Symbol,
+    pub old: i128,
+    pub new: i128,
+    pub timestamp: u64,
+    /// The owner at the time of the change
+    pub actor: Address,
+    /// The address replaced, for address-valued keys (`None` if it was unset)
+    pub old_address: Option<Address>,
+    /// The address set, for address-valued keys (`None` if it was cleared)
+    pub new_address: Option<Address>,
+}
+
+/// A user's share balance as of a snapshot epoch and ledger.
+///
+/// `epoch` is the snapshot count when the balance was written, so the
+/// balance at snapshot `id` is the latest checkpoint with `epoch < id`.
+/// `ledger` is the ledger sequence of the write, so the balance before
+/// ledger `n` is the latest checkpoint with `ledger < n`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ShareCheckpoint {
+    pub epoch: u32,
+    pub ledger: u32,
+    pub shares: i128,
+}
+
+/// A single airdrop of a reward token, claimable against a snapshot.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AirdropRound {
+    /// Snapshot whose share balances determine each holder's portion
+    pub snapshot_id: u32,
+    /// Total reward tokens distributed in this round
+    pub total_amount: i128,
+    /// Shares held outside withdrawal-queue escrow at the snapshot, which
+    /// split the round; escrowed shares are not checkpointed to any holder
+    pub eligible_shares: i128,
+}
+
+/// A price quote returned by the price oracle.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PriceData {
+    /// USDC units per asset unit, scaled by `ORACLE_PRICE_SCALE`
+    pub price: i128,
+    /// Ledger timestamp at which the price was published
+    pub timestamp: u64,
+}
+
+/// A queued withdrawal awaiting its cooldown.
+///
+/// Escrowed shares stay in `TotalShares` until claimed, so they keep
+/// earning yield and are paid out at the share price at claim time.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WithdrawalRequest {
+    pub id: u64,
+    pub user: Address,
+    pub shares: i128,
+    /// Ledger timestamp from which the request can be claimed
+    pub unlock_time: u64,
+    /// Cost basis removed from the user's position when the request was queued
+    pub cost_basis: i128,
+}
+
+/// A user's profit and loss, returned by `get_pnl()`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Pnl {
+    /// Withdrawal payouts minus the cost basis of the shares withdrawn
+    pub realized: i128,
+    /// Current value of held shares minus their cost basis
+    pub unrealized: i128,
+}
+
+/// A user's share price high-water mark and worst drawdown from it.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Drawdown {
+    /// Highest share price recorded while the user held shares
+    pub peak_price: i128,
+    /// Largest recorded fall from the peak, in basis points
+    pub max_drawdown_bps: i128,
+}
+
+/// A user's position split by how soon it can be withdrawn.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Withdrawable {
+    /// USDC value of shares that can be withdrawn immediately
+    pub liquid_assets: i128,
+    /// USDC value of shares locked as collateral
+    pub locked_assets: i128,
+    /// USDC value of shares escrowed in the user's pending withdrawal requests
+    pub queued_assets: i128,
+}
+
+/// Estimated resource profile of a vault operation, returned by `operation_cost_hint()`.
+///
+/// Counts distinct vault ledger entries in the operation's footprint; all
+/// instance storage shares one entry. Entries touched by called contracts
+/// (e.g. token balances) and auth nonces are not included.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OperationCost {
+    /// Vault ledger entries in the footprint, read-only or read-write
+    pub ledger_reads: u32,
+    /// Vault ledger entries in the read-write footprint
+    pub ledger_writes: u32,
+    /// Whether the operation calls another contract
+    pub cross_contract: bool,
+}
+
+/// Shares a user minted inside the accrual delay window.
+///
+/// Repeat deposits by the same user merge into one entry stamped with the
+/// latest deposit time.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RecentDeposit {
+    pub user: Address,
+    pub shares: i128,
+    /// Ledger timestamp of the user's latest deposit
+    pub timestamp: u64,
+}
+
+/// Receipt returned by `redeem_to_strategy()`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StrategyRedemption {
+    /// The partner strategy that received the USDC
+    pub strategy: Address,
+    /// Vault shares burned from the user
+    pub shares: i128,
+    /// USDC transferred to the strategy (7 decimal places)
+    pub amount: i128,
+    /// Position credited to the user by the strategy, in its own units
+    pub strategy_units: i128,
+}
+
+/// A time-locked deposit earning a multiplied share of future yield.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Boost {
+    pub user: Address,
+    /// Shares locked under the boost, including bonus shares it has earned
+    pub shares: i128,
+    /// Yield multiplier scaled by `BOOST_SCALE`
+    pub multiplier: i128,
+    /// Ledger timestamp at which the lock and the boost end
+    pub unlock_time: u64,
 }
 
+/// A depositor slot reserved for a user ahead of their first deposit.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PreRegistration {
+    pub user: Address,
+    /// Ledger timestamp after which the slot is released
+    pub expires_at: u64,
+}
+
+/// A user's position in the vault.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UserData {
+    pub shares: i128,
+    /// Current USDC value of `shares` (7 decimal places)
+    pub balance: i128,
+}
+
+/// Every configuration value of the vault, returned by `get_config()`.
+///
+/// Lets operators verify a deployment against its intended setup in one call.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VaultConfigView {
+    pub agent: Address,
+    pub owner: Address,
+    pub usdc_token: Address,
+    pub tvl_cap: i128,
+    pub user_cap: i128,
+    pub min_deposit: i128,
+    pub paused: bool,
+    pub version: u32,
+    /// Annual management fee in basis points
+    pub management_fee_bps: i128,
+    /// Performance fee on gains above the high-water mark, in basis points
+    pub performance_fee_bps: i128,
+    /// Fee on withdrawals in basis points
+    pub withdrawal_fee_bps: i128,
+}
+
+/// Cumulative fees collected by the vault, returned by `get_fees_collected()`.
+///
+/// Each counter is the USDC value of the fee when it was collected.
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct FeesCollected {
+    pub deposit: i128,
+    pub withdrawal: i128,
+    pub management: i128,
+    pub performance: i128,
+}
+
+/// Aggregate vault figures, part of `VaultSummary`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VaultStats {
+    pub total_assets: i128,
+    pub total_shares: i128,
+    /// USDC per whole share (scaled by `SHARE_PRICE_SCALE`)
+    pub share_price: i128,
+    pub depositor_count: u32,
+    /// USDC deployed to registered strategies
+    pub total_deployed: i128,
+    /// USDC parked in the reserve strategy
+    pub reserve_deployed: i128,
+    pub pending_withdrawal_shares: i128,
+    pub pending_withdrawal_assets: i128,
+}
+
+/// Health indicators of the vault, part of `VaultSummary`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VaultHealth {
+    pub backing_ratio_bps: i128,
+    pub underwater: bool,
+    pub winding_down: bool,
+    /// Seconds since the last `update_total_assets`, `u64::MAX` if never
+    pub seconds_since_yield: u64,
+}
+
+/// Configuration, figures and health of the vault, returned by `get_summary()`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VaultSummary {
+    pub config: VaultConfigView,
+    pub stats: VaultStats,
+    pub health: VaultHealth,
+}
+
+// ============================================================================
+// EXTERNAL INTERFACES
+// ============================================================================
+
+/// Price oracle interface used to value non-USDC deposits.
+#[contractclient(name = "PriceOracleClient")]
+pub trait PriceOracle {
+    /// Returns the latest USDC price of `asset`.
+    fn price(env: Env, asset: Address) -> PriceData;
+}
+
+/// TVL oracle interface used to cross-check reported total assets.
+#[contractclient(name = "AssetsOracleClient")]
+pub trait AssetsOracle {
+    /// Returns an independent figure for the total assets of `vault`, in raw USDC units.
+    fn total_assets(env: Env, vault: Address) -> i128;
+}
+
+/// Money market interface used to earn interest on the idle on-chain reserve.
+///
+/// The vault transfers `amount` of USDC to the market before calling
+/// `supply`; `redeem` must send exactly `amount` of USDC back to `to`.
+#[contractclient(name = "MoneyMarketClient")]
+pub trait MoneyMarket {
+    /// Credits `from` with `amount` of USDC already held by the market.
+    fn supply(env: Env, from: Address, amount: i128);
+    /// Returns `amount` of USDC previously supplied by the caller to `to`.
+    fn redeem(env: Env, to: Address, amount: i128);
+}
+
+/// Partner strategy interface used to roll vault positions into a strategy.
+///
+/// The vault transfers `amount` of USDC to the strategy before calling
+/// `deposit_for`; the strategy must credit the position to `beneficiary`.
+#[contractclient(name = "PartnerStrategyClient")]
+pub trait PartnerStrategy {
+    /// Credits `beneficiary` with `amount` of USDC already held by the strategy.
+    /// Returns the position credited, in the strategy's own units.
+    fn deposit_for(env: Env, beneficiary: Address, amount: i128) -> i128;
+}
+
+/// DEX router interface used to convert native XLM deposits into USDC.
+///
+/// The vault transfers `amount_in` of `token_in` to the router before
+/// calling `swap_exact_in`; the router must send at least `min_out` of
+/// `token_out` to `to`.
+#[contractclient(name = "SwapRouterClient")]
+pub trait SwapRouter {
+    /// Swaps `amount_in` of `token_in` already held by the router for `token_out`.
+    fn swap_exact_in(
+        env: Env,
+        token_in: Address,
+        token_out: Address,
+        amount_in: i128,
+        min_out: i128,
+        to: Address,
+    ) -> i128;
+}
+
+/// The agent's latest report of USDC held outside the vault.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReserveAttestation {
+    /// USDC deployed to external strategies (7 decimal places)
+    pub amount: i128,
+    /// Ledger timestamp of the attestation
+    pub timestamp: u64,
+}
+
+/// Verifiable reserves of the vault, returned by `proof_of_reserves()`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProofOfReserves {
+    /// USDC held by the vault contract, read from the token
+    pub on_chain_balance: i128,
+    /// USDC the agent attests is deployed to strategies
+    pub attested_deployed: i128,
+    /// Ledger timestamp of the attestation, 0 if none was made
+    pub attested_at: u64,
+    /// Total assets reported by the vault
+    pub total_assets: i128,
+    /// USDC parked in the reserve strategy
+    pub reserve_deployed: i128,
+    /// Whether `on_chain_balance + reserve_deployed + attested_deployed == total_assets`
+    pub reconciled: bool,
+}
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Fixed-point scale for share prices (7 decimals, matching USDC).
+///
+/// A share price of `SHARE_PRICE_SCALE` means 1 share is worth 1 USDC unit.
+pub const SHARE_PRICE_SCALE: i128 = 10_000_000;
+
+/// Decimal places of the USDC asset.
+pub const USDC_DECIMALS: u32 = 7;
+
+/// Largest supported number of share decimal places.
+pub const MAX_SHARE_DECIMALS: u32 = 18;
+
+/// Layout version of the position index this code reads and writes.
+///
+/// A release that changes how positions are indexed bumps this and teaches
+/// `migrate_positions` to read the previous layout; existing vaults then
+/// re-index once after the upgrade.
+pub const POSITION_LAYOUT_VERSION: u32 = 1;
+
+/// Fixed-point scale for oracle prices (7 decimals).
+///
+/// `usdc_value = amount * price / ORACLE_PRICE_SCALE`.
+pub const ORACLE_PRICE_SCALE: i128 = 10_000_000;
+
+/// Agent permission: emit rebalance signals via `rebalance()`.
+pub const PERM_REBALANCE: u32 = 1 << 0;
+
+/// Agent permission: move USDC into strategies via `rebalance_funds()`.
+pub const PERM_DEPLOY_FUNDS: u32 = 1 << 1;
+
+/// Agent permission: settle queued withdrawals via `process_withdrawals()`.
+pub const PERM_PROCESS_WITHDRAWALS: u32 = 1 << 2;
+
+/// Every agent permission; the default granted by `initialize()`.
+pub const ALL_AGENT_PERMISSIONS: u32 =
+    PERM_REBALANCE | PERM_DEPLOY_FUNDS | PERM_PROCESS_WITHDRAWALS;
+
+/// Feature flag: users may pledge shares via `lock_shares()`.
+pub const FEATURE_LOCKUPS: u32 = 1 << 0;
+
+/// Feature flag: holders may move shares via `split_position()`.
+pub const FEATURE_SPLITS: u32 = 1 << 1;
+
+/// Feature flag: the owner may reserve depositor slots via `pre_register()`.
+pub const FEATURE_PRE_REGISTRATION: u32 = 1 << 2;
+
+/// Every feature flag; the default before any flags are configured.
+pub const ALL_FEATURES: u32 = FEATURE_LOCKUPS | FEATURE_SPLITS | FEATURE_PRE_REGISTRATION;
+
+/// Maximum number of entries accepted by batch read functions.
+pub const MAX_BATCH_SIZE: u32 = 50;
+
+/// Maximum number of withdrawal requests pending at once.
+///
+/// Bounds the `PendingRequests` list every queue operation reads.
+pub const MAX_PENDING_REQUESTS: u32 = 200;
+
+/// Number of `update_total_assets` samples kept in `AssetsHistory`.
+pub const MAX_HISTORY_SAMPLES: u32 = 32;
+
+/// Number of configuration changes kept in `ConfigHistory`.
+pub const MAX_CONFIG_HISTORY: u32 = 32;
+
+/// Highest annual management fee the owner may set, in basis points.
+pub const MAX_MANAGEMENT_FEE_BPS: i128 = 500;
+
+/// Highest performance fee the owner may set, in basis points.
+pub const MAX_PERFORMANCE_FEE_BPS: i128 = 3_000;
+
+/// Highest withdrawal fee the owner may set, in basis points.
+pub const MAX_WITHDRAWAL_FEE_BPS: i128 = 100;
+
+/// Virtual assets added to total assets in every conversion, in raw USDC
+/// units (0.001 USDC).
+///
+/// Conversions also add the shares these assets are worth at the initial
+/// share ratio, so a vault at its initial price converts exactly as before.
+/// A depositor's rounding loss is at most one share's value, and a price
+/// pump is shared with the virtual shares, so an attacker holding a tiny
+/// supply must burn roughly this many times the loss they inflict.
+pub const VIRTUAL_ASSETS: i128 = 10_000;
+
+/// Seconds in a 365-day year, used to annualize returns.
+pub const SECONDS_PER_YEAR: u64 = 31_536_000;
+
+/// Fixed-point scale for boost multipliers: `BOOST_SCALE` is a 1x share of yield.
+pub const BOOST_SCALE: i128 = 10_000;
+
+/// Multiplier earned by the longest boost lock (2x).
+pub const MAX_BOOST_MULTIPLIER: i128 = 20_000;
+
+/// Lock duration that earns `MAX_BOOST_MULTIPLIER`; longer locks earn no more.
+pub const MAX_BOOST_LOCK: u64 = SECONDS_PER_YEAR;
+
+/// Seconds a pre-registered depositor slot stays reserved (7 days).
+pub const PRE_REGISTRATION_TTL: u64 = 604_800;
+
+/// Minimum deposit: 1 USDC (7 decimal places).
+pub const MIN_DEPOSIT: i128 = 1_000_000;
+
+/// Default maximum oracle price age: 1 hour.
+pub const DEFAULT_MAX_ORACLE_AGE: u64 = 3_600;
+
 // ============================================================================
 // EVENTS
 // ============================================================================
@@ -147,6 +956,29 @@ pub struct DepositEvent {
     pub user: Address,
     /// Amount of USDC deposited (7 decimal places)
     pub amount: i128,
+    /// Vault shares minted to the user
+    pub shares: i128,
+}
+
+/// Emitted instead of `DepositEvent` when a deposit carries a referrer.
+///
+/// Plain deposits keep the slim `DepositEvent`, so the common path never
+/// pays for an empty referrer field; this richer shape is published only
+/// when referral data is present.
+///
+/// # Topics
+/// - `SymbolShort("deposit")` - Event identifier
+/// - `SymbolShort("referral")` - Referred deposit marker
+#[contracttype]
+pub struct ReferredDepositEvent {
+    /// The user who made the deposit
+    pub user: Address,
+    /// Amount of USDC deposited (7 decimal places)
+    pub amount: i128,
+    /// Vault shares minted to the user
+    pub shares: i128,
+    /// The address credited with referring the deposit
+    pub referrer: Address,
 }
 
 /// Emitted when a user withdraws USDC from the vault.
@@ -162,6 +994,28 @@ pub struct WithdrawEvent {
     pub user: Address,
     /// Amount of USDC withdrawn (7 decimal places)
     pub amount: i128,
+    /// Vault shares burned from the user
+    pub shares: i128,
+}
+
+/// Pre-share shape of `DepositEvent`, published only while dual emission is on.
+///
+/// # Topics
+/// - `SymbolShort("deposit")`, `SymbolShort("legacy")` - Event identifier
+#[contracttype]
+pub struct LegacyDepositEvent {
+    pub user: Address,
+    pub amount: i128,
+}
+
+/// Pre-share shape of `WithdrawEvent`, published only while dual emission is on.
+///
+/// # Topics
+/// - `SymbolShort("withdraw")`, `SymbolShort("legacy")` - Event identifier
+#[contracttype]
+pub struct LegacyWithdrawEvent {
+    pub user: Address,
+    pub amount: i128,
 }
 
 /// Emitted when the AI agent rebalances funds between yield strategies.
@@ -180,6 +1034,35 @@ pub struct RebalanceEvent {
     pub expected_apy: i128,
 }
 
+/// Emitted when the agent moves USDC from the vault into a strategy.
+///
+/// # Topics
+/// - `SymbolShort("rebal_fnd")` - Event identifier
+#[contracttype]
+pub struct FundsRebalancedEvent {
+    pub strategy: Address,
+    pub amount: i128,
+}
+
+/// Emitted when the agent attests the USDC deployed outside the vault.
+///
+/// # Topics
+/// - `SymbolShort("attest")` - Event identifier
+#[contracttype]
+pub struct ReservesAttestedEvent {
+    pub amount: i128,
+    pub timestamp: u64,
+}
+
+/// Emitted when the owner registers a strategy.
+///
+/// # Topics
+/// - `SymbolShort("strat_add")` - Event identifier
+#[contracttype]
+pub struct StrategyRegisteredEvent {
+    pub strategy: Address,
+}
+
 /// Emitted when the vault is paused or unpaused.
 ///
 /// # Topics
@@ -203,13 +1086,111 @@ pub struct VaultInitializedEvent {
     pub tvl_cap: i128,
 }
 
+/// Warning emitted when the owner and agent roles are held by the same address.
+///
+/// # Topics
+/// - `SymbolShort("roles_cnf")` - Event identifier
+#[contracttype]
+pub struct RoleConflationEvent {
+    pub address: Address,
+}
+
 /// Emitted when the vault is paused.
 ///
 /// # Topics
 /// - `SymbolShort("vault_paused")` - Event identifier
 #[contracttype]
 pub struct VaultPausedEvent {
+    /// The owner or pauser that triggered the pause
+    pub owner: Address,
+}
+
+/// Emitted when rebalancing is paused or resumed.
+///
+/// # Topics
+/// - `SymbolShort("rebal_pau")` - Event identifier
+#[contracttype]
+pub struct RebalancePausedEvent {
+    /// The owner or pauser that changed the flag
+    pub caller: Address,
+    pub paused: bool,
+}
+
+/// Emitted when the pauser role is assigned or cleared.
+///
+/// # Topics
+/// - `SymbolShort("pauser")` - Event identifier
+#[contracttype]
+pub struct PauserUpdatedEvent {
+    pub pauser: Option<Address>,
+}
+
+/// Emitted when a user assigns or clears their withdraw manager.
+///
+/// # Topics
+/// - `SymbolShort("wd_mgr")` - Event identifier
+#[contracttype]
+pub struct WithdrawManagerUpdatedEvent {
+    pub user: Address,
+    pub manager: Option<Address>,
+}
+
+/// Emitted when the fee recipient is assigned or cleared.
+///
+/// # Topics
+/// - `SymbolShort("fee_rcpt")` - Event identifier
+#[contracttype]
+pub struct FeeRecipientUpdatedEvent {
+    /// The new recipient, or `None` when fees revert to the owner
+    pub recipient: Option<Address>,
+}
+
+/// Emitted when the vault is initialized with a guardian.
+///
+/// # Topics
+/// - `SymbolShort("guardian")` - Event identifier
+#[contracttype]
+pub struct GuardianConfiguredEvent {
+    pub guardian: Address,
+    /// Safe address that recovered funds are sent to
+    pub recovery_address: Address,
+    /// Seconds between starting and executing a recovery
+    pub recovery_delay: u64,
+}
+
+/// Emitted when the guardian starts an emergency recovery.
+///
+/// # Topics
+/// - `SymbolShort("rcv_start")` - Event identifier
+#[contracttype]
+pub struct RecoveryStartedEvent {
+    /// The recovery address funds will be sent to
+    pub to: Address,
+    /// Ledger timestamp from which the recovery may execute
+    pub ready_at: u64,
+}
+
+/// Emitted when the owner vetoes a pending recovery.
+///
+/// # Topics
+/// - `SymbolShort("rcv_veto")` - Event identifier
+#[contracttype]
+pub struct RecoveryVetoedEvent {
     pub owner: Address,
+    /// Ledger timestamp the vetoed recovery would have executed at
+    pub ready_at: u64,
+}
+
+/// Emitted when a guardian recovery moves the vault's USDC out.
+///
+/// # Topics
+/// - `SymbolShort("rcv_exec")` - Event identifier
+#[contracttype]
+pub struct RecoveryExecutedEvent {
+    /// The recovery address that received the funds
+    pub to: Address,
+    /// USDC transferred, including any reserve pulled back
+    pub amount: i128,
 }
 
 /// Emitted when the vault is unpaused.
@@ -221,6 +1202,16 @@ pub struct VaultUnpausedEvent {
     pub owner: Address,
 }
 
+/// Emitted when a pause is lifted by `try_auto_unpause()`.
+///
+/// # Topics
+/// - `SymbolShort("auto_unps")` - Event identifier
+#[contracttype]
+pub struct AutoUnpausedEvent {
+    /// Ledger timestamp since which no loss was reported
+    pub stable_since: u64,
+}
+
 /// Emitted when the vault is emergency paused.
 ///
 /// # Topics
@@ -242,20 +1233,476 @@ pub struct LimitsUpdatedEvent {
     pub new_max: i128,
 }
 
-/// Emitted when the AI agent is updated.
+/// Emitted when the multi-sig signer set or threshold changes.
 ///
 /// # Topics
-/// - `SymbolShort("agent_updated")` - Event identifier
+/// - `SymbolShort("owners")` - Event identifier
 #[contracttype]
-pub struct AgentUpdatedEvent {
-    pub old_agent: Address,
-    pub new_agent: Address,
+pub struct OwnersUpdatedEvent {
+    pub owners: Vec<Address>,
+    pub threshold: u32,
 }
 
-/// Emitted when total assets are updated.
+/// Emitted when a signer approves a critical action.
 ///
 /// # Topics
-/// - `SymbolShort("assets_updated")` - Event identifier
+/// - `SymbolShort("approve")` - Event identifier
+#[contracttype]
+pub struct ActionApprovedEvent {
+    pub signer: Address,
+    pub action_hash: BytesN<32>,
+    /// Approvals collected so far, including this one
+    pub approvals: u32,
+}
+
+/// Emitted when the AI agent is updated.
+///
+/// # Topics
+/// - `SymbolShort("agent_updated")` - Event identifier
+#[contracttype]
+pub struct AgentUpdatedEvent {
+    pub old_agent: Address,
+    pub new_agent: Address,
+}
+
+/// Emitted when the owner nominates a new owner.
+///
+/// # Topics
+/// - `SymbolShort("own_start")` - Event identifier
+#[contracttype]
+pub struct OwnershipTransferStartedEvent {
+    pub owner: Address,
+    pub pending_owner: Address,
+}
+
+/// Emitted when a nominated owner accepts ownership.
+///
+/// # Topics
+/// - `SymbolShort("own_xfer")` - Event identifier
+#[contracttype]
+pub struct OwnershipTransferredEvent {
+    pub old_owner: Address,
+    pub new_owner: Address,
+}
+
+/// Emitted when the share price sanity bounds are updated.
+///
+/// # Topics
+/// - `SymbolShort("price_bnd")` - Event identifier
+#[contracttype]
+pub struct SharePriceBoundsUpdatedEvent {
+    pub min_price: i128,
+    pub max_price: i128,
+}
+
+/// Emitted when a snapshot of vault totals and share balances is taken.
+///
+/// # Topics
+/// - `SymbolShort("snapshot")` - Event identifier
+#[contracttype]
+pub struct SnapshotTakenEvent {
+    pub snapshot_id: u32,
+    pub total_assets: i128,
+    pub total_shares: i128,
+}
+
+/// Emitted when the owner distributes a reward token airdrop to shareholders.
+///
+/// # Topics
+/// - `SymbolShort("airdrop")` - Event identifier
+#[contracttype]
+pub struct AirdropDistributedEvent {
+    pub token: Address,
+    pub snapshot_id: u32,
+    pub total_amount: i128,
+}
+
+/// Emitted when a shareholder claims their airdrop portion.
+///
+/// # Topics
+/// - `SymbolShort("air_claim")` - Event identifier
+#[contracttype]
+pub struct AirdropClaimedEvent {
+    pub user: Address,
+    pub token: Address,
+    pub amount: i128,
+}
+
+/// Emitted when the owner starts winding down the vault.
+///
+/// # Topics
+/// - `SymbolShort("wind_down")` - Event identifier
+#[contracttype]
+pub struct WindDownStartedEvent {
+    pub owner: Address,
+}
+
+/// Emitted when the owner force-withdraws a user's position during wind-down.
+///
+/// # Topics
+/// - `SymbolShort("force_wd")` - Event identifier
+#[contracttype]
+pub struct ForceWithdrawEvent {
+    /// The user whose shares were redeemed
+    pub user: Address,
+    /// The recipient of the redeemed USDC
+    pub to: Address,
+    /// Shares burned from the user
+    pub shares: i128,
+    /// USDC transferred to the recipient
+    pub amount: i128,
+}
+
+/// Emitted when a user redeems shares straight into a partner strategy.
+///
+/// # Topics
+/// - `SymbolShort("rdm_strat")` - Event identifier
+#[contracttype]
+pub struct StrategyRedemptionEvent {
+    pub user: Address,
+    /// The partner strategy that received the USDC
+    pub strategy: Address,
+    /// Shares burned from the user
+    pub shares: i128,
+    /// USDC transferred to the strategy
+    pub amount: i128,
+    /// Position credited to the user by the strategy
+    pub strategy_units: i128,
+}
+
+/// Emitted when a user deposits a non-USDC asset valued by the price oracle.
+///
+/// # Topics
+/// - `SymbolShort("asset_dep")` - Event identifier
+#[contracttype]
+pub struct AssetDepositEvent {
+    pub user: Address,
+    /// The deposited asset's token address
+    pub asset: Address,
+    /// Amount of the asset deposited (in the asset's units)
+    pub amount: i128,
+    /// USDC-equivalent value credited to total assets
+    pub usdc_value: i128,
+    /// Vault shares minted to the user
+    pub shares: i128,
+}
+
+/// Emitted when the price oracle is updated.
+///
+/// # Topics
+/// - `SymbolShort("oracle_up")` - Event identifier
+#[contracttype]
+pub struct PriceOracleUpdatedEvent {
+    pub oracle: Address,
+}
+
+/// Emitted when a user splits part of their position off to another address.
+///
+/// # Topics
+/// - `SymbolShort("split")` - Event identifier
+#[contracttype]
+pub struct SplitEvent {
+    pub from: Address,
+    pub to: Address,
+    pub shares: i128,
+    /// Cost basis moved along with the shares
+    pub cost_basis: i128,
+}
+
+/// Emitted when the assets oracle is updated.
+///
+/// # Topics
+/// - `SymbolShort("ast_orc")` - Event identifier
+#[contracttype]
+pub struct AssetsOracleUpdatedEvent {
+    pub oracle: Address,
+}
+
+/// Emitted when the reserve strategy is updated.
+///
+/// # Topics
+/// - `SymbolShort("rsv_strat")` - Event identifier
+#[contracttype]
+pub struct ReserveStrategyUpdatedEvent {
+    pub strategy: Option<Address>,
+}
+
+/// Emitted when idle reserve USDC is parked in or pulled from the reserve strategy.
+///
+/// # Topics
+/// - `SymbolShort("rsv_move")` - Event identifier
+#[contracttype]
+pub struct ReserveMovedEvent {
+    pub strategy: Address,
+    /// USDC parked (positive) or pulled back into the vault (negative)
+    pub amount: i128,
+    /// USDC parked in the strategy after the move
+    pub deployed: i128,
+}
+
+/// Emitted when a withdrawal drops on-chain liquidity below the reserve target.
+///
+/// # Topics
+/// - `SymbolShort("rsv_low")` - Event identifier
+#[contracttype]
+pub struct ReserveLowEvent {
+    /// USDC held by the vault plus any parked in the reserve strategy
+    pub liquidity: i128,
+    /// Liquidity the reserve target calls for at current total assets
+    pub target: i128,
+    /// USDC the agent should pull from strategies to restore the target
+    pub shortfall: i128,
+}
+
+/// Emitted when a gain is withheld from deposits inside the accrual delay.
+///
+/// # Topics
+/// - `SymbolShort("acr_excl")` - Event identifier
+#[contracttype]
+pub struct AccrualExcludedEvent {
+    /// Shares minted inside the delay window before the update
+    pub excluded_shares: i128,
+    /// Shares burned from those deposits so they keep their pre-update value
+    pub burned_shares: i128,
+}
+
+/// Emitted when a user makes a boosted, time-locked deposit.
+///
+/// # Topics
+/// - `SymbolShort("boost")` - Event identifier
+#[contracttype]
+pub struct BoostedDepositEvent {
+    pub user: Address,
+    /// Shares minted and locked by the deposit
+    pub shares: i128,
+    /// Yield multiplier scaled by `BOOST_SCALE`
+    pub multiplier: i128,
+    pub unlock_time: u64,
+}
+
+/// Emitted when a gain is redistributed towards boosted deposits.
+///
+/// # Topics
+/// - `SymbolShort("boost_dst")` - Event identifier
+#[contracttype]
+pub struct BoostDistributedEvent {
+    /// Shares held under active boosts before the update
+    pub boosted_shares: i128,
+    /// Bonus shares minted to them
+    pub minted_shares: i128,
+}
+
+/// Emitted when the share price moves a whole step away from the last milestone.
+///
+/// # Topics
+/// - `SymbolShort("price_ms")` - Event identifier
+#[contracttype]
+pub struct PriceMilestoneEvent {
+    pub previous_milestone: i128,
+    /// The furthest step boundary crossed, now the reference for the next event
+    pub milestone: i128,
+    /// Share price that crossed it (scaled by `SHARE_PRICE_SCALE`)
+    pub share_price: i128,
+}
+
+/// Emitted when a user locks shares to a locker.
+///
+/// # Topics
+/// - `SymbolShort("lock")` - Event identifier
+#[contracttype]
+pub struct SharesLockedEvent {
+    pub user: Address,
+    pub locker: Address,
+    pub shares: i128,
+}
+
+/// Emitted when a locker releases a user's locked shares.
+///
+/// # Topics
+/// - `SymbolShort("unlock")` - Event identifier
+#[contracttype]
+pub struct SharesUnlockedEvent {
+    pub user: Address,
+    pub locker: Address,
+    pub shares: i128,
+}
+
+/// Emitted when the owner reserves a depositor slot for a user.
+///
+/// # Topics
+/// - `SymbolShort("pre_reg")` - Event identifier
+#[contracttype]
+pub struct PreRegisteredEvent {
+    pub user: Address,
+    pub expires_at: u64,
+}
+
+/// Emitted when a user queues a withdrawal.
+///
+/// # Topics
+/// - `SymbolShort("wd_req")` - Event identifier
+#[contracttype]
+pub struct WithdrawalRequestedEvent {
+    pub request_id: u64,
+    pub user: Address,
+    pub shares: i128,
+    pub unlock_time: u64,
+}
+
+/// Emitted when a queued withdrawal is claimed.
+///
+/// # Topics
+/// - `SymbolShort("wd_claim")` - Event identifier
+#[contracttype]
+pub struct WithdrawalClaimedEvent {
+    pub request_id: u64,
+    pub user: Address,
+    pub shares: i128,
+    pub amount: i128,
+}
+
+/// Emitted when a queued withdrawal is cancelled and its shares returned.
+///
+/// # Topics
+/// - `SymbolShort("wd_cancel")` - Event identifier
+#[contracttype]
+pub struct WithdrawalCancelledEvent {
+    pub request_id: u64,
+    pub user: Address,
+    pub shares: i128,
+}
+
+/// Emitted when a client grants or revokes an advisor's read access.
+///
+/// # Topics
+/// - `SymbolShort("advisor")` - Event identifier
+#[contracttype]
+pub struct AdvisorUpdatedEvent {
+    pub client: Address,
+    pub advisor: Address,
+    pub authorized: bool,
+}
+
+/// Emitted when the vault crosses into or out of the underwater state.
+///
+/// # Topics
+/// - `SymbolShort("underwatr")` - Event identifier
+#[contracttype]
+pub struct UnderwaterEvent {
+    pub underwater: bool,
+    pub total_assets: i128,
+    pub total_cost_basis: i128,
+}
+
+/// Emitted when the owner configures native XLM deposits.
+///
+/// # Topics
+/// - `SymbolShort("router_up")` - Event identifier
+#[contracttype]
+pub struct SwapRouterUpdatedEvent {
+    pub router: Address,
+    pub native_token: Address,
+}
+
+/// Emitted when a scalar configuration value is updated.
+///
+/// # Topics
+/// - `SymbolShort("cfg_upd")` - Event identifier
+#[contracttype]
+pub struct ConfigUpdatedEvent {
+    /// The configuration key that changed (e.g. "max_dep")
+    pub key: Symbol,
+    pub old_value: i128,
+    pub new_value: i128,
+}
+
+/// Emitted when the owner recomputes total shares from per-user balances.
+///
+/// # Topics
+/// - `SymbolShort("shr_recmp")` - Event identifier
+#[contracttype]
+pub struct TotalSharesRecomputedEvent {
+    pub old_total: i128,
+    pub new_total: i128,
+}
+
+/// Emitted when a read finds total shares out of line with the share checksum.
+///
+/// # Topics
+/// - `SymbolShort("drift")` - Event identifier
+#[contracttype]
+pub struct DriftDetectedEvent {
+    /// Stored total shares
+    pub total_shares: i128,
+    /// Running sum of user balances plus shares escrowed in withdrawal requests
+    pub checksum: i128,
+}
+
+/// Emitted when the management fee is accrued to the fee recipient.
+///
+/// # Topics
+/// - `SymbolShort("fee_acr")` - Event identifier
+#[contracttype]
+pub struct FeeAccruedEvent {
+    /// Shares minted to the fee recipient
+    pub shares_minted: i128,
+    pub fee_bps: i128,
+    /// Seconds the fee was charged for
+    pub period_seconds: u64,
+}
+
+/// Emitted when a withdrawal fee is deducted from a payout.
+///
+/// # Topics
+/// - `SymbolShort("wd_fee")` - Event identifier
+#[contracttype]
+pub struct WithdrawalFeeEvent {
+    pub user: Address,
+    /// USDC kept in the vault
+    pub fee: i128,
+}
+
+/// Emitted when a user opts in to or out of auto-compounding withdrawal fees.
+///
+/// # Topics
+/// - `SymbolShort("auto_cmp")` - Event identifier
+#[contracttype]
+pub struct AutoCompoundUpdatedEvent {
+    pub user: Address,
+    pub enabled: bool,
+}
+
+/// Emitted when an opted-in user's withdrawal fee is re-minted to them as shares.
+///
+/// # Topics
+/// - `SymbolShort("fee_cmpd")` - Event identifier
+#[contracttype]
+pub struct WithdrawalFeeCompoundedEvent {
+    pub user: Address,
+    /// USDC withheld from the payout and kept in the vault
+    pub fee: i128,
+    /// Shares minted to the user for the withheld USDC
+    pub shares: i128,
+}
+
+/// Emitted when a performance fee is taken on gains above the high-water mark.
+///
+/// # Topics
+/// - `SymbolShort("perf_fee")` - Event identifier
+#[contracttype]
+pub struct PerformanceFeeEvent {
+    /// Shares minted to the fee recipient
+    pub shares_minted: i128,
+    /// USDC value of the fee
+    pub fee: i128,
+    /// New high-water mark, the share price after the fee
+    pub high_water_mark: i128,
+}
+
+/// Emitted when total assets are updated.
+///
+/// # Topics
+/// - `SymbolShort("assets_updated")` - Event identifier
 #[contracttype]
 pub struct AssetsUpdatedEvent {
     pub old_total: i128,
@@ -297,7 +1744,8 @@ impl NeuroWealthVault {
     ///
     /// This function must be called exactly once after contract deployment
     /// to set up the vault's core configuration. After initialization,
-    /// the vault is ready to accept deposits.
+    /// the vault is ready to accept deposits. The agent is granted every
+    /// permission; use `initialize_with_permissions` to restrict it.
     ///
     /// # Arguments
     /// * `env` - The Soroban environment
@@ -309,6 +1757,8 @@ impl NeuroWealthVault {
     ///
     /// # Panics
     /// - If the vault has already been initialized (Agent key already exists)
+    /// - If the agent (who also becomes owner) is the USDC token or the vault itself
+    /// - If the USDC token is the vault itself
     ///
     /// # Events
     /// Emits `VaultInitializedEvent` with:
@@ -316,41 +1766,109 @@ impl NeuroWealthVault {
     /// - `usdc_token`: The USDC token contract address
     /// - `tvl_cap`: The initial TVL cap
     ///
+    /// Also emits `RoleConflationEvent`, since the agent starts out as the
+    /// owner; split the roles with `update_agent` or `transfer_ownership`.
+    ///
     /// # Security
     /// - This function can only be called once (idempotent initialization prevention)
     /// - The deployer should verify the agent and token addresses are correct
     /// - After initialization, the deployer should transfer ownership or destroy
     ///   the deployer key to prevent re-initialization
     pub fn initialize(env: Env, agent: Address, usdc_token: Address) {
-        if env.storage().instance().has(&DataKey::Agent) {
-            panic!("Already initialized");
-        }
-
-        let tvl_cap = 100_000_000_000_i128; // 100M USDC default
-
-        env.storage().instance().set(&DataKey::Agent, &agent);
-        env.storage()
-            .instance()
-            .set(&DataKey::UsdcToken, &usdc_token);
-        env.storage()
-            .instance()
-            .set(&DataKey::TotalDeposits, &0_i128);
-        env.storage().instance().set(&DataKey::Paused, &false);
-        env.storage().instance().set(&DataKey::Owner, &agent);
-        env.storage().instance().set(&DataKey::TvLCap, &tvl_cap);
-        env.storage()
-            .instance()
-            .set(&DataKey::UserDepositCap, &10_000_000_000_i128); // 10K USDC default
-        env.storage().instance().set(&DataKey::Version, &1_u32);
-
-        env.events().publish(
-            (symbol_short!("v_init"),),
-            VaultInitializedEvent {
-                agent: agent.clone(),
-                usdc_token: usdc_token.clone(),
-                tvl_cap,
-            },
-        );
+        Self::init_vault(&env, agent, usdc_token, ALL_AGENT_PERMISSIONS);
+    }
+
+    /// Initializes the vault with a restricted set of agent permissions.
+    ///
+    /// Identical to `initialize`, except the agent is only granted the
+    /// operations in `permissions` from day one.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `agent` - The authorized AI agent address
+    /// * `usdc_token` - The USDC token contract address
+    /// * `permissions` - Bitmask of `PERM_*` flags granted to the agent
+    ///
+    /// # Returns
+    /// Nothing. This function mutates state but returns nothing.
+    ///
+    /// # Panics
+    /// - If the vault has already been initialized
+    /// - If the agent is the USDC token or the vault itself
+    /// - If the USDC token is the vault itself
+    /// - If `permissions` contains unknown bits
+    ///
+    /// # Events
+    /// Emits `VaultInitializedEvent` as `initialize` does
+    pub fn initialize_with_permissions(
+        env: Env,
+        agent: Address,
+        usdc_token: Address,
+        permissions: u32,
+    ) {
+        Self::require_valid_permissions(permissions);
+        Self::init_vault(&env, agent, usdc_token, permissions);
+    }
+
+    /// Initializes the vault with a guardian able to recover funds in an emergency.
+    ///
+    /// Identical to `initialize`, and additionally fixes the guardian, the
+    /// recovery address and the recovery delay for the life of the vault.
+    /// There is no setter for any of the three, so a compromised owner
+    /// cannot redirect a recovery.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `agent` - The authorized AI agent address, who also becomes owner
+    /// * `usdc_token` - The USDC token contract address
+    /// * `guardian` - Address allowed to start a recovery
+    /// * `recovery_address` - Safe address that recovered funds are sent to
+    /// * `recovery_delay` - Seconds the owner has to veto a started recovery
+    ///
+    /// # Returns
+    /// Nothing. This function mutates state but returns nothing.
+    ///
+    /// # Panics
+    /// - Any panic raised by `initialize`
+    /// - If the guardian is the agent (and therefore the owner)
+    /// - If the guardian or recovery address is the USDC token or the vault itself
+    /// - If recovery_delay is zero
+    ///
+    /// # Events
+    /// Emits `VaultInitializedEvent` as `initialize` does, then
+    /// `GuardianConfiguredEvent` with the guardian, recovery address and delay
+    pub fn initialize_with_guardian(
+        env: Env,
+        agent: Address,
+        usdc_token: Address,
+        guardian: Address,
+        recovery_address: Address,
+        recovery_delay: u64,
+    ) {
+        assert!(guardian != agent, "Guardian must be distinct from owner");
+        Self::require_valid_role_address(&env, &guardian, &usdc_token);
+        Self::require_valid_role_address(&env, &recovery_address, &usdc_token);
+        assert!(recovery_delay > 0, "Recovery delay must be positive");
+        Self::init_vault(&env, agent, usdc_token, ALL_AGENT_PERMISSIONS);
+
+        env.storage()
+            .instance()
+            .set(&ConfigKey::Guardian, &guardian);
+        env.storage()
+            .instance()
+            .set(&ConfigKey::RecoveryAddress, &recovery_address);
+        env.storage()
+            .instance()
+            .set(&ConfigKey::RecoveryDelay, &recovery_delay);
+
+        env.events().publish(
+            (symbol_short!("guardian"),),
+            GuardianConfiguredEvent {
+                guardian,
+                recovery_address,
+                recovery_delay,
+            },
+        );
     }
 
     // ==========================================================================
@@ -360,9 +1878,14 @@ impl NeuroWealthVault {
     /// Deposits USDC into the vault on behalf of a user.
     ///
     /// The user must authorize this transaction with their signature.
-    /// The vault transfers USDC from the user and records their balance.
+    /// The vault transfers USDC from the user and mints vault shares at the
+    /// current share price.
     /// An event is emitted for the AI agent to detect and initiate yield deployment.
     ///
+    /// Soroban executes invocations strictly in order, so several deposits
+    /// in one transaction or ledger are priced one after another: each sees
+    /// the total shares and assets written by the deposits before it.
+    ///
     /// # Arguments
     /// * `env` - The Soroban environment
     /// * `user` - The user address making the deposit (must authorize)
@@ -373,520 +1896,8925 @@ impl NeuroWealthVault {
     ///
     /// # Panics
     /// - If the vault is paused
+    /// - If the vault is winding down
     /// - If amount is not positive
     /// - If amount is less than 1 USDC (minimum deposit)
     /// - If amount would exceed the user's deposit cap
     /// - If amount would exceed the TVL cap
+    /// - If amount would exceed the per-ledger deposit cap
+    /// - If the user is a new depositor and the depositor cap is reached
+    /// - If the user's deposit cooldown has not elapsed
+    /// - If amount would mint zero shares at the current share price
+    /// - If the vault's USDC balance does not rise by exactly `amount`
     /// - If the USDC transfer fails
     ///
     /// # Events
     /// Emits `DepositEvent` with:
     /// - `user`: The depositing user's address
     /// - `amount`: The amount deposited
+    /// - `shares`: The shares minted
     ///
     /// # Security
     /// - `user.require_auth()` ensures only the user can deposit to their own account
     /// - Checks are performed before state updates (checks-effects-interactions pattern)
-    /// - Balance is updated after successful token transfer
+    /// - Shares are priced before the deposit changes total assets
     pub fn deposit(env: Env, user: Address, amount: i128) {
         user.require_auth();
 
-        Self::require_not_paused(&env);
-        Self::require_positive_amount(amount);
-        Self::require_minimum_deposit(amount);
-        Self::require_within_deposit_cap(&env, &user, amount);
-        Self::require_within_tvl_cap(&env, amount);
-
-        let usdc_token: Address = env.storage().instance().get(&DataKey::UsdcToken).unwrap();
-        let token_client = token::Client::new(&env, &usdc_token);
-        token_client.transfer(&user, &env.current_contract_address(), &amount);
-
-        let current_balance: i128 = env
-            .storage()
-            .persistent()
-            .get(&DataKey::Balance(user.clone()))
-            .unwrap_or(0);
-        env.storage()
-            .persistent()
-            .set(&DataKey::Balance(user.clone()), &(current_balance + amount));
-
-        let total: i128 = env
-            .storage()
-            .instance()
-            .get(&DataKey::TotalDeposits)
-            .unwrap_or(0);
-        env.storage()
-            .instance()
-            .set(&DataKey::TotalDeposits, &(total + amount));
-
-        env.events()
-            .publish((symbol_short!("deposit"),), DepositEvent { user, amount });
+        let shares = Self::process_deposit(&env, &user, amount, None, DepositFunding::Transfer);
+        Self::publish_deposit_event(&env, user, amount, shares, None);
     }
 
-    // ==========================================================================
-    // CORE LIFECYCLE - WITHDRAW
-    // ==========================================================================
-
-    /// Withdraws USDC from the vault for a user.
+    /// Deposits USDC on behalf of a referrer.
     ///
-    /// The user must authorize this transaction with their signature.
-    /// The vault transfers USDC from its balance to the user.
+    /// Behaves exactly like `deposit`; the referrer is only recorded in the
+    /// emitted event so off-chain programs can attribute the deposit.
     ///
     /// # Arguments
     /// * `env` - The Soroban environment
-    /// * `user` - The user address withdrawing funds (must authorize)
-    /// * `amount` - Amount of USDC to withdraw (7 decimal places)
+    /// * `user` - The user address making the deposit (must authorize)
+    /// * `amount` - Amount of USDC to deposit (7 decimal places)
+    /// * `referrer` - The address credited with referring the deposit
     ///
     /// # Returns
-    /// Nothing. This function processes the withdrawal and returns nothing.
+    /// Nothing. This function records the deposit and returns nothing.
     ///
     /// # Panics
-    /// - If the vault is paused
-    /// - If amount is not positive
-    /// - If user has insufficient balance
-    /// - If the USDC transfer fails
+    /// - If `referrer` is the depositing user
+    /// - Any panic raised by `deposit`
     ///
     /// # Events
-    /// Emits `WithdrawEvent` with:
-    /// - `user`: The withdrawing user's address
-    /// - `amount`: The amount withdrawn
+    /// Emits `ReferredDepositEvent` with:
+    /// - `user`: The depositing user's address
+    /// - `amount`: The amount deposited
+    /// - `shares`: The shares minted
+    /// - `referrer`: The referring address
     ///
     /// # Security
-    /// - `user.require_auth()` ensures users can only withdraw their own funds
-    /// - Balance check is performed before any state updates
-    /// - Uses checks-effects-interactions pattern: balance updated before transfer
-    pub fn withdraw(env: Env, user: Address, amount: i128) {
+    /// - `user.require_auth()` ensures only the user can deposit to their own account
+    /// - The referrer does not sign and gains no claim on the position
+    pub fn deposit_with_referral(env: Env, user: Address, amount: i128, referrer: Address) {
         user.require_auth();
+        assert!(referrer != user, "Cannot refer yourself");
 
-        Self::require_not_paused(&env);
-        Self::require_positive_amount(amount);
+        let shares = Self::process_deposit(&env, &user, amount, None, DepositFunding::Transfer);
+        Self::publish_deposit_event(&env, user, amount, shares, Some(referrer));
+    }
+
+    /// Deposits USDC for a user who signed a permit, pulling it via allowance.
+    ///
+    /// Lets a relayer submit the deposit: the user signs the permit off-chain
+    /// with the ed25519 key registered through `set_permit_key` and approves
+    /// the vault as spender on the USDC token beforehand. The signed message
+    /// is the SHA-256 of `get_permit_payload(user, amount, deadline)`, which
+    /// binds the vault address and the user's current permit nonce, so each
+    /// permit can be used once and only on this vault.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `user` - The user whose shares are minted and whose USDC is pulled
+    /// * `amount` - Amount of USDC to deposit (7 decimal places)
+    /// * `deadline` - Last ledger timestamp at which the permit is valid
+    /// * `signature` - The user's ed25519 signature over the permit digest
+    ///
+    /// # Panics
+    /// - If the ledger timestamp is past `deadline` ("Permit expired")
+    /// - If the user has not registered a permit key
+    /// - If the signature does not verify, including a replayed permit signed
+    ///   over an already used nonce
+    /// - If the vault's allowance from the user is below `amount`
+    /// - Under the same conditions as `deposit`
+    ///
+    /// # Events
+    /// Emits `DepositEvent`, as `deposit` does
+    ///
+    /// # Security
+    /// - No `require_auth()`: the signature is the user's authorization
+    /// - The nonce is consumed before any tokens move
+    pub fn deposit_with_permit(
+        env: Env,
+        user: Address,
+        amount: i128,
+        deadline: u64,
+        signature: BytesN<64>,
+    ) {
+        assert!(env.ledger().timestamp() <= deadline, "Permit expired");
 
-        let balance: i128 = env
+        let key: BytesN<32> = env
             .storage()
             .persistent()
-            .get(&DataKey::Balance(user.clone()))
-            .unwrap_or(0);
+            .get(&PositionKey::PermitKey(user.clone()))
+            .expect("No permit key registered");
 
-        assert!(balance >= amount, "Insufficient balance");
+        let payload = Self::get_permit_payload(env.clone(), user.clone(), amount, deadline);
+        let digest: Bytes = env.crypto().sha256(&payload).to_bytes().into();
+        env.crypto().ed25519_verify(&key, &digest, &signature);
 
+        let nonce = Self::get_permit_nonce(env.clone(), user.clone());
         env.storage()
             .persistent()
-            .set(&DataKey::Balance(user.clone()), &(balance - amount));
+            .set(&PositionKey::PermitNonce(user.clone()), &(nonce + 1));
+
+        let shares = Self::process_deposit(&env, &user, amount, None, DepositFunding::Allowance);
+        Self::publish_deposit_event(&env, user, amount, shares, None);
+    }
+
+    /// Registers the ed25519 public key that signs a user's deposit permits.
+    ///
+    /// Replacing the key invalidates permits signed with the old one; the
+    /// nonce is kept, so old permits stay unusable if the key is restored.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `user` - The user registering the key (must authorize)
+    /// * `key` - The ed25519 public key
+    pub fn set_permit_key(env: Env, user: Address, key: BytesN<32>) {
+        user.require_auth();
 
-        let total: i128 = env
-            .storage()
-            .instance()
-            .get(&DataKey::TotalDeposits)
-            .unwrap_or(0);
         env.storage()
-            .instance()
-            .set(&DataKey::TotalDeposits, &(total - amount));
+            .persistent()
+            .set(&PositionKey::PermitKey(user), &key);
+    }
 
-        let usdc_token: Address = env.storage().instance().get(&DataKey::UsdcToken).unwrap();
-        let token_client = token::Client::new(&env, &usdc_token);
-        token_client.transfer(&env.current_contract_address(), &user, &amount);
+    /// Returns the ed25519 key a user registered for permits, if any.
+    pub fn get_permit_key(env: Env, user: Address) -> Option<BytesN<32>> {
+        env.storage()
+            .persistent()
+            .get(&PositionKey::PermitKey(user))
+    }
 
-        env.events()
-            .publish((symbol_short!("withdraw"),), WithdrawEvent { user, amount });
+    /// Returns the nonce the user's next deposit permit must be signed over.
+    pub fn get_permit_nonce(env: Env, user: Address) -> u64 {
+        env.storage()
+            .persistent()
+            .get(&PositionKey::PermitNonce(user))
+            .unwrap_or(0)
     }
 
-    // ==========================================================================
-    // CORE LIFECYCLE - REBALANCE
-    // ==========================================================================
+    /// Returns the bytes a deposit permit signs over, before hashing.
+    ///
+    /// The XDR encoding of `(vault, user, amount, deadline, nonce)`, using
+    /// the user's current permit nonce.
+    pub fn get_permit_payload(env: Env, user: Address, amount: i128, deadline: u64) -> Bytes {
+        let nonce = Self::get_permit_nonce(env.clone(), user.clone());
+        (
+            env.current_contract_address(),
+            user,
+            amount,
+            deadline,
+            nonce,
+        )
+            .to_xdr(&env)
+    }
 
-    /// Rebalances vault funds between yield strategies.
+    /// Deposits USDC, reverting if the share price moved beyond tolerance.
     ///
-    /// Only the authorized AI agent can call this function. The agent uses
-    /// this to move funds between different yield-generating protocols based
-    /// on market conditions and strategy performance.
+    /// The caller quotes the share price it observed (see `get_share_price`);
+    /// the deposit only executes if the current price is within
+    /// `MaxSlippageBps` of the quote.
     ///
     /// # Arguments
     /// * `env` - The Soroban environment
-    /// * `protocol` - The target protocol symbol (e.g., "conservative", "balanced", "growth")
-    /// * `expected_apy` - Expected APY in basis points (e.g., 850 = 8.5%)
+    /// * `user` - The user address making the deposit (must authorize)
+    /// * `amount` - Amount of USDC to deposit (7 decimal places)
+    /// * `quoted_price` - Share price scaled by `SHARE_PRICE_SCALE`
     ///
     /// # Returns
-    /// Nothing. This function triggers rebalancing and returns nothing.
+    /// Nothing. This function performs the deposit and returns nothing.
     ///
     /// # Panics
-    /// - If the vault is paused
-    /// - If the caller is not the authorized agent
+    /// - If the current share price deviates from `quoted_price` by more than
+    ///   `MaxSlippageBps`
+    /// - Any panic raised by `deposit`
     ///
     /// # Events
-    /// Emits `RebalanceEvent` with:
-    /// - `protocol`: The target protocol
-    /// - `expected_apy`: Expected APY in basis points
-    ///
-    /// # Security
-    /// - `agent.require_auth()` ensures only the authorized AI agent can rebalance
-    /// - Agent is set during initialization and can be updated by owner
-    /// - This function does NOT transfer funds - it's a signal to external protocols
-    /// - Phase 2 will add actual protocol interactions (Blend, DEX)
-    pub fn rebalance(env: Env, protocol: Symbol, expected_apy: i128) {
-        Self::require_not_paused(&env);
-        Self::require_is_agent(&env);
-
-        env.events().publish(
-            (symbol_short!("rebalance"),),
-            RebalanceEvent {
-                protocol,
-                expected_apy,
-            },
-        );
+    /// Emits `DepositEvent` as `deposit` does
+    pub fn deposit_with_quote(env: Env, user: Address, amount: i128, quoted_price: i128) {
+        Self::require_within_slippage(&env, quoted_price);
+        Self::deposit(env, user, amount);
     }
 
-    // ==========================================================================
-    // ADMINISTRATIVE - PAUSE CONTROL
-    // ==========================================================================
-
-    /// Pauses the vault, disabling deposits and withdrawals.
+    /// Mints an exact number of vault shares to a user.
     ///
-    /// Emergency function to halt all user-facing operations.
-    /// When paused:
-    /// - Deposits are rejected
-    /// - Withdrawals are rejected
-    /// - Rebalancing is rejected
-    /// - Read functions remain operational
+    /// The ERC-4626 `mint` counterpart to `deposit`: the caller names the
+    /// shares they want and the vault pulls the USDC required, rounded up.
     ///
     /// # Arguments
     /// * `env` - The Soroban environment
-    /// * `owner` - The owner address (must authorize this call)
+    /// * `user` - The user address receiving the shares (must authorize)
+    /// * `shares` - Number of shares to mint
     ///
     /// # Returns
-    /// Nothing. This function pauses the vault and returns nothing.
+    /// The amount of USDC pulled from the user (7 decimal places)
     ///
     /// # Panics
-    /// - If the caller is not the owner
+    /// - If the vault is paused
+    /// - If the vault is winding down
+    /// - If shares is not positive
+    /// - If the required USDC is less than 1 USDC (minimum deposit)
+    /// - If the required USDC would exceed the user's deposit cap, the TVL cap
+    ///   or the per-ledger deposit cap
+    /// - If the user is a new depositor and the depositor cap is reached
+    /// - If the user's deposit cooldown has not elapsed
+    /// - If the USDC transfer fails
     ///
     /// # Events
-    /// Emits `VaultPausedEvent` with:
-    /// - `owner`: The owner's address that triggered the pause
+    /// Emits `DepositEvent` with:
+    /// - `user`: The depositing user's address
+    /// - `amount`: The USDC pulled
+    /// - `shares`: The shares minted
     ///
     /// # Security
-    /// - Only the owner can pause the vault (verified via require_auth)
-    /// - There is no automatic unpause - owner must explicitly call unpause()
-    /// - Users' funds remain safe and can be withdrawn after unpause
-    pub fn pause(env: Env, owner: Address) {
-        owner.require_auth();
-        let stored_owner: Address = env.storage().instance().get(&DataKey::Owner).unwrap();
-        assert_eq!(owner, stored_owner, "Only owner can pause");
+    /// - `user.require_auth()` ensures only the user can mint to their own account
+    /// - Required assets round up so the vault is never shortchanged
+    pub fn mint(env: Env, user: Address, shares: i128) -> i128 {
+        user.require_auth();
 
-        env.storage().instance().set(&DataKey::Paused, &true);
+        Self::require_not_paused(&env);
+        Self::require_not_winding_down(&env);
+        Self::require_positive_amount(shares);
+        Self::accrue_management_fee(&env);
 
-        env.events()
-            .publish((symbol_short!("v_paused"),), VaultPausedEvent { owner });
+        let amount = Self::preview_mint(env.clone(), shares);
+        Self::process_deposit(&env, &user, amount, Some(shares), DepositFunding::Transfer);
+
+        Self::publish_deposit_event(&env, user, amount, shares, None);
+
+        amount
     }
 
-    /// Unpauses the vault, re-enabling deposits and withdrawals.
+    /// Deposits a non-USDC asset, minting shares on its USDC-equivalent value.
+    ///
+    /// The asset is valued through a cross-contract `price(asset)` call to the
+    /// configured price oracle. The vault keeps the asset; withdrawals are
+    /// always paid in USDC, so the agent is expected to convert it.
     ///
     /// # Arguments
     /// * `env` - The Soroban environment
-    /// * `owner` - The owner address (must authorize this call)
+    /// * `user` - The user address making the deposit (must authorize)
+    /// * `asset` - The token address of the asset being deposited
+    /// * `amount` - Amount of the asset to deposit (in the asset's units)
     ///
     /// # Returns
-    /// Nothing. This function unpauses the vault and returns nothing.
+    /// The number of shares minted
     ///
     /// # Panics
-    /// - If the caller is not the owner
-    /// - If the vault is not currently paused
+    /// - If the vault is paused or winding down
+    /// - If amount is not positive
+    /// - If asset is the USDC token (use `deposit()`)
+    /// - If no price oracle is configured
+    /// - If the oracle price is not positive or older than `MaxOracleAge`
+    /// - If the USDC value fails the minimum deposit, deposit cap, TVL cap,
+    ///   per-ledger deposit cap or depositor cap checks
+    /// - If the user's deposit cooldown has not elapsed
+    /// - If the value would mint zero shares
+    /// - If called re-entrantly
     ///
     /// # Events
-    /// Emits `VaultUnpausedEvent` with:
-    /// - `owner`: The owner's address that triggered the unpause
+    /// Emits `AssetDepositEvent` with the asset amount, USDC value and shares
     ///
     /// # Security
-    /// - Only the owner can unpause the vault (verified via require_auth)
-    pub fn unpause(env: Env, owner: Address) {
-        owner.require_auth();
-        let stored_owner: Address = env.storage().instance().get(&DataKey::Owner).unwrap();
-        assert_eq!(owner, stored_owner, "Only owner can unpause");
+    /// - `user.require_auth()` ensures only the user can deposit to their own account
+    /// - An explicit reentrancy guard is held across the oracle call; Soroban
+    ///   already rejects contract re-entry, so this is defense in depth
+    /// - Stale prices are rejected to limit oracle manipulation windows
+    pub fn deposit_asset(env: Env, user: Address, asset: Address, amount: i128) -> i128 {
+        user.require_auth();
 
-        let paused: bool = env
-            .storage()
-            .instance()
-            .get(&DataKey::Paused)
-            .unwrap_or(false);
-        assert!(paused, "Vault is not paused");
+        Self::require_not_paused(&env);
+        Self::require_not_winding_down(&env);
+        Self::require_positive_amount(amount);
 
-        env.storage().instance().set(&DataKey::Paused, &false);
+        let usdc_token: Address = env.storage().instance().get(&DataKey::UsdcToken).unwrap();
+        assert!(asset != usdc_token, "Use deposit for USDC");
 
-        env.events()
-            .publish((symbol_short!("v_unpause"),), VaultUnpausedEvent { owner });
+        Self::enter_non_reentrant(&env);
+
+        let usdc_value = Self::oracle_value(&env, &asset, amount);
+        let shares = Self::process_deposit(
+            &env,
+            &user,
+            usdc_value,
+            None,
+            DepositFunding::Asset(asset.clone(), amount),
+        );
+
+        Self::exit_non_reentrant(&env);
+
+        env.events().publish(
+            (symbol_short!("asset_dep"),),
+            AssetDepositEvent {
+                user,
+                asset,
+                amount,
+                usdc_value,
+                shares,
+            },
+        );
+
+        shares
     }
 
-    /// Emergency pause function that immediately halts all operations.
+    /// Deposits native XLM, swapping it to USDC and minting shares on the proceeds.
     ///
-    /// This is a separate function from pause() to distinguish emergency
-    /// situations in event logs. Functionally identical to pause().
+    /// The XLM is pulled from the user, sent to the configured swap router and
+    /// exchanged for USDC paid back to the vault. Shares are minted on the USDC
+    /// actually received, which must be at least `min_usdc_out`.
     ///
     /// # Arguments
     /// * `env` - The Soroban environment
-    /// * `owner` - The owner address (must authorize this call)
+    /// * `user` - The user address making the deposit (must authorize)
+    /// * `amount` - Amount of XLM to deposit (7 decimal places)
+    /// * `min_usdc_out` - Minimum USDC the swap must deliver
     ///
     /// # Returns
-    /// Nothing. This function emergency pauses the vault and returns nothing.
+    /// The number of shares minted
     ///
     /// # Panics
-    /// - If the caller is not the owner
+    /// - If the vault is paused or winding down
+    /// - If amount is not positive
+    /// - If no swap router is configured
+    /// - If the swap delivers less than `min_usdc_out`
+    /// - If the USDC received fails the minimum deposit, deposit cap, TVL cap,
+    ///   per-ledger deposit cap or depositor cap checks
+    /// - If the user's deposit cooldown has not elapsed
+    /// - If the USDC received would mint zero shares
+    /// - If called re-entrantly
     ///
     /// # Events
-    /// Emits `EmergencyPausedEvent` with:
-    /// - `owner`: The owner's address that triggered the emergency pause
+    /// Emits `AssetDepositEvent` with the XLM amount, USDC received and shares
     ///
     /// # Security
-    /// - Only the owner can emergency pause the vault (verified via require_auth)
-    pub fn emergency_pause(env: Env, owner: Address) {
-        owner.require_auth();
-        let stored_owner: Address = env.storage().instance().get(&DataKey::Owner).unwrap();
-        assert_eq!(owner, stored_owner, "Only owner can emergency pause");
+    /// - `user.require_auth()` ensures only the user can deposit to their own account
+    /// - The USDC received is measured from the vault's balance delta rather
+    ///   than the router's return value
+    /// - An explicit reentrancy guard is held across the router call
+    pub fn deposit_native(env: Env, user: Address, amount: i128, min_usdc_out: i128) -> i128 {
+        user.require_auth();
 
-        env.storage().instance().set(&DataKey::Paused, &true);
+        Self::require_not_paused(&env);
+        Self::require_not_winding_down(&env);
+        Self::require_positive_amount(amount);
 
-        env.events()
-            .publish((symbol_short!("e_paused"),), EmergencyPausedEvent { owner });
-    }
+        let router = Self::get_swap_router(env.clone()).expect("Swap router not set");
+        let native_token: Address = env
+            .storage()
+            .instance()
+            .get(&ConfigKey::NativeToken)
+            .unwrap();
+        let usdc_token: Address = env.storage().instance().get(&DataKey::UsdcToken).unwrap();
+        let vault = env.current_contract_address();
 
-    // ==========================================================================
-    // ADMINISTRATIVE - CONFIGURATION
-    // ==========================================================================
+        Self::enter_non_reentrant(&env);
 
-    /// Sets the TVL (Total Value Locked) cap for the vault.
+        Self::receive_tokens(&env, &native_token, &user, amount);
+
+        let usdc_client = token::Client::new(&env, &usdc_token);
+        let usdc_before = usdc_client.balance(&vault);
+        token::Client::new(&env, &native_token).transfer(&vault, &router, &amount);
+        SwapRouterClient::new(&env, &router).swap_exact_in(
+            &native_token,
+            &usdc_token,
+            &amount,
+            &min_usdc_out,
+            &vault,
+        );
+        let usdc_value = usdc_client.balance(&vault) - usdc_before;
+        assert!(usdc_value >= min_usdc_out, "Swap slippage exceeded");
+
+        let shares = Self::process_deposit(&env, &user, usdc_value, None, DepositFunding::Received);
+
+        Self::exit_non_reentrant(&env);
+
+        env.events().publish(
+            (symbol_short!("asset_dep"),),
+            AssetDepositEvent {
+                user,
+                asset: native_token,
+                amount,
+                usdc_value,
+                shares,
+            },
+        );
+
+        shares
+    }
+
+    // ==========================================================================
+    // CORE LIFECYCLE - WITHDRAW
+    // ==========================================================================
+
+    /// Withdraws USDC from the vault for a user.
     ///
-    /// Maximum total USDC that can be deposited in the vault.
-    /// Setting to 0 removes the cap.
+    /// The user must authorize this transaction with their signature.
+    /// The vault burns the shares backing `amount` (rounded up) and transfers
+    /// USDC from its balance to the user. Withdrawals below
+    /// `FavorableRoundingThreshold` round down instead while the favorable
+    /// rounding budget lasts. If the withdrawal would leave fewer than
+    /// `DustShareThreshold` shares, the remainder is redeemed too and the
+    /// user exits fully. Any `WithdrawalFeeBps` fee is deducted from the
+    /// USDC paid out.
     ///
     /// # Arguments
     /// * `env` - The Soroban environment
-    /// * `cap` - New TVL cap in USDC units (7 decimal places)
+    /// * `user` - The user address withdrawing funds (must authorize)
+    /// * `amount` - Amount of USDC to withdraw (7 decimal places)
     ///
     /// # Returns
-    /// Nothing. This function updates the cap and returns nothing.
+    /// Nothing. This function processes the withdrawal and returns nothing.
     ///
     /// # Panics
-    /// - If the caller is not the owner
+    /// - If the vault is paused, unless the user deposited within the last
+    ///   `PauseGraceSeconds`
+    /// - If amount is not positive
+    /// - If amount exceeds the per-transaction withdrawal cap
+    /// - If user has insufficient shares to cover the amount
+    /// - If the shares to burn would include locked shares
+    /// - If the USDC transfer fails
     ///
     /// # Events
-    /// Emits `LimitsUpdatedEvent` with old and new values for both limits
+    /// Emits `WithdrawEvent` with:
+    /// - `user`: The withdrawing user's address
+    /// - `amount`: The amount paid out, including any swept dust and net of
+    ///   any withdrawal fee
+    /// - `shares`: The shares burned
+    ///
+    /// Emits `WithdrawalFeeEvent` if a withdrawal fee is charged, and
+    /// `ReserveLowEvent` if the withdrawal drops on-chain liquidity
+    /// below `ReserveTargetBps` of total assets
     ///
     /// # Security
-    /// - Only the owner can modify the TVL cap
-    /// - Reducing the cap below current total deposits does not affect existing deposits
-    pub fn set_tvl_cap(env: Env, cap: i128) {
-        Self::require_is_owner(&env);
-
-        let old_tvl_cap = env.storage().instance().get(&DataKey::TvLCap).unwrap_or(0);
-        let old_user_cap = env
-            .storage()
-            .instance()
-            .get(&DataKey::UserDepositCap)
-            .unwrap_or(0);
-
-        env.storage().instance().set(&DataKey::TvLCap, &cap);
+    /// - `user.require_auth()` ensures users can only withdraw their own funds
+    /// - Share check is performed before any state updates
+    /// - Shares burned are rounded up so rounding never favors the withdrawer,
+    ///   except within the owner-funded favorable rounding budget
+    /// - Uses checks-effects-interactions pattern: shares updated before transfer
+    pub fn withdraw(env: Env, user: Address, amount: i128) {
+        user.require_auth();
 
-        env.events().publish(
-            (symbol_short!("lim_upd"),),
-            LimitsUpdatedEvent {
-                old_min: old_user_cap,
-                new_min: old_user_cap,
-                old_max: old_tvl_cap,
-                new_max: cap,
-            },
-        );
+        Self::process_withdrawal(&env, user, amount);
     }
 
-    /// Sets the maximum deposit amount per user.
+    /// Withdraws USDC for a user as their withdraw manager.
     ///
-    /// Maximum amount that any single user can have deposited in the vault.
-    /// Setting to 0 removes the cap.
+    /// For managed accounts: the manager the user assigned with
+    /// `set_withdraw_manager` may withdraw on their behalf, but the USDC is
+    /// always paid to the user, never to the manager or a third party.
+    /// Otherwise behaves exactly like `withdraw`.
     ///
     /// # Arguments
     /// * `env` - The Soroban environment
-    /// * `cap` - New per-user deposit cap in USDC units (7 decimal places)
+    /// * `manager` - The user's withdraw manager (must authorize)
+    /// * `user` - The user whose shares are withdrawn
+    /// * `amount` - Amount of USDC to withdraw (7 decimal places)
+    /// * `to` - The destination, which must be `user`
     ///
     /// # Returns
-    /// Nothing. This function updates the cap and returns nothing.
+    /// Nothing. This function processes the withdrawal and returns nothing.
     ///
     /// # Panics
-    /// - If the caller is not the owner
+    /// - If `manager` is not the user's withdraw manager
+    /// - If `to` is not the user
+    /// - Under the same conditions as `withdraw`
     ///
     /// # Events
-    /// Emits `LimitsUpdatedEvent` with old and new values for both limits
+    /// Emits the same events as `withdraw`
     ///
     /// # Security
-    /// - Only the owner can modify the user deposit cap
-    /// - Reducing the cap below a user's current balance does not affect them
-    pub fn set_user_deposit_cap(env: Env, cap: i128) {
-        Self::require_is_owner(&env);
+    /// - `manager.require_auth()` replaces the user's authorization
+    /// - The destination is pinned to the user, so a compromised manager
+    ///   key cannot move funds out of the user's control
+    pub fn withdraw_for(env: Env, manager: Address, user: Address, amount: i128, to: Address) {
+        manager.require_auth();
 
-        let old_tvl_cap = env.storage().instance().get(&DataKey::TvLCap).unwrap_or(0);
-        let old_user_cap = env
-            .storage()
-            .instance()
-            .get(&DataKey::UserDepositCap)
-            .unwrap_or(0);
+        assert!(
+            Self::get_withdraw_manager(env.clone(), user.clone()) == Some(manager),
+            "Not the withdraw manager"
+        );
+        assert!(to == user, "Destination must be the user");
 
-        env.storage().instance().set(&DataKey::UserDepositCap, &cap);
+        Self::process_withdrawal(&env, user, amount);
+    }
+
+    /// Assigns or clears the manager allowed to withdraw for a user.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `user` - The user assigning the manager (must authorize)
+    /// * `manager` - The manager address, or `None` to revoke
+    ///
+    /// # Returns
+    /// Nothing. This function updates the manager and returns nothing.
+    ///
+    /// # Panics
+    /// - If `manager` is the user
+    ///
+    /// # Events
+    /// Emits `WithdrawManagerUpdatedEvent` with the user and new manager
+    pub fn set_withdraw_manager(env: Env, user: Address, manager: Option<Address>) {
+        user.require_auth();
+
+        let key = PositionKey::WithdrawManager(user.clone());
+        match &manager {
+            Some(manager) => {
+                assert!(*manager != user, "Manager cannot be the user");
+                env.storage().persistent().set(&key, manager);
+            }
+            None => env.storage().persistent().remove(&key),
+        }
 
         env.events().publish(
-            (symbol_short!("lim_upd"),),
-            LimitsUpdatedEvent {
-                old_min: old_user_cap,
-                new_min: cap,
-                old_max: old_tvl_cap,
-                new_max: old_tvl_cap,
-            },
+            (symbol_short!("wd_mgr"),),
+            WithdrawManagerUpdatedEvent { user, manager },
         );
     }
 
-    /// Sets both the user deposit cap (min) and TVL cap (max) in a single transaction.
+    /// Returns the manager allowed to withdraw for a user, if any.
     ///
-    /// This function allows updating both limits atomically and emits a single
-    /// `LimitsUpdatedEvent` with all old and new values.
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `user` - The user address to query
+    ///
+    /// # Returns
+    /// The withdraw manager, or `None` if none is assigned
+    pub fn get_withdraw_manager(env: Env, user: Address) -> Option<Address> {
+        env.storage()
+            .persistent()
+            .get(&PositionKey::WithdrawManager(user))
+    }
+
+    /// Opts a user in to or out of auto-compounding withdrawal fees.
+    ///
+    /// While opted in, the `WithdrawalFeeBps` fee on the user's exits is
+    /// still withheld from the payout, but shares worth it are minted back
+    /// to the user instead of the USDC accruing to remaining holders. This
+    /// waives the fee in effect while every exit keeps the same accounting.
     ///
     /// # Arguments
     /// * `env` - The Soroban environment
-    /// * `min` - New per-user deposit cap in USDC units (7 decimal places)
-    /// * `max` - New TVL cap in USDC units (7 decimal places)
+    /// * `user` - The user changing the setting (must authorize)
+    /// * `enabled` - `true` to opt in, `false` to opt out
     ///
     /// # Returns
-    /// Nothing. This function updates both caps and returns nothing.
+    /// Nothing. This function updates the setting and returns nothing.
     ///
     /// # Panics
-    /// - If the caller is not the owner
+    /// None
     ///
     /// # Events
-    /// Emits `LimitsUpdatedEvent` with:
-    /// - `old_min`: Previous user deposit cap
-    /// - `new_min`: New user deposit cap
-    /// - `old_max`: Previous TVL cap
-    /// - `new_max`: New TVL cap
-    ///
-    /// # Security
-    /// - Only the owner can modify the limits
-    pub fn set_limits(env: Env, min: i128, max: i128) {
-        Self::require_is_owner(&env);
-
-        let old_user_cap = env
-            .storage()
-            .instance()
-            .get(&DataKey::UserDepositCap)
-            .unwrap_or(0);
-        let old_tvl_cap = env.storage().instance().get(&DataKey::TvLCap).unwrap_or(0);
+    /// Emits `AutoCompoundUpdatedEvent` with the user and new setting
+    pub fn set_auto_compound(env: Env, user: Address, enabled: bool) {
+        user.require_auth();
 
-        env.storage().instance().set(&DataKey::UserDepositCap, &min);
-        env.storage().instance().set(&DataKey::TvLCap, &max);
+        let key = PositionKey::AutoCompound(user.clone());
+        if enabled {
+            env.storage().persistent().set(&key, &true);
+        } else {
+            env.storage().persistent().remove(&key);
+        }
 
         env.events().publish(
-            (symbol_short!("lim_upd"),),
-            LimitsUpdatedEvent {
-                old_min: old_user_cap,
-                new_min: min,
-                old_max: old_tvl_cap,
-                new_max: max,
-            },
+            (symbol_short!("auto_cmp"),),
+            AutoCompoundUpdatedEvent { user, enabled },
         );
     }
 
-    /// Returns the current TVL cap.
+    /// Returns whether a user has opted in to auto-compounding withdrawal fees.
     ///
     /// # Arguments
     /// * `env` - The Soroban environment
+    /// * `user` - The user address to query
     ///
     /// # Returns
-    /// The current TVL cap in USDC units (7 decimal places), or 0 if no cap
-    pub fn get_tvl_cap(env: Env) -> i128 {
-        env.storage().instance().get(&DataKey::TvLCap).unwrap_or(0)
+    /// `true` if the user's withdrawal fees are re-minted to them as shares
+    pub fn get_auto_compound(env: Env, user: Address) -> bool {
+        env.storage()
+            .persistent()
+            .has(&PositionKey::AutoCompound(user))
     }
 
-    /// Returns the current per-user deposit cap.
+    /// Withdraws USDC, reverting if the share price moved beyond tolerance.
     ///
     /// # Arguments
     /// * `env` - The Soroban environment
+    /// * `user` - The user address making the withdrawal (must authorize)
+    /// * `amount` - Amount of USDC to withdraw (7 decimal places)
+    /// * `quoted_price` - Share price scaled by `SHARE_PRICE_SCALE`
     ///
     /// # Returns
-    /// The current per-user deposit cap in USDC units (7 decimal places), or 0 if no cap
-    pub fn get_user_deposit_cap(env: Env) -> i128 {
-        env.storage()
-            .instance()
-            .get(&DataKey::UserDepositCap)
-            .unwrap_or(0)
+    /// Nothing. This function performs the withdrawal and returns nothing.
+    ///
+    /// # Panics
+    /// - If the current share price deviates from `quoted_price` by more than
+    ///   `MaxSlippageBps`
+    /// - Any panic raised by `withdraw`
+    ///
+    /// # Events
+    /// Emits `WithdrawEvent` as `withdraw` does
+    pub fn withdraw_with_quote(env: Env, user: Address, amount: i128, quoted_price: i128) {
+        Self::require_within_slippage(&env, quoted_price);
+        Self::withdraw(env, user, amount);
     }
 
-    /// Updates the authorized AI agent address.
+    /// Redeems an exact number of shares for USDC.
     ///
-    /// Only the owner can update the agent. This allows for agent key rotation
-    /// or migration to a new agent implementation.
+    /// Unlike `withdraw`, which burns the shares a USDC amount costs
+    /// rounded up, this burns exactly `shares` and pays their value rounded
+    /// down, as `preview_redeem` reports, less any `WithdrawalFeeBps` fee.
+    /// Redeeming the full balance closes a position without leaving dust
+    /// shares behind.
     ///
     /// # Arguments
     /// * `env` - The Soroban environment
-    /// * `new_agent` - The new AI agent address
+    /// * `user` - The user redeeming shares (must authorize)
+    /// * `shares` - Number of shares to redeem
     ///
     /// # Returns
-    /// Nothing. This function updates the agent and returns nothing.
+    /// The USDC transferred to the user, net of any withdrawal fee
     ///
     /// # Panics
-    /// - If the caller is not the owner
+    /// - If the vault is paused and the user is outside the pause grace
+    /// - If shares is not positive
+    /// - If user has insufficient shares, or the shares include locked shares
+    /// - If the shares are worth no USDC
+    /// - If the payout exceeds `MaxWithdrawPerTx`
     ///
     /// # Events
-    /// Emits `AgentUpdatedEvent` with:
-    /// - `old_agent`: Previous agent address
-    /// - `new_agent`: New agent address
+    /// Emits `WithdrawEvent` with the user, amount paid and shares burned,
+    /// and `WithdrawalFeeEvent` if a withdrawal fee is charged
     ///
     /// # Security
-    /// - Only the owner can update the agent
-    /// - The old agent will immediately lose access to rebalance()
-    pub fn update_agent(env: Env, new_agent: Address) {
-        Self::require_is_owner(&env);
+    /// - `user.require_auth()` ensures users can only redeem their own shares
+    /// - Shares are burned before the transfer (checks-effects-interactions)
+    pub fn redeem(env: Env, user: Address, shares: i128) -> i128 {
+        user.require_auth();
 
-        let old_agent: Address = env.storage().instance().get(&DataKey::Agent).unwrap();
+        assert!(shares > 0, "Shares must be positive");
 
-        env.storage().instance().set(&DataKey::Agent, &new_agent);
+        let user_shares = Self::get_shares(env.clone(), user.clone());
+        assert!(user_shares >= shares, "Insufficient balance");
+        Self::require_within_total_shares(&env, shares);
+        Self::require_unlocked_shares(&env, &user, user_shares, shares);
+
+        Self::accrue_management_fee(&env);
+        let amount = Self::preview_redeem(env.clone(), shares);
+        assert!(amount > 0, "Redemption too small");
+
+        let amount = Self::process_exit(&env, &user, shares, amount, &user, None, true);
+
+        Self::publish_withdraw_event(&env, user, amount, shares);
+        amount
+    }
+
+    /// Redeems a user's shares and deposits the USDC into a partner strategy.
+    ///
+    /// The USDC never reaches the user's wallet: the vault transfers it to
+    /// `strategy` and calls `deposit_for` so the strategy credits the user.
+    /// Any `WithdrawalFeeBps` fee is deducted first, as `redeem` does.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `user` - The user redeeming shares (must authorize)
+    /// * `shares` - Number of shares to redeem
+    /// * `strategy` - The registered strategy receiving the USDC
+    ///
+    /// # Returns
+    /// A `StrategyRedemption` receipt with the shares burned, the USDC moved
+    /// net of any withdrawal fee, and the position the strategy credited to
+    /// the user
+    ///
+    /// # Panics
+    /// - If the vault is paused and the user is outside the pause grace
+    /// - If shares is not positive
+    /// - If `strategy` is not registered
+    /// - If user has insufficient shares, or the shares include locked shares
+    /// - If the shares are worth no USDC
+    /// - If the amount exceeds `MaxWithdrawPerTx`
+    ///
+    /// # Events
+    /// Emits `StrategyRedemptionEvent` with the user, strategy, shares, amount
+    /// and strategy units, and `WithdrawalFeeEvent` if a withdrawal fee is
+    /// charged
+    ///
+    /// # Security
+    /// - `user.require_auth()` ensures users can only redeem their own shares
+    /// - Only strategies registered by the owner can receive funds
+    /// - Shares are burned before the transfer (checks-effects-interactions)
+    pub fn redeem_to_strategy(
+        env: Env,
+        user: Address,
+        shares: i128,
+        strategy: Address,
+    ) -> StrategyRedemption {
+        user.require_auth();
+
+        assert!(shares > 0, "Shares must be positive");
+        assert!(
+            Self::get_strategies(env.clone()).contains(&strategy),
+            "Strategy not registered"
+        );
+
+        let user_shares = Self::get_shares(env.clone(), user.clone());
+        assert!(user_shares >= shares, "Insufficient balance");
+        Self::require_within_total_shares(&env, shares);
+        Self::require_unlocked_shares(&env, &user, user_shares, shares);
+
+        Self::accrue_management_fee(&env);
+        let amount = Self::convert_to_assets(env.clone(), shares);
+        assert!(amount > 0, "Redemption too small");
+
+        let amount = Self::process_exit(&env, &user, shares, amount, &strategy, None, true);
+        let strategy_units =
+            PartnerStrategyClient::new(&env, &strategy).deposit_for(&user, &amount);
 
         env.events().publish(
-            (symbol_short!("agent_upd"),),
-            AgentUpdatedEvent {
-                old_agent: old_agent.clone(),
-                new_agent: new_agent.clone(),
+            (symbol_short!("rdm_strat"),),
+            StrategyRedemptionEvent {
+                user,
+                strategy: strategy.clone(),
+                shares,
+                amount,
+                strategy_units,
             },
         );
+
+        StrategyRedemption {
+            strategy,
+            shares,
+            amount,
+            strategy_units,
+        }
     }
 
-    /// Updates the total assets tracked by the vault.
+    // ==========================================================================
+    // CORE LIFECYCLE - WITHDRAWAL QUEUE
+    // ==========================================================================
+
+    /// Queues a withdrawal of `shares`, claimable after the withdrawal cooldown.
     ///
-    /// This function allows the owner to manually adjust the total assets
-    /// value, typically used for reconciliation or accounting corrections.
-    /// Use with caution as it affects TVL calculations.
+    /// The shares are moved out of the user's balance into escrow. They stay
+    /// part of `TotalShares` until claimed, so they continue to earn yield.
+    /// This gives the agent time to pull liquidity from strategies.
     ///
     /// # Arguments
     /// * `env` - The Soroban environment
-    /// * `new_total` - New total assets value in USDC units (7 decimal places)
+    /// * `user` - The user queueing the withdrawal (must authorize)
+    /// * `shares` - Number of shares to queue
     ///
     /// # Returns
-    /// Nothing. This function updates total assets and returns nothing.
+    /// The id of the new withdrawal request
     ///
     /// # Panics
-    /// - If the caller is not the owner
+    /// - If the vault is paused
+    /// - If shares is not positive
+    /// - If `shares` are worth less than `MIN_DEPOSIT` at the current price
+    /// - If `MaxWithdrawPerTx` is set and `shares` are worth more than it
+    /// - If `MAX_PENDING_REQUESTS` requests are already pending
+    /// - If the user holds fewer than `shares`
+    /// - If fewer than `shares` of the user's shares are unlocked
     ///
     /// # Events
-    /// Emits `AssetsUpdatedEvent` with:
-    /// - `old_total`: Previous total assets
-    /// - `new_total`: New total assets
+    /// Emits `WithdrawalRequestedEvent` with the request id, user, shares and unlock time
     ///
     /// # Security
-    /// - Only the owner can update total assets
-    /// - This should only be used for reconciliation, not for manipulating balances
-    pub fn update_total_assets(env: Env, new_total: i128) {
-        Self::require_is_owner(&env);
+    /// - `user.require_auth()` ensures only the holder can queue their shares
+    pub fn request_withdrawal(env: Env, user: Address, shares: i128) -> u64 {
+        user.require_auth();
+
+        Self::require_not_paused(&env);
+        Self::require_positive_amount(shares);
+        let value = Self::convert_to_assets(env.clone(), shares);
+        assert!(value >= MIN_DEPOSIT, "Withdrawal request too small");
+        Self::require_within_withdraw_cap(&env, value);
+        let mut pending = Self::pending_request_ids(&env);
+        assert!(
+            pending.len() < MAX_PENDING_REQUESTS,
+            "Withdrawal queue full"
+        );
+
+        let user_shares = Self::get_shares(env.clone(), user.clone());
+        assert!(user_shares >= shares, "Insufficient balance");
+        Self::require_unlocked_shares(&env, &user, user_shares, shares);
+        Self::set_shares(&env, &user, user_shares - shares);
+        let cost_basis = Self::reduce_cost_basis(&env, &user, shares, user_shares);
+
+        let total_pending = Self::get_total_pending_shares(&env);
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalPendingShares, &(total_pending + shares));
+        Self::add_user_pending_shares(&env, &user, shares);
+
+        let request_id: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::NextRequestId)
+            .unwrap_or(1);
+        env.storage()
+            .instance()
+            .set(&DataKey::NextRequestId, &(request_id + 1));
+
+        let unlock_time = env.ledger().timestamp() + Self::get_withdrawal_cooldown(env.clone());
+        env.storage().persistent().set(
+            &DataKey::WithdrawalRequest(request_id),
+            &WithdrawalRequest {
+                id: request_id,
+                user: user.clone(),
+                shares,
+                unlock_time,
+                cost_basis,
+            },
+        );
+
+        // Keep the queue sorted by (unlock_time, id). Ids only grow, so the
+        // new request lands at the back unless the cooldown was shortened.
+        let mut index = pending.len();
+        while index > 0 {
+            let before =
+                Self::get_withdrawal_request(env.clone(), pending.get_unchecked(index - 1));
+            if before.unlock_time <= unlock_time {
+                break;
+            }
+            index -= 1;
+        }
+        pending.insert(index, request_id);
+        env.storage()
+            .persistent()
+            .set(&DataKey::PendingRequests, &pending);
+
+        env.events().publish(
+            (symbol_short!("wd_req"),),
+            WithdrawalRequestedEvent {
+                request_id,
+                user,
+                shares,
+                unlock_time,
+            },
+        );
+
+        request_id
+    }
+
+    /// Claims a queued withdrawal once its cooldown has elapsed.
+    ///
+    /// The escrowed shares are burned and paid out at the current share
+    /// price, less any `WithdrawalFeeBps` fee.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `user` - The user who queued the request (must authorize)
+    /// * `request_id` - The withdrawal request to claim
+    ///
+    /// # Returns
+    /// The amount of USDC transferred to the user net of any withdrawal fee
+    /// (7 decimal places)
+    ///
+    /// # Panics
+    /// - If the vault is paused and the user's last deposit is outside
+    ///   `PauseGraceSeconds`
+    /// - If the request does not exist or belongs to another user
+    /// - If the request is still in its cooldown
+    /// - If `MaxWithdrawPerTx` is set and the request is now worth more than it
+    /// - If the USDC transfer fails
+    ///
+    /// # Events
+    /// Emits `WithdrawalClaimedEvent` with the request id, user, shares and
+    /// amount paid, and `WithdrawalFeeEvent` if a withdrawal fee is charged
+    ///
+    /// # Security
+    /// - `user.require_auth()` ensures only the requester can claim
+    /// - The request is removed before the transfer (checks-effects-interactions)
+    pub fn claim_withdrawal(env: Env, user: Address, request_id: u64) -> i128 {
+        user.require_auth();
+
+        let request = Self::get_withdrawal_request(env.clone(), request_id);
+        assert!(request.user == user, "Not the request owner");
+        assert!(
+            env.ledger().timestamp() >= request.unlock_time,
+            "Withdrawal still in cooldown"
+        );
+
+        let mut pending = Self::pending_request_ids(&env);
+        if let Some(index) = pending.first_index_of(request_id) {
+            pending.remove(index);
+        }
+        env.storage()
+            .persistent()
+            .set(&DataKey::PendingRequests, &pending);
+
+        Self::settle_withdrawal_request(&env, &request)
+    }
+
+    /// Settles up to `limit` matured withdrawal requests on behalf of their owners.
+    ///
+    /// Requests are processed in a stable order: earliest `unlock_time` first,
+    /// with ties broken by ascending request id. Off-chain tooling can rely on
+    /// this order to predict which requests a batch will settle. A matured
+    /// request now worth more than `MaxWithdrawPerTx` is skipped and stays
+    /// queued, so it cannot block the requests behind it.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `limit` - Maximum number of requests to settle in this call
+    ///
+    /// # Returns
+    /// The ids of the settled requests, in processing order
+    ///
+    /// # Panics
+    /// - If the vault is paused
+    /// - If the caller is not the agent
+    /// - If the agent lacks `PERM_PROCESS_WITHDRAWALS`
+    /// - If a USDC transfer fails
+    ///
+    /// # Events
+    /// Emits `WithdrawalClaimedEvent` for each settled request, and
+    /// `WithdrawalFeeEvent` for each one charged a withdrawal fee
+    ///
+    /// # Security
+    /// - Only the agent can trigger batch settlement
+    /// - Funds are always paid to each request's owner
+    pub fn process_withdrawals(env: Env, limit: u32) -> Vec<u64> {
+        Self::require_not_paused(&env);
+        Self::require_agent_permission(&env, PERM_PROCESS_WITHDRAWALS);
+
+        // The queue is sorted by (unlock_time, id), so matured requests
+        // are always at the front
+        let now = env.ledger().timestamp();
+        let cap = Self::get_max_withdraw_per_tx(env.clone());
+        let mut pending = Self::pending_request_ids(&env);
+        let mut matured: Vec<WithdrawalRequest> = Vec::new(&env);
+        let mut index = 0;
+        while matured.len() < limit && index < pending.len() {
+            let request = Self::get_withdrawal_request(env.clone(), pending.get_unchecked(index));
+            if request.unlock_time > now {
+                break;
+            }
+            if cap > 0 && Self::convert_to_assets(env.clone(), request.shares) > cap {
+                index += 1;
+                continue;
+            }
+            pending.remove(index);
+            matured.push_back(request);
+        }
+        if matured.is_empty() {
+            return Vec::new(&env);
+        }
+        env.storage()
+            .persistent()
+            .set(&DataKey::PendingRequests, &pending);
+
+        let mut processed = Vec::new(&env);
+        for request in matured.iter() {
+            Self::settle_withdrawal_request(&env, &request);
+            processed.push_back(request.id);
+        }
+        processed
+    }
+
+    /// Returns a queued withdrawal request.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `request_id` - The request to query
+    ///
+    /// # Returns
+    /// The pending `WithdrawalRequest`
+    ///
+    /// # Panics
+    /// - If the request does not exist (never created or already claimed)
+    ///
+    /// # Events
+    /// None
+    pub fn get_withdrawal_request(env: Env, request_id: u64) -> WithdrawalRequest {
+        env.storage()
+            .persistent()
+            .get(&DataKey::WithdrawalRequest(request_id))
+            .expect("Withdrawal request not found")
+    }
+
+    /// Returns the aggregate of all pending withdrawal requests.
+    ///
+    /// Lets the agent arrange enough on-chain liquidity for upcoming claims.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// `(pending_shares, pending_assets)` where `pending_assets` is the current
+    /// USDC value of the pending shares
+    ///
+    /// # Panics
+    /// None
+    ///
+    /// # Events
+    /// None
+    pub fn get_total_pending_withdrawals(env: Env) -> (i128, i128) {
+        let pending_shares = Self::get_total_pending_shares(&env);
+        let pending_assets = Self::convert_to_assets(env, pending_shares);
+        (pending_shares, pending_assets)
+    }
+
+    /// Estimates when a withdrawal request for `shares` made now could be claimed.
+    ///
+    /// Every request waits out `WithdrawalCooldown`, and the queue ahead is
+    /// paid first. If the vault's liquid USDC (including any parked reserve)
+    /// covers the pending requests plus this one, the request becomes
+    /// claimable as soon as its cooldown ends. Otherwise it depends on the
+    /// agent returning funds from strategies, which cannot be predicted.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `shares` - Number of shares the request would queue
+    ///
+    /// # Returns
+    /// The estimated ledger timestamp at which the request is claimable, or
+    /// `u64::MAX` if current liquidity cannot cover the queue ahead plus
+    /// this request
+    ///
+    /// # Panics
+    /// - If shares is not positive
+    /// - If the implied share price is outside the configured sanity band
+    ///
+    /// # Events
+    /// None
+    pub fn estimate_wait(env: Env, shares: i128) -> u64 {
+        Self::require_positive_amount(shares);
+
+        let (_, pending_assets) = Self::get_total_pending_withdrawals(env.clone());
+        let needed = pending_assets + Self::convert_to_assets(env.clone(), shares);
+        let usdc_token = Self::get_usdc_token(env.clone());
+        let liquidity = token::Client::new(&env, &usdc_token)
+            .balance(&env.current_contract_address())
+            + Self::get_reserve_deployed(env.clone());
+        if liquidity < needed {
+            return u64::MAX;
+        }
+        env.ledger().timestamp() + Self::get_withdrawal_cooldown(env)
+    }
+
+    /// Returns a user's position split into liquid, locked and queued value.
+    ///
+    /// All values are at the current share price, so queued assets show
+    /// what the user's pending requests would pay if claimed now.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `user` - The user address to query
+    ///
+    /// # Returns
+    /// A `Withdrawable` breakdown in raw USDC units
+    ///
+    /// # Panics
+    /// - If the implied share price is outside the configured sanity band
+    ///
+    /// # Events
+    /// None
+    pub fn get_withdrawable_breakdown(env: Env, user: Address) -> Withdrawable {
+        let queued_shares = Self::user_pending_shares(&env, &user);
+        let shares = Self::get_shares(env.clone(), user.clone());
+        let locked_shares = Self::total_locked_shares(&env, &user);
+
+        Withdrawable {
+            liquid_assets: Self::convert_to_assets(env.clone(), shares - locked_shares),
+            locked_assets: Self::convert_to_assets(env.clone(), locked_shares),
+            queued_assets: Self::convert_to_assets(env, queued_shares),
+        }
+    }
+
+    /// Returns the most USDC a user can withdraw in one `withdraw` call.
+    ///
+    /// Values the user's unlocked shares at the current share price,
+    /// rounded down, and stops at `MaxWithdrawPerTx` when it is set.
+    /// Withdrawing this amount never burns more shares than the user holds.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `user` - The user address to query
+    ///
+    /// # Returns
+    /// The maximum withdrawal in raw USDC units, or 0 if the user holds no
+    /// unlocked shares
+    ///
+    /// # Panics
+    /// - If the implied share price is outside the configured sanity band
+    ///
+    /// # Events
+    /// None
+    pub fn max_withdraw(env: Env, user: Address) -> i128 {
+        let shares = Self::unlocked_shares(&env, &user);
+        let assets = Self::convert_to_assets(env.clone(), shares);
+        match Self::get_max_withdraw_per_tx(env) {
+            0 => assets,
+            cap => assets.min(cap),
+        }
+    }
+
+    /// Returns the most shares a user can redeem in one `redeem` call.
+    ///
+    /// Counts the user's unlocked shares and, when `MaxWithdrawPerTx` is
+    /// set, stops at the shares whose value fits under it.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `user` - The user address to query
+    ///
+    /// # Returns
+    /// The maximum redeemable share count, or 0 if the user holds no
+    /// unlocked shares
+    ///
+    /// # Panics
+    /// - If the implied share price is outside the configured sanity band
+    ///
+    /// # Events
+    /// None
+    pub fn max_redeem(env: Env, user: Address) -> i128 {
+        let shares = Self::unlocked_shares(&env, &user);
+        match Self::get_max_withdraw_per_tx(env.clone()) {
+            0 => shares,
+            cap => shares.min(Self::convert_to_shares(env, cap)),
+        }
+    }
+
+    /// Returns the soonest unlock time among pending withdrawal requests.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The earliest `unlock_time`, or 0 if no requests are pending
+    ///
+    /// # Panics
+    /// None
+    ///
+    /// # Events
+    /// None
+    pub fn get_next_claimable_timestamp(env: Env) -> u64 {
+        // The queue is sorted by unlock time, so the front is the soonest
+        match Self::pending_request_ids(&env).first() {
+            Some(request_id) => Self::get_withdrawal_request(env, request_id).unlock_time,
+            None => 0,
+        }
+    }
+
+    // ==========================================================================
+    // CORE LIFECYCLE - POSITION SPLIT
+    // ==========================================================================
+
+    /// Moves `shares` from a user's position to another address.
+    ///
+    /// Intended for estate planning or splitting an account. The cost basis
+    /// moves pro rata with the shares, so each side keeps a proportional
+    /// basis and the aggregate cost basis is unchanged.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `user` - The holder splitting their position (must authorize)
+    /// * `to` - The address receiving the shares
+    /// * `shares` - Number of shares to move
+    ///
+    /// # Returns
+    /// Nothing. This function moves shares and returns nothing.
+    ///
+    /// # Panics
+    /// - If splits are disabled by the feature flags
+    /// - If the vault is paused
+    /// - If shares is not positive
+    /// - If `to` is the same address as `user`
+    /// - If the user holds fewer than `shares`
+    /// - If `shares` exceeds the total shares outstanding
+    /// - If fewer than `shares` of the user's shares are unlocked
+    /// - If `to` is a new depositor and the depositor cap is reached
+    ///
+    /// # Events
+    /// Emits `SplitEvent` with both addresses, the shares and the cost basis moved
+    ///
+    /// # Security
+    /// - `user.require_auth()` ensures only the holder can split their position
+    pub fn split_position(env: Env, user: Address, to: Address, shares: i128) {
+        user.require_auth();
+
+        Self::require_feature_enabled(&env, FEATURE_SPLITS);
+        Self::require_not_paused(&env);
+        Self::require_positive_amount(shares);
+        assert!(user != to, "Cannot split to self");
+
+        let user_shares = Self::get_shares(env.clone(), user.clone());
+        assert!(user_shares >= shares, "Insufficient balance");
+        Self::require_within_total_shares(&env, shares);
+        Self::require_unlocked_shares(&env, &user, user_shares, shares);
+        Self::require_depositor_slot(&env, &to);
+
+        Self::set_shares(&env, &user, user_shares - shares);
+        let cost_basis = Self::reduce_cost_basis(&env, &user, shares, user_shares);
+
+        let to_shares = Self::get_shares(env.clone(), to.clone());
+        Self::set_shares(&env, &to, to_shares + shares);
+        Self::add_cost_basis(&env, &to, cost_basis);
+
+        let principal = Self::get_protected_principal(env.clone(), user.clone());
+        let moved_principal = if shares >= user_shares {
+            principal
+        } else {
+            principal * shares / user_shares
+        };
+        Self::reduce_protected_principal(&env, &user, moved_principal);
+        Self::add_protected_principal(&env, &to, moved_principal);
+
+        env.events().publish(
+            (symbol_short!("split"),),
+            SplitEvent {
+                from: user,
+                to,
+                shares,
+                cost_basis,
+            },
+        );
+    }
+
+    // ==========================================================================
+    // CORE LIFECYCLE - SHARE LOCKS
+    // ==========================================================================
+
+    /// Locks some of a user's shares to a locker, e.g. as loan collateral.
+    ///
+    /// Locked shares stay in the user's balance and keep earning yield, but
+    /// cannot be withdrawn, queued for withdrawal or split off until the
+    /// locker releases them.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `user` - The holder locking their shares (must authorize)
+    /// * `amount` - Number of shares to lock
+    /// * `locker` - The address allowed to release the lock
+    ///
+    /// # Returns
+    /// Nothing. This function records the lock and returns nothing.
+    ///
+    /// # Panics
+    /// - If lockups are disabled by the feature flags
+    /// - If amount is not positive
+    /// - If the user holds fewer than `amount` unlocked shares
+    ///
+    /// # Events
+    /// Emits `SharesLockedEvent` with the user, locker and shares
+    ///
+    /// # Security
+    /// - `user.require_auth()` ensures only the holder can pledge their shares
+    pub fn lock_shares(env: Env, user: Address, amount: i128, locker: Address) {
+        user.require_auth();
+        Self::require_feature_enabled(&env, FEATURE_LOCKUPS);
+        Self::require_positive_amount(amount);
+
+        let user_shares = Self::get_shares(env.clone(), user.clone());
+        Self::require_unlocked_shares(&env, &user, user_shares, amount);
+
+        let locked = Self::get_locked_shares_by(&env, &user, &locker);
+        env.storage().persistent().set(
+            &DataKey::LockedShares(user.clone(), locker.clone()),
+            &(locked + amount),
+        );
+        let total_locked = Self::get_locked_shares(env.clone(), user.clone());
+        env.storage().persistent().set(
+            &DataKey::UserLockedShares(user.clone()),
+            &(total_locked + amount),
+        );
+
+        env.events().publish(
+            (symbol_short!("lock"),),
+            SharesLockedEvent {
+                user,
+                locker,
+                shares: amount,
+            },
+        );
+    }
+
+    /// Releases shares a user locked to the calling locker.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `locker` - The locker releasing the shares (must authorize)
+    /// * `user` - The holder whose shares are released
+    /// * `amount` - Number of shares to release
+    ///
+    /// # Returns
+    /// Nothing. This function releases the lock and returns nothing.
+    ///
+    /// # Panics
+    /// - If amount is not positive
+    /// - If amount exceeds the shares the user locked to this locker
+    ///
+    /// # Events
+    /// Emits `SharesUnlockedEvent` with the user, locker and shares
+    ///
+    /// # Security
+    /// - `locker.require_auth()` ensures only the locker can release its collateral
+    pub fn unlock_shares(env: Env, locker: Address, user: Address, amount: i128) {
+        locker.require_auth();
+        Self::require_positive_amount(amount);
+
+        let locked = Self::get_locked_shares_by(&env, &user, &locker);
+        assert!(amount <= locked, "Exceeds locked shares");
+        env.storage().persistent().set(
+            &DataKey::LockedShares(user.clone(), locker.clone()),
+            &(locked - amount),
+        );
+        let total_locked = Self::get_locked_shares(env.clone(), user.clone());
+        env.storage().persistent().set(
+            &DataKey::UserLockedShares(user.clone()),
+            &(total_locked - amount),
+        );
+
+        env.events().publish(
+            (symbol_short!("unlock"),),
+            SharesUnlockedEvent {
+                user,
+                locker,
+                shares: amount,
+            },
+        );
+    }
+
+    /// Returns the shares a user has locked across all lockers.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `user` - The user address to query
+    ///
+    /// # Returns
+    /// The locked share count
+    pub fn get_locked_shares(env: Env, user: Address) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::UserLockedShares(user))
+            .unwrap_or(0)
+    }
+
+    /// Deposits USDC and locks the minted shares for a boosted share of yield.
+    ///
+    /// The multiplier grows linearly with `lock_seconds`, from 1x up to
+    /// `MAX_BOOST_MULTIPLIER` at `MAX_BOOST_LOCK`. Until the lock ends, each
+    /// positive `update_total_assets` gives the boosted shares their
+    /// multiplied part of the gain, paid in bonus shares at the expense of
+    /// unboosted holders, and the shares cannot be withdrawn, queued, split
+    /// or pledged.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `user` - The user address making the deposit (must authorize)
+    /// * `amount` - Amount of USDC to deposit (7 decimal places)
+    /// * `lock_seconds` - How long the minted shares stay locked
+    ///
+    /// # Returns
+    /// The yield multiplier granted, scaled by `BOOST_SCALE`
+    ///
+    /// # Panics
+    /// - If lockups are disabled by the feature flags
+    /// - If lock_seconds is zero
+    /// - If the user already has an active boost
+    /// - Any panic raised by `deposit`
+    ///
+    /// # Events
+    /// Emits `DepositEvent` as `deposit` does, then `BoostedDepositEvent`
+    /// with the shares, multiplier and unlock time
+    pub fn deposit_boosted(env: Env, user: Address, amount: i128, lock_seconds: u64) -> i128 {
+        Self::require_feature_enabled(&env, FEATURE_LOCKUPS);
+        assert!(lock_seconds > 0, "Lock must be positive");
+        let now = env.ledger().timestamp();
+        let mut boosts = Vec::new(&env);
+        for boost in Self::get_boosts(&env).iter() {
+            if boost.unlock_time > now {
+                assert!(boost.user != user, "Boost already active");
+                boosts.push_back(boost);
+            }
+        }
+
+        let shares_before = Self::get_shares(env.clone(), user.clone());
+        Self::deposit(env.clone(), user.clone(), amount);
+        let shares = Self::get_shares(env.clone(), user.clone()) - shares_before;
+
+        let multiplier = BOOST_SCALE
+            + (MAX_BOOST_MULTIPLIER - BOOST_SCALE) * lock_seconds.min(MAX_BOOST_LOCK) as i128
+                / MAX_BOOST_LOCK as i128;
+        let unlock_time = now + lock_seconds;
+        boosts.push_back(Boost {
+            user: user.clone(),
+            shares,
+            multiplier,
+            unlock_time,
+        });
+        env.storage()
+            .persistent()
+            .set(&PositionKey::Boosts, &boosts);
+
+        env.events().publish(
+            (symbol_short!("boost"),),
+            BoostedDepositEvent {
+                user,
+                shares,
+                multiplier,
+                unlock_time,
+            },
+        );
+        multiplier
+    }
+
+    /// Returns the yield multiplier of a user's active boost.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `user` - The user address to query
+    ///
+    /// # Returns
+    /// The multiplier scaled by `BOOST_SCALE`, or `BOOST_SCALE` if the user
+    /// has no active boost
+    pub fn get_boost_multiplier(env: Env, user: Address) -> i128 {
+        match Self::active_boost(&env, &user) {
+            Some(boost) => boost.multiplier,
+            None => BOOST_SCALE,
+        }
+    }
+
+    // ==========================================================================
+    // CORE LIFECYCLE - REBALANCE
+    // ==========================================================================
+
+    /// Rebalances vault funds between yield strategies.
+    ///
+    /// Only the authorized AI agent can call this function. The agent uses
+    /// this to move funds between different yield-generating protocols based
+    /// on market conditions and strategy performance.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `protocol` - The target protocol symbol (e.g., "conservative", "balanced", "growth")
+    /// * `expected_apy` - Expected APY in basis points (e.g., 850 = 8.5%)
+    ///
+    /// # Returns
+    /// Nothing. This function triggers rebalancing and returns nothing.
+    ///
+    /// # Panics
+    /// - If the vault is paused
+    /// - If rebalancing is paused
+    /// - If the caller is not the authorized agent
+    /// - If the agent lacks `PERM_REBALANCE`
+    ///
+    /// # Events
+    /// Emits `RebalanceEvent` with:
+    /// - `protocol`: The target protocol
+    /// - `expected_apy`: Expected APY in basis points
+    ///
+    /// # Security
+    /// - `agent.require_auth()` ensures only the authorized AI agent can rebalance
+    /// - Agent is set during initialization and can be updated by owner
+    /// - This function does NOT transfer funds - it's a signal to external protocols
+    /// - Phase 2 will add actual protocol interactions (Blend, DEX)
+    pub fn rebalance(env: Env, protocol: Symbol, expected_apy: i128) {
+        Self::require_not_paused(&env);
+        Self::require_rebalance_not_paused(&env);
+        Self::require_agent_permission(&env, PERM_REBALANCE);
+
+        env.events().publish(
+            (symbol_short!("rebalance"),),
+            RebalanceEvent {
+                protocol,
+                expected_apy,
+            },
+        );
+    }
+
+    /// Moves USDC from the vault into a registered strategy.
+    ///
+    /// The deployed USDC remains part of total assets; only its location
+    /// changes, so the share price is unaffected.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `strategy` - A registered strategy address
+    /// * `amount` - Amount of USDC to deploy (7 decimal places)
+    ///
+    /// # Returns
+    /// Nothing. This function transfers funds and returns nothing.
+    ///
+    /// # Panics
+    /// - If the vault is paused
+    /// - If rebalancing is paused
+    /// - If the caller is not the authorized agent
+    /// - If the agent lacks `PERM_DEPLOY_FUNDS`
+    /// - If amount is not positive
+    /// - If the strategy is not registered
+    /// - If amount exceeds the vault's on-chain USDC balance plus the parked reserve
+    ///
+    /// # Events
+    /// Emits `FundsRebalancedEvent` with the strategy and amount
+    ///
+    /// # Security
+    /// - Only the agent can move funds, and only to owner-registered strategies
+    pub fn rebalance_funds(env: Env, strategy: Address, amount: i128) {
+        Self::require_not_paused(&env);
+        Self::require_rebalance_not_paused(&env);
+        Self::require_agent_permission(&env, PERM_DEPLOY_FUNDS);
+
+        Self::deploy_to_strategy(&env, strategy, amount);
+    }
+
+    /// Moves USDC into a strategy, signals the rebalance and reports the new
+    /// total assets in one call.
+    ///
+    /// Combines `rebalance_funds`, `rebalance` and `update_total_assets`, so
+    /// the fund movement, the allocation and the assets report land
+    /// together: if any step fails, none of them take effect.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `protocol` - The target protocol symbol (e.g., "conservative", "balanced", "growth")
+    /// * `expected_apy` - Expected APY in basis points (e.g., 850 = 8.5%)
+    /// * `strategy` - A registered strategy address
+    /// * `amount` - Amount of USDC to deploy (7 decimal places)
+    /// * `new_total` - New total assets value in USDC units (7 decimal places)
+    ///
+    /// # Returns
+    /// Nothing. This function rebalances and updates total assets and
+    /// returns nothing.
+    ///
+    /// # Panics
+    /// - If the caller is not the authorized agent
+    /// - If the agent lacks `PERM_REBALANCE` or `PERM_DEPLOY_FUNDS`
+    /// - If the owner has not authorized the call, when the owner is not
+    ///   also the agent; with multi-sig on, if the `("assets", new_total)`
+    ///   action lacks `threshold` approvals instead
+    /// - Under the same conditions as `rebalance_funds` and `update_total_assets`
+    ///
+    /// # Events
+    /// Emits `FundsRebalancedEvent`, `RebalanceEvent` and `AssetsUpdatedEvent`,
+    /// plus any event `update_total_assets` emits
+    ///
+    /// # Security
+    /// - Reporting total assets stays an owner power: a separate agent
+    ///   cannot use this to move the share price on its own
+    pub fn rebalance_and_update(
+        env: Env,
+        protocol: Symbol,
+        expected_apy: i128,
+        strategy: Address,
+        amount: i128,
+        new_total: i128,
+    ) {
+        Self::require_not_paused(&env);
+        Self::require_rebalance_not_paused(&env);
+        Self::require_agent_permission(&env, PERM_REBALANCE | PERM_DEPLOY_FUNDS);
+        if !Self::roles_conflated(env.clone()) {
+            Self::require_owner_approval(&env, symbol_short!("assets"), new_total);
+        }
+
+        Self::deploy_to_strategy(&env, strategy, amount);
+        env.events().publish(
+            (symbol_short!("rebalance"),),
+            RebalanceEvent {
+                protocol,
+                expected_apy,
+            },
+        );
+        Self::apply_total_assets(&env, new_total);
+    }
+
+    /// Records the agent's attestation of USDC deployed to external strategies.
+    ///
+    /// The attestation is informational: it feeds `proof_of_reserves()` but
+    /// does not change total assets or the share price.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `amount` - USDC currently deployed outside the vault (7 decimal places)
+    ///
+    /// # Returns
+    /// Nothing. This function stores the attestation and returns nothing.
+    ///
+    /// # Panics
+    /// - If the caller is not the authorized agent
+    /// - If amount is negative
+    ///
+    /// # Events
+    /// Emits `ReservesAttestedEvent` with the amount and ledger timestamp
+    pub fn attest_reserves(env: Env, amount: i128) {
+        Self::require_is_agent(&env);
+        assert!(amount >= 0, "Amount must be non-negative");
+
+        let timestamp = env.ledger().timestamp();
+        env.storage().instance().set(
+            &DataKey::ReserveAttestation,
+            &ReserveAttestation { amount, timestamp },
+        );
+
+        env.events().publish(
+            (symbol_short!("attest"),),
+            ReservesAttestedEvent { amount, timestamp },
+        );
+    }
+
+    // ==========================================================================
+    // ADMINISTRATIVE - OWNER APPROVALS
+    // ==========================================================================
+
+    /// Replaces the single owner with an M-of-N signer set for critical actions.
+    ///
+    /// While a threshold is set, `update_agent`, `upgrade`, `set_owners`,
+    /// `transfer_ownership`, `update_total_assets`, `register_strategy`,
+    /// `set_reserve_strategy`, `set_fee_recipient`, the fee setters and
+    /// `set_fee_holiday` no longer accept the owner's signature alone: each
+    /// call only executes once `threshold` signers have approved its action
+    /// hash through `approve_action`. Passing an empty set with threshold 0
+    /// returns control to the single owner. Approvals from signers the new
+    /// set drops stop counting.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `owners` - The signer addresses (no duplicates)
+    /// * `threshold` - Approvals required, from 1 to the number of signers
+    ///
+    /// # Returns
+    /// Nothing. This function updates the signer set and returns nothing.
+    ///
+    /// # Panics
+    /// - If multi-sig is off and the caller is not the owner
+    /// - If multi-sig is on and the action lacks `threshold` approvals
+    /// - If the signer set contains duplicates
+    /// - If threshold is 0 with signers, or exceeds the number of signers
+    ///
+    /// # Events
+    /// Emits `OwnersUpdatedEvent` with the new signers and threshold
+    ///
+    /// # Security
+    /// - Changing the signer set is itself a critical action
+    pub fn set_owners(env: Env, owners: Vec<Address>, threshold: u32) {
+        Self::require_owner_approval(&env, symbol_short!("owners"), (owners.clone(), threshold));
+        for (i, owner) in owners.iter().enumerate() {
+            assert!(
+                owners.first_index_of(&owner) == Some(i as u32),
+                "Duplicate owner"
+            );
+        }
+        assert!(
+            threshold <= owners.len() && (threshold > 0 || owners.is_empty()),
+            "Invalid threshold"
+        );
+
+        env.storage().instance().set(&ConfigKey::Owners, &owners);
+        env.storage()
+            .instance()
+            .set(&ConfigKey::Threshold, &threshold);
+
+        env.events().publish(
+            (symbol_short!("owners"),),
+            OwnersUpdatedEvent { owners, threshold },
+        );
+    }
+
+    /// Records a signer's approval of a critical action.
+    ///
+    /// The action hash commits to the action and its arguments; see
+    /// `get_action_hash`. Approvals are consumed when the action executes.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `signer` - The approving signer (must authorize)
+    /// * `action_hash` - Hash of the action being approved
+    ///
+    /// # Returns
+    /// The number of approvals collected for the action
+    ///
+    /// # Panics
+    /// - If the signer is not in the owner set
+    /// - If the signer already approved this action
+    ///
+    /// # Events
+    /// Emits `ActionApprovedEvent` with the signer, hash and approval count
+    ///
+    /// # Security
+    /// - `signer.require_auth()` ensures approvals cannot be forged
+    pub fn approve_action(env: Env, signer: Address, action_hash: BytesN<32>) -> u32 {
+        signer.require_auth();
+        assert!(
+            Self::get_owners(env.clone()).contains(&signer),
+            "Not an owner signer"
+        );
+
+        let key = DataKey::ActionApprovals(action_hash.clone());
+        let mut approvals: Vec<Address> = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .unwrap_or(Vec::new(&env));
+        assert!(!approvals.contains(&signer), "Already approved");
+        approvals.push_back(signer.clone());
+        env.storage().persistent().set(&key, &approvals);
+
+        env.events().publish(
+            (symbol_short!("approve"),),
+            ActionApprovedEvent {
+                signer,
+                action_hash,
+                approvals: approvals.len(),
+            },
+        );
+
+        approvals.len()
+    }
+
+    /// Returns the hash signers approve for a critical action.
+    ///
+    /// Computed as SHA-256 of the XDR encoding of the `(action, args)`
+    /// tuple. Actions and their arguments are `("agent", new_agent)`,
+    /// `("upgrade", new_wasm_hash)`, `("owners", (owners, threshold))`,
+    /// `("owner", new_owner)`, `("assets", new_total)`,
+    /// `("strategy", strategy)`, `("reserve", strategy)`,
+    /// `("fee_rcpt", recipient)`, `("mgmt_fee", bps)`, `("perf_fee", bps)`,
+    /// `("wd_fee", bps)` and `("fee_hol", until_timestamp)`.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `action` - The action name
+    /// * `args` - The action's arguments
+    ///
+    /// # Returns
+    /// The 32-byte action hash
+    pub fn get_action_hash(env: Env, action: Symbol, args: Val) -> BytesN<32> {
+        env.crypto().sha256(&(action, args).to_xdr(&env)).to_bytes()
+    }
+
+    /// Returns the number of approvals collected for a pending action.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `action_hash` - Hash of the action
+    ///
+    /// # Returns
+    /// The approval count (0 once the action has executed). Only signers in
+    /// the current owner set count, so approvals from removed signers lapse.
+    pub fn get_approvals(env: Env, action_hash: BytesN<32>) -> u32 {
+        let owners = Self::get_owners(env.clone());
+        let approvals: Vec<Address> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::ActionApprovals(action_hash))
+            .unwrap_or(Vec::new(&env));
+        approvals
+            .iter()
+            .filter(|signer| owners.contains(signer))
+            .count() as u32
+    }
+
+    /// Returns the multi-sig signer set.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The signer addresses (empty when multi-sig is off)
+    pub fn get_owners(env: Env) -> Vec<Address> {
+        env.storage()
+            .instance()
+            .get(&ConfigKey::Owners)
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Returns the approvals required to execute a critical action.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The threshold, or 0 when multi-sig is off
+    pub fn get_threshold(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&ConfigKey::Threshold)
+            .unwrap_or(0)
+    }
+
+    /// Upgrades the contract code, preserving all storage state.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `new_wasm_hash` - Hash of the uploaded WASM to switch to
+    ///
+    /// # Returns
+    /// Nothing. This function replaces the contract code and returns nothing.
+    ///
+    /// # Panics
+    /// - If multi-sig is off and the caller is not the owner
+    /// - If multi-sig is on and the action lacks `threshold` approvals
+    ///
+    /// # Events
+    /// None (the host records the code update)
+    ///
+    /// # Security
+    /// - A critical action subject to multi-sig approval
+    pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) {
+        Self::require_owner_approval(&env, symbol_short!("upgrade"), new_wasm_hash.clone());
+        env.deployer().update_current_contract_wasm(new_wasm_hash);
+    }
+
+    // ==========================================================================
+    // ADMINISTRATIVE - OWNERSHIP
+    // ==========================================================================
+
+    /// Nominates a new owner, who takes over once they call `accept_ownership()`.
+    ///
+    /// Ownership only moves when the nominee proves control of the address,
+    /// so a mistyped address cannot lock the owner out. Nominating again
+    /// replaces the pending owner.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `new_owner` - The address to nominate
+    ///
+    /// # Returns
+    /// Nothing. This function records the nomination and returns nothing.
+    ///
+    /// # Panics
+    /// - If multi-sig is off and the caller is not the owner
+    /// - If multi-sig is on and the action lacks `threshold` approvals
+    /// - If `new_owner` is the current owner
+    /// - If `new_owner` is the guardian
+    /// - If `new_owner` is the USDC token or the vault itself
+    ///
+    /// # Events
+    /// Emits `OwnershipTransferStartedEvent` with the owner and nominee
+    ///
+    /// # Security
+    /// - Only the owner can nominate, and the current owner keeps full
+    ///   control until the nominee accepts
+    pub fn transfer_ownership(env: Env, new_owner: Address) {
+        Self::require_owner_approval(&env, symbol_short!("owner"), new_owner.clone());
+        Self::require_valid_role_address(&env, &new_owner, &Self::get_usdc_token(env.clone()));
+
+        let owner = Self::get_owner(env.clone());
+        assert!(new_owner != owner, "Already the owner");
+        assert!(
+            Self::get_guardian(env.clone()) != Some(new_owner.clone()),
+            "Guardian must be distinct from owner"
+        );
+
+        env.storage()
+            .instance()
+            .set(&ConfigKey::PendingOwner, &new_owner);
+
+        env.events().publish(
+            (symbol_short!("own_start"),),
+            OwnershipTransferStartedEvent {
+                owner,
+                pending_owner: new_owner,
+            },
+        );
+    }
+
+    /// Completes an ownership transfer started by `transfer_ownership()`.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// Nothing. This function promotes the pending owner and returns nothing.
+    ///
+    /// # Panics
+    /// - If no ownership transfer is pending
+    /// - If the caller is not the pending owner
+    ///
+    /// # Events
+    /// Emits `OwnershipTransferredEvent` with the old and new owner, and
+    /// `RoleConflationEvent` if the new owner is the agent
+    ///
+    /// # Security
+    /// - Requires the pending owner's authorization
+    pub fn accept_ownership(env: Env) {
+        let new_owner = Self::get_pending_owner(env.clone()).expect("No pending owner");
+        new_owner.require_auth();
+
+        let old_owner = Self::get_owner(env.clone());
+        env.storage().instance().set(&DataKey::Owner, &new_owner);
+        env.storage().instance().remove(&ConfigKey::PendingOwner);
+        Self::warn_if_roles_conflated(&env);
+        Self::append_address_change(
+            &env,
+            symbol_short!("owner"),
+            Some(old_owner.clone()),
+            Some(new_owner.clone()),
+        );
+
+        env.events().publish(
+            (symbol_short!("own_xfer"),),
+            OwnershipTransferredEvent {
+                old_owner,
+                new_owner,
+            },
+        );
+    }
+
+    /// Returns the nominated owner that has yet to accept.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The pending owner, or `None` if no transfer is pending
+    pub fn get_pending_owner(env: Env) -> Option<Address> {
+        env.storage().instance().get(&ConfigKey::PendingOwner)
+    }
+
+    // ==========================================================================
+    // ADMINISTRATIVE - PAUSE CONTROL
+    // ==========================================================================
+
+    /// Pauses the vault, disabling deposits and withdrawals.
+    ///
+    /// Emergency function to halt all user-facing operations.
+    /// When paused:
+    /// - Deposits are rejected
+    /// - Withdrawals are rejected
+    /// - Rebalancing is rejected
+    /// - Read functions remain operational
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `owner` - The owner or pauser address (must authorize this call)
+    ///
+    /// # Returns
+    /// Nothing. This function pauses the vault and returns nothing.
+    ///
+    /// # Panics
+    /// - If the caller is neither the owner nor the pauser
+    ///
+    /// # Events
+    /// Emits `VaultPausedEvent` with:
+    /// - `owner`: The owner's or pauser's address that triggered the pause
+    ///
+    /// # Security
+    /// - Only the owner or pauser can pause the vault (verified via require_auth)
+    /// - The pause lifts only through unpause(), or through try_auto_unpause()
+    ///   once `AutoUnpauseWindow` seconds pass with no loss reported
+    /// - Users' funds remain safe and can be withdrawn after unpause
+    pub fn pause(env: Env, owner: Address) {
+        owner.require_auth();
+        assert!(
+            Self::can_pause(&env, &owner),
+            "Only owner or pauser can pause"
+        );
+
+        env.storage().instance().set(&DataKey::Paused, &true);
+        env.storage()
+            .instance()
+            .set(&DataKey::StableSince, &env.ledger().timestamp());
+
+        env.events()
+            .publish((symbol_short!("v_paused"),), VaultPausedEvent { owner });
+    }
+
+    /// Unpauses the vault, re-enabling deposits and withdrawals.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `owner` - The owner address (must authorize this call)
+    ///
+    /// # Returns
+    /// Nothing. This function unpauses the vault and returns nothing.
+    ///
+    /// # Panics
+    /// - If the caller is not the owner
+    /// - If the vault is not currently paused
+    ///
+    /// # Events
+    /// Emits `VaultUnpausedEvent` with:
+    /// - `owner`: The owner's address that triggered the unpause
+    ///
+    /// # Security
+    /// - Only the owner can unpause the vault (verified via require_auth)
+    pub fn unpause(env: Env, owner: Address) {
+        owner.require_auth();
+        let stored_owner: Address = env.storage().instance().get(&DataKey::Owner).unwrap();
+        assert_eq!(owner, stored_owner, "Only owner can unpause");
+
+        let paused: bool = env
+            .storage()
+            .instance()
+            .get(&DataKey::Paused)
+            .unwrap_or(false);
+        assert!(paused, "Vault is not paused");
+
+        env.storage().instance().set(&DataKey::Paused, &false);
+        env.storage().instance().remove(&DataKey::StableSince);
+
+        env.events()
+            .publish((symbol_short!("v_unpause"),), VaultUnpausedEvent { owner });
+    }
+
+    /// Emergency pause function that immediately halts all operations.
+    ///
+    /// This is a separate function from pause() to distinguish emergency
+    /// situations in event logs. Functionally identical to pause().
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `owner` - The owner or pauser address (must authorize this call)
+    ///
+    /// # Returns
+    /// Nothing. This function emergency pauses the vault and returns nothing.
+    ///
+    /// # Panics
+    /// - If the caller is neither the owner nor the pauser
+    ///
+    /// # Events
+    /// Emits `EmergencyPausedEvent` with:
+    /// - `owner`: The owner's or pauser's address that triggered the emergency pause
+    ///
+    /// # Security
+    /// - Only the owner or pauser can emergency pause the vault (verified via require_auth)
+    /// - An emergency pause never lifts automatically, even over an earlier pause()
+    pub fn emergency_pause(env: Env, owner: Address) {
+        owner.require_auth();
+        assert!(
+            Self::can_pause(&env, &owner),
+            "Only owner or pauser can emergency pause"
+        );
+
+        env.storage().instance().set(&DataKey::Paused, &true);
+        env.storage().instance().remove(&DataKey::StableSince);
+
+        env.events()
+            .publish((symbol_short!("e_paused"),), EmergencyPausedEvent { owner });
+    }
+
+    /// Assigns or clears the pauser, an address that may pause the vault.
+    ///
+    /// The pauser can call `pause()` and `emergency_pause()` but not
+    /// `unpause()`, so a hot key can halt the vault in an incident without
+    /// being able to reopen it.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `pauser` - The pauser address, or `None` to remove the role
+    ///
+    /// # Returns
+    /// Nothing. This function updates the pauser and returns nothing.
+    ///
+    /// # Panics
+    /// - If the caller is not the owner
+    ///
+    /// # Events
+    /// Emits `PauserUpdatedEvent` with the new pauser
+    ///
+    /// # Security
+    /// - Only the owner can assign the pauser
+    pub fn set_pauser(env: Env, pauser: Option<Address>) {
+        Self::require_is_owner(&env);
+        let old_pauser = Self::get_pauser(env.clone());
+
+        match &pauser {
+            Some(pauser) => env.storage().instance().set(&ConfigKey::Pauser, pauser),
+            None => env.storage().instance().remove(&ConfigKey::Pauser),
+        }
+        Self::append_address_change(&env, symbol_short!("pauser"), old_pauser, pauser.clone());
+
+        env.events()
+            .publish((symbol_short!("pauser"),), PauserUpdatedEvent { pauser });
+    }
+
+    /// Returns the pauser, if one is assigned.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The pauser address, or `None` if the role is unassigned
+    pub fn get_pauser(env: Env) -> Option<Address> {
+        env.storage().instance().get(&ConfigKey::Pauser)
+    }
+
+    /// Pauses rebalancing while leaving deposits and withdrawals open.
+    ///
+    /// Meant for agent maintenance windows. While set, `rebalance`,
+    /// `rebalance_funds` and `update_total_assets` are rejected, so neither
+    /// strategy allocations nor the share price can move.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `caller` - The owner or pauser address (must authorize this call)
+    ///
+    /// # Returns
+    /// Nothing. This function pauses rebalancing and returns nothing.
+    ///
+    /// # Panics
+    /// - If the caller is neither the owner nor the pauser
+    ///
+    /// # Events
+    /// Emits `RebalancePausedEvent` with `paused` set to `true`
+    ///
+    /// # Security
+    /// - As with `pause()`, only the owner can lift it again
+    pub fn pause_rebalancing(env: Env, caller: Address) {
+        caller.require_auth();
+        assert!(
+            Self::can_pause(&env, &caller),
+            "Only owner or pauser can pause rebalancing"
+        );
+
+        env.storage()
+            .instance()
+            .set(&ConfigKey::RebalancePaused, &true);
+
+        env.events().publish(
+            (symbol_short!("rebal_pau"),),
+            RebalancePausedEvent {
+                caller,
+                paused: true,
+            },
+        );
+    }
+
+    /// Resumes rebalancing after `pause_rebalancing()`.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `owner` - The owner address (must authorize this call)
+    ///
+    /// # Returns
+    /// Nothing. This function resumes rebalancing and returns nothing.
+    ///
+    /// # Panics
+    /// - If the caller is not the owner
+    /// - If rebalancing is not paused
+    ///
+    /// # Events
+    /// Emits `RebalancePausedEvent` with `paused` set to `false`
+    pub fn unpause_rebalancing(env: Env, owner: Address) {
+        owner.require_auth();
+        assert_eq!(
+            owner,
+            Self::get_owner(env.clone()),
+            "Only owner can unpause rebalancing"
+        );
+        assert!(
+            Self::is_rebalance_paused(env.clone()),
+            "Rebalancing is not paused"
+        );
+
+        env.storage().instance().remove(&ConfigKey::RebalancePaused);
+
+        env.events().publish(
+            (symbol_short!("rebal_pau"),),
+            RebalancePausedEvent {
+                caller: owner,
+                paused: false,
+            },
+        );
+    }
+
+    /// Returns whether rebalancing is paused.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// `true` if rebalancing is paused
+    pub fn is_rebalance_paused(env: Env) -> bool {
+        env.storage()
+            .instance()
+            .get(&ConfigKey::RebalancePaused)
+            .unwrap_or(false)
+    }
+
+    /// Sets how long a pause must go without a loss before it lifts itself.
+    ///
+    /// The window runs from the `pause()` call and restarts whenever
+    /// `update_total_assets` reports lower total assets. Once it elapses,
+    /// anyone may call `try_auto_unpause()`. Emergency pauses are never
+    /// lifted automatically. Setting to 0 disables auto-unpause.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `secs` - Window in seconds
+    ///
+    /// # Returns
+    /// Nothing. This function updates the window and returns nothing.
+    ///
+    /// # Panics
+    /// - If the caller is not the owner
+    ///
+    /// # Events
+    /// Emits `ConfigUpdatedEvent` with key `auto_unps`
+    ///
+    /// # Security
+    /// - Only the owner can modify the window
+    pub fn set_auto_unpause_window(env: Env, secs: u64) {
+        Self::require_is_owner(&env);
+
+        let old_window = Self::get_auto_unpause_window(env.clone());
+        env.storage()
+            .instance()
+            .set(&ConfigKey::AutoUnpauseWindow, &secs);
+
+        Self::record_config_change(
+            &env,
+            symbol_short!("auto_unps"),
+            old_window as i128,
+            secs as i128,
+        );
+    }
+
+    /// Returns the loss-free window after which a pause lifts itself.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The window in seconds, or 0 if disabled
+    pub fn get_auto_unpause_window(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&ConfigKey::AutoUnpauseWindow)
+            .unwrap_or(0)
+    }
+
+    /// Lifts the pause if the share price has been stable for the window.
+    ///
+    /// Keeper entry point: callable by anyone, and a no-op unless the vault
+    /// was paused with `pause()`, auto-unpause is enabled, and no loss has
+    /// been reported for `AutoUnpauseWindow` seconds.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// True if the vault was unpaused, false otherwise
+    ///
+    /// # Panics
+    /// None
+    ///
+    /// # Events
+    /// Emits `AutoUnpausedEvent` with the start of the stable period when
+    /// the pause is lifted
+    ///
+    /// # Security
+    /// - Requires no auth; the stored conditions alone decide the outcome
+    pub fn try_auto_unpause(env: Env) -> bool {
+        let window = Self::get_auto_unpause_window(env.clone());
+        if window == 0 || !Self::is_paused(env.clone()) {
+            return false;
+        }
+        let stable_since: u64 = match env.storage().instance().get(&DataKey::StableSince) {
+            Some(stable_since) => stable_since,
+            None => return false,
+        };
+        if env.ledger().timestamp() < stable_since + window {
+            return false;
+        }
+
+        env.storage().instance().set(&DataKey::Paused, &false);
+        env.storage().instance().remove(&DataKey::StableSince);
+
+        env.events().publish(
+            (symbol_short!("auto_unps"),),
+            AutoUnpausedEvent { stable_since },
+        );
+        true
+    }
+
+    /// Sets how long after a deposit a user may still withdraw while paused.
+    ///
+    /// Lets users who deposited just before a pause back out. Only
+    /// deposits made while the grace is set are tracked. Setting to 0
+    /// disables the grace.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `secs` - Grace in seconds
+    ///
+    /// # Returns
+    /// Nothing. This function updates the grace and returns nothing.
+    ///
+    /// # Panics
+    /// - If the caller is not the owner
+    ///
+    /// # Events
+    /// Emits `ConfigUpdatedEvent` with key `pause_grc`
+    ///
+    /// # Security
+    /// - Only the owner can modify the grace
+    pub fn set_pause_grace_seconds(env: Env, secs: u64) {
+        Self::require_is_owner(&env);
+
+        let old_grace = Self::get_pause_grace_seconds(env.clone());
+        env.storage()
+            .instance()
+            .set(&ConfigKey::PauseGraceSeconds, &secs);
+
+        Self::record_config_change(
+            &env,
+            symbol_short!("pause_grc"),
+            old_grace as i128,
+            secs as i128,
+        );
+    }
+
+    /// Returns the grace after a deposit in which withdrawals ignore a pause.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The grace in seconds, or 0 if disabled
+    pub fn get_pause_grace_seconds(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&ConfigKey::PauseGraceSeconds)
+            .unwrap_or(0)
+    }
+
+    // ==========================================================================
+    // ADMINISTRATIVE - GUARDIAN RECOVERY
+    // ==========================================================================
+
+    /// Starts a time-delayed recovery of all vault funds to the recovery address.
+    ///
+    /// For a severe emergency in which the owner is compromised or
+    /// unresponsive. The recovery can be executed with
+    /// `execute_guardian_recovery()` once `RecoveryDelay` seconds pass, and
+    /// the owner may veto it with `veto_recovery()` until then.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `to` - Must be the recovery address fixed at initialization
+    ///
+    /// # Returns
+    /// The ledger timestamp from which the recovery may execute
+    ///
+    /// # Panics
+    /// - If the vault has no guardian
+    /// - If the caller is not the guardian
+    /// - If `to` is not the recovery address
+    /// - If a recovery is already pending
+    ///
+    /// # Events
+    /// Emits `RecoveryStartedEvent` with the recovery address and ready time
+    ///
+    /// # Security
+    /// - Only the guardian can start a recovery, and only towards the
+    ///   predetermined address
+    pub fn guardian_recover(env: Env, to: Address) -> u64 {
+        let guardian = Self::get_guardian(env.clone()).expect("Guardian not configured");
+        guardian.require_auth();
+        assert!(
+            Some(to.clone()) == Self::get_recovery_address(env.clone()),
+            "Not the recovery address"
+        );
+        assert!(
+            Self::get_pending_recovery(env.clone()).is_none(),
+            "Recovery already pending"
+        );
+
+        let ready_at = env.ledger().timestamp() + Self::get_recovery_delay(env.clone());
+        env.storage()
+            .instance()
+            .set(&DataKey::PendingRecovery, &ready_at);
+
+        env.events().publish(
+            (symbol_short!("rcv_start"),),
+            RecoveryStartedEvent { to, ready_at },
+        );
+        ready_at
+    }
+
+    /// Executes a pending recovery once its delay has elapsed.
+    ///
+    /// Pulls back any reserve parked in the money market, sends the vault's
+    /// entire USDC balance to the recovery address and pauses the vault.
+    /// Share balances are left untouched as the record of what each user
+    /// is owed.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The amount of USDC transferred (7 decimal places)
+    ///
+    /// # Panics
+    /// - If the vault has no guardian
+    /// - If the caller is not the guardian
+    /// - If no recovery is pending
+    /// - If the recovery delay has not elapsed
+    ///
+    /// # Events
+    /// Emits `RecoveryExecutedEvent` with the recovery address and amount
+    ///
+    /// # Security
+    /// - Only the guardian can execute, and only after the owner's veto window
+    pub fn execute_guardian_recovery(env: Env) -> i128 {
+        let guardian = Self::get_guardian(env.clone()).expect("Guardian not configured");
+        guardian.require_auth();
+        let ready_at = Self::get_pending_recovery(env.clone()).expect("No pending recovery");
+        assert!(
+            env.ledger().timestamp() >= ready_at,
+            "Recovery timelock active"
+        );
+
+        env.storage().instance().remove(&DataKey::PendingRecovery);
+        env.storage().instance().set(&DataKey::Paused, &true);
+        env.storage().instance().remove(&DataKey::StableSince);
+
+        let usdc_token: Address = env.storage().instance().get(&DataKey::UsdcToken).unwrap();
+        let token_client = token::Client::new(&env, &usdc_token);
+        let deployed = Self::get_reserve_deployed(env.clone());
+        if deployed > 0 {
+            Self::pull_reserve(&env, &token_client, deployed);
+        }
+        let to = Self::get_recovery_address(env.clone()).unwrap();
+        let amount = token_client.balance(&env.current_contract_address());
+        token_client.transfer(&env.current_contract_address(), &to, &amount);
+
+        env.events().publish(
+            (symbol_short!("rcv_exec"),),
+            RecoveryExecutedEvent { to, amount },
+        );
+        amount
+    }
+
+    /// Cancels a pending recovery before its delay elapses.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// Nothing. This function cancels the recovery and returns nothing.
+    ///
+    /// # Panics
+    /// - If the caller is not the owner
+    /// - If no recovery is pending
+    /// - If the recovery delay has already elapsed
+    ///
+    /// # Events
+    /// Emits `RecoveryVetoedEvent` with the owner and the vetoed ready time
+    ///
+    /// # Security
+    /// - Only the owner can veto, and only inside the window, so an
+    ///   unresponsive owner cannot block a recovery indefinitely
+    pub fn veto_recovery(env: Env) {
+        Self::require_is_owner(&env);
+        let ready_at = Self::get_pending_recovery(env.clone()).expect("No pending recovery");
+        assert!(env.ledger().timestamp() < ready_at, "Veto window closed");
+
+        env.storage().instance().remove(&DataKey::PendingRecovery);
+
+        env.events().publish(
+            (symbol_short!("rcv_veto"),),
+            RecoveryVetoedEvent {
+                owner: Self::get_owner(env.clone()),
+                ready_at,
+            },
+        );
+    }
+
+    /// Returns the guardian.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The guardian address, or `None` if the vault was initialized without one
+    pub fn get_guardian(env: Env) -> Option<Address> {
+        env.storage().instance().get(&ConfigKey::Guardian)
+    }
+
+    /// Returns the address that recovered funds are sent to.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The recovery address, or `None` if the vault has no guardian
+    pub fn get_recovery_address(env: Env) -> Option<Address> {
+        env.storage().instance().get(&ConfigKey::RecoveryAddress)
+    }
+
+    /// Returns the delay between starting and executing a recovery.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The delay in seconds, or 0 if the vault has no guardian
+    pub fn get_recovery_delay(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&ConfigKey::RecoveryDelay)
+            .unwrap_or(0)
+    }
+
+    /// Returns when the pending recovery may execute.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The ledger timestamp, or `None` if no recovery is pending
+    pub fn get_pending_recovery(env: Env) -> Option<u64> {
+        env.storage().instance().get(&DataKey::PendingRecovery)
+    }
+
+    // ==========================================================================
+    // ADMINISTRATIVE - WIND-DOWN
+    // ==========================================================================
+
+    /// Starts winding down the vault ahead of closing it.
+    ///
+    /// Wind-down is irreversible. New deposits and mints are rejected, users
+    /// can still withdraw, and the owner gains `force_withdraw()` to return
+    /// positions of users who never come back.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// Nothing. This function starts the wind-down and returns nothing.
+    ///
+    /// # Panics
+    /// - If the caller is not the owner
+    /// - If the vault is already winding down
+    ///
+    /// # Events
+    /// Emits `WindDownStartedEvent` with the owner's address
+    ///
+    /// # Security
+    /// - Only the owner can start a wind-down
+    pub fn start_wind_down(env: Env) {
+        Self::require_is_owner(&env);
+        assert!(!Self::is_winding_down(env.clone()), "Already winding down");
+
+        env.storage().instance().set(&DataKey::WindDown, &true);
+
+        let owner: Address = env.storage().instance().get(&DataKey::Owner).unwrap();
+        env.events().publish(
+            (symbol_short!("wind_down"),),
+            WindDownStartedEvent { owner },
+        );
+    }
+
+    /// Redeems all of a user's shares to a recipient during wind-down.
+    ///
+    /// Lets the owner fully close the vault when some users never return,
+    /// e.g. by sending their USDC to a recovery custodian. Any withdrawal
+    /// requests the user has queued are cancelled first and their shares
+    /// redeemed with the rest of the position.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `user` - The user whose position is redeemed
+    /// * `to` - The recipient of the redeemed USDC
+    ///
+    /// # Returns
+    /// The amount of USDC transferred to `to` (7 decimal places)
+    ///
+    /// # Panics
+    /// - If the caller is not the owner
+    /// - If the vault is not winding down
+    /// - If the vault is paused
+    /// - If the user holds no shares, liquid or queued
+    ///
+    /// # Events
+    /// Emits `WithdrawalCancelledEvent` for each queued request cancelled,
+    /// then `ForceWithdrawEvent` with the user, recipient, shares and amount
+    ///
+    /// # Security
+    /// - Only the owner can force-withdraw, and only during wind-down
+    /// - Shares are burned before the transfer (checks-effects-interactions)
+    pub fn force_withdraw(env: Env, user: Address, to: Address) -> i128 {
+        Self::require_is_owner(&env);
+        assert!(
+            Self::is_winding_down(env.clone()),
+            "Vault is not winding down"
+        );
+        Self::require_not_paused(&env);
+
+        Self::cancel_withdrawal_requests(&env, &user);
+        let shares = Self::get_shares(env.clone(), user.clone());
+        assert!(shares > 0, "User has no shares");
+        Self::accrue_management_fee(&env);
+        let amount = Self::convert_to_assets(env.clone(), shares);
+
+        let amount = Self::process_exit(&env, &user, shares, amount, &to, None, false);
+
+        env.events().publish(
+            (symbol_short!("force_wd"),),
+            ForceWithdrawEvent {
+                user,
+                to,
+                shares,
+                amount,
+            },
+        );
+
+        amount
+    }
+
+    /// Returns whether the vault is winding down.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// True if wind-down has started, false otherwise
+    pub fn is_winding_down(env: Env) -> bool {
+        env.storage()
+            .instance()
+            .get(&DataKey::WindDown)
+            .unwrap_or(false)
+    }
+
+    // ==========================================================================
+    // ADMINISTRATIVE - CONFIGURATION
+    // ==========================================================================
+
+    /// Sets the TVL (Total Value Locked) cap for the vault.
+    ///
+    /// Maximum total USDC that can be deposited in the vault.
+    /// Setting to 0 removes the cap.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `cap` - New TVL cap in USDC units (7 decimal places)
+    ///
+    /// # Returns
+    /// Nothing. This function updates the cap and returns nothing.
+    ///
+    /// # Panics
+    /// - If the caller is not the owner
+    ///
+    /// # Events
+    /// Emits `LimitsUpdatedEvent` with old and new values for both limits
+    ///
+    /// # Security
+    /// - Only the owner can modify the TVL cap
+    /// - Reducing the cap below current total deposits does not affect existing deposits
+    pub fn set_tvl_cap(env: Env, cap: i128) {
+        Self::require_is_owner(&env);
+
+        let old_tvl_cap = env.storage().instance().get(&DataKey::TvLCap).unwrap_or(0);
+        let old_user_cap = env
+            .storage()
+            .instance()
+            .get(&DataKey::UserDepositCap)
+            .unwrap_or(0);
+
+        env.storage().instance().set(&DataKey::TvLCap, &cap);
+        Self::append_config_change(&env, symbol_short!("tvl_cap"), old_tvl_cap, cap);
+
+        env.events().publish(
+            (symbol_short!("lim_upd"),),
+            LimitsUpdatedEvent {
+                old_min: old_user_cap,
+                new_min: old_user_cap,
+                old_max: old_tvl_cap,
+                new_max: cap,
+            },
+        );
+    }
+
+    /// Sets the maximum deposit amount per user.
+    ///
+    /// Maximum amount that any single user can have deposited in the vault.
+    /// Setting to 0 removes the cap.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `cap` - New per-user deposit cap in USDC units (7 decimal places)
+    ///
+    /// # Returns
+    /// Nothing. This function updates the cap and returns nothing.
+    ///
+    /// # Panics
+    /// - If the caller is not the owner
+    ///
+    /// # Events
+    /// Emits `LimitsUpdatedEvent` with old and new values for both limits
+    ///
+    /// # Security
+    /// - Only the owner can modify the user deposit cap
+    /// - Reducing the cap below a user's current balance does not affect them
+    pub fn set_user_deposit_cap(env: Env, cap: i128) {
+        Self::require_is_owner(&env);
+
+        let old_tvl_cap = env.storage().instance().get(&DataKey::TvLCap).unwrap_or(0);
+        let old_user_cap = env
+            .storage()
+            .instance()
+            .get(&DataKey::UserDepositCap)
+            .unwrap_or(0);
+
+        env.storage().instance().set(&DataKey::UserDepositCap, &cap);
+        Self::append_config_change(&env, symbol_short!("user_cap"), old_user_cap, cap);
+
+        env.events().publish(
+            (symbol_short!("lim_upd"),),
+            LimitsUpdatedEvent {
+                old_min: old_user_cap,
+                new_min: cap,
+                old_max: old_tvl_cap,
+                new_max: old_tvl_cap,
+            },
+        );
+    }
+
+    /// Sets both the user deposit cap (min) and TVL cap (max) in a single transaction.
+    ///
+    /// This function allows updating both limits atomically and emits a single
+    /// `LimitsUpdatedEvent` with all old and new values.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `min` - New per-user deposit cap in USDC units (7 decimal places)
+    /// * `max` - New TVL cap in USDC units (7 decimal places)
+    ///
+    /// # Returns
+    /// Nothing. This function updates both caps and returns nothing.
+    ///
+    /// # Panics
+    /// - If the caller is not the owner
+    ///
+    /// # Events
+    /// Emits `LimitsUpdatedEvent` with:
+    /// - `old_min`: Previous user deposit cap
+    /// - `new_min`: New user deposit cap
+    /// - `old_max`: Previous TVL cap
+    /// - `new_max`: New TVL cap
+    ///
+    /// # Security
+    /// - Only the owner can modify the limits
+    pub fn set_limits(env: Env, min: i128, max: i128) {
+        Self::require_is_owner(&env);
+
+        let old_user_cap = env
+            .storage()
+            .instance()
+            .get(&DataKey::UserDepositCap)
+            .unwrap_or(0);
+        let old_tvl_cap = env.storage().instance().get(&DataKey::TvLCap).unwrap_or(0);
+
+        env.storage().instance().set(&DataKey::UserDepositCap, &min);
+        env.storage().instance().set(&DataKey::TvLCap, &max);
+        Self::append_config_change(&env, symbol_short!("user_cap"), old_user_cap, min);
+        Self::append_config_change(&env, symbol_short!("tvl_cap"), old_tvl_cap, max);
+
+        env.events().publish(
+            (symbol_short!("lim_upd"),),
+            LimitsUpdatedEvent {
+                old_min: old_user_cap,
+                new_min: min,
+                old_max: old_tvl_cap,
+                new_max: max,
+            },
+        );
+    }
+
+    /// Returns the current TVL cap.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The current TVL cap in USDC units (7 decimal places), or 0 if no cap
+    pub fn get_tvl_cap(env: Env) -> i128 {
+        env.storage().instance().get(&DataKey::TvLCap).unwrap_or(0)
+    }
+
+    /// Returns the current per-user deposit cap.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The current per-user deposit cap in USDC units (7 decimal places), or 0 if no cap
+    pub fn get_user_deposit_cap(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::UserDepositCap)
+            .unwrap_or(0)
+    }
+
+    /// Sets the share price sanity band checked on every share conversion.
+    ///
+    /// Acts as a tripwire against accounting corruption or manipulation: any
+    /// conversion performed while the implied share price sits outside the
+    /// band panics instead of minting or paying out against a broken price.
+    /// Setting a bound to 0 disables it.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `min_price` - Minimum share price scaled by `SHARE_PRICE_SCALE` (0 disables)
+    /// * `max_price` - Maximum share price scaled by `SHARE_PRICE_SCALE` (0 disables)
+    ///
+    /// # Returns
+    /// Nothing. This function updates the bounds and returns nothing.
+    ///
+    /// # Panics
+    /// - If the caller is not the owner
+    /// - If either bound is negative
+    /// - If both bounds are set and `min_price > max_price`
+    ///
+    /// # Events
+    /// Emits `SharePriceBoundsUpdatedEvent` with the new bounds
+    ///
+    /// # Security
+    /// - Only the owner can modify the bounds
+    pub fn set_share_price_bounds(env: Env, min_price: i128, max_price: i128) {
+        Self::require_is_owner(&env);
+
+        assert!(
+            min_price >= 0 && max_price >= 0,
+            "Share price bounds must be non-negative"
+        );
+        if min_price > 0 && max_price > 0 {
+            assert!(min_price <= max_price, "Invalid share price bounds");
+        }
+
+        let old_min: i128 = env
+            .storage()
+            .instance()
+            .get(&ConfigKey::MinSharePrice)
+            .unwrap_or(0);
+        let old_max: i128 = env
+            .storage()
+            .instance()
+            .get(&ConfigKey::MaxSharePrice)
+            .unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(&ConfigKey::MinSharePrice, &min_price);
+        env.storage()
+            .instance()
+            .set(&ConfigKey::MaxSharePrice, &max_price);
+        Self::append_config_change(&env, symbol_short!("min_price"), old_min, min_price);
+        Self::append_config_change(&env, symbol_short!("max_price"), old_max, max_price);
+
+        env.events().publish(
+            (symbol_short!("price_bnd"),),
+            SharePriceBoundsUpdatedEvent {
+                min_price,
+                max_price,
+            },
+        );
+    }
+
+    /// Returns the share price sanity band as `(min_price, max_price)`.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The bounds scaled by `SHARE_PRICE_SCALE`; 0 means the bound is disabled
+    pub fn get_share_price_bounds(env: Env) -> (i128, i128) {
+        let min_price = env
+            .storage()
+            .instance()
+            .get(&ConfigKey::MinSharePrice)
+            .unwrap_or(0);
+        let max_price = env
+            .storage()
+            .instance()
+            .get(&ConfigKey::MaxSharePrice)
+            .unwrap_or(0);
+        (min_price, max_price)
+    }
+
+    /// Sets the maximum number of distinct depositors.
+    ///
+    /// Once the live depositor count reaches the cap, brand-new depositors
+    /// are rejected while existing depositors can still add funds.
+    /// Setting to 0 removes the cap.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `max` - New depositor cap
+    ///
+    /// # Returns
+    /// Nothing. This function updates the cap and returns nothing.
+    ///
+    /// # Panics
+    /// - If the caller is not the owner
+    ///
+    /// # Events
+    /// Emits `ConfigUpdatedEvent` with key `max_dep`
+    ///
+    /// # Security
+    /// - Only the owner can modify the depositor cap
+    /// - Lowering the cap below the current count does not evict anyone
+    pub fn set_max_depositors(env: Env, max: u32) {
+        Self::require_is_owner(&env);
+
+        let old_max = Self::get_max_depositors(env.clone());
+        env.storage()
+            .instance()
+            .set(&ConfigKey::MaxDepositors, &max);
+
+        Self::record_config_change(&env, symbol_short!("max_dep"), old_max as i128, max as i128);
+    }
+
+    /// Returns the maximum number of distinct depositors.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The depositor cap, or 0 if no cap
+    pub fn get_max_depositors(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&ConfigKey::MaxDepositors)
+            .unwrap_or(0)
+    }
+
+    /// Returns the number of users currently holding shares.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The live depositor count
+    pub fn get_depositor_count(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&DataKey::DepositorCount)
+            .unwrap_or(0)
+    }
+
+    /// Reserves a depositor slot for a user ahead of their first deposit.
+    ///
+    /// While the reservation is live it counts toward the depositor cap, and
+    /// the user is admitted even if the cap is otherwise full. Reservations
+    /// expire after `PRE_REGISTRATION_TTL` seconds, freeing the slot, and
+    /// are consumed by the user's first deposit. Registering again renews
+    /// the reservation.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `user` - The user to reserve a slot for
+    ///
+    /// # Returns
+    /// The timestamp at which the reservation expires
+    ///
+    /// # Panics
+    /// - If the caller is not the owner
+    /// - If pre-registration is disabled by the feature flags
+    /// - If the user already holds shares
+    /// - If no depositor slot is free for a new reservation
+    ///
+    /// # Events
+    /// Emits `PreRegisteredEvent` with the user and expiry
+    ///
+    /// # Security
+    /// - Only the owner can reserve slots
+    pub fn pre_register(env: Env, user: Address) -> u64 {
+        Self::require_is_owner(&env);
+        Self::require_feature_enabled(&env, FEATURE_PRE_REGISTRATION);
+        assert!(
+            Self::get_shares(env.clone(), user.clone()) == 0,
+            "Already a depositor"
+        );
+
+        let now = env.ledger().timestamp();
+        let mut reservations = Vec::new(&env);
+        for reservation in Self::get_pre_registrations(env.clone()).iter() {
+            if reservation.expires_at > now && reservation.user != user {
+                reservations.push_back(reservation);
+            }
+        }
+        let max = Self::get_max_depositors(env.clone());
+        if max > 0 {
+            assert!(
+                Self::get_depositor_count(env.clone()) + reservations.len() < max,
+                "Depositor limit reached"
+            );
+        }
+
+        let expires_at = now + PRE_REGISTRATION_TTL;
+        reservations.push_back(PreRegistration {
+            user: user.clone(),
+            expires_at,
+        });
+        env.storage()
+            .persistent()
+            .set(&DataKey::PreRegistrations, &reservations);
+
+        env.events().publish(
+            (symbol_short!("pre_reg"),),
+            PreRegisteredEvent { user, expires_at },
+        );
+
+        expires_at
+    }
+
+    /// Returns the stored depositor slot reservations, including expired ones.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The reservations, in registration order
+    pub fn get_pre_registrations(env: Env) -> Vec<PreRegistration> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::PreRegistrations)
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Returns whether a user holds a live depositor slot reservation.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `user` - The user address to query
+    ///
+    /// # Returns
+    /// `true` if the user is pre-registered and the reservation has not expired
+    pub fn is_pre_registered(env: Env, user: Address) -> bool {
+        let now = env.ledger().timestamp();
+        Self::get_pre_registrations(env)
+            .iter()
+            .any(|reservation| reservation.user == user && reservation.expires_at > now)
+    }
+
+    /// Replaces the set of operations the agent may perform.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `permissions` - Bitmask of `PERM_*` flags granted to the agent
+    ///
+    /// # Returns
+    /// Nothing. This function updates the permissions and returns nothing.
+    ///
+    /// # Panics
+    /// - If the caller is not the owner
+    /// - If `permissions` contains unknown bits
+    ///
+    /// # Events
+    /// Emits `ConfigUpdatedEvent` with key `agent_prm`
+    ///
+    /// # Security
+    /// - Only the owner can widen or narrow the agent's permissions
+    pub fn set_agent_permissions(env: Env, permissions: u32) {
+        Self::require_is_owner(&env);
+        Self::require_valid_permissions(permissions);
+
+        let old_permissions = Self::get_agent_permissions(env.clone());
+        env.storage()
+            .instance()
+            .set(&ConfigKey::AgentPermissions, &permissions);
+
+        Self::record_config_change(
+            &env,
+            symbol_short!("agent_prm"),
+            old_permissions as i128,
+            permissions as i128,
+        );
+    }
+
+    /// Returns the operations the agent may perform.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// Bitmask of `PERM_*` flags; vaults initialized before permissions
+    /// existed report `ALL_AGENT_PERMISSIONS`
+    pub fn get_agent_permissions(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&ConfigKey::AgentPermissions)
+            .unwrap_or(ALL_AGENT_PERMISSIONS)
+    }
+
+    /// Replaces the set of enabled optional features.
+    ///
+    /// Disabling a feature blocks new use of it without unwinding existing
+    /// state: locks already taken can still be released, and reservations
+    /// already made still hold their slot until they expire.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `flags` - Bitmask of `FEATURE_*` flags to enable
+    ///
+    /// # Returns
+    /// Nothing. This function updates the flags and returns nothing.
+    ///
+    /// # Panics
+    /// - If the caller is not the owner
+    /// - If `flags` contains unknown bits
+    ///
+    /// # Events
+    /// Emits `ConfigUpdatedEvent` with key `features`
+    ///
+    /// # Security
+    /// - Only the owner can enable or disable features
+    pub fn set_feature_flags(env: Env, flags: u32) {
+        Self::require_is_owner(&env);
+        assert!(flags & !ALL_FEATURES == 0, "Invalid feature flags");
+
+        let old_flags = Self::get_feature_flags(env.clone());
+        env.storage()
+            .instance()
+            .set(&ConfigKey::FeatureFlags, &flags);
+
+        Self::record_config_change(
+            &env,
+            symbol_short!("features"),
+            old_flags as i128,
+            flags as i128,
+        );
+    }
+
+    /// Returns the enabled optional features.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// Bitmask of `FEATURE_*` flags; `ALL_FEATURES` until flags are configured
+    pub fn get_feature_flags(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&ConfigKey::FeatureFlags)
+            .unwrap_or(ALL_FEATURES)
+    }
+
+    /// Sets the share balance below which a withdrawal sweeps the remainder.
+    ///
+    /// Keeps the holder set free of dust positions. The sweep is skipped
+    /// while the user has locked shares. Setting to 0 disables sweeping.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `threshold` - Threshold in shares
+    ///
+    /// # Returns
+    /// Nothing. This function updates the threshold and returns nothing.
+    ///
+    /// # Panics
+    /// - If the caller is not the owner
+    /// - If threshold is negative
+    ///
+    /// # Events
+    /// Emits `ConfigUpdatedEvent` with key `dust_shr`
+    ///
+    /// # Security
+    /// - Only the owner can modify the threshold
+    pub fn set_dust_share_threshold(env: Env, threshold: i128) {
+        Self::require_is_owner(&env);
+        assert!(threshold >= 0, "Threshold cannot be negative");
+
+        let old_threshold = Self::get_dust_share_threshold(env.clone());
+        env.storage()
+            .instance()
+            .set(&ConfigKey::DustShareThreshold, &threshold);
+
+        Self::record_config_change(&env, symbol_short!("dust_shr"), old_threshold, threshold);
+    }
+
+    /// Returns the dust share threshold.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The threshold in shares, or 0 if disabled
+    pub fn get_dust_share_threshold(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&ConfigKey::DustShareThreshold)
+            .unwrap_or(0)
+    }
+
+    /// Sets the withdrawal amount below which shares are rounded in the user's favor.
+    ///
+    /// A withdrawal of less than `threshold` burns the shares backing
+    /// `amount` rounded down rather than up, so tiny withdrawals never cost
+    /// the user an extra share. Each share forgone is drawn from the
+    /// favorable rounding budget. Setting to 0 disables favorable rounding.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `threshold` - Threshold in raw USDC units
+    ///
+    /// # Returns
+    /// Nothing. This function updates the threshold and returns nothing.
+    ///
+    /// # Panics
+    /// - If the caller is not the owner
+    /// - If threshold is negative
+    ///
+    /// # Events
+    /// Emits `ConfigUpdatedEvent` with key `fav_thr`
+    ///
+    /// # Security
+    /// - Only the owner can modify the threshold
+    pub fn set_favorable_rounding_threshold(env: Env, threshold: i128) {
+        Self::require_is_owner(&env);
+        assert!(threshold >= 0, "Threshold cannot be negative");
+
+        let old_threshold = Self::get_favorable_rounding_threshold(env.clone());
+        env.storage()
+            .instance()
+            .set(&ConfigKey::FavorableRoundingThreshold, &threshold);
+
+        Self::record_config_change(&env, symbol_short!("fav_thr"), old_threshold, threshold);
+    }
+
+    /// Returns the favorable rounding threshold.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The threshold in raw USDC units, or 0 if disabled
+    pub fn get_favorable_rounding_threshold(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&ConfigKey::FavorableRoundingThreshold)
+            .unwrap_or(0)
+    }
+
+    /// Sets how many shares the vault may still forgo through favorable rounding.
+    ///
+    /// Every share a withdrawal does not burn is value the remaining holders
+    /// absorb, so the budget caps that total. Once it is spent, withdrawals
+    /// round up again.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `budget` - Budget in shares
+    ///
+    /// # Returns
+    /// Nothing. This function updates the budget and returns nothing.
+    ///
+    /// # Panics
+    /// - If the caller is not the owner
+    /// - If budget is negative
+    ///
+    /// # Events
+    /// Emits `ConfigUpdatedEvent` with key `fav_bdgt`
+    ///
+    /// # Security
+    /// - Only the owner can modify the budget
+    pub fn set_favorable_rounding_budget(env: Env, budget: i128) {
+        Self::require_is_owner(&env);
+        assert!(budget >= 0, "Budget cannot be negative");
+
+        let old_budget = Self::get_favorable_rounding_budget(env.clone());
+        env.storage()
+            .instance()
+            .set(&ConfigKey::FavorableRoundingBudget, &budget);
+
+        Self::record_config_change(&env, symbol_short!("fav_bdgt"), old_budget, budget);
+    }
+
+    /// Returns the remaining favorable rounding budget.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The shares that may still be forgone
+    pub fn get_favorable_rounding_budget(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&ConfigKey::FavorableRoundingBudget)
+            .unwrap_or(0)
+    }
+
+    /// Sets the share price step that emits a `PriceMilestoneEvent`.
+    ///
+    /// After each total assets update, a milestone is emitted only once the
+    /// share price has moved at least `step_bps` of the last milestone
+    /// price, up or down, so alerting systems see whole steps rather than
+    /// every small change. Setting a step makes the current price the
+    /// reference milestone. Setting to 0 disables milestones.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `step_bps` - Step in basis points (e.g., 500 = 5%)
+    ///
+    /// # Returns
+    /// Nothing. This function updates the step and returns nothing.
+    ///
+    /// # Panics
+    /// - If the caller is not the owner
+    /// - If `step_bps` is outside 0..=10000
+    ///
+    /// # Events
+    /// Emits `ConfigUpdatedEvent` with key `prc_step`
+    ///
+    /// # Security
+    /// - Only the owner can modify the step
+    pub fn set_price_step_bps(env: Env, step_bps: i128) {
+        Self::require_is_owner(&env);
+        assert!((0..=10_000).contains(&step_bps), "Invalid price step");
+
+        let old_step = Self::get_price_step_bps(env.clone());
+        env.storage()
+            .instance()
+            .set(&ConfigKey::PriceStepBps, &step_bps);
+        env.storage().instance().set(
+            &DataKey::LastPriceMilestone,
+            &Self::get_share_price(env.clone()),
+        );
+
+        Self::record_config_change(&env, symbol_short!("prc_step"), old_step, step_bps);
+    }
+
+    /// Returns the share price step that emits milestone events.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The step in basis points, or 0 if disabled
+    pub fn get_price_step_bps(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&ConfigKey::PriceStepBps)
+            .unwrap_or(0)
+    }
+
+    /// Returns the share price at the last milestone.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The milestone price (scaled by `SHARE_PRICE_SCALE`), or 0 if no step
+    /// has been configured
+    pub fn get_last_price_milestone(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::LastPriceMilestone)
+            .unwrap_or(0)
+    }
+
+    /// Sets the maximum USDC that may be deposited within a single ledger.
+    ///
+    /// Throttles sudden inflows so strategy deployment can keep up. The
+    /// count resets with each new ledger. Setting to 0 disables the cap.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `cap` - Maximum deposits per ledger (7 decimal places)
+    ///
+    /// # Returns
+    /// Nothing. This function updates the cap and returns nothing.
+    ///
+    /// # Panics
+    /// - If the caller is not the owner
+    /// - If cap is negative
+    ///
+    /// # Events
+    /// Emits `ConfigUpdatedEvent` with key `dep_ledgr`
+    ///
+    /// # Security
+    /// - Only the owner can modify the cap
+    pub fn set_max_deposit_per_ledger(env: Env, cap: i128) {
+        Self::require_is_owner(&env);
+        assert!(cap >= 0, "Cap cannot be negative");
+
+        let old_cap = Self::get_max_deposit_per_ledger(env.clone());
+        env.storage()
+            .instance()
+            .set(&ConfigKey::MaxDepositPerLedger, &cap);
+
+        Self::record_config_change(&env, symbol_short!("dep_ledgr"), old_cap, cap);
+    }
+
+    /// Returns the per-ledger deposit cap.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The cap in raw USDC units, or 0 if disabled
+    pub fn get_max_deposit_per_ledger(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&ConfigKey::MaxDepositPerLedger)
+            .unwrap_or(0)
+    }
+
+    /// Returns how much more USDC may be deposited in the current ledger.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The remaining capacity in raw USDC units, or `i128::MAX` if the cap
+    /// is disabled
+    pub fn get_ledger_deposit_remaining(env: Env) -> i128 {
+        let cap = Self::get_max_deposit_per_ledger(env.clone());
+        if cap == 0 {
+            return i128::MAX;
+        }
+        let (ledger, deposited): (u32, i128) = env
+            .storage()
+            .instance()
+            .get(&DataKey::LedgerDeposits)
+            .unwrap_or((0, 0));
+        if ledger != env.ledger().sequence() {
+            return cap;
+        }
+        (cap - deposited).max(0)
+    }
+
+    /// Sets the maximum USDC a single `withdraw` call may remove.
+    ///
+    /// Forces large exits into several transactions to smooth liquidity.
+    /// Setting to 0 disables the cap.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `cap` - Maximum withdrawal per transaction (7 decimal places)
+    ///
+    /// # Returns
+    /// Nothing. This function updates the cap and returns nothing.
+    ///
+    /// # Panics
+    /// - If the caller is not the owner
+    /// - If cap is negative
+    ///
+    /// # Events
+    /// Emits `ConfigUpdatedEvent` with key `max_wd_tx`
+    ///
+    /// # Security
+    /// - Only the owner can modify the cap
+    pub fn set_max_withdraw_per_tx(env: Env, cap: i128) {
+        Self::require_is_owner(&env);
+        assert!(cap >= 0, "Cap cannot be negative");
+
+        let old_cap = Self::get_max_withdraw_per_tx(env.clone());
+        env.storage()
+            .instance()
+            .set(&ConfigKey::MaxWithdrawPerTx, &cap);
+
+        Self::record_config_change(&env, symbol_short!("max_wd_tx"), old_cap, cap);
+    }
+
+    /// Returns the per-transaction withdrawal cap.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The cap in raw USDC units, or 0 if disabled
+    pub fn get_max_withdraw_per_tx(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&ConfigKey::MaxWithdrawPerTx)
+            .unwrap_or(0)
+    }
+
+    /// Sets how many shares the first deposit mints per unit of USDC.
+    ///
+    /// For vaults seeded with off-chain value, this aligns the opening share
+    /// price with the seed. Only allowed while no shares exist.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `ratio` - Shares per USDC scaled by `SHARE_PRICE_SCALE`
+    ///   (e.g. `2 * SHARE_PRICE_SCALE` mints 2 shares per USDC unit)
+    ///
+    /// # Returns
+    /// Nothing. This function updates the ratio and returns nothing.
+    ///
+    /// # Panics
+    /// - If the caller is not the owner
+    /// - If ratio is not positive
+    /// - If any shares have already been minted
+    ///
+    /// # Events
+    /// Emits `ConfigUpdatedEvent` with key `init_rat`
+    ///
+    /// # Security
+    /// - Only the owner can set the ratio, and only before the first deposit
+    pub fn set_initial_share_ratio(env: Env, ratio: i128) {
+        Self::require_is_owner(&env);
+        Self::require_positive_amount(ratio);
+        assert!(
+            Self::stored_total_shares(&env) == 0,
+            "Vault already has deposits"
+        );
+
+        let old_ratio = Self::get_initial_share_ratio(env.clone());
+        env.storage()
+            .instance()
+            .set(&ConfigKey::InitialShareRatio, &ratio);
+
+        Self::record_config_change(&env, symbol_short!("init_rat"), old_ratio, ratio);
+    }
+
+    /// Returns the shares minted per USDC when the vault is empty.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The ratio scaled by `SHARE_PRICE_SCALE` (defaults to 1:1)
+    pub fn get_initial_share_ratio(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&ConfigKey::InitialShareRatio)
+            .unwrap_or(SHARE_PRICE_SCALE)
+    }
+
+    /// Sets the number of decimal places of the share unit.
+    ///
+    /// Lets integrations that expect 18-decimal shares hold the vault
+    /// directly while the asset keeps 7 decimals. Each USDC unit then mints
+    /// `10^(decimals - USDC_DECIMALS)` times as many share units; share
+    /// prices stay quoted per whole share. Finer share units also leave less
+    /// rounding dust on each deposit. Only allowed while no shares exist.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `decimals` - Share decimal places, from `USDC_DECIMALS` to `MAX_SHARE_DECIMALS`
+    ///
+    /// # Returns
+    /// Nothing. This function updates the share decimals and returns nothing.
+    ///
+    /// # Panics
+    /// - If the caller is not the owner
+    /// - If decimals is outside the supported range
+    /// - If any shares have already been minted
+    ///
+    /// # Events
+    /// Emits `ConfigUpdatedEvent` with key `share_dec`
+    ///
+    /// # Security
+    /// - Only the owner can set the decimals, and only before the first deposit
+    pub fn set_share_decimals(env: Env, decimals: u32) {
+        Self::require_is_owner(&env);
+        assert!(
+            (USDC_DECIMALS..=MAX_SHARE_DECIMALS).contains(&decimals),
+            "Invalid share decimals"
+        );
+        assert!(
+            Self::stored_total_shares(&env) == 0,
+            "Vault already has deposits"
+        );
+
+        let old_decimals = Self::decimals(env.clone());
+        env.storage()
+            .instance()
+            .set(&ConfigKey::ShareDecimals, &decimals);
+
+        Self::record_config_change(
+            &env,
+            symbol_short!("share_dec"),
+            old_decimals as i128,
+            decimals as i128,
+        );
+    }
+
+    /// Returns the number of decimal places of the share unit.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The share decimals (defaults to `USDC_DECIMALS`)
+    pub fn decimals(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&ConfigKey::ShareDecimals)
+            .unwrap_or(USDC_DECIMALS)
+    }
+
+    /// Sets the price oracle used to value non-USDC deposits.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `oracle` - The price oracle contract address
+    ///
+    /// # Returns
+    /// Nothing. This function updates the oracle and returns nothing.
+    ///
+    /// # Panics
+    /// - If the caller is not the owner
+    ///
+    /// # Events
+    /// Emits `PriceOracleUpdatedEvent` with the new oracle address
+    ///
+    /// # Security
+    /// - Only the owner can set the oracle; it is fully trusted to price assets
+    pub fn set_price_oracle(env: Env, oracle: Address) {
+        Self::require_is_owner(&env);
+        let old_oracle = Self::get_price_oracle(env.clone());
+
+        env.storage()
+            .instance()
+            .set(&ConfigKey::PriceOracle, &oracle);
+        Self::append_address_change(
+            &env,
+            symbol_short!("oracle"),
+            old_oracle,
+            Some(oracle.clone()),
+        );
+
+        env.events().publish(
+            (symbol_short!("oracle_up"),),
+            PriceOracleUpdatedEvent { oracle },
+        );
+    }
+
+    /// Returns the configured price oracle, if any.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The oracle contract address, or `None` if not configured
+    pub fn get_price_oracle(env: Env) -> Option<Address> {
+        env.storage().instance().get(&ConfigKey::PriceOracle)
+    }
+
+    /// Configures the router and native asset used by `deposit_native`.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `router` - The DEX router contract address
+    /// * `native_token` - The Stellar asset contract address of native XLM
+    ///
+    /// # Returns
+    /// Nothing. This function updates the configuration and returns nothing.
+    ///
+    /// # Panics
+    /// - If the caller is not the owner
+    ///
+    /// # Events
+    /// Emits `SwapRouterUpdatedEvent` with the router and native token
+    ///
+    /// # Security
+    /// - Only the owner can set the router; it receives deposited XLM
+    pub fn set_swap_router(env: Env, router: Address, native_token: Address) {
+        Self::require_is_owner(&env);
+        let old_router = Self::get_swap_router(env.clone());
+
+        env.storage()
+            .instance()
+            .set(&ConfigKey::SwapRouter, &router);
+        env.storage()
+            .instance()
+            .set(&ConfigKey::NativeToken, &native_token);
+        Self::append_address_change(
+            &env,
+            symbol_short!("router"),
+            old_router,
+            Some(router.clone()),
+        );
+
+        env.events().publish(
+            (symbol_short!("router_up"),),
+            SwapRouterUpdatedEvent {
+                router,
+                native_token,
+            },
+        );
+    }
+
+    /// Returns the configured swap router, if any.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The router contract address, or `None` if not configured
+    pub fn get_swap_router(env: Env) -> Option<Address> {
+        env.storage().instance().get(&ConfigKey::SwapRouter)
+    }
+
+    /// Sets the maximum accepted age of an oracle price.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `max_age` - Maximum price age in seconds
+    ///
+    /// # Returns
+    /// Nothing. This function updates the limit and returns nothing.
+    ///
+    /// # Panics
+    /// - If the caller is not the owner
+    ///
+    /// # Events
+    /// Emits `ConfigUpdatedEvent` with key `orc_age`
+    ///
+    /// # Security
+    /// - Only the owner can modify the staleness limit
+    pub fn set_max_oracle_age(env: Env, max_age: u64) {
+        Self::require_is_owner(&env);
+
+        let old_age = Self::get_max_oracle_age(env.clone());
+        env.storage()
+            .instance()
+            .set(&ConfigKey::MaxOracleAge, &max_age);
+
+        Self::record_config_change(
+            &env,
+            symbol_short!("orc_age"),
+            old_age as i128,
+            max_age as i128,
+        );
+    }
+
+    /// Returns the maximum accepted age of an oracle price.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The limit in seconds (defaults to `DEFAULT_MAX_ORACLE_AGE`)
+    pub fn get_max_oracle_age(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&ConfigKey::MaxOracleAge)
+            .unwrap_or(DEFAULT_MAX_ORACLE_AGE)
+    }
+
+    /// Sets the cooldown queued withdrawals must wait before being claimed.
+    ///
+    /// Applies to requests queued after the change.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `cooldown` - Cooldown in seconds (0 makes requests claimable immediately)
+    ///
+    /// # Returns
+    /// Nothing. This function updates the cooldown and returns nothing.
+    ///
+    /// # Panics
+    /// - If the caller is not the owner
+    ///
+    /// # Events
+    /// Emits `ConfigUpdatedEvent` with key `wd_cool`
+    ///
+    /// # Security
+    /// - Only the owner can modify the cooldown
+    pub fn set_withdrawal_cooldown(env: Env, cooldown: u64) {
+        Self::require_is_owner(&env);
+
+        let old_cooldown = Self::get_withdrawal_cooldown(env.clone());
+        env.storage()
+            .instance()
+            .set(&ConfigKey::WithdrawalCooldown, &cooldown);
+
+        Self::record_config_change(
+            &env,
+            symbol_short!("wd_cool"),
+            old_cooldown as i128,
+            cooldown as i128,
+        );
+    }
+
+    /// Returns the withdrawal queue cooldown.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The cooldown in seconds, or 0 if not set
+    pub fn get_withdrawal_cooldown(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&ConfigKey::WithdrawalCooldown)
+            .unwrap_or(0)
+    }
+
+    /// Sets the minimum time between a user's last action and their next deposit.
+    ///
+    /// Throttles abusive deposit patterns. Setting to 0 disables the cooldown.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `cooldown` - Cooldown in seconds
+    ///
+    /// # Returns
+    /// Nothing. This function updates the cooldown and returns nothing.
+    ///
+    /// # Panics
+    /// - If the caller is not the owner
+    ///
+    /// # Events
+    /// Emits `ConfigUpdatedEvent` with key `dep_cool`
+    ///
+    /// # Security
+    /// - Only the owner can modify the cooldown
+    pub fn set_deposit_cooldown(env: Env, cooldown: u64) {
+        Self::require_is_owner(&env);
+
+        let old_cooldown = Self::get_deposit_cooldown(env.clone());
+        env.storage()
+            .instance()
+            .set(&ConfigKey::DepositCooldownSeconds, &cooldown);
+
+        Self::record_config_change(
+            &env,
+            symbol_short!("dep_cool"),
+            old_cooldown as i128,
+            cooldown as i128,
+        );
+    }
+
+    /// Returns the per-user deposit cooldown.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The cooldown in seconds, or 0 if disabled
+    pub fn get_deposit_cooldown(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&ConfigKey::DepositCooldownSeconds)
+            .unwrap_or(0)
+    }
+
+    /// Sets how long a fresh deposit is excluded from reported gains.
+    ///
+    /// Deposits made within `delay` seconds before a positive
+    /// `update_total_assets` keep their pre-update value; the whole gain
+    /// goes to the older shares. This stops just-in-time deposits from
+    /// capturing yield they did not earn. Setting to 0 disables the delay.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `delay` - Delay in seconds
+    ///
+    /// # Returns
+    /// Nothing. This function updates the delay and returns nothing.
+    ///
+    /// # Panics
+    /// - If the caller is not the owner
+    ///
+    /// # Events
+    /// Emits `ConfigUpdatedEvent` with key `acr_delay`
+    ///
+    /// # Security
+    /// - Only the owner can modify the delay
+    pub fn set_accrual_delay(env: Env, delay: u64) {
+        Self::require_is_owner(&env);
+
+        let old_delay = Self::get_accrual_delay(env.clone());
+        env.storage()
+            .instance()
+            .set(&ConfigKey::AccrualDelay, &delay);
+
+        Self::record_config_change(
+            &env,
+            symbol_short!("acr_delay"),
+            old_delay as i128,
+            delay as i128,
+        );
+    }
+
+    /// Returns the accrual delay for fresh deposits.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The delay in seconds, or 0 if disabled
+    pub fn get_accrual_delay(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&ConfigKey::AccrualDelay)
+            .unwrap_or(0)
+    }
+
+    /// Registers a strategy the agent may deploy funds to.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `strategy` - The strategy contract address
+    ///
+    /// # Returns
+    /// Nothing. This function registers the strategy and returns nothing.
+    ///
+    /// # Panics
+    /// - If multi-sig is off and the caller is not the owner
+    /// - If multi-sig is on and the action lacks `threshold` approvals
+    /// - If the strategy is already registered
+    /// - If the strategy cap is set and already reached
+    ///
+    /// # Events
+    /// Emits `StrategyRegisteredEvent` with the strategy address
+    ///
+    /// # Security
+    /// - Only the owner can widen the set of addresses funds may flow to
+    pub fn register_strategy(env: Env, strategy: Address) {
+        Self::require_owner_approval(&env, symbol_short!("strategy"), strategy.clone());
+
+        let mut strategies = Self::get_strategies(env.clone());
+        assert!(
+            !strategies.contains(&strategy),
+            "Strategy already registered"
+        );
+        let max = Self::get_max_strategies(env.clone());
+        assert!(max == 0 || strategies.len() < max, "Strategy limit reached");
+        strategies.push_back(strategy.clone());
+        env.storage()
+            .instance()
+            .set(&DataKey::Strategies, &strategies);
+
+        env.events().publish(
+            (symbol_short!("strat_add"),),
+            StrategyRegisteredEvent { strategy },
+        );
+    }
+
+    /// Sets the maximum number of registered strategies.
+    ///
+    /// Every strategy widens the set of contracts holding vault funds, so
+    /// the cap bounds how far `rebalance_funds` can spread them.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `max` - The strategy cap, 0 to disable
+    ///
+    /// # Panics
+    /// - If the caller is not the owner
+    ///
+    /// # Events
+    /// Emits `ConfigUpdatedEvent` with key `max_strat`
+    ///
+    /// # Security
+    /// - Only the owner can modify the strategy cap
+    /// - Lowering the cap below the current count does not unregister anything
+    pub fn set_max_strategies(env: Env, max: u32) {
+        Self::require_is_owner(&env);
+
+        let old_max = Self::get_max_strategies(env.clone());
+        env.storage()
+            .instance()
+            .set(&ConfigKey::MaxStrategies, &max);
+
+        Self::record_config_change(
+            &env,
+            symbol_short!("max_strat"),
+            old_max as i128,
+            max as i128,
+        );
+    }
+
+    /// Returns the maximum number of registered strategies.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The strategy cap, or 0 if no cap
+    pub fn get_max_strategies(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&ConfigKey::MaxStrategies)
+            .unwrap_or(0)
+    }
+
+    /// Sets the minimum interval between successive `update_total_assets` calls.
+    ///
+    /// Limits how quickly a compromised reporter can move the share price.
+    /// Setting to 0 disables the cooldown.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `cooldown` - Minimum interval in seconds
+    ///
+    /// # Returns
+    /// Nothing. This function updates the cooldown and returns nothing.
+    ///
+    /// # Panics
+    /// - If the caller is not the owner
+    ///
+    /// # Events
+    /// Emits `ConfigUpdatedEvent` with key `ast_cool`
+    ///
+    /// # Security
+    /// - Only the owner can modify the cooldown
+    pub fn set_assets_update_cooldown(env: Env, cooldown: u64) {
+        Self::require_is_owner(&env);
+
+        let old_cooldown = Self::get_assets_update_cooldown(env.clone());
+        env.storage()
+            .instance()
+            .set(&ConfigKey::AssetsUpdateCooldown, &cooldown);
+
+        Self::record_config_change(
+            &env,
+            symbol_short!("ast_cool"),
+            old_cooldown as i128,
+            cooldown as i128,
+        );
+    }
+
+    /// Returns the minimum interval between `update_total_assets` calls.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The cooldown in seconds, or 0 if disabled
+    pub fn get_assets_update_cooldown(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&ConfigKey::AssetsUpdateCooldown)
+            .unwrap_or(0)
+    }
+
+    /// Sets the maximum change a single `update_total_assets` call may make.
+    ///
+    /// The change is measured against the current total assets, in basis
+    /// points. Setting to 0 disables the cap.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `max_delta_bps` - Maximum change in basis points (e.g., 500 = 5%)
+    ///
+    /// # Returns
+    /// Nothing. This function updates the cap and returns nothing.
+    ///
+    /// # Panics
+    /// - If the caller is not the owner
+    /// - If max_delta_bps is negative
+    ///
+    /// # Events
+    /// Emits `ConfigUpdatedEvent` with key `ast_delta`
+    ///
+    /// # Security
+    /// - Only the owner can modify the cap
+    pub fn set_max_assets_delta_bps(env: Env, max_delta_bps: i128) {
+        Self::require_is_owner(&env);
+        assert!(max_delta_bps >= 0, "Delta cap cannot be negative");
+
+        let old_delta = Self::get_max_assets_delta_bps(env.clone());
+        env.storage()
+            .instance()
+            .set(&ConfigKey::MaxAssetsDeltaBps, &max_delta_bps);
+
+        Self::record_config_change(&env, symbol_short!("ast_delta"), old_delta, max_delta_bps);
+    }
+
+    /// Returns the maximum change per `update_total_assets` call.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The cap in basis points, or 0 if disabled
+    pub fn get_max_assets_delta_bps(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&ConfigKey::MaxAssetsDeltaBps)
+            .unwrap_or(0)
+    }
+
+    /// Sets the oracle that `update_total_assets` is cross-checked against.
+    ///
+    /// Once set, every reported total must lie within
+    /// `AssetsOracleToleranceBps` of the oracle's independent figure.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `oracle` - The assets oracle contract address
+    ///
+    /// # Returns
+    /// Nothing. This function updates the oracle and returns nothing.
+    ///
+    /// # Panics
+    /// - If the caller is not the owner
+    ///
+    /// # Events
+    /// Emits `AssetsOracleUpdatedEvent` with the new oracle address
+    ///
+    /// # Security
+    /// - Only the owner can set the oracle; it bounds every total assets report
+    pub fn set_assets_oracle(env: Env, oracle: Address) {
+        Self::require_is_owner(&env);
+        let old_oracle = Self::get_assets_oracle(env.clone());
+
+        env.storage()
+            .instance()
+            .set(&ConfigKey::AssetsOracle, &oracle);
+        Self::append_address_change(
+            &env,
+            symbol_short!("ast_orc"),
+            old_oracle,
+            Some(oracle.clone()),
+        );
+
+        env.events().publish(
+            (symbol_short!("ast_orc"),),
+            AssetsOracleUpdatedEvent { oracle },
+        );
+    }
+
+    /// Returns the configured assets oracle, if any.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The oracle contract address, or `None` if not configured
+    pub fn get_assets_oracle(env: Env) -> Option<Address> {
+        env.storage().instance().get(&ConfigKey::AssetsOracle)
+    }
+
+    /// Sets how far a total assets report may deviate from the assets oracle.
+    ///
+    /// With the default of 0 a report must match the oracle exactly.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `tolerance_bps` - Maximum deviation in basis points (e.g., 100 = 1%)
+    ///
+    /// # Returns
+    /// Nothing. This function updates the tolerance and returns nothing.
+    ///
+    /// # Panics
+    /// - If the caller is not the owner
+    /// - If tolerance_bps is outside 0..=10000
+    ///
+    /// # Events
+    /// Emits `ConfigUpdatedEvent` with key `orc_tol`
+    ///
+    /// # Security
+    /// - Only the owner can modify the tolerance
+    pub fn set_assets_oracle_tolerance_bps(env: Env, tolerance_bps: i128) {
+        Self::require_is_owner(&env);
+        assert!(
+            (0..=10_000).contains(&tolerance_bps),
+            "Invalid oracle tolerance"
+        );
+
+        let old_tolerance = Self::get_assets_oracle_tolerance_bps(env.clone());
+        env.storage()
+            .instance()
+            .set(&ConfigKey::AssetsOracleToleranceBps, &tolerance_bps);
+
+        Self::record_config_change(&env, symbol_short!("orc_tol"), old_tolerance, tolerance_bps);
+    }
+
+    /// Returns the tolerance between total assets reports and the assets oracle.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The tolerance in basis points
+    pub fn get_assets_oracle_tolerance_bps(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&ConfigKey::AssetsOracleToleranceBps)
+            .unwrap_or(0)
+    }
+
+    /// Sets the money market that idle on-chain reserves are parked in.
+    ///
+    /// While set, deposits and withdrawals keep `ReserveBufferBps` of the
+    /// on-chain reserve liquid in the vault and park the rest in the
+    /// strategy; any withdrawal the vault cannot cover from its balance
+    /// pulls the shortfall back first. Replacing or clearing the strategy
+    /// pulls everything out of the old one.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `strategy` - The money market contract address, or `None` to stop parking
+    ///
+    /// # Returns
+    /// Nothing. This function updates the strategy and returns nothing.
+    ///
+    /// # Panics
+    /// - If multi-sig is off and the caller is not the owner
+    /// - If multi-sig is on and the action lacks `threshold` approvals
+    /// - If the old strategy does not return the parked reserve in full
+    ///
+    /// # Events
+    /// Emits `ReserveStrategyUpdatedEvent` with the new strategy, plus
+    /// `ReserveMovedEvent` for any funds moved
+    ///
+    /// # Security
+    /// - Only the owner can choose where the reserve is parked
+    pub fn set_reserve_strategy(env: Env, strategy: Option<Address>) {
+        Self::require_owner_approval(&env, symbol_short!("reserve"), strategy.clone());
+        let old_strategy = Self::get_reserve_strategy(env.clone());
+
+        let usdc_token = Self::get_usdc_token(env.clone());
+        let token_client = token::Client::new(&env, &usdc_token);
+        let deployed = Self::get_reserve_deployed(env.clone());
+        if deployed > 0 {
+            Self::pull_reserve(&env, &token_client, deployed);
+        }
+
+        match &strategy {
+            Some(strategy) => env
+                .storage()
+                .instance()
+                .set(&ConfigKey::ReserveStrategy, strategy),
+            None => env.storage().instance().remove(&ConfigKey::ReserveStrategy),
+        }
+        Self::append_address_change(
+            &env,
+            symbol_short!("rsv_strat"),
+            old_strategy,
+            strategy.clone(),
+        );
+        Self::sync_reserve(&env);
+
+        env.events().publish(
+            (symbol_short!("rsv_strat"),),
+            ReserveStrategyUpdatedEvent { strategy },
+        );
+    }
+
+    /// Returns the configured reserve strategy, if any.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The money market contract address, or `None` if not configured
+    pub fn get_reserve_strategy(env: Env) -> Option<Address> {
+        env.storage().instance().get(&ConfigKey::ReserveStrategy)
+    }
+
+    /// Sets the share of the on-chain reserve kept liquid in the vault.
+    ///
+    /// With the default of 0 the whole idle reserve is parked and every
+    /// withdrawal pulls from the strategy.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `buffer_bps` - Liquid share in basis points (e.g., 2000 = 20%)
+    ///
+    /// # Returns
+    /// Nothing. This function updates the buffer and returns nothing.
+    ///
+    /// # Panics
+    /// - If the caller is not the owner
+    /// - If `buffer_bps` is outside 0..=10000
+    ///
+    /// # Events
+    /// Emits `ConfigUpdatedEvent` with key `rsv_buf`
+    ///
+    /// # Security
+    /// - Only the owner can change the liquid buffer
+    pub fn set_reserve_buffer_bps(env: Env, buffer_bps: i128) {
+        Self::require_is_owner(&env);
+        assert!((0..=10_000).contains(&buffer_bps), "Invalid reserve buffer");
+
+        let old_buffer = Self::get_reserve_buffer_bps(env.clone());
+        env.storage()
+            .instance()
+            .set(&ConfigKey::ReserveBufferBps, &buffer_bps);
+
+        Self::record_config_change(&env, symbol_short!("rsv_buf"), old_buffer, buffer_bps);
+    }
+
+    /// Returns the share of the on-chain reserve kept liquid in the vault.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The buffer in basis points
+    pub fn get_reserve_buffer_bps(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&ConfigKey::ReserveBufferBps)
+            .unwrap_or(0)
+    }
+
+    /// Returns the USDC currently parked in the reserve strategy.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The parked amount in raw units (7 decimal places)
+    pub fn get_reserve_deployed(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::ReserveDeployed)
+            .unwrap_or(0)
+    }
+
+    /// Sets the share of total assets the agent should keep on-chain.
+    ///
+    /// On-chain liquidity is the vault's USDC balance plus whatever is parked
+    /// in the reserve strategy. A withdrawal that takes it from at or above
+    /// the target to below it emits `ReserveLowEvent`, so the agent can pull
+    /// funds back from strategies without polling. With the default of 0 the
+    /// event never fires.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `target_bps` - Target in basis points of total assets (e.g., 1000 = 10%)
+    ///
+    /// # Returns
+    /// Nothing. This function updates the target and returns nothing.
+    ///
+    /// # Panics
+    /// - If the caller is not the owner
+    /// - If `target_bps` is outside 0..=10000
+    ///
+    /// # Events
+    /// Emits `ConfigUpdatedEvent` with key `rsv_tgt`
+    ///
+    /// # Security
+    /// - Only the owner can change the reserve target
+    pub fn set_reserve_target_bps(env: Env, target_bps: i128) {
+        Self::require_is_owner(&env);
+        assert!((0..=10_000).contains(&target_bps), "Invalid reserve target");
+
+        let old_target = Self::get_reserve_target_bps(env.clone());
+        env.storage()
+            .instance()
+            .set(&ConfigKey::ReserveTargetBps, &target_bps);
+
+        Self::record_config_change(&env, symbol_short!("rsv_tgt"), old_target, target_bps);
+    }
+
+    /// Returns the share of total assets the agent should keep on-chain.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The target in basis points, or 0 when disabled
+    pub fn get_reserve_target_bps(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&ConfigKey::ReserveTargetBps)
+            .unwrap_or(0)
+    }
+
+    /// Sets the tolerance between a quoted and executed share price.
+    ///
+    /// Applies to `deposit_with_quote` and `withdraw_with_quote`. With the
+    /// default of 0 the current price must match the quote exactly.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `max_slippage_bps` - Maximum deviation in basis points (e.g., 50 = 0.5%)
+    ///
+    /// # Returns
+    /// Nothing. This function updates the tolerance and returns nothing.
+    ///
+    /// # Panics
+    /// - If the caller is not the owner
+    /// - If max_slippage_bps is negative or above 10000
+    ///
+    /// # Events
+    /// Emits `ConfigUpdatedEvent` with key `slippage`
+    ///
+    /// # Security
+    /// - Only the owner can modify the tolerance
+    pub fn set_max_slippage_bps(env: Env, max_slippage_bps: i128) {
+        Self::require_is_owner(&env);
+        assert!(
+            (0..=10_000).contains(&max_slippage_bps),
+            "Invalid slippage tolerance"
+        );
+
+        let old_slippage = Self::get_max_slippage_bps(env.clone());
+        env.storage()
+            .instance()
+            .set(&ConfigKey::MaxSlippageBps, &max_slippage_bps);
+
+        Self::record_config_change(
+            &env,
+            symbol_short!("slippage"),
+            old_slippage,
+            max_slippage_bps,
+        );
+    }
+
+    /// Returns the tolerance between a quoted and executed share price.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The tolerance in basis points
+    pub fn get_max_slippage_bps(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&ConfigKey::MaxSlippageBps)
+            .unwrap_or(0)
+    }
+
+    /// Turns publication of legacy deposit/withdraw event shapes on or off.
+    ///
+    /// While enabled, every `DepositEvent` and `WithdrawEvent` is accompanied
+    /// by its pre-share `{ user, amount }` shape under an extra `legacy`
+    /// topic, so indexers can migrate without downtime.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `enabled` - Whether to publish legacy shapes
+    ///
+    /// # Returns
+    /// Nothing. This function updates the flag and returns nothing.
+    ///
+    /// # Panics
+    /// - If the caller is not the owner
+    ///
+    /// # Events
+    /// Emits `ConfigUpdatedEvent` with key `dual_emit` (0 = off, 1 = on)
+    ///
+    /// # Security
+    /// - Only the owner can change event output
+    pub fn set_dual_emit(env: Env, enabled: bool) {
+        Self::require_is_owner(&env);
+
+        let old_enabled = Self::get_dual_emit(env.clone());
+        env.storage().instance().set(&ConfigKey::DualEmit, &enabled);
+
+        Self::record_config_change(
+            &env,
+            symbol_short!("dual_emit"),
+            old_enabled as i128,
+            enabled as i128,
+        );
+    }
+
+    /// Returns whether legacy event shapes are being published.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// `true` if dual emission is enabled
+    pub fn get_dual_emit(env: Env) -> bool {
+        env.storage()
+            .instance()
+            .get(&ConfigKey::DualEmit)
+            .unwrap_or(false)
+    }
+
+    /// Turns the total shares drift check on or off.
+    ///
+    /// While on, every share balance change also updates a running checksum
+    /// of user balances, and `get_total_shares` emits `DriftDetectedEvent`
+    /// when the stored total disagrees with it. Turning the check on seeds
+    /// the checksum from the current depositor balances.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `enabled` - Whether to run the check
+    ///
+    /// # Returns
+    /// Nothing. This function updates the flag and returns nothing.
+    ///
+    /// # Panics
+    /// - If the caller is not the owner
+    ///
+    /// # Events
+    /// Emits `ConfigUpdatedEvent` with key `drift_chk` (0 = off, 1 = on)
+    ///
+    /// # Security
+    /// - Only the owner can toggle the check; reads never revert on drift
+    pub fn set_drift_check(env: Env, enabled: bool) {
+        Self::require_is_owner(&env);
+
+        let old_enabled = Self::get_drift_check(env.clone());
+        if enabled && !old_enabled {
+            let mut checksum = 0;
+            for depositor in Self::get_depositors(env.clone()).iter() {
+                checksum += Self::get_shares(env.clone(), depositor);
+            }
+            env.storage()
+                .persistent()
+                .set(&PositionKey::ShareChecksum, &checksum);
+        }
+        env.storage()
+            .instance()
+            .set(&ConfigKey::DriftCheck, &enabled);
+
+        Self::record_config_change(
+            &env,
+            symbol_short!("drift_chk"),
+            old_enabled as i128,
+            enabled as i128,
+        );
+    }
+
+    /// Returns whether the total shares drift check is on.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// `true` if the drift check is enabled
+    pub fn get_drift_check(env: Env) -> bool {
+        env.storage()
+            .instance()
+            .get(&ConfigKey::DriftCheck)
+            .unwrap_or(false)
+    }
+
+    /// Waives all vault fees until `until_timestamp`.
+    ///
+    /// Intended for promotional campaigns. Every fee computation returns 0
+    /// while the ledger time is before `until_timestamp`. Passing a time in
+    /// the past ends the holiday immediately.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `until_timestamp` - Ledger timestamp at which fees resume
+    ///
+    /// # Returns
+    /// Nothing. This function updates the holiday window and returns nothing.
+    ///
+    /// # Panics
+    /// - If multi-sig is off and the caller is not the owner
+    /// - If multi-sig is on and the action lacks `threshold` approvals
+    ///
+    /// # Events
+    /// Emits `ConfigUpdatedEvent` with key `fee_hol`
+    ///
+    /// # Security
+    /// - Only the owner can declare a fee holiday
+    pub fn set_fee_holiday(env: Env, until_timestamp: u64) {
+        Self::require_owner_approval(&env, symbol_short!("fee_hol"), until_timestamp);
+
+        let old_until = Self::get_fee_holiday(env.clone());
+        env.storage()
+            .instance()
+            .set(&ConfigKey::FeeHolidayUntil, &until_timestamp);
+
+        Self::record_config_change(
+            &env,
+            symbol_short!("fee_hol"),
+            old_until as i128,
+            until_timestamp as i128,
+        );
+    }
+
+    /// Returns the end of the current fee holiday.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The timestamp at which fees resume, or 0 if none was set
+    pub fn get_fee_holiday(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&ConfigKey::FeeHolidayUntil)
+            .unwrap_or(0)
+    }
+
+    /// Returns whether a fee holiday is currently in effect.
+    ///
+    /// Every fee computation returns 0 while this is true.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// `true` if the ledger time is before the fee holiday end
+    pub fn is_fee_holiday(env: Env) -> bool {
+        env.ledger().timestamp() < Self::get_fee_holiday(env.clone())
+    }
+
+    /// Sets the annual management fee on total assets.
+    ///
+    /// The fee accrues in `update_total_assets` and before every deposit
+    /// and exit is priced, for the time elapsed since the last accrual, so
+    /// no one enters or leaves at a price that omits fees already owed. It
+    /// is paid by minting shares to the fee recipient, so
+    /// holders are diluted pro rata instead of USDC leaving the vault. Fees
+    /// owed at the old rate are accrued first.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `bps` - Annual fee in basis points, 0 to disable
+    ///
+    /// # Returns
+    /// Nothing. This function updates the fee and returns nothing.
+    ///
+    /// # Panics
+    /// - If multi-sig is off and the caller is not the owner
+    /// - If multi-sig is on and the action lacks `threshold` approvals
+    /// - If bps is negative or exceeds `MAX_MANAGEMENT_FEE_BPS`
+    ///
+    /// # Events
+    /// Emits `ConfigUpdatedEvent` with key `mgmt_fee`, and
+    /// `FeeAccruedEvent` if fees at the old rate were owed
+    ///
+    /// # Security
+    /// - Only the owner can set the fee, and never above the cap
+    pub fn set_management_fee_bps(env: Env, bps: i128) {
+        Self::require_owner_approval(&env, symbol_short!("mgmt_fee"), bps);
+        assert!(
+            (0..=MAX_MANAGEMENT_FEE_BPS).contains(&bps),
+            "Invalid management fee"
+        );
+
+        Self::accrue_management_fee(&env);
+        let old_bps = Self::get_management_fee_bps(env.clone());
+        env.storage()
+            .instance()
+            .set(&ConfigKey::ManagementFeeBps, &bps);
+
+        Self::record_config_change(&env, symbol_short!("mgmt_fee"), old_bps, bps);
+    }
+
+    /// Returns the annual management fee.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The fee in basis points, or 0 if disabled
+    pub fn get_management_fee_bps(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&ConfigKey::ManagementFeeBps)
+            .unwrap_or(0)
+    }
+
+    /// Returns the cumulative fees collected, by fee type.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// A `FeesCollected` in raw USDC units
+    pub fn get_fees_collected(env: Env) -> FeesCollected {
+        env.storage()
+            .instance()
+            .get(&ConfigKey::FeesCollected)
+            .unwrap_or_default()
+    }
+
+    /// Sets the performance fee on share price gains.
+    ///
+    /// In `update_total_assets`, once the share price rises above the
+    /// high-water mark, the fee recipient is minted shares worth `bps` of
+    /// the gain above it, and the mark moves up to the price after the fee.
+    /// The mark never falls, so a loss must be recovered before any further
+    /// fee is taken.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `bps` - Fee in basis points of the gain, 0 to disable
+    ///
+    /// # Returns
+    /// Nothing. This function updates the fee and returns nothing.
+    ///
+    /// # Panics
+    /// - If multi-sig is off and the caller is not the owner
+    /// - If multi-sig is on and the action lacks `threshold` approvals
+    /// - If bps is negative or exceeds `MAX_PERFORMANCE_FEE_BPS`
+    ///
+    /// # Events
+    /// Emits `ConfigUpdatedEvent` with key `perf_fee`
+    ///
+    /// # Security
+    /// - Only the owner can set the fee, and never above the cap
+    pub fn set_performance_fee_bps(env: Env, bps: i128) {
+        Self::require_owner_approval(&env, symbol_short!("perf_fee"), bps);
+        assert!(
+            (0..=MAX_PERFORMANCE_FEE_BPS).contains(&bps),
+            "Invalid performance fee"
+        );
+
+        let old_bps = Self::get_performance_fee_bps(env.clone());
+        env.storage()
+            .instance()
+            .set(&ConfigKey::PerformanceFeeBps, &bps);
+
+        Self::record_config_change(&env, symbol_short!("perf_fee"), old_bps, bps);
+    }
+
+    /// Returns the performance fee.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The fee in basis points of gains, or 0 if disabled
+    pub fn get_performance_fee_bps(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&ConfigKey::PerformanceFeeBps)
+            .unwrap_or(0)
+    }
+
+    /// Returns the share price above which a performance fee is owed.
+    ///
+    /// Until fees are first settled this is the share price of an empty vault.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The high-water mark scaled by `SHARE_PRICE_SCALE`
+    pub fn get_high_water_mark(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&ConfigKey::HighWaterMark)
+            .unwrap_or_else(|| {
+                SHARE_PRICE_SCALE * SHARE_PRICE_SCALE / Self::get_initial_share_ratio(env.clone())
+            })
+    }
+
+    /// Assigns or clears the address fee shares are minted to.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `recipient` - The fee recipient, or `None` to pay fees to the owner
+    ///
+    /// # Returns
+    /// Nothing. This function updates the recipient and returns nothing.
+    ///
+    /// # Panics
+    /// - If multi-sig is off and the caller is not the owner
+    /// - If multi-sig is on and the action lacks `threshold` approvals
+    ///
+    /// # Events
+    /// Emits `FeeRecipientUpdatedEvent` with the new recipient
+    ///
+    /// # Security
+    /// - Only the owner can redirect fees
+    pub fn set_fee_recipient(env: Env, recipient: Option<Address>) {
+        Self::require_owner_approval(&env, symbol_short!("fee_rcpt"), recipient.clone());
+        let old_recipient: Option<Address> = env.storage().instance().get(&ConfigKey::FeeRecipient);
+
+        match &recipient {
+            Some(recipient) => env
+                .storage()
+                .instance()
+                .set(&ConfigKey::FeeRecipient, recipient),
+            None => env.storage().instance().remove(&ConfigKey::FeeRecipient),
+        }
+        Self::append_address_change(
+            &env,
+            symbol_short!("fee_rcpt"),
+            old_recipient,
+            recipient.clone(),
+        );
+
+        env.events().publish(
+            (symbol_short!("fee_rcpt"),),
+            FeeRecipientUpdatedEvent { recipient },
+        );
+    }
+
+    /// Returns the address fee shares are minted to.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The fee recipient, or the owner if none is assigned
+    pub fn get_fee_recipient(env: Env) -> Address {
+        env.storage()
+            .instance()
+            .get(&ConfigKey::FeeRecipient)
+            .unwrap_or_else(|| Self::get_owner(env.clone()))
+    }
+
+    /// Sets the fee deducted from every voluntary exit: `withdraw`,
+    /// `redeem`, `redeem_to_strategy` and queued withdrawal claims.
+    ///
+    /// The fee stays in the vault, raising the share price for remaining
+    /// holders, to discourage depositing and withdrawing in quick
+    /// succession. The exit that burns the last shares, and any exit during
+    /// a fee holiday, pays no fee. Users opted in with `set_auto_compound`
+    /// get the fee back as shares.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `bps` - Fee in basis points of the payout, 0 to disable
+    ///
+    /// # Returns
+    /// Nothing. This function updates the fee and returns nothing.
+    ///
+    /// # Panics
+    /// - If multi-sig is off and the caller is not the owner
+    /// - If multi-sig is on and the action lacks `threshold` approvals
+    /// - If bps is negative or exceeds `MAX_WITHDRAWAL_FEE_BPS`
+    ///
+    /// # Events
+    /// Emits `ConfigUpdatedEvent` with key `wd_fee`
+    ///
+    /// # Security
+    /// - Only the owner can set the fee, and never above the cap
+    pub fn set_withdrawal_fee_bps(env: Env, bps: i128) {
+        Self::require_owner_approval(&env, symbol_short!("wd_fee"), bps);
+        assert!(
+            (0..=MAX_WITHDRAWAL_FEE_BPS).contains(&bps),
+            "Invalid withdrawal fee"
+        );
+
+        let old_bps = Self::get_withdrawal_fee_bps(env.clone());
+        env.storage()
+            .instance()
+            .set(&ConfigKey::WithdrawalFeeBps, &bps);
+
+        Self::record_config_change(&env, symbol_short!("wd_fee"), old_bps, bps);
+    }
+
+    /// Returns the withdrawal fee.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The fee in basis points of the payout, or 0 if disabled
+    pub fn get_withdrawal_fee_bps(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&ConfigKey::WithdrawalFeeBps)
+            .unwrap_or(0)
+    }
+
+    /// Updates the authorized AI agent address.
+    ///
+    /// Only the owner can update the agent. This allows for agent key rotation
+    /// or migration to a new agent implementation.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `new_agent` - The new AI agent address
+    ///
+    /// # Returns
+    /// Nothing. This function updates the agent and returns nothing.
+    ///
+    /// # Panics
+    /// - If multi-sig is off and the caller is not the owner
+    /// - If multi-sig is on and the action lacks `threshold` approvals
+    /// - If the new agent is the USDC token or the vault itself
+    ///
+    /// # Events
+    /// Emits `AgentUpdatedEvent` with:
+    /// - `old_agent`: Previous agent address
+    /// - `new_agent`: New agent address
+    ///
+    /// Also emits `RoleConflationEvent` if the new agent is the owner.
+    ///
+    /// # Security
+    /// - Only the owner can update the agent
+    /// - The old agent will immediately lose access to rebalance()
+    pub fn update_agent(env: Env, new_agent: Address) {
+        Self::require_owner_approval(&env, symbol_short!("agent"), new_agent.clone());
+        Self::require_valid_role_address(&env, &new_agent, &Self::get_usdc_token(env.clone()));
+
+        let old_agent: Address = env.storage().instance().get(&DataKey::Agent).unwrap();
+
+        env.storage().instance().set(&DataKey::Agent, &new_agent);
+        Self::warn_if_roles_conflated(&env);
+        Self::append_address_change(
+            &env,
+            symbol_short!("agent"),
+            Some(old_agent.clone()),
+            Some(new_agent.clone()),
+        );
+
+        env.events().publish(
+            (symbol_short!("agent_upd"),),
+            AgentUpdatedEvent {
+                old_agent: old_agent.clone(),
+                new_agent: new_agent.clone(),
+            },
+        );
+    }
+
+    /// Updates the total assets tracked by the vault.
+    ///
+    /// This function allows the owner to manually adjust the total assets
+    /// value, typically used for reconciliation or accounting corrections.
+    /// Use with caution as it affects TVL calculations. A gain is attributed
+    /// to the strategies pro rata to their allocations and weighted towards
+    /// active boosts, and a drop in total assets during a pause restarts the
+    /// auto-unpause window.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `new_total` - New total assets value in USDC units (7 decimal places)
+    ///
+    /// # Returns
+    /// Nothing. This function updates total assets and returns nothing.
+    ///
+    /// # Panics
+    /// - If multi-sig is off and the caller is not the owner
+    /// - If multi-sig is on and the action lacks `threshold` approvals
+    /// - If rebalancing is paused
+    /// - If called again within `AssetsUpdateCooldown` seconds
+    /// - If the change exceeds `MaxAssetsDeltaBps` of the current total
+    /// - If the new total deviates from the assets oracle beyond its tolerance
+    ///
+    /// # Events
+    /// Emits `AssetsUpdatedEvent` with:
+    /// - `old_total`: Previous total assets
+    /// - `new_total`: New total assets
+    ///
+    /// Also emits `UnderwaterEvent` if the new total crosses the aggregate
+    /// cost basis in either direction, `AccrualExcludedEvent` if a gain is
+    /// withheld from deposits inside the accrual delay, `BoostDistributedEvent`
+    /// if a gain is weighted towards active boosts, `FeeAccruedEvent` if a
+    /// management fee is charged, `PerformanceFeeEvent` if the share price
+    /// rises above the high-water mark, and `PriceMilestoneEvent` if the share
+    /// price crosses a `PriceStepBps` step.
+    ///
+    /// # Security
+    /// - Only the owner can update total assets
+    /// - This should only be used for reconciliation, not for manipulating balances
+    pub fn update_total_assets(env: Env, new_total: i128) {
+        Self::require_owner_approval(&env, symbol_short!("assets"), new_total);
+        Self::require_rebalance_not_paused(&env);
+
+        Self::apply_total_assets(&env, new_total);
+    }
+
+    /// Rebuilds `TotalShares` from the per-user share balances.
+    ///
+    /// Recovery tool for the case where the total drifts from the sum of
+    /// `Shares(user)`. Sums the balance of every enumerated depositor plus
+    /// the shares escrowed in pending withdrawal requests, which remain part
+    /// of the total until claimed.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The recomputed total shares
+    ///
+    /// # Panics
+    /// - If the caller is not the owner
+    /// - If the vault is not paused
+    ///
+    /// # Events
+    /// Emits `TotalSharesRecomputedEvent` with the old and new totals
+    ///
+    /// # Security
+    /// - Only the owner can recompute, and only while the vault is paused so
+    ///   no user action can race the correction
+    pub fn recompute_total_shares(env: Env) -> i128 {
+        Self::require_is_owner(&env);
+        assert!(Self::is_paused(env.clone()), "Vault is not paused");
+
+        let mut new_total = Self::get_total_pending_shares(&env);
+        for depositor in Self::get_depositors(env.clone()).iter() {
+            new_total += Self::get_shares(env.clone(), depositor);
+        }
+        let old_total = Self::stored_total_shares(&env);
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalShares, &new_total);
+
+        env.events().publish(
+            (symbol_short!("shr_recmp"),),
+            TotalSharesRecomputedEvent {
+                old_total,
+                new_total,
+            },
+        );
+
+        new_total
+    }
+
+    // ==========================================================================
+    // SHARE ACCOUNTING
+    // ==========================================================================
+
+    /// Converts a USDC amount into vault shares at the current share price.
+    ///
+    /// Rounds down, so the vault never mints more shares than the assets
+    /// justify. An empty vault converts at the initial share ratio (1:1
+    /// unless set with `set_initial_share_ratio`). The product is taken before the
+    /// division, so the result is the exact floor of the true value and the
+    /// rounding dust is at most one share per conversion.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `amount` - Amount of USDC (7 decimal places)
+    ///
+    /// # Returns
+    /// The number of shares `amount` is worth
+    ///
+    /// # Panics
+    /// - If the implied share price is outside the configured sanity band
+    ///
+    /// # Events
+    /// None
+    pub fn convert_to_shares(env: Env, amount: i128) -> i128 {
+        let total_shares = Self::stored_total_shares(&env);
+        let total_assets = Self::get_total_deposits(env.clone());
+        if total_shares == 0 || total_assets == 0 {
+            return amount * Self::empty_vault_ratio(&env) / SHARE_PRICE_SCALE;
+        }
+        Self::require_share_price_in_bounds(&env, total_assets, total_shares);
+        let (total_assets, total_shares) = Self::offset_totals(&env, total_assets, total_shares);
+        Self::mul_div(amount, total_shares, total_assets)
+    }
+
+    /// Converts vault shares into their USDC value at the current share price.
+    ///
+    /// Rounds down, so reported values never exceed what the vault holds.
+    /// The whole supply converts to every asset the vault holds, so the
+    /// virtual shares never strand a remainder. An empty vault converts 1:1.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `shares` - Number of vault shares
+    ///
+    /// # Returns
+    /// The USDC value of `shares` in raw units (7 decimal places)
+    ///
+    /// # Panics
+    /// - If the implied share price is outside the configured sanity band
+    ///
+    /// # Events
+    /// None
+    pub fn convert_to_assets(env: Env, shares: i128) -> i128 {
+        let total_shares = Self::stored_total_shares(&env);
+        if total_shares == 0 {
+            return shares * SHARE_PRICE_SCALE / Self::empty_vault_ratio(&env);
+        }
+        let total_assets = Self::get_total_deposits(env.clone());
+        Self::require_share_price_in_bounds(&env, total_assets, total_shares);
+        if shares == total_shares {
+            return total_assets;
+        }
+        let (offset_assets, offset_shares) = Self::offset_totals(&env, total_assets, total_shares);
+        Self::mul_div(shares, offset_assets, offset_shares).min(total_assets)
+    }
+
+    /// Converts several share amounts into USDC at the current share price.
+    ///
+    /// Equivalent to calling `convert_to_assets` for each entry, in one read.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `shares` - Share amounts to convert (at most `MAX_BATCH_SIZE`)
+    ///
+    /// # Returns
+    /// The USDC value of each entry, in input order
+    ///
+    /// # Panics
+    /// - If more than `MAX_BATCH_SIZE` amounts are passed
+    /// - If the implied share price is outside the configured sanity band
+    ///
+    /// # Events
+    /// None
+    pub fn convert_to_assets_batch(env: Env, shares: Vec<i128>) -> Vec<i128> {
+        assert!(shares.len() <= MAX_BATCH_SIZE, "Batch too large");
+
+        let mut assets = Vec::new(&env);
+        for amount in shares.iter() {
+            assets.push_back(Self::convert_to_assets(env.clone(), amount));
+        }
+        assets
+    }
+
+    /// Returns the shares depositing `amount` mints (ERC-4626 `previewDeposit`).
+    ///
+    /// Rounds down exactly as `deposit()` does at current state. An empty
+    /// vault converts at the initial share ratio, 1:1 by default. A result
+    /// of 0 means `deposit()` would reject the amount as too small.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `amount` - Amount of USDC to deposit (7 decimal places)
+    ///
+    /// # Returns
+    /// The shares `amount` would mint
+    ///
+    /// # Panics
+    /// - If the implied share price is outside the configured sanity band
+    ///
+    /// # Events
+    /// None
+    pub fn preview_deposit(env: Env, amount: i128) -> i128 {
+        Self::convert_to_shares(env, amount)
+    }
+
+    /// Returns the shares withdrawing `amount` burns (ERC-4626 `previewWithdraw`).
+    ///
+    /// Rounds up exactly as `withdraw()` does, so any positive amount burns
+    /// at least one share, and applies favorable rounding for small amounts
+    /// while the budget covers it. Dust sweeping depends on the caller's
+    /// balance and is not reflected.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `amount` - Amount of USDC to withdraw (7 decimal places)
+    ///
+    /// # Returns
+    /// The shares `amount` would burn, or 0 if `amount` is not positive
+    ///
+    /// # Panics
+    /// - If the implied share price is outside the configured sanity band
+    ///
+    /// # Events
+    /// None
+    pub fn preview_withdraw(env: Env, amount: i128) -> i128 {
+        if amount <= 0 {
+            return 0;
+        }
+        let shares = Self::convert_to_shares_ceil(&env, amount);
+        Self::favorably_rounded_shares(&env, amount, shares)
+    }
+
+    /// Returns the USDC required to mint exactly `shares` (ERC-4626 `previewMint`).
+    ///
+    /// Rounds up so the vault is never shortchanged; this is exactly the
+    /// amount `mint()` pulls at current state. An empty vault converts 1:1.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `shares` - Number of shares to mint
+    ///
+    /// # Returns
+    /// The USDC cost of `shares` in raw units (7 decimal places)
+    ///
+    /// # Panics
+    /// - If the implied share price is outside the configured sanity band
+    ///
+    /// # Events
+    /// None
+    pub fn preview_mint(env: Env, shares: i128) -> i128 {
+        let total_shares = Self::stored_total_shares(&env);
+        let total_assets = Self::get_total_deposits(env.clone());
+        if total_shares == 0 || total_assets == 0 {
+            let ratio = Self::empty_vault_ratio(&env);
+            return Self::mul_div_ceil(shares, SHARE_PRICE_SCALE, ratio);
+        }
+        Self::require_share_price_in_bounds(&env, total_assets, total_shares);
+        let (total_assets, total_shares) = Self::offset_totals(&env, total_assets, total_shares);
+        Self::mul_div_ceil(shares, total_assets, total_shares)
+    }
+
+    /// Returns the USDC redeeming exactly `shares` pays out (ERC-4626 `previewRedeem`).
+    ///
+    /// Rounds down so the vault is never overdrawn; this is exactly the
+    /// amount `redeem()` and `redeem_to_strategy()` pay for `shares` at
+    /// current state, before any withdrawal fee `redeem()` deducts.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `shares` - Number of shares to redeem
+    ///
+    /// # Returns
+    /// The USDC value of `shares` in raw units (7 decimal places)
+    ///
+    /// # Panics
+    /// - If the implied share price is outside the configured sanity band
+    ///
+    /// # Events
+    /// None
+    pub fn preview_redeem(env: Env, shares: i128) -> i128 {
+        Self::convert_to_assets(env, shares)
+    }
+
+    /// Returns the smallest USDC amount that mints at least one share.
+    ///
+    /// After significant yield a small deposit can round down to zero
+    /// shares; this tells users the least they must deposit at the current
+    /// price. Deposits are still subject to the 1 USDC minimum.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// `ceil(total_assets / total_shares)`, or `MIN_DEPOSIT` for an empty vault
+    ///
+    /// # Panics
+    /// - If the implied share price is outside the configured sanity band
+    ///
+    /// # Events
+    /// None
+    pub fn min_deposit_for_shares(env: Env) -> i128 {
+        let total_shares = Self::stored_total_shares(&env);
+        let total_assets = Self::get_total_deposits(env.clone());
+        if total_shares == 0 || total_assets == 0 {
+            return MIN_DEPOSIT;
+        }
+        Self::require_share_price_in_bounds(&env, total_assets, total_shares);
+        let (total_assets, total_shares) = Self::offset_totals(&env, total_assets, total_shares);
+        Self::mul_div_ceil(1, total_assets, total_shares)
+    }
+
+    /// Returns the current share price.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// USDC per whole share scaled by `SHARE_PRICE_SCALE`; when no shares
+    /// exist, the price implied by the initial share ratio
+    ///
+    /// # Panics
+    /// None
+    ///
+    /// # Events
+    /// None
+    pub fn get_share_price(env: Env) -> i128 {
+        let total_shares = Self::stored_total_shares(&env);
+        if total_shares == 0 {
+            return SHARE_PRICE_SCALE * SHARE_PRICE_SCALE / Self::get_initial_share_ratio(env);
+        }
+        Self::get_total_deposits(env.clone()) * SHARE_PRICE_SCALE * Self::share_unit(&env)
+            / total_shares
+    }
+
+    /// Returns the price a deposit of `amount` actually transacts at.
+    ///
+    /// Share minting prices against the totals plus the virtual offset and
+    /// rounds down, so a deposit's entry price can differ from the spot
+    /// `get_share_price`; this reports the true price per whole share the
+    /// depositor pays.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `amount` - Amount of USDC to deposit (7 decimal places)
+    ///
+    /// # Returns
+    /// USDC per whole share scaled by `SHARE_PRICE_SCALE`, rounded up
+    ///
+    /// # Panics
+    /// - If amount is not positive
+    /// - If the deposit would mint no shares
+    /// - If the implied share price is outside the configured sanity band
+    ///
+    /// # Events
+    /// None
+    pub fn effective_deposit_price(env: Env, amount: i128) -> i128 {
+        Self::require_positive_amount(amount);
+        let shares = Self::convert_to_shares(env.clone(), amount);
+        assert!(shares > 0, "Deposit mints no shares");
+        Self::mul_div_ceil(amount, SHARE_PRICE_SCALE * Self::share_unit(&env), shares)
+    }
+
+    /// Returns the total assets at which `get_share_price` reaches a target.
+    ///
+    /// Lets operators seeding yield work out the value to pass to
+    /// `update_total_assets`. Rounds up to the smallest total that reaches
+    /// the target; if the target is not exactly representable at the current
+    /// total shares, the resulting price is the next one above it.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `target_price` - Desired USDC per whole share scaled by `SHARE_PRICE_SCALE`
+    ///
+    /// # Returns
+    /// The smallest total assets in raw USDC units whose share price is at
+    /// least `target_price` given the current total shares
+    ///
+    /// # Panics
+    /// - If target_price is not positive
+    /// - If no shares are outstanding
+    ///
+    /// # Events
+    /// None
+    pub fn assets_for_target_price(env: Env, target_price: i128) -> i128 {
+        Self::require_positive_amount(target_price);
+        let total_shares = Self::stored_total_shares(&env);
+        assert!(total_shares > 0, "No shares outstanding");
+        Self::mul_div_ceil(
+            target_price,
+            total_shares,
+            SHARE_PRICE_SCALE * Self::share_unit(&env),
+        )
+    }
+
+    /// Returns the vault shares held by a specific user.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `user` - The user address to query
+    ///
+    /// # Returns
+    /// The user's share balance
+    ///
+    /// # Panics
+    /// None
+    ///
+    /// # Events
+    /// None
+    pub fn get_shares(env: Env, user: Address) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Shares(user))
+            .unwrap_or(0)
+    }
+
+    /// Returns the total vault shares outstanding.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// Total shares across all users
+    ///
+    /// # Panics
+    /// None
+    ///
+    /// # Events
+    /// Emits `DriftDetectedEvent` if the drift check is on and the total
+    /// disagrees with the running share checksum; the total is still returned
+    pub fn get_total_shares(env: Env) -> i128 {
+        let total_shares = Self::stored_total_shares(&env);
+        if Self::get_drift_check(env.clone()) {
+            let checksum = Self::share_checksum(&env) + Self::get_total_pending_shares(&env);
+            if checksum != total_shares {
+                env.events().publish(
+                    (symbol_short!("drift"),),
+                    DriftDetectedEvent {
+                        total_shares,
+                        checksum,
+                    },
+                );
+            }
+        }
+        total_shares
+    }
+
+    // ==========================================================================
+    // SNAPSHOTS
+    // ==========================================================================
+
+    /// Takes a snapshot of vault totals and every user's share balance.
+    ///
+    /// Share balances are checkpointed lazily as they change, so taking a
+    /// snapshot only records the vault totals and bumps the snapshot id.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The new snapshot id (ids start at 1)
+    ///
+    /// # Panics
+    /// - If the caller is not the owner
+    ///
+    /// # Events
+    /// Emits `SnapshotTakenEvent` with the id and recorded totals
+    ///
+    /// # Security
+    /// - Only the owner can take snapshots
+    pub fn take_snapshot(env: Env) -> u32 {
+        Self::require_is_owner(&env);
+        Self::record_snapshot(&env)
+    }
+
+    /// Returns the vault totals recorded at a snapshot.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `snapshot_id` - The snapshot to query
+    ///
+    /// # Returns
+    /// The recorded `VaultSnapshot`
+    ///
+    /// # Panics
+    /// - If the snapshot does not exist
+    ///
+    /// # Events
+    /// None
+    pub fn get_snapshot(env: Env, snapshot_id: u32) -> VaultSnapshot {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Snapshot(snapshot_id))
+            .expect("Snapshot not found")
+    }
+
+    /// Returns the total shares outstanding at a past snapshot.
+    ///
+    /// Reads the total recorded with the snapshot, so later deposits and
+    /// withdrawals do not change it. Useful for governance quorums.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `snapshot_id` - The snapshot to query
+    ///
+    /// # Returns
+    /// The total shares recorded at the snapshot
+    ///
+    /// # Panics
+    /// - If the snapshot does not exist
+    ///
+    /// # Events
+    /// None
+    pub fn total_supply_at(env: Env, snapshot_id: u32) -> i128 {
+        Self::get_snapshot(env, snapshot_id).total_shares
+    }
+
+    /// Returns the net change in a user's share balance since a ledger.
+    ///
+    /// Compares the current balance with the balance checkpointed before
+    /// `ledger`, so changes made in `ledger` itself are included. Lets
+    /// indexers sync a position incrementally instead of replaying events.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `user` - The user address to query
+    /// * `ledger` - The first ledger sequence whose changes are counted
+    ///
+    /// # Returns
+    /// Current shares minus shares held before `ledger`; negative if the
+    /// position shrank
+    ///
+    /// # Panics
+    /// None
+    ///
+    /// # Events
+    /// None
+    pub fn shares_delta_since(env: Env, user: Address, ledger: u32) -> i128 {
+        let checkpoints: Vec<ShareCheckpoint> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::ShareCheckpoints(user.clone()))
+            .unwrap_or(Vec::new(&env));
+        let mut shares_before = 0;
+        for checkpoint in checkpoints.iter().rev() {
+            if checkpoint.ledger < ledger {
+                shares_before = checkpoint.shares;
+                break;
+            }
+        }
+        Self::get_shares(env, user) - shares_before
+    }
+
+    /// Returns the asset value of a user's shares at a past snapshot.
+    ///
+    /// Uses the user's share balance and the vault's total assets and total
+    /// shares as recorded at the snapshot, so later yield or withdrawals do
+    /// not change the result. Intended for tax and accounting exports.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `user` - The user address to query
+    /// * `snapshot_id` - The snapshot to value against
+    ///
+    /// # Returns
+    /// The USDC value of the user's shares at the snapshot (rounded down)
+    ///
+    /// # Panics
+    /// - If the snapshot does not exist
+    ///
+    /// # Events
+    /// None
+    pub fn assets_at(env: Env, user: Address, snapshot_id: u32) -> i128 {
+        let snapshot = Self::get_snapshot(env.clone(), snapshot_id);
+        let shares = Self::shares_at_snapshot(&env, &user, snapshot_id);
+        if snapshot.total_shares == 0 {
+            return shares;
+        }
+        shares * snapshot.total_assets / snapshot.total_shares
+    }
+
+    // ==========================================================================
+    // AIRDROPS
+    // ==========================================================================
+
+    /// Distributes a reward token airdrop proportionally to current shareholders.
+    ///
+    /// The reward tokens must already be held by the vault (e.g. sent by a
+    /// partner). A snapshot of share balances is taken and each holder can
+    /// later claim `total_amount * shares_at_snapshot / eligible_shares`,
+    /// where `eligible_shares` leaves out shares escrowed in the withdrawal
+    /// queue, which no holder's balance includes.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `token` - The reward token contract address
+    /// * `total_amount` - Total reward tokens to distribute
+    ///
+    /// # Returns
+    /// The id of the snapshot the airdrop is claimable against
+    ///
+    /// # Panics
+    /// - If the caller is not the owner
+    /// - If total_amount is not positive
+    /// - If token is the vault's USDC token
+    /// - If no shares are held outside the withdrawal queue
+    /// - If the vault does not hold enough unreserved reward tokens
+    ///
+    /// # Events
+    /// Emits `AirdropDistributedEvent` with the token, snapshot id and amount
+    ///
+    /// # Security
+    /// - Only the owner can distribute airdrops
+    /// - Previously distributed but unclaimed amounts stay reserved
+    pub fn distribute_airdrop(env: Env, token: Address, total_amount: i128) -> u32 {
+        Self::require_is_owner(&env);
+        Self::require_positive_amount(total_amount);
+
+        let usdc_token: Address = env.storage().instance().get(&DataKey::UsdcToken).unwrap();
+        assert!(token != usdc_token, "Cannot airdrop the vault asset");
+        let eligible_shares =
+            Self::stored_total_shares(&env) - Self::get_total_pending_shares(&env);
+        assert!(eligible_shares > 0, "No shareholders to airdrop to");
+
+        let reserved: i128 = env
+            .storage()
+            .persistent()
+            .get(&DataKey::AirdropReserved(token.clone()))
+            .unwrap_or(0);
+        let held = token::Client::new(&env, &token).balance(&env.current_contract_address());
+        assert!(
+            held >= reserved + total_amount,
+            "Insufficient airdrop balance"
+        );
+
+        let snapshot_id = Self::record_snapshot(&env);
+
+        let mut rounds: Vec<AirdropRound> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Airdrops(token.clone()))
+            .unwrap_or(Vec::new(&env));
+        rounds.push_back(AirdropRound {
+            snapshot_id,
+            total_amount,
+            eligible_shares,
+        });
+        env.storage()
+            .persistent()
+            .set(&DataKey::Airdrops(token.clone()), &rounds);
+        env.storage().persistent().set(
+            &DataKey::AirdropReserved(token.clone()),
+            &(reserved + total_amount),
+        );
+
+        env.events().publish(
+            (symbol_short!("airdrop"),),
+            AirdropDistributedEvent {
+                token,
+                snapshot_id,
+                total_amount,
+            },
+        );
+
+        snapshot_id
+    }
+
+    /// Claims a user's portion of every unclaimed airdrop round for a token.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `user` - The shareholder claiming (must authorize)
+    /// * `token` - The reward token contract address
+    ///
+    /// # Returns
+    /// The amount of reward tokens transferred to the user
+    ///
+    /// # Panics
+    /// - If the vault is paused
+    /// - If the user has already claimed every round for this token
+    ///
+    /// # Events
+    /// Emits `AirdropClaimedEvent` with the user, token and amount
+    ///
+    /// # Security
+    /// - `user.require_auth()` ensures only the holder can claim
+    /// - Rounds are marked claimed before the transfer, preventing double claims
+    pub fn claim_airdrop(env: Env, user: Address, token: Address) -> i128 {
+        user.require_auth();
+        Self::pay_airdrop_claim(&env, user, token)
+    }
+
+    /// Claims a user's portion of every unclaimed airdrop round for a token,
+    /// without checking authorization.
+    ///
+    /// # Returns
+    /// The amount of reward tokens transferred to the user
+    ///
+    /// # Panics
+    /// - If the vault is paused
+    /// - If the user has already claimed every round for this token
+    ///
+    /// # Events
+    /// Emits `AirdropClaimedEvent` with the user, token and amount
+    fn pay_airdrop_claim(env: &Env, user: Address, token: Address) -> i128 {
+        Self::require_not_paused(env);
+
+        let rounds: Vec<AirdropRound> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Airdrops(token.clone()))
+            .unwrap_or(Vec::new(env));
+        let claimed: u32 = env
+            .storage()
+            .persistent()
+            .get(&DataKey::AirdropClaimed(token.clone(), user.clone()))
+            .unwrap_or(0);
+        assert!(claimed < rounds.len(), "No airdrop to claim");
+
+        let amount = Self::airdrop_amount(env, &user, &rounds, claimed);
+
+        env.storage().persistent().set(
+            &DataKey::AirdropClaimed(token.clone(), user.clone()),
+            &rounds.len(),
+        );
+
+        if amount > 0 {
+            let reserved: i128 = env
+                .storage()
+                .persistent()
+                .get(&DataKey::AirdropReserved(token.clone()))
+                .unwrap_or(0);
+            env.storage().persistent().set(
+                &DataKey::AirdropReserved(token.clone()),
+                &(reserved - amount),
+            );
+
+            token::Client::new(env, &token).transfer(
+                &env.current_contract_address(),
+                &user,
+                &amount,
+            );
+        }
+
+        env.events().publish(
+            (symbol_short!("air_claim"),),
+            AirdropClaimedEvent {
+                user,
+                token,
+                amount,
+            },
+        );
+
+        amount
+    }
+
+    /// Claims a user's pending airdrops and then withdraws, in one transaction.
+    ///
+    /// Tokens with no unclaimed round are skipped rather than rejected. If
+    /// the withdrawal fails, the claims revert with it.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `user` - The user withdrawing (must authorize)
+    /// * `amount` - Amount of USDC to withdraw (7 decimal places)
+    /// * `tokens` - Reward tokens to claim (at most `MAX_BATCH_SIZE`)
+    ///
+    /// # Returns
+    /// The reward amount claimed for each token, in input order
+    ///
+    /// # Panics
+    /// - If more than `MAX_BATCH_SIZE` tokens are passed
+    /// - Any panic raised by `claim_airdrop` or `withdraw`
+    ///
+    /// # Events
+    /// Emits `AirdropClaimedEvent` for each token claimed and `WithdrawEvent`
+    /// as `withdraw` does
+    ///
+    /// # Security
+    /// - `withdraw` requires the user's authorization; without it the
+    ///   whole transaction, claims included, reverts
+    pub fn withdraw_and_claim(
+        env: Env,
+        user: Address,
+        amount: i128,
+        tokens: Vec<Address>,
+    ) -> Vec<i128> {
+        assert!(tokens.len() <= MAX_BATCH_SIZE, "Batch too large");
+
+        let mut claimed = Vec::new(&env);
+        for token in tokens.iter() {
+            let rounds: u32 = env
+                .storage()
+                .persistent()
+                .get(&DataKey::Airdrops(token.clone()))
+                .map(|rounds: Vec<AirdropRound>| rounds.len())
+                .unwrap_or(0);
+            let claimed_rounds: u32 = env
+                .storage()
+                .persistent()
+                .get(&DataKey::AirdropClaimed(token.clone(), user.clone()))
+                .unwrap_or(0);
+            if claimed_rounds < rounds {
+                claimed.push_back(Self::pay_airdrop_claim(&env, user.clone(), token));
+            } else {
+                claimed.push_back(0);
+            }
+        }
+
+        Self::withdraw(env, user, amount);
+        claimed
+    }
+
+    /// Returns the reward tokens a user could currently claim for a token.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `user` - The shareholder to query
+    /// * `token` - The reward token contract address
+    ///
+    /// # Returns
+    /// The unclaimed airdrop amount, or 0 if nothing is claimable
+    ///
+    /// # Panics
+    /// None
+    ///
+    /// # Events
+    /// None
+    pub fn get_claimable_airdrop(env: Env, user: Address, token: Address) -> i128 {
+        let rounds: Vec<AirdropRound> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Airdrops(token.clone()))
+            .unwrap_or(Vec::new(&env));
+        let claimed: u32 = env
+            .storage()
+            .persistent()
+            .get(&DataKey::AirdropClaimed(token, user.clone()))
+            .unwrap_or(0);
+        Self::airdrop_amount(&env, &user, &rounds, claimed)
+    }
+
+    // ==========================================================================
+    // ADVISORS
+    // ==========================================================================
+
+    /// Grants an advisor read access to the client's position.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `client` - The client granting access (must authorize)
+    /// * `advisor` - The advisor being authorized
+    ///
+    /// # Returns
+    /// Nothing. This function records the authorization and returns nothing.
+    ///
+    /// # Panics
+    /// None
+    ///
+    /// # Events
+    /// Emits `AdvisorUpdatedEvent` with `authorized: true`
+    ///
+    /// # Security
+    /// - `client.require_auth()` ensures only the client can grant access
+    /// - Access is read-only; advisors cannot move funds
+    pub fn authorize_advisor(env: Env, client: Address, advisor: Address) {
+        client.require_auth();
+        Self::set_advisor(&env, client, advisor, true);
+    }
+
+    /// Revokes an advisor's read access to the client's position.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `client` - The client revoking access (must authorize)
+    /// * `advisor` - The advisor being revoked
+    ///
+    /// # Returns
+    /// Nothing. This function removes the authorization and returns nothing.
+    ///
+    /// # Panics
+    /// None
+    ///
+    /// # Events
+    /// Emits `AdvisorUpdatedEvent` with `authorized: false`
+    ///
+    /// # Security
+    /// - `client.require_auth()` ensures only the client can revoke access
+    pub fn revoke_advisor(env: Env, client: Address, advisor: Address) {
+        client.require_auth();
+        Self::set_advisor(&env, client, advisor, false);
+    }
+
+    /// Returns a client's position to an authorized advisor.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `advisor` - The advisor reading the position (must authorize)
+    /// * `client` - The client whose position is read
+    ///
+    /// # Returns
+    /// The client's `UserData`
+    ///
+    /// # Panics
+    /// - If the advisor has not been authorized by the client
+    ///
+    /// # Events
+    /// None
+    ///
+    /// # Security
+    /// - `advisor.require_auth()` prevents reading under another advisor's grant
+    pub fn get_client_position(env: Env, advisor: Address, client: Address) -> UserData {
+        advisor.require_auth();
+
+        let authorized: bool = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Advisor(client.clone(), advisor))
+            .unwrap_or(false);
+        assert!(authorized, "Advisor not authorized");
+
+        let shares = Self::get_shares(env.clone(), client);
+        UserData {
+            shares,
+            balance: Self::convert_to_assets(env, shares),
+        }
+    }
+
+    // ==========================================================================
+    // READ FUNCTIONS
+    // ==========================================================================
+
+    /// Returns the USDC balance of a specific user.
+    ///
+    /// This is the current USDC value of the user's shares, including any
+    /// yield reported to the vault through `update_total_assets()`.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `user` - The user address to query
+    ///
+    /// # Returns
+    /// The user's USDC balance in raw units (7 decimal places)
+    ///
+    /// # Panics
+    /// - If the implied share price is outside the configured sanity band
+    ///
+    /// # Events
+    /// None
+    pub fn get_balance(env: Env, user: Address) -> i128 {
+        let shares = Self::get_shares(env.clone(), user);
+        Self::convert_to_assets(env, shares)
+    }
+
+    /// Returns the USDC balances of several users in one read.
+    ///
+    /// Equivalent to calling `get_balance` for each user; users without
+    /// shares report 0.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `users` - The user addresses to query (at most `MAX_BATCH_SIZE`)
+    ///
+    /// # Returns
+    /// Each user's USDC balance in raw units, in input order
+    ///
+    /// # Panics
+    /// - If more than `MAX_BATCH_SIZE` users are passed
+    /// - If the implied share price is outside the configured sanity band
+    ///
+    /// # Events
+    /// None
+    pub fn get_balances(env: Env, users: Vec<Address>) -> Vec<i128> {
+        assert!(users.len() <= MAX_BATCH_SIZE, "Batch too large");
+
+        let mut balances = Vec::new(&env);
+        for user in users.iter() {
+            balances.push_back(Self::get_balance(env.clone(), user));
+        }
+        balances
+    }
+
+    /// Returns a user's raw balance under the deprecated 1:1 model.
+    ///
+    /// **Deprecated:** the vault no longer writes `Balance(Address)`; share
+    /// balances are authoritative. This exists only so migration tooling
+    /// can verify that every legacy balance has been moved to shares.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `user` - The user address to query
+    ///
+    /// # Returns
+    /// The legacy balance in raw USDC units, or 0 if none was ever stored
+    ///
+    /// # Panics
+    /// None
+    ///
+    /// # Events
+    /// None
+    pub fn get_legacy_balance(env: Env, user: Address) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Balance(user))
+            .unwrap_or(0)
+    }
+
+    /// Returns the total assets under management.
+    ///
+    /// This is the sum of all deposits net of withdrawals, adjusted by any
+    /// yield or loss reported through `update_total_assets()`. It backs
+    /// the value of all outstanding shares.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// Total USDC deposits in raw units (7 decimal places)
+    ///
+    /// # Panics
+    /// None
+    ///
+    /// # Events
+    /// None
+    pub fn get_total_deposits(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::TotalDeposits)
+            .unwrap_or(0)
+    }
+
+    /// Returns total assets exactly as last written to storage.
+    ///
+    /// Unlike `get_total_assets`, this does not account for the management
+    /// fee that has accrued since it was last minted, so auditors can tell
+    /// the stored figure from the accrued one.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The stored total assets in raw USDC units (7 decimal places)
+    ///
+    /// # Panics
+    /// None
+    ///
+    /// # Events
+    /// None
+    pub fn get_stored_total_assets(env: Env) -> i128 {
+        Self::get_total_deposits(env)
+    }
+
+    /// Returns the shareholders' total assets accrued to the current ledger.
+    ///
+    /// The management fee accrues lazily and is only minted to the fee
+    /// recipient on the next deposit, exit or assets update, so between
+    /// those the stored total overstates what the other holders own. This
+    /// deducts the fee owed so far; immediately after an accrual it equals
+    /// `get_stored_total_assets`.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// Stored total assets less the accrued, unminted management fee
+    ///
+    /// # Panics
+    /// None
+    ///
+    /// # Events
+    /// None
+    pub fn get_total_assets(env: Env) -> i128 {
+        let (fee, _) = Self::management_fee_owed(&env);
+        Self::get_total_deposits(env) - fee
+    }
+
+    /// Returns the authorized AI agent address.
+    ///
+    /// This is the only address that can call rebalance() to move funds
+    /// between yield strategies.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The agent's Address
+    ///
+    /// # Panics
+    /// None
+    ///
+    /// # Events
+    /// None
+    pub fn get_agent(env: Env) -> Address {
+        env.storage().instance().get(&DataKey::Agent).unwrap()
+    }
+
+    /// Returns the contract owner address.
+    ///
+    /// The owner can pause/unpause the vault, set limits, and upgrade the contract.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The owner's Address
+    ///
+    /// # Panics
+    /// None
+    ///
+    /// # Events
+    /// None
+    pub fn get_owner(env: Env) -> Address {
+        env.storage().instance().get(&DataKey::Owner).unwrap()
+    }
+
+    /// Returns whether the owner and agent roles are held by the same address.
+    ///
+    /// `initialize` makes the agent the owner, so a fresh deployment reports
+    /// `true` until `update_agent` moves the agent role elsewhere. A single
+    /// compromised key then controls both strategy and administration.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// `true` if the owner is also the agent
+    pub fn roles_conflated(env: Env) -> bool {
+        Self::get_owner(env.clone()) == Self::get_agent(env)
+    }
+
+    /// Returns whether an address holds a role, without requiring its auth.
+    ///
+    /// Lets frontends adapt to the connected wallet, e.g. to show admin
+    /// panels. Multi-sig signers count as `owner`; `user` means the
+    /// address holds none of the privileged roles.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `addr` - The address to check
+    /// * `role` - One of `owner`, `agent`, `pauser`, `guardian` or `user`
+    ///
+    /// # Returns
+    /// `true` if `addr` holds `role`
+    ///
+    /// # Panics
+    /// - If `role` is not a known role
+    ///
+    /// # Events
+    /// None
+    pub fn is_role(env: Env, addr: Address, role: Symbol) -> bool {
+        let is_owner =
+            addr == Self::get_owner(env.clone()) || Self::get_owners(env.clone()).contains(&addr);
+        let is_agent = addr == Self::get_agent(env.clone());
+        let is_pauser = Self::get_pauser(env.clone()) == Some(addr.clone());
+        let is_guardian = Self::get_guardian(env) == Some(addr);
+
+        if role == symbol_short!("owner") {
+            is_owner
+        } else if role == symbol_short!("agent") {
+            is_agent
+        } else if role == symbol_short!("pauser") {
+            is_pauser
+        } else if role == symbol_short!("guardian") {
+            is_guardian
+        } else if role == symbol_short!("user") {
+            !is_owner && !is_agent && !is_pauser && !is_guardian
+        } else {
+            panic!("Unknown role")
+        }
+    }
+
+    /// Returns whether the vault is currently paused.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// True if paused, false otherwise
+    ///
+    /// # Panics
+    /// None
+    ///
+    /// # Events
+    /// None
+    pub fn is_paused(env: Env) -> bool {
+        env.storage()
+            .instance()
+            .get(&DataKey::Paused)
+            .unwrap_or(false)
+    }
+
+    /// Returns the contract version.
+    ///
+    /// Used to track upgrades and ensure compatibility with external systems.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The current contract version (u32)
+    ///
+    /// # Panics
+    /// None
+    ///
+    /// # Events
+    /// None
+    pub fn get_version(env: Env) -> u32 {
+        env.storage().instance().get(&DataKey::Version).unwrap_or(1)
+    }
+
+    /// Returns the USDC token address.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The USDC token contract address
+    ///
+    /// # Panics
+    /// None
+    ///
+    /// # Events
+    /// None
+    pub fn get_usdc_token(env: Env) -> Address {
+        env.storage().instance().get(&DataKey::UsdcToken).unwrap()
+    }
+
+    /// Returns every configuration value of the vault in a single read.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// A `VaultConfigView` with the agent, owner, token, caps, minimum
+    /// deposit, pause state and version
+    ///
+    /// # Panics
+    /// - If the vault has not been initialized
+    ///
+    /// # Events
+    /// None
+    pub fn get_config(env: Env) -> VaultConfigView {
+        VaultConfigView {
+            agent: Self::get_agent(env.clone()),
+            owner: Self::get_owner(env.clone()),
+            usdc_token: Self::get_usdc_token(env.clone()),
+            tvl_cap: Self::get_tvl_cap(env.clone()),
+            user_cap: Self::get_user_deposit_cap(env.clone()),
+            min_deposit: MIN_DEPOSIT,
+            paused: Self::is_paused(env.clone()),
+            version: Self::get_version(env.clone()),
+            management_fee_bps: Self::get_management_fee_bps(env.clone()),
+            performance_fee_bps: Self::get_performance_fee_bps(env.clone()),
+            withdrawal_fee_bps: Self::get_withdrawal_fee_bps(env),
+        }
+    }
+
+    /// Returns the configuration, aggregate figures and health of the vault
+    /// in a single read, for block explorers and dashboards.
+    ///
+    /// Every field is read through its standalone getter, so the summary
+    /// always agrees with them.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// A `VaultSummary` bundling `get_config()`, the vault totals and the
+    /// health indicators
+    ///
+    /// # Panics
+    /// - If the vault has not been initialized
+    ///
+    /// # Events
+    /// None
+    pub fn get_summary(env: Env) -> VaultSummary {
+        VaultSummary {
+            config: Self::get_config(env.clone()),
+            stats: Self::vault_stats(&env),
+            health: VaultHealth {
+                backing_ratio_bps: Self::get_backing_ratio_bps(env.clone()),
+                underwater: Self::is_underwater(env.clone()),
+                winding_down: Self::is_winding_down(env.clone()),
+                seconds_since_yield: Self::get_seconds_since_yield(env),
+            },
+        }
+    }
+
+    /// Simulates a deposit without requiring auth or changing state.
+    ///
+    /// Prices the shares exactly as `deposit()` would and projects the vault
+    /// totals afterwards, including the reserve rebalancing the deposit
+    /// triggers. Caps, cooldowns and the pause are not checked, so the real
+    /// deposit may still be rejected.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `user` - The user who would deposit
+    /// * `amount` - Amount of USDC to deposit (7 decimal places)
+    ///
+    /// # Returns
+    /// A tuple of the shares that would be minted and the resulting `VaultStats`
+    ///
+    /// # Panics
+    /// - If amount is not positive
+    /// - If amount would mint zero shares at the current share price
+    /// - If the implied share price is outside the configured sanity band
+    ///
+    /// # Events
+    /// None
+    pub fn simulate_deposit(env: Env, user: Address, amount: i128) -> (i128, VaultStats) {
+        Self::require_positive_amount(amount);
+        let shares = Self::convert_to_shares(env.clone(), amount);
+        assert!(shares > 0, "Deposit too small to mint shares");
+
+        let mut stats = Self::vault_stats(&env);
+        if Self::get_shares(env.clone(), user) == 0 {
+            stats.depositor_count += 1;
+        }
+        stats.total_assets += amount;
+        stats.total_shares += shares;
+        stats.share_price =
+            stats.total_assets * SHARE_PRICE_SCALE * Self::share_unit(&env) / stats.total_shares;
+
+        if Self::get_reserve_strategy(env.clone()).is_some() {
+            let token_client = token::Client::new(&env, &Self::get_usdc_token(env.clone()));
+            let balance = token_client.balance(&env.current_contract_address()) + amount;
+            let target = (balance + stats.reserve_deployed)
+                * Self::get_reserve_buffer_bps(env.clone())
+                / 10_000;
+            stats.reserve_deployed += (balance - target).max(-stats.reserve_deployed);
+        }
+        (shares, stats)
+    }
+
+    /// Returns the registered strategies, in registration order.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The strategy addresses
+    pub fn get_strategies(env: Env) -> Vec<Address> {
+        env.storage()
+            .instance()
+            .get(&DataKey::Strategies)
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Returns the number of registered strategies.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The strategy count
+    pub fn get_strategy_count(env: Env) -> u32 {
+        Self::get_strategies(env).len()
+    }
+
+    /// Returns the USDC deployed to a strategy.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `strategy` - The strategy to query
+    ///
+    /// # Returns
+    /// The deployed amount in raw USDC units, or 0 if none
+    pub fn get_strategy_allocation(env: Env, strategy: Address) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::StrategyAllocation(strategy))
+            .unwrap_or(0)
+    }
+
+    /// Returns the reported gains attributed to a strategy.
+    ///
+    /// Each positive `update_total_assets` report is split across the
+    /// strategies pro rata to their allocations at the time, rounding down.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `strategy` - The strategy to query
+    ///
+    /// # Returns
+    /// The cumulative attributed yield in raw USDC units, or 0 if none
+    pub fn get_strategy_yield(env: Env, strategy: Address) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::StrategyRealizedYield(strategy))
+            .unwrap_or(0)
+    }
+
+    /// Returns the total USDC deployed across all strategies.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The deployed amount in raw USDC units
+    pub fn get_total_deployed(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::TotalDeployed)
+            .unwrap_or(0)
+    }
+
+    /// Returns the shares minted to and burned from a user over their lifetime.
+    ///
+    /// High velocity relative to the current balance indicates churn.
+    /// Queued withdrawals count as burned when they are settled.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `user` - The user address to query
+    ///
+    /// # Returns
+    /// `(minted, burned)` cumulative share counts
+    pub fn get_share_velocity(env: Env, user: Address) -> (i128, i128) {
+        env.storage()
+            .persistent()
+            .get(&DataKey::ShareVelocity(user))
+            .unwrap_or((0, 0))
+    }
+
+    /// Returns the USDC a user has paid in for their current shares.
+    ///
+    /// Reduced pro rata as shares are withdrawn or queued for withdrawal.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `user` - The user address to query
+    ///
+    /// # Returns
+    /// The cost basis in raw USDC units
+    pub fn get_cost_basis(env: Env, user: Address) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::CostBasis(user))
+            .unwrap_or(0)
+    }
+
+    /// Returns the principal protected for a user.
+    ///
+    /// Protected principal is the USDC the user has deposited minus the USDC
+    /// paid out to them, floored at zero, so it ignores yield and losses
+    /// alike. Splitting a position moves a pro rata part of it along with
+    /// the shares. A principal-protected payout must return at least this
+    /// amount.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `user` - The user address to query
+    ///
+    /// # Returns
+    /// The protected principal in raw USDC units
+    pub fn get_protected_principal(env: Env, user: Address) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::ProtectedPrincipal(user))
+            .unwrap_or(0)
+    }
+
+    /// Returns a user's realized and unrealized profit and loss.
+    ///
+    /// Realized PnL accrues at each withdrawal as the payout minus the cost
+    /// basis of the shares withdrawn; queued withdrawals realize when
+    /// claimed. Unrealized PnL is the current value of the user's shares
+    /// minus their remaining cost basis.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `user` - The user address to query
+    ///
+    /// # Returns
+    /// A `Pnl` in raw USDC units; negative values are losses
+    ///
+    /// # Panics
+    /// - If the implied share price is outside the configured sanity band
+    ///
+    /// # Events
+    /// None
+    pub fn get_pnl(env: Env, user: Address) -> Pnl {
+        let realized = env
+            .storage()
+            .persistent()
+            .get(&DataKey::RealizedPnl(user.clone()))
+            .unwrap_or(0);
+        let unrealized =
+            Self::get_balance(env.clone(), user.clone()) - Self::get_cost_basis(env, user);
+        Pnl {
+            realized,
+            unrealized,
+        }
+    }
+
+    /// Returns the largest loss from peak a user has absorbed, in basis points.
+    ///
+    /// Measured on the share price rather than the raw balance, so deposits
+    /// and withdrawals do not register as gains or losses. The peak is
+    /// recorded whenever the user's shares are minted or burned, and by
+    /// `checkpoint_drawdown()`; the current price is always compared with
+    /// it while the user holds shares. A peak reached and lost between two
+    /// recordings is not seen.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `user` - The user address to query
+    ///
+    /// # Returns
+    /// The maximum drawdown in basis points, or 0 if the user never held shares
+    ///
+    /// # Panics
+    /// None
+    ///
+    /// # Events
+    /// None
+    pub fn get_max_drawdown_bps(env: Env, user: Address) -> i128 {
+        let holding = Self::get_shares(env.clone(), user.clone()) > 0;
+        match Self::get_drawdown(&env, &user) {
+            Some(drawdown) if holding => {
+                let price = Self::get_share_price(env);
+                drawdown
+                    .max_drawdown_bps
+                    .max(Self::drawdown_bps(drawdown.peak_price, price))
+            }
+            Some(drawdown) => drawdown.max_drawdown_bps,
+            None => 0,
+        }
+    }
+
+    /// Records the current share price against a user's high-water mark.
+    ///
+    /// Lets a keeper capture peaks between the user's own operations, so a
+    /// later loss is measured from them.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `user` - The user address to record for
+    ///
+    /// # Returns
+    /// The user's maximum drawdown in basis points after recording
+    ///
+    /// # Panics
+    /// - If the user holds no shares
+    ///
+    /// # Events
+    /// None
+    pub fn checkpoint_drawdown(env: Env, user: Address) -> i128 {
+        assert!(
+            Self::get_shares(env.clone(), user.clone()) > 0,
+            "No shares held"
+        );
+        Self::track_drawdown(&env, &user, false);
+        Self::get_max_drawdown_bps(env, user)
+    }
+
+    /// Returns the yield a user's current holdings have earned.
+    ///
+    /// The current value of the user's shares minus their cost basis, as a
+    /// single display figure. Losses show as 0; `is_underwater()` and
+    /// `get_pnl()` report them.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `user` - The user address to query
+    ///
+    /// # Returns
+    /// The yield earned in raw USDC units, never negative
+    ///
+    /// # Panics
+    /// - If the implied share price is outside the configured sanity band
+    ///
+    /// # Events
+    /// None
+    pub fn get_yield_earned(env: Env, user: Address) -> i128 {
+        let value = Self::get_balance(env.clone(), user.clone());
+        (value - Self::get_cost_basis(env, user)).max(0)
+    }
+
+    /// Returns the sum of all users' cost bases.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The aggregate cost basis in raw USDC units
+    pub fn get_total_cost_basis(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::TotalCostBasis)
+            .unwrap_or(0)
+    }
+
+    /// Returns the net principal deposited into the vault.
+    ///
+    /// Deposits add their USDC and every exit removes the cost basis of the
+    /// shares it burns (queued withdrawals at request time), so principal
+    /// stays flat as yield accrues. Total assets minus principal is the
+    /// vault's aggregate unrealized earnings.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The net principal in raw USDC units
+    pub fn get_total_principal(env: Env) -> i128 {
+        Self::get_total_cost_basis(env)
+    }
+
+    /// Returns whether total assets are below the aggregate cost basis.
+    ///
+    /// Withdrawals stay open while underwater; users exit at the reduced
+    /// share price. Integrators can use this to warn users.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// `true` if the last reported total assets fell below the cost basis
+    pub fn is_underwater(env: Env) -> bool {
+        env.storage()
+            .instance()
+            .get(&DataKey::Underwater)
+            .unwrap_or(false)
+    }
+
+    /// Returns the seconds elapsed since total assets were last updated.
+    ///
+    /// Powers "last updated X ago" labels for yield staleness.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// Seconds since the last `update_total_assets`, or `u64::MAX` if it
+    /// has never been called
+    pub fn get_seconds_since_yield(env: Env) -> u64 {
+        let last: Option<u64> = env.storage().instance().get(&DataKey::LastAssetsUpdate);
+        match last {
+            Some(last) => env.ledger().timestamp() - last,
+            None => u64::MAX,
+        }
+    }
+
+    /// Returns the whole days elapsed since total assets were last updated.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// Days since the last `update_total_assets` (rounded down), or
+    /// `u64::MAX` if it has never been called
+    pub fn get_days_since_yield(env: Env) -> u64 {
+        match Self::get_seconds_since_yield(env) {
+            u64::MAX => u64::MAX,
+            seconds => seconds / 86_400,
+        }
+    }
+
+    /// Returns the recorded `update_total_assets` samples, oldest first.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// Up to `MAX_HISTORY_SAMPLES` samples
+    pub fn get_assets_history(env: Env) -> Vec<AssetsSample> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::AssetsHistory)
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Returns the share price at each recorded `update_total_assets`
+    /// sample, oldest first, for charting.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// Up to `MAX_HISTORY_SAMPLES` `(timestamp, share_price)` pairs, with
+    /// prices scaled by `SHARE_PRICE_SCALE`
+    pub fn get_share_price_history(env: Env) -> Vec<(u64, i128)> {
+        let mut prices = Vec::new(&env);
+        for sample in Self::get_assets_history(env.clone()).iter() {
+            prices.push_back((sample.timestamp, sample.share_price));
+        }
+        prices
+    }
+
+    /// Returns the most recent configuration changes, oldest first.
+    ///
+    /// Every setter that emits `ConfigUpdatedEvent`, along with
+    /// `set_limits`, `set_tvl_cap` and `update_agent`, appends an entry. Only
+    /// the last `MAX_CONFIG_HISTORY` changes are kept.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `limit` - Maximum number of entries to return
+    ///
+    /// # Returns
+    /// Up to `limit` of the newest changes, in the order they were made
+    pub fn get_config_history(env: Env, limit: u32) -> Vec<ConfigChange> {
+        let history: Vec<ConfigChange> = env
+            .storage()
+            .persistent()
+            .get(&ConfigKey::ConfigHistory)
+            .unwrap_or(Vec::new(&env));
+        let skip = history.len().saturating_sub(limit);
+        history.slice(skip..)
+    }
+
+    /// Returns the annualized share price growth over a lookback window.
+    ///
+    /// Compares the current share price with the price at the newest
+    /// sample taken at least `lookback` seconds ago (or the oldest sample if
+    /// history does not reach that far) and annualizes the simple return.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `lookback` - Window in seconds to measure growth over
+    ///
+    /// # Returns
+    /// The implied APY in basis points, or 0 if no earlier sample exists
+    ///
+    /// # Panics
+    /// None
+    ///
+    /// # Events
+    /// None
+    pub fn get_implied_apy_bps(env: Env, lookback: u64) -> i128 {
+        match Self::price_growth(&env, lookback) {
+            Some((price_then, price_now, elapsed)) => {
+                (price_now - price_then) * 10_000 * SECONDS_PER_YEAR as i128
+                    / (price_then * elapsed as i128)
+            }
+            None => 0,
+        }
+    }
+
+    /// Returns a user's annualized return since their shares were bought.
+    ///
+    /// Compares the current value of the user's shares with their cost
+    /// basis over the holding period, measured from the cost-basis-weighted
+    /// average time their deposits went in. If the user has held for longer
+    /// than `lookback`, every share they hold has seen the vault's price
+    /// path over that window, so the result matches `get_implied_apy_bps`.
+    /// Shares received through `split_position` count as bought at the split.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `user` - The user address to query
+    /// * `lookback` - Longest window in seconds to measure over, 0 for the
+    ///   whole holding period
+    ///
+    /// # Returns
+    /// The user's APY in basis points, or 0 if they hold no cost basis or
+    /// bought in the current ledger
+    ///
+    /// # Panics
+    /// None
+    ///
+    /// # Events
+    /// None
+    pub fn get_user_apy(env: Env, user: Address, lookback: u64) -> i128 {
+        let basis = Self::get_cost_basis(env.clone(), user.clone());
+        let since = match Self::holding_since(&env, &user) {
+            Some(since) if basis > 0 => since,
+            _ => return 0,
+        };
+        let held = env.ledger().timestamp().saturating_sub(since);
+        if held == 0 {
+            return 0;
+        }
+        if lookback > 0 && held > lookback {
+            return Self::get_implied_apy_bps(env, lookback);
+        }
+        let value = Self::convert_to_assets(env.clone(), Self::get_shares(env, user));
+        (value - basis) * 10_000 * SECONDS_PER_YEAR as i128 / (basis * held as i128)
+    }
+
+    /// Estimates how long the share price takes to double at the implied APY.
+    ///
+    /// Applies the rule of 72 to the growth measured over `lookback`, as in
+    /// `get_implied_apy_bps`. Accurate to within a few percent for APYs
+    /// below 20%.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `lookback` - Window in seconds to measure growth over
+    ///
+    /// # Returns
+    /// The doubling time in seconds, or `u64::MAX` if the implied APY is
+    /// zero or negative or no earlier sample exists
+    ///
+    /// # Panics
+    /// None
+    ///
+    /// # Events
+    /// None
+    pub fn get_time_to_double_seconds(env: Env, lookback: u64) -> u64 {
+        match Self::price_growth(&env, lookback) {
+            Some((price_then, price_now, elapsed)) if price_now > price_then => {
+                // 72 / (100 * growth per period) periods of `elapsed` seconds
+                (72 * price_then * elapsed as i128 / (100 * (price_now - price_then))) as u64
+            }
+            _ => u64::MAX,
+        }
+    }
+
+    /// Returns how fully on-chain and deployed USDC back the reported total assets.
+    ///
+    /// Computed as `(on_chain_balance + total_deployed) * 10000 / total_assets`.
+    /// A value below 10000 signals accounting drift or an over-reported total.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The backing ratio in basis points, or 10000 if total assets are zero
+    ///
+    /// # Panics
+    /// None
+    ///
+    /// # Events
+    /// None
+    pub fn get_backing_ratio_bps(env: Env) -> i128 {
+        let total_assets = Self::get_total_deposits(env.clone());
+        if total_assets == 0 {
+            return 10_000;
+        }
+        let usdc_token = Self::get_usdc_token(env.clone());
+        let on_chain =
+            token::Client::new(&env, &usdc_token).balance(&env.current_contract_address());
+        (on_chain + Self::get_reserve_deployed(env.clone()) + Self::get_total_deployed(env))
+            * 10_000
+            / total_assets
+    }
+
+    /// Returns the vault's reserves for public verification.
+    ///
+    /// Combines the USDC balance read directly from the token contract with
+    /// the agent's latest attestation of deployed funds, and flags whether
+    /// the two sum exactly to the reported total assets.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// A `ProofOfReserves`; without an attestation the deployed amount and
+    /// timestamp are 0
+    ///
+    /// # Panics
+    /// None
+    ///
+    /// # Events
+    /// None
+    pub fn proof_of_reserves(env: Env) -> ProofOfReserves {
+        let usdc_token = Self::get_usdc_token(env.clone());
+        let on_chain_balance =
+            token::Client::new(&env, &usdc_token).balance(&env.current_contract_address());
+        let attestation = env
+            .storage()
+            .instance()
+            .get(&DataKey::ReserveAttestation)
+            .unwrap_or(ReserveAttestation {
+                amount: 0,
+                timestamp: 0,
+            });
+        let total_assets = Self::get_total_deposits(env.clone());
+
+        let reserve_deployed = Self::get_reserve_deployed(env);
+
+        ProofOfReserves {
+            on_chain_balance,
+            attested_deployed: attestation.amount,
+            attested_at: attestation.timestamp,
+            total_assets,
+            reserve_deployed,
+            reconciled: on_chain_balance + reserve_deployed + attestation.amount == total_assets,
+        }
+    }
+
+    /// Returns every address currently holding a non-zero share balance.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The depositor addresses, in the order they first acquired shares
+    ///
+    /// # Panics
+    /// None
+    ///
+    /// # Events
+    /// None
+    pub fn get_depositors(env: Env) -> Vec<Address> {
+        let layout = Self::get_position_layout(env.clone());
+        let depositors = if layout == 0 {
+            env.storage().persistent().get(&DataKey::Depositors)
+        } else {
+            env.storage()
+                .persistent()
+                .get(&PositionKey::DepositorIndex(layout))
+        };
+        depositors.unwrap_or(Vec::new(&env))
+    }
+
+    /// Returns the layout version of the position index in storage.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The layout version, 0 for a vault that predates versioned positions
+    ///
+    /// # Panics
+    /// None
+    ///
+    /// # Events
+    /// None
+    pub fn get_position_layout(env: Env) -> u32 {
+        env.storage()
+            .persistent()
+            .get(&PositionKey::PositionLayout)
+            .unwrap_or(0)
+    }
+
+    /// Re-indexes positions written by an older storage layout.
+    ///
+    /// Reads the depositor index from the layout currently in storage,
+    /// keeps each address that still holds shares once, and writes the
+    /// result under `POSITION_LAYOUT_VERSION`, then recounts depositors
+    /// and removes the old index. Positions stay queryable through the
+    /// same getters before and after. Calling it again once the vault is on
+    /// the current layout changes nothing.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The number of positions in the index
+    ///
+    /// # Panics
+    /// - If the caller is not the owner
+    ///
+    /// # Events
+    /// Emits `ConfigUpdatedEvent` with key `pos_lay` if the layout changed
+    ///
+    /// # Security
+    /// - Only the owner can migrate, typically right after an upgrade
+    pub fn migrate_positions(env: Env) -> u32 {
+        Self::require_is_owner(&env);
+        let layout = Self::get_position_layout(env.clone());
+        let depositors = Self::get_depositors(env.clone());
+        if layout == POSITION_LAYOUT_VERSION {
+            return depositors.len();
+        }
+
+        let mut index = Vec::new(&env);
+        for user in depositors.iter() {
+            if Self::get_shares(env.clone(), user.clone()) > 0 && !index.contains(&user) {
+                index.push_back(user);
+            }
+        }
+        if layout == 0 {
+            env.storage().persistent().remove(&DataKey::Depositors);
+        } else {
+            env.storage()
+                .persistent()
+                .remove(&PositionKey::DepositorIndex(layout));
+        }
+        env.storage()
+            .persistent()
+            .set(&PositionKey::PositionLayout, &POSITION_LAYOUT_VERSION);
+        Self::set_depositors(&env, &index);
+        env.storage()
+            .instance()
+            .set(&DataKey::DepositorCount, &index.len());
+
+        Self::record_config_change(
+            &env,
+            symbol_short!("pos_lay"),
+            layout as i128,
+            POSITION_LAYOUT_VERSION as i128,
+        );
+        index.len()
+    }
+
+    /// Returns the depositor holding the most shares.
+    ///
+    /// Computed by enumerating the depositor list; on a tie the earliest
+    /// depositor wins.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// A tuple of the largest holder's address and their share count
+    ///
+    /// # Panics
+    /// - If no shares are outstanding
+    ///
+    /// # Events
+    /// None
+    pub fn get_largest_holder(env: Env) -> (Address, i128) {
+        let mut largest: Option<(Address, i128)> = None;
+        for depositor in Self::get_depositors(env.clone()).iter() {
+            let shares = Self::get_shares(env.clone(), depositor.clone());
+            match &largest {
+                Some((_, top)) if shares <= *top => {}
+                _ => largest = Some((depositor, shares)),
+            }
+        }
+        largest.expect("No shares outstanding")
+    }
+
+    /// Returns the largest holder's fraction of total shares.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    ///
+    /// # Returns
+    /// The top holder's shares in basis points of total shares, or 0 if no
+    /// shares are outstanding
+    pub fn get_concentration_bps(env: Env) -> i128 {
+        let total_shares = Self::stored_total_shares(&env);
+        if total_shares == 0 {
+            return 0;
+        }
+        let (_, shares) = Self::get_largest_holder(env);
+        shares * 10_000 / total_shares
+    }
+
+    /// Returns the timestamp of a user's last deposit or withdrawal.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `user` - The user address to query
+    ///
+    /// # Returns
+    /// The ledger timestamp, or `None` if the user has never acted
+    ///
+    /// # Panics
+    /// None
+    ///
+    /// # Events
+    /// None
+    pub fn get_last_action(env: Env, user: Address) -> Option<u64> {
+        env.storage().persistent().get(&DataKey::LastAction(user))
+    }
+
+    /// Returns an estimated resource profile for an operation, to help wallets pre-fund it.
+    ///
+    /// The figures are estimates measured on one representative path per
+    /// operation: a reserved first deposit, a full withdrawal, a request for
+    /// a whole position and a rebalance, all with no fees configured. Other
+    /// paths (e.g. a withdrawal fee minting shares to the fee recipient) can
+    /// touch more entries, so wallets should add headroom or simulate.
+    /// Supported operations are `deposit`, `withdraw`, `wd_req`
+    /// (`request_withdrawal`) and `rebalance`.
+    ///
+    /// The counts are hand-maintained. When a change to one of these paths
+    /// reads or writes another entry, `test_cost_hints_match_deposit_and_withdraw_profiles`
+    /// fails with the measured `(reads, writes)`; copy those into the table
+    /// below and update the entry list beside it.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `op` - The operation name
+    ///
+    /// # Returns
+    /// The operation's `OperationCost`
+    ///
+    /// # Panics
+    /// - If the operation is not supported
+    ///
+    /// # Events
+    /// None
+    pub fn operation_cost_hint(_env: Env, op: Symbol) -> OperationCost {
+        // Instance plus Shares, ShareCheckpoints, DepositorIndex, CostBasis,
+        // HoldingSince, ProtectedPrincipal, Drawdown, ShareVelocity,
+        // LastAction and PreRegistrations; PositionLayout is only read.
+        // Deposits also pull USDC in
+        if op == symbol_short!("deposit") {
+            return OperationCost {
+                ledger_reads: 12,
+                ledger_writes: 11,
+                cross_contract: true,
+            };
+        }
+        // As deposit without HoldingSince and PreRegistrations, plus
+        // RealizedPnl; PositionLayout, Boosts and UserLockedShares are only
+        // read
+        if op == symbol_short!("withdraw") {
+            return OperationCost {
+                ledger_reads: 13,
+                ledger_writes: 10,
+                cross_contract: true,
+            };
+        }
+        // Instance plus Shares, ShareCheckpoints, DepositorIndex, CostBasis,
+        // PendingShares, PendingRequests and WithdrawalRequest;
+        // PositionLayout, Boosts and UserLockedShares are only read
+        if op == symbol_short!("wd_req") {
+            return OperationCost {
+                ledger_reads: 11,
+                ledger_writes: 8,
+                cross_contract: false,
+            };
+        }
+        // Only reads the agent and permissions; the event is the signal
+        if op == symbol_short!("rebalance") {
+            return OperationCost {
+                ledger_reads: 1,
+                ledger_writes: 0,
+                cross_contract: false,
+            };
+        }
+        panic!("Unknown operation");
+    }
+
+    // ==========================================================================
+    // INTERNAL VALIDATION HELPERS
+    // ==========================================================================
+
+    /// Validates that the vault is not paused.
+    ///
+    /// # Panics
+    /// - If the vault is paused
+    #[inline]
+    fn require_not_paused(env: &Env) {
+        let paused: bool = env
+            .storage()
+            .instance()
+            .get(&DataKey::Paused)
+            .unwrap_or(false);
+        assert!(!paused, "Vault is paused");
+    }
+
+    /// Validates that the vault is not paused, or that the user's last
+    /// deposit is still inside `PauseGraceSeconds`.
+    ///
+    /// # Panics
+    /// - If the vault is paused and the user is outside the grace
+    fn require_not_paused_or_in_grace(env: &Env, user: &Address) {
+        if !Self::is_paused(env.clone()) {
+            return;
+        }
+        let grace = Self::get_pause_grace_seconds(env.clone());
+        let last_deposit: Option<u64> = env
+            .storage()
+            .persistent()
+            .get(&PositionKey::LastDeposit(user.clone()));
+        let in_grace =
+            grace > 0 && last_deposit.is_some_and(|at| env.ledger().timestamp() <= at + grace);
+        assert!(in_grace, "Vault is paused");
+    }
+
+    /// Validates that rebalancing is not paused.
+    ///
+    /// # Panics
+    /// - If `pause_rebalancing()` is in effect
+    #[inline]
+    fn require_rebalance_not_paused(env: &Env) {
+        assert!(
+            !Self::is_rebalance_paused(env.clone()),
+            "Rebalancing is paused"
+        );
+    }
+
+    /// Validates that the vault is not winding down.
+    ///
+    /// # Panics
+    /// - If wind-down has started
+    #[inline]
+    fn require_not_winding_down(env: &Env) {
+        assert!(!Self::is_winding_down(env.clone()), "Vault is winding down");
+    }
+
+    /// Reads `TotalShares` without the drift check.
+    fn stored_total_shares(env: &Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::TotalShares)
+            .unwrap_or(0)
+    }
+
+    /// Reads the running sum of user share balances.
+    fn share_checksum(env: &Env) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&PositionKey::ShareChecksum)
+            .unwrap_or(0)
+    }
+
+    /// Returns the total shares escrowed in pending withdrawal requests.
+    #[inline]
+    fn get_total_pending_shares(env: &Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::TotalPendingShares)
+            .unwrap_or(0)
+    }
+
+    /// Returns the shares a user has escrowed in pending withdrawal requests.
+    fn user_pending_shares(env: &Env, user: &Address) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&PositionKey::PendingShares(user.clone()))
+            .unwrap_or(0)
+    }
+
+    /// Adjusts a user's escrowed share count, removing the entry at zero.
+    fn add_user_pending_shares(env: &Env, user: &Address, delta: i128) {
+        let key = PositionKey::PendingShares(user.clone());
+        let pending = Self::user_pending_shares(env, user) + delta;
+        if pending == 0 {
+            env.storage().persistent().remove(&key);
+        } else {
+            env.storage().persistent().set(&key, &pending);
+        }
+    }
+
+    /// Returns the ids of all pending withdrawal requests, sorted by
+    /// `(unlock_time, id)`.
+    fn pending_request_ids(env: &Env) -> Vec<u64> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::PendingRequests)
+            .unwrap_or(Vec::new(env))
+    }
+
+    /// Cancels a user's pending withdrawal requests, returning the escrowed
+    /// shares and their cost basis to the user's balance.
+    ///
+    /// The shares never left `TotalShares`, so only the pending totals change.
+    ///
+    /// # Events
+    /// Emits `WithdrawalCancelledEvent` for each request cancelled
+    fn cancel_withdrawal_requests(env: &Env, user: &Address) {
+        if Self::user_pending_shares(env, user) == 0 {
+            return;
+        }
+
+        let mut remaining = Vec::new(env);
+        let mut returned = 0;
+        for request_id in Self::pending_request_ids(env).iter() {
+            let request = Self::get_withdrawal_request(env.clone(), request_id);
+            if request.user != *user {
+                remaining.push_back(request_id);
+                continue;
+            }
+            env.storage()
+                .persistent()
+                .remove(&DataKey::WithdrawalRequest(request_id));
+            Self::add_cost_basis(env, user, request.cost_basis);
+            returned += request.shares;
+
+            env.events().publish(
+                (symbol_short!("wd_cancel"),),
+                WithdrawalCancelledEvent {
+                    request_id,
+                    user: user.clone(),
+                    shares: request.shares,
+                },
+            );
+        }
+        env.storage()
+            .persistent()
+            .set(&DataKey::PendingRequests, &remaining);
+
+        let total_pending = Self::get_total_pending_shares(env);
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalPendingShares, &(total_pending - returned));
+        Self::add_user_pending_shares(env, user, -returned);
+        let user_shares = Self::get_shares(env.clone(), user.clone());
+        Self::set_shares(env, user, user_shares + returned);
+    }
+
+    /// Burns a request's escrowed shares and pays it out, less any
+    /// withdrawal fee.
+    ///
+    /// The caller must already have removed the request id from `PendingRequests`.
+    ///
+    /// # Returns
+    /// The amount of USDC transferred to the requester, net of any fee
+    fn settle_withdrawal_request(env: &Env, request: &WithdrawalRequest) -> i128 {
+        env.storage()
+            .persistent()
+            .remove(&DataKey::WithdrawalRequest(request.id));
+
+        Self::accrue_management_fee(env);
+        let amount = Self::convert_to_assets(env.clone(), request.shares);
+        let amount = Self::process_exit(
+            env,
+            &request.user,
+            request.shares,
+            amount,
+            &request.user,
+            Some(request.cost_basis),
+            true,
+        );
+
+        env.events().publish(
+            (symbol_short!("wd_claim"),),
+            WithdrawalClaimedEvent {
+                request_id: request.id,
+                user: request.user.clone(),
+                shares: request.shares,
+                amount,
+            },
+        );
+
+        amount
+    }
+
+    /// Acquires the reentrancy guard.
+    ///
+    /// # Panics
+    /// - If the guard is already held
+    #[inline]
+    fn enter_non_reentrant(env: &Env) {
+        let locked: bool = env
+            .storage()
+            .instance()
+            .get(&DataKey::Locked)
+            .unwrap_or(false);
+        assert!(!locked, "Reentrant call");
+        env.storage().instance().set(&DataKey::Locked, &true);
+    }
+
+    /// Releases the reentrancy guard.
+    #[inline]
+    fn exit_non_reentrant(env: &Env) {
+        env.storage().instance().set(&DataKey::Locked, &false);
+    }
+
+    /// Values an asset amount in USDC through the price oracle.
+    ///
+    /// # Panics
+    /// - If no price oracle is configured
+    /// - If the price is not positive or older than `MaxOracleAge`
+    fn oracle_value(env: &Env, asset: &Address, amount: i128) -> i128 {
+        let oracle = Self::get_price_oracle(env.clone()).expect("Price oracle not set");
+        let quote = PriceOracleClient::new(env, &oracle).price(asset);
+
+        assert!(quote.price > 0, "Invalid oracle price");
+        let now = env.ledger().timestamp();
+        let max_age = Self::get_max_oracle_age(env.clone());
+        assert!(
+            quote.timestamp <= now && now - quote.timestamp <= max_age,
+            "Stale oracle price"
+        );
+
+        amount * quote.price / ORACLE_PRICE_SCALE
+    }
+
+    /// Validates that the caller is the contract owner.
+    ///
+    /// # Panics
+    /// - If the caller is not the owner
+    #[inline]
+    fn require_is_owner(env: &Env) {
+        let owner: Address = env.storage().instance().get(&DataKey::Owner).unwrap();
+        owner.require_auth();
+    }
+
+    /// Authorizes a critical action.
+    ///
+    /// With multi-sig off this is `require_is_owner`. Otherwise the action's
+    /// approvals must meet the threshold, and are consumed so each approval
+    /// set executes the action once.
+    ///
+    /// # Panics
+    /// - If multi-sig is off and the caller is not the owner
+    /// - If multi-sig is on and the action lacks `threshold` approvals
+    fn require_owner_approval(env: &Env, action: Symbol, args: impl IntoVal<Env, Val>) {
+        let threshold = Self::get_threshold(env.clone());
+        if threshold == 0 {
+            Self::require_is_owner(env);
+            return;
+        }
+        let action_hash = Self::get_action_hash(env.clone(), action, args.into_val(env));
+        assert!(
+            Self::get_approvals(env.clone(), action_hash.clone()) >= threshold,
+            "Insufficient approvals"
+        );
+        env.storage()
+            .persistent()
+            .remove(&DataKey::ActionApprovals(action_hash));
+    }
+
+    /// Validates that the caller is the AI agent.
+    ///
+    /// # Panics
+    /// - If the caller is not the agent
+    #[inline]
+    fn require_is_agent(env: &Env) {
+        let agent: Address = env.storage().instance().get(&DataKey::Agent).unwrap();
+        agent.require_auth();
+    }
+
+    /// Validates that an amount is positive.
+    ///
+    /// # Panics
+    /// - If amount is <= 0
+    #[inline]
+    fn require_positive_amount(amount: i128) {
+        assert!(amount > 0, "Amount must be positive");
+    }
+
+    /// Validates that a deposit meets the minimum requirement.
+    ///
+    /// Minimum deposit is 1 USDC (1_000_000 in 7-decimal units).
+    ///
+    /// # Panics
+    /// - If amount < minimum deposit
+    #[inline]
+    fn require_minimum_deposit(amount: i128) {
+        assert!(amount >= MIN_DEPOSIT, "Minimum deposit is 1 USDC");
+    }
+
+    /// Validates that a deposit is within the user's cap.
+    ///
+    /// # Panics
+    /// - If user's new balance would exceed the deposit cap
+    #[inline]
+    fn require_within_deposit_cap(env: &Env, user: &Address, amount: i128) {
+        let cap: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::UserDepositCap)
+            .unwrap_or(0);
+        if cap > 0 {
+            let current_balance = Self::get_balance(env.clone(), user.clone());
+            assert!(current_balance + amount <= cap, "Exceeds user deposit cap");
+        }
+    }
+
+    /// Mints shares to a user and increases total shares.
+    fn mint_shares(env: &Env, user: &Address, shares: i128) {
+        let user_shares = Self::get_shares(env.clone(), user.clone());
+        Self::track_drawdown(env, user, user_shares == 0);
+        Self::set_shares(env, user, user_shares + shares);
+        Self::record_share_velocity(env, user, shares, 0);
+        Self::record_recent_deposit(env, user, shares);
+        if Self::get_pause_grace_seconds(env.clone()) > 0 {
+            env.storage().persistent().set(
+                &PositionKey::LastDeposit(user.clone()),
+                &env.ledger().timestamp(),
+            );
+        }
+
+        let total_shares = Self::stored_total_shares(env);
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalShares, &(total_shares + shares));
+    }
+
+    /// Rounds the shares burned for a small withdrawal down instead of up,
+    /// drawing the forgone share from the favorable rounding budget.
+    ///
+    /// # Returns
+    /// The shares to burn
+    fn apply_favorable_rounding(env: &Env, amount: i128, shares: i128) -> i128 {
+        let rounded = Self::favorably_rounded_shares(env, amount, shares);
+        if rounded < shares {
+            let budget = Self::get_favorable_rounding_budget(env.clone());
+            env.storage().instance().set(
+                &ConfigKey::FavorableRoundingBudget,
+                &(budget - (shares - rounded)),
+            );
+        }
+        rounded
+    }
+
+    /// Returns the shares a withdrawal burns after favorable rounding,
+    /// without drawing on the budget.
+    ///
+    /// Never rounds to zero shares, and leaves `shares` unchanged once the
+    /// budget cannot cover the difference.
+    fn favorably_rounded_shares(env: &Env, amount: i128, shares: i128) -> i128 {
+        let threshold = Self::get_favorable_rounding_threshold(env.clone());
+        if amount >= threshold {
+            return shares;
+        }
+        let floor = Self::convert_to_shares(env.clone(), amount);
+        let forgone = shares - floor;
+        let budget = Self::get_favorable_rounding_budget(env.clone());
+        if floor == 0 || forgone == 0 || forgone > budget {
+            return shares;
+        }
+        floor
+    }
+
+    /// Extends a withdrawal to the user's whole position when it would leave
+    /// fewer than `DustShareThreshold` shares behind.
+    ///
+    /// # Returns
+    /// The shares to burn and USDC to pay, unchanged unless dust is swept
+    fn sweep_dust(
+        env: &Env,
+        user: &Address,
+        user_shares: i128,
+        shares: i128,
+        amount: i128,
+    ) -> (i128, i128) {
+        let remainder = user_shares - shares;
+        let threshold = Self::get_dust_share_threshold(env.clone());
+        if remainder == 0 || remainder >= threshold || Self::total_locked_shares(env, user) > 0 {
+            return (shares, amount);
+        }
+        let swept = Self::convert_to_assets(env.clone(), user_shares);
+        (user_shares, swept.max(amount))
+    }
+
+    /// Tracks freshly minted shares while the accrual delay is enabled,
+    /// dropping entries whose window has closed.
+    fn record_recent_deposit(env: &Env, user: &Address, shares: i128) {
+        let delay = Self::get_accrual_delay(env.clone());
+        if delay == 0 {
+            return;
+        }
+        let now = env.ledger().timestamp();
+        let mut recent = Vec::new(env);
+        let mut merged = shares;
+        for deposit in Self::get_recent_deposits(env).iter() {
+            if deposit.user == *user {
+                merged += deposit.shares;
+            } else if deposit.timestamp + delay > now {
+                recent.push_back(deposit);
+            }
+        }
+        recent.push_back(RecentDeposit {
+            user: user.clone(),
+            shares: merged,
+            timestamp: now,
+        });
+        env.storage()
+            .persistent()
+            .set(&DataKey::RecentDeposits, &recent);
+    }
+
+    /// Returns the tracked deposits, including ones whose window has closed.
+    fn get_recent_deposits(env: &Env) -> Vec<RecentDeposit> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::RecentDeposits)
+            .unwrap_or(Vec::new(env))
+    }
+
+    /// Returns all recorded boosts, including expired ones not yet pruned.
+    fn get_boosts(env: &Env) -> Vec<Boost> {
+        env.storage()
+            .persistent()
+            .get(&PositionKey::Boosts)
+            .unwrap_or(Vec::new(env))
+    }
+
+    /// Returns a user's boost if its lock is still running.
+    fn active_boost(env: &Env, user: &Address) -> Option<Boost> {
+        let now = env.ledger().timestamp();
+        Self::get_boosts(env)
+            .iter()
+            .find(|boost| boost.user == *user && boost.unlock_time > now)
+    }
+
+    /// Redistributes a gain so active boosts earn their multiplied share of it.
+    ///
+    /// Every share is weighted by its multiplier (1x when unboosted) and the
+    /// gain is split by weight. Boosted holders receive bonus shares worth
+    /// their extra part; unboosted shares end at the price a 1x share
+    /// reaches, so total assets are unchanged. `old_shares` is the share
+    /// total the pre-update price applies to; the accrual delay may have
+    /// burned shares since. Expired boosts are pruned.
+    ///
+    /// # Events
+    /// Emits `BoostDistributedEvent` if any boosted shares were outstanding
+    fn distribute_boosted_yield(env: &Env, old_total: i128, new_total: i128, old_shares: i128) {
+        let total_shares = Self::stored_total_shares(env);
+        if old_shares == 0 || total_shares == 0 {
+            return;
+        }
+        let now = env.ledger().timestamp();
+
+        // Boosted shares still held by each user, and the total share weight
+        let mut active = Vec::new(env);
+        let mut live = Vec::new(env);
+        let mut boosted_shares = 0;
+        let mut weight = total_shares * BOOST_SCALE;
+        for boost in Self::get_boosts(env).iter() {
+            if boost.unlock_time <= now {
+                continue;
+            }
+            let shares = boost
+                .shares
+                .min(Self::get_shares(env.clone(), boost.user.clone()));
+            boosted_shares += shares;
+            weight += shares * (boost.multiplier - BOOST_SCALE);
+            active.push_back((boost.clone(), shares));
+            live.push_back(boost);
+        }
+
+        // Pre-update value of the current shares, and the gain on top of it
+        let old_value = old_total * total_shares / old_shares;
+        let gain = new_total - old_value;
+        if boosted_shares == 0 || gain <= 0 {
+            env.storage().persistent().set(&PositionKey::Boosts, &live);
+            return;
+        }
+
+        // A share with multiplier m ends worth (base + gain * m) / (base + gain * 1x)
+        // unboosted shares, where base is the pre-update value of the share weight
+        let base = old_total * weight / old_shares;
+        let mut boosts = Vec::new(env);
+        let mut minted_shares = 0;
+        for (mut boost, shares) in active.iter() {
+            let target = shares * (base + gain * boost.multiplier) / (base + gain * BOOST_SCALE);
+            let bonus = target - shares;
+            let user_shares = Self::get_shares(env.clone(), boost.user.clone());
+            Self::set_shares(env, &boost.user, user_shares + bonus);
+            boost.shares = shares + bonus;
+            minted_shares += bonus;
+            boosts.push_back(boost);
+        }
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalShares, &(total_shares + minted_shares));
+        env.storage()
+            .persistent()
+            .set(&PositionKey::Boosts, &boosts);
+
+        env.events().publish(
+            (symbol_short!("boost_dst"),),
+            BoostDistributedEvent {
+                boosted_shares,
+                minted_shares,
+            },
+        );
+    }
+
+    /// Withholds a gain from deposits made inside the accrual delay.
+    ///
+    /// Each fresh deposit is valued at the pre-update price, and just enough
+    /// of its shares are burned that it keeps that value at the post-update
+    /// price, so the older shares receive the whole gain. Does nothing if
+    /// every outstanding share is fresh.
+    ///
+    /// # Events
+    /// Emits `AccrualExcludedEvent` if any fresh shares were outstanding
+    fn exclude_recent_deposits(env: &Env, old_total: i128, new_total: i128) {
+        let delay = Self::get_accrual_delay(env.clone());
+        let total_shares = Self::stored_total_shares(env);
+        if delay == 0 || total_shares == 0 {
+            return;
+        }
+        let now = env.ledger().timestamp();
+        let (old_offset, offset_shares) = Self::offset_totals(env, old_total, total_shares);
+
+        // Fresh shares still held by each depositor, valued at the old price
+        let mut fresh = Vec::new(env);
+        let mut fresh_shares = 0;
+        let mut fresh_value = 0;
+        for deposit in Self::get_recent_deposits(env).iter() {
+            if deposit.timestamp + delay <= now {
+                continue;
+            }
+            let shares = deposit
+                .shares
+                .min(Self::get_shares(env.clone(), deposit.user.clone()));
+            let value = Self::mul_div(shares, old_offset, offset_shares);
+            fresh_shares += shares;
+            fresh_value += value;
+            fresh.push_back((deposit, shares, value));
+        }
+        if fresh_shares == 0 || fresh_shares == total_shares {
+            return;
+        }
+
+        // Shares that keep each fresh value at the price the mature shares,
+        // virtual ones included, reach once they receive the whole gain
+        let (new_offset, _) = Self::offset_totals(env, new_total, total_shares);
+        let mature_shares = offset_shares - fresh_shares;
+        let mature_value = new_offset - fresh_value;
+        let mut recent = Vec::new(env);
+        let mut burned_shares = 0;
+        for (mut deposit, shares, value) in fresh.iter() {
+            let burned = shares - value * mature_shares / mature_value;
+            let user_shares = Self::get_shares(env.clone(), deposit.user.clone());
+            Self::set_shares(env, &deposit.user, user_shares - burned);
+            deposit.shares = shares - burned;
+            burned_shares += burned;
+            recent.push_back(deposit);
+        }
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalShares, &(total_shares - burned_shares));
+        env.storage()
+            .persistent()
+            .set(&DataKey::RecentDeposits, &recent);
+
+        env.events().publish(
+            (symbol_short!("acr_excl"),),
+            AccrualExcludedEvent {
+                excluded_shares: fresh_shares,
+                burned_shares,
+            },
+        );
+    }
+
+    /// Burns shares from a user and decreases total shares.
+    ///
+    /// Callers must have verified the user holds at least `shares`.
+    ///
+    /// # Returns
+    /// The cost basis removed along with the shares
+    fn burn_shares(env: &Env, user: &Address, shares: i128) -> i128 {
+        let user_shares = Self::get_shares(env.clone(), user.clone());
+        Self::track_drawdown(env, user, false);
+        Self::set_shares(env, user, user_shares - shares);
+        let cost_basis = Self::reduce_cost_basis(env, user, shares, user_shares);
+        Self::record_share_velocity(env, user, 0, shares);
+
+        let total_shares = Self::stored_total_shares(env);
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalShares, &(total_shares - shares));
+        cost_basis
+    }
+
+    /// Burns shares a user escrowed in a withdrawal request, decreasing
+    /// total and pending shares.
+    ///
+    /// The user's balance and cost basis already dropped when the request
+    /// was queued.
+    fn burn_escrowed_shares(env: &Env, user: &Address, shares: i128) {
+        Self::record_share_velocity(env, user, 0, shares);
+
+        let total_shares = Self::stored_total_shares(env);
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalShares, &(total_shares - shares));
+        let total_pending = Self::get_total_pending_shares(env);
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalPendingShares, &(total_pending - shares));
+        Self::add_user_pending_shares(env, user, -shares);
+    }
+
+    /// Returns a user's recorded drawdown, if any.
+    #[inline]
+    fn get_drawdown(env: &Env, user: &Address) -> Option<Drawdown> {
+        env.storage()
+            .persistent()
+            .get(&PositionKey::Drawdown(user.clone()))
+    }
+
+    /// Folds the current share price into a user's drawdown record.
+    ///
+    /// `new_position` restarts the peak at the current price, so a user
+    /// re-entering after a full exit is not measured against an old peak.
+    /// The worst drawdown already recorded is kept.
+    fn track_drawdown(env: &Env, user: &Address, new_position: bool) {
+        let price = Self::get_share_price(env.clone());
+        let mut drawdown = Self::get_drawdown(env, user).unwrap_or(Drawdown {
+            peak_price: price,
+            max_drawdown_bps: 0,
+        });
+        if new_position {
+            drawdown.peak_price = price;
+        }
+        drawdown.max_drawdown_bps = drawdown
+            .max_drawdown_bps
+            .max(Self::drawdown_bps(drawdown.peak_price, price));
+        drawdown.peak_price = drawdown.peak_price.max(price);
+        env.storage()
+            .persistent()
+            .set(&PositionKey::Drawdown(user.clone()), &drawdown);
+    }
+
+    /// Returns the fall from `peak` to `price` in basis points, 0 if none.
+    #[inline]
+    fn drawdown_bps(peak: i128, price: i128) -> i128 {
+        if peak <= 0 || price >= peak {
+            return 0;
+        }
+        (peak - price) * 10_000 / peak
+    }
+
+    /// Adds a withdrawal's gain (or loss, if negative) to the user's realized PnL.
+    fn record_realized_pnl(env: &Env, user: &Address, pnl: i128) {
+        let key = DataKey::RealizedPnl(user.clone());
+        let realized: i128 = env.storage().persistent().get(&key).unwrap_or(0);
+        env.storage().persistent().set(&key, &(realized + pnl));
+    }
+
+    /// Adds to a user's lifetime minted and burned share counters.
+    fn record_share_velocity(env: &Env, user: &Address, minted: i128, burned: i128) {
+        let (total_minted, total_burned) = Self::get_share_velocity(env.clone(), user.clone());
+        env.storage().persistent().set(
+            &DataKey::ShareVelocity(user.clone()),
+            &(total_minted + minted, total_burned + burned),
+        );
+    }
+
+    /// Adds USDC paid in to a user's cost basis and the aggregate cost basis.
+    fn add_cost_basis(env: &Env, user: &Address, amount: i128) {
+        let basis = Self::get_cost_basis(env.clone(), user.clone());
+        let now = env.ledger().timestamp();
+        let since = match Self::holding_since(env, user) {
+            Some(since) if basis > 0 && basis + amount > 0 => {
+                ((since as i128 * basis + now as i128 * amount) / (basis + amount)) as u64
+            }
+            _ => now,
+        };
+        env.storage()
+            .persistent()
+            .set(&PositionKey::HoldingSince(user.clone()), &since);
+        env.storage()
+            .persistent()
+            .set(&DataKey::CostBasis(user.clone()), &(basis + amount));
+
+        let total_basis = Self::get_total_cost_basis(env.clone());
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalCostBasis, &(total_basis + amount));
+    }
+
+    /// Returns the cost-basis-weighted average time a user's USDC went in.
+    #[inline]
+    fn holding_since(env: &Env, user: &Address) -> Option<u64> {
+        env.storage()
+            .persistent()
+            .get(&PositionKey::HoldingSince(user.clone()))
+    }
+
+    /// Adds USDC deposited to a user's protected principal.
+    fn add_protected_principal(env: &Env, user: &Address, amount: i128) {
+        let principal = Self::get_protected_principal(env.clone(), user.clone());
+        env.storage().persistent().set(
+            &DataKey::ProtectedPrincipal(user.clone()),
+            &(principal + amount),
+        );
+    }
+
+    /// Removes USDC paid out from a user's protected principal, stopping at zero.
+    fn reduce_protected_principal(env: &Env, user: &Address, amount: i128) {
+        let principal = Self::get_protected_principal(env.clone(), user.clone());
+        env.storage().persistent().set(
+            &DataKey::ProtectedPrincipal(user.clone()),
+            &(principal - amount).max(0),
+        );
+    }
+
+    /// Removes the cost basis attributable to `shares` leaving a user's position.
+    ///
+    /// The basis is reduced pro rata to `shares / shares_before`; removing the
+    /// whole position removes the whole basis so no residue is left behind.
+    ///
+    /// # Returns
+    /// The cost basis removed
+    fn reduce_cost_basis(env: &Env, user: &Address, shares: i128, shares_before: i128) -> i128 {
+        let basis = Self::get_cost_basis(env.clone(), user.clone());
+        if basis == 0 || shares_before == 0 {
+            return 0;
+        }
+        let removed = if shares >= shares_before {
+            basis
+        } else {
+            basis * shares / shares_before
+        };
+        env.storage()
+            .persistent()
+            .set(&DataKey::CostBasis(user.clone()), &(basis - removed));
+
+        let total_basis = Self::get_total_cost_basis(env.clone());
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalCostBasis, &(total_basis - removed));
+        removed
+    }
+
+    /// Reads the vault totals reported in `VaultStats`.
+    fn vault_stats(env: &Env) -> VaultStats {
+        let (pending_withdrawal_shares, pending_withdrawal_assets) =
+            Self::get_total_pending_withdrawals(env.clone());
+
+        VaultStats {
+            total_assets: Self::get_total_deposits(env.clone()),
+            total_shares: Self::stored_total_shares(env),
+            share_price: Self::get_share_price(env.clone()),
+            depositor_count: Self::get_depositor_count(env.clone()),
+            total_deployed: Self::get_total_deployed(env.clone()),
+            reserve_deployed: Self::get_reserve_deployed(env.clone()),
+            pending_withdrawal_shares,
+            pending_withdrawal_assets,
+        }
+    }
+
+    /// Splits a reported gain across the strategies pro rata to their allocations.
+    ///
+    /// Does nothing while no funds are deployed.
+    fn attribute_strategy_yield(env: &Env, gain: i128) {
+        let total_deployed = Self::get_total_deployed(env.clone());
+        if total_deployed <= 0 {
+            return;
+        }
+        for strategy in Self::get_strategies(env.clone()).iter() {
+            let allocation = Self::get_strategy_allocation(env.clone(), strategy.clone());
+            if allocation <= 0 {
+                continue;
+            }
+            let earned = Self::get_strategy_yield(env.clone(), strategy.clone());
+            env.storage().persistent().set(
+                &DataKey::StrategyRealizedYield(strategy),
+                &(earned + gain * allocation / total_deployed),
+            );
+        }
+    }
+
+    /// Emits `PriceMilestoneEvent` once the share price has moved a whole
+    /// `PriceStepBps` step away from the last milestone.
+    ///
+    /// The new milestone is the furthest step boundary the price crossed,
+    /// so a jump over several steps emits a single event.
+    fn check_price_milestone(env: &Env) {
+        let step_bps = Self::get_price_step_bps(env.clone());
+        let previous_milestone = Self::get_last_price_milestone(env.clone());
+        let step = previous_milestone * step_bps / 10_000;
+        if step == 0 {
+            return;
+        }
+
+        let share_price = Self::get_share_price(env.clone());
+        let steps = (share_price - previous_milestone) / step;
+        if steps == 0 {
+            return;
+        }
+        let milestone = previous_milestone + steps * step;
+        env.storage()
+            .instance()
+            .set(&DataKey::LastPriceMilestone, &milestone);
+        env.events().publish(
+            (symbol_short!("price_ms"),),
+            PriceMilestoneEvent {
+                previous_milestone,
+                milestone,
+                share_price,
+            },
+        );
+    }
+
+    /// Re-evaluates the underwater flag after total assets change.
+    ///
+    /// Emits `UnderwaterEvent` only when the state flips.
+    fn update_underwater_state(env: &Env) {
+        let total_assets = Self::get_total_deposits(env.clone());
+        let total_cost_basis = Self::get_total_cost_basis(env.clone());
+        let underwater = total_assets < total_cost_basis;
+        if underwater == Self::is_underwater(env.clone()) {
+            return;
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::Underwater, &underwater);
+        env.events().publish(
+            (symbol_short!("underwatr"),),
+            UnderwaterEvent {
+                underwater,
+                total_assets,
+                total_cost_basis,
+            },
+        );
+    }
+
+    /// Writes the depositor index under the layout currently in storage.
+    fn set_depositors(env: &Env, depositors: &Vec<Address>) {
+        let layout = Self::get_position_layout(env.clone());
+        if layout == 0 {
+            env.storage()
+                .persistent()
+                .set(&DataKey::Depositors, depositors);
+        } else {
+            env.storage()
+                .persistent()
+                .set(&PositionKey::DepositorIndex(layout), depositors);
+        }
+    }
+
+    /// Writes a user's share balance and records it in their checkpoint history.
+    ///
+    /// The checkpoint is tagged with the current snapshot count, so balances
+    /// changed after snapshot `id` is taken never affect lookups at `id`.
+    fn set_shares(env: &Env, user: &Address, shares: i128) {
+        let old_shares = Self::get_shares(env.clone(), user.clone());
+        env.storage()
+            .persistent()
+            .set(&DataKey::Shares(user.clone()), &shares);
+        if Self::get_drift_check(env.clone()) {
+            let checksum = Self::share_checksum(env) + shares - old_shares;
+            env.storage()
+                .persistent()
+                .set(&PositionKey::ShareChecksum, &checksum);
+        }
+
+        let count = Self::get_depositor_count(env.clone());
+        if old_shares == 0 && shares > 0 {
+            env.storage()
+                .instance()
+                .set(&DataKey::DepositorCount, &(count + 1));
+            let mut depositors = Self::get_depositors(env.clone());
+            depositors.push_back(user.clone());
+            Self::set_depositors(env, &depositors);
+            Self::clear_pre_registration(env, user);
+        } else if old_shares > 0 && shares == 0 {
+            env.storage()
+                .instance()
+                .set(&DataKey::DepositorCount, &(count - 1));
+            let mut depositors = Self::get_depositors(env.clone());
+            if let Some(index) = depositors.first_index_of(user) {
+                depositors.remove(index);
+            }
+            Self::set_depositors(env, &depositors);
+        }
+
+        let epoch = Self::snapshot_count(env);
+        let key = DataKey::ShareCheckpoints(user.clone());
+        let mut checkpoints: Vec<ShareCheckpoint> = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .unwrap_or(Vec::new(env));
+        let ledger = env.ledger().sequence();
+        let checkpoint = ShareCheckpoint {
+            epoch,
+            ledger,
+            shares,
+        };
+        match checkpoints.last() {
+            Some(last) if last.epoch == epoch && last.ledger == ledger => {
+                checkpoints.set(checkpoints.len() - 1, checkpoint);
+            }
+            _ => checkpoints.push_back(checkpoint),
+        }
+        env.storage().persistent().set(&key, &checkpoints);
+    }
+
+    /// Returns the management fee owed since the last accrual, and the
+    /// seconds it covers.
+    ///
+    /// Nothing is owed for time spent in a fee holiday or while the vault is
+    /// empty.
+    fn management_fee_owed(env: &Env) -> (i128, u64) {
+        let now = env.ledger().timestamp();
+        let last: Option<u64> = env.storage().instance().get(&ConfigKey::LastFeeAccrual);
+        let period = now - last.unwrap_or(now);
+        if Self::is_fee_holiday(env.clone()) || Self::stored_total_shares(env) == 0 {
+            return (0, period);
+        }
+        let fee = Self::mul_div(
+            Self::get_total_deposits(env.clone()),
+            Self::get_management_fee_bps(env.clone()) * period as i128,
+            10_000 * SECONDS_PER_YEAR as i128,
+        );
+        (fee, period)
+    }
+
+    /// Mints the fee recipient the management fee owed since the last accrual.
+    ///
+    /// Nothing is owed for time spent in a fee holiday, or while the fee is
+    /// 0 or the vault is empty. A fee too small to mint a share is left to
+    /// accrue further.
+    ///
+    /// # Events
+    /// Emits `FeeAccruedEvent` if shares are minted
+    fn accrue_management_fee(env: &Env) {
+        let now = env.ledger().timestamp();
+        let (fee, period) = Self::management_fee_owed(env);
+        let bps = Self::get_management_fee_bps(env.clone());
+        let total_assets = Self::get_total_deposits(env.clone());
+        let total_shares = Self::stored_total_shares(env);
+
+        let shares = if fee > 0 && fee < total_assets {
+            let (offset_assets, offset_shares) =
+                Self::offset_totals(env, total_assets, total_shares);
+            Self::mul_div(fee, offset_shares, offset_assets - fee)
+        } else {
+            0
+        };
+        if shares == 0 && fee > 0 {
+            return;
+        }
+        env.storage()
+            .instance()
+            .set(&ConfigKey::LastFeeAccrual, &now);
+        if shares == 0 {
+            return;
+        }
+
+        Self::mint_fee_shares(env, shares);
+
+        let mut fees = Self::get_fees_collected(env.clone());
+        fees.management += fee;
+        env.storage()
+            .instance()
+            .set(&ConfigKey::FeesCollected, &fees);
+
+        env.events().publish(
+            (symbol_short!("fee_acr"),),
+            FeeAccruedEvent {
+                shares_minted: shares,
+                fee_bps: bps,
+                period_seconds: period,
+            },
+        );
+    }
+
+    /// Mints the fee recipient the performance fee on any share price gain
+    /// above the high-water mark, then raises the mark to the price after
+    /// the fee.
+    ///
+    /// Gains made during a fee holiday or while the fee is 0 raise the mark
+    /// without a fee. A fee too small to mint a share leaves the mark in
+    /// place so the gain can accrue further. The mark resets once the vault
+    /// is empty.
+    ///
+    /// # Events
+    /// Emits `PerformanceFeeEvent` if shares are minted
+    fn accrue_performance_fee(env: &Env) {
+        let total_shares = Self::stored_total_shares(env);
+        if total_shares == 0 {
+            env.storage().instance().remove(&ConfigKey::HighWaterMark);
+            return;
+        }
+        let high_water_mark = Self::get_high_water_mark(env.clone());
+        let price = Self::get_share_price(env.clone());
+        if price <= high_water_mark {
+            return;
+        }
+
+        let total_assets = Self::get_total_deposits(env.clone());
+        let peak_assets = Self::mul_div(
+            high_water_mark,
+            total_shares,
+            SHARE_PRICE_SCALE * Self::share_unit(env),
+        );
+        let fee = if Self::is_fee_holiday(env.clone()) {
+            0
+        } else {
+            (total_assets - peak_assets) * Self::get_performance_fee_bps(env.clone()) / 10_000
+        };
+        let shares = if fee > 0 {
+            let (offset_assets, offset_shares) =
+                Self::offset_totals(env, total_assets, total_shares);
+            Self::mul_div(fee, offset_shares, offset_assets - fee)
+        } else {
+            0
+        };
+        if shares == 0 && fee > 0 {
+            return;
+        }
+
+        if shares > 0 {
+            Self::mint_fee_shares(env, shares);
+            let mut fees = Self::get_fees_collected(env.clone());
+            fees.performance += fee;
+            env.storage()
+                .instance()
+                .set(&ConfigKey::FeesCollected, &fees);
+        }
+        let high_water_mark = Self::get_share_price(env.clone());
+        env.storage()
+            .instance()
+            .set(&ConfigKey::HighWaterMark, &high_water_mark);
+
+        if shares > 0 {
+            env.events().publish(
+                (symbol_short!("perf_fee"),),
+                PerformanceFeeEvent {
+                    shares_minted: shares,
+                    fee,
+                    high_water_mark,
+                },
+            );
+        }
+    }
+
+    /// Raises the high-water mark by any share price rise an exit caused.
+    ///
+    /// A withdrawal fee kept in the vault (and favorable exit rounding)
+    /// lifts the share price without the strategies earning anything, so
+    /// the mark moves with it and the performance fee only ever charges
+    /// reported yield.
+    fn exclude_exit_from_high_water_mark(env: &Env, price_before: i128) {
+        if Self::stored_total_shares(env) == 0 {
+            return;
+        }
+        let price = Self::get_share_price(env.clone());
+        if price <= price_before {
+            return;
+        }
+        let high_water_mark = Self::get_high_water_mark(env.clone()) + price - price_before;
+        env.storage()
+            .instance()
+            .set(&ConfigKey::HighWaterMark, &high_water_mark);
+    }
+
+    /// Returns the withdrawal fee on a payout of `amount`, recording it in
+    /// `FeesCollected`, or re-minting it as shares if the user opted in to
+    /// `AutoCompound`.
+    ///
+    /// Called after the exit's shares are burned, so an exit that leaves no
+    /// shares outstanding (and no holder to benefit) is charged nothing. An
+    /// opted-in user's full exit is charged nothing either: there is no
+    /// position left to re-mint the fee into, so it is paid out in USDC.
+    ///
+    /// # Events
+    /// Emits `WithdrawalFeeEvent` if a fee is charged, or
+    /// `WithdrawalFeeCompoundedEvent` if it is re-minted
+    fn charge_withdrawal_fee(env: &Env, user: &Address, amount: i128) -> i128 {
+        if Self::is_fee_holiday(env.clone()) || Self::stored_total_shares(env) == 0 {
+            return 0;
+        }
+        let fee = amount * Self::get_withdrawal_fee_bps(env.clone()) / 10_000;
+        if fee == 0 {
+            return 0;
+        }
+        if Self::get_auto_compound(env.clone(), user.clone()) {
+            let remaining =
+                Self::get_shares(env.clone(), user.clone()) + Self::user_pending_shares(env, user);
+            if remaining == 0 {
+                return 0;
+            }
+            if Self::compound_withdrawal_fee(env, user, amount, fee) {
+                return fee;
+            }
+        }
+
+        let mut fees = Self::get_fees_collected(env.clone());
+        fees.withdrawal += fee;
+        env.storage()
+            .instance()
+            .set(&ConfigKey::FeesCollected, &fees);
+
+        env.events().publish(
+            (symbol_short!("wd_fee"),),
+            WithdrawalFeeEvent {
+                user: user.clone(),
+                fee,
+            },
+        );
+        fee
+    }
+
+    /// Mints an opted-in user shares worth their withheld withdrawal fee.
+    ///
+    /// Called after the exit's shares are burned but before `TotalDeposits`
+    /// drops by the payout, so the shares are priced as if the whole
+    /// `amount` had left. The fee USDC stays in the vault backing them,
+    /// leaving the share price for other holders unchanged.
+    ///
+    /// # Returns
+    /// `false` if the fee is worth no shares and was not compounded
+    fn compound_withdrawal_fee(env: &Env, user: &Address, amount: i128, fee: i128) -> bool {
+        let total: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::TotalDeposits)
+            .unwrap_or(0);
+        let total_shares = Self::stored_total_shares(env);
+        if total <= amount {
+            return false;
+        }
+        let (offset_assets, offset_shares) = Self::offset_totals(env, total - amount, total_shares);
+        let shares = Self::mul_div(fee, offset_shares, offset_assets);
+        if shares == 0 {
+            return false;
+        }
+
+        let user_shares = Self::get_shares(env.clone(), user.clone());
+        Self::set_shares(env, user, user_shares + shares);
+        Self::record_share_velocity(env, user, shares, 0);
+        Self::add_cost_basis(env, user, fee);
+        Self::add_protected_principal(env, user, fee);
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalShares, &(total_shares + shares));
+
+        env.events().publish(
+            (symbol_short!("fee_cmpd"),),
+            WithdrawalFeeCompoundedEvent {
+                user: user.clone(),
+                fee,
+                shares,
+            },
+        );
+        true
+    }
+
+    /// Mints fee shares to the fee recipient.
+    fn mint_fee_shares(env: &Env, shares: i128) {
+        let recipient = Self::get_fee_recipient(env.clone());
+        let recipient_shares = Self::get_shares(env.clone(), recipient.clone());
+        Self::set_shares(env, &recipient, recipient_shares + shares);
+        let total_shares = Self::stored_total_shares(env);
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalShares, &(total_shares + shares));
+    }
+
+    /// Emits `ConfigUpdatedEvent` and appends the change to `ConfigHistory`.
+    fn record_config_change(env: &Env, key: Symbol, old_value: i128, new_value: i128) {
+        Self::append_config_change(env, key.clone(), old_value, new_value);
+        env.events().publish(
+            (symbol_short!("cfg_upd"),),
+            ConfigUpdatedEvent {
+                key,
+                old_value,
+                new_value,
+            },
+        );
+    }
+
+    /// Appends a change to a scalar key to `ConfigHistory`.
+    fn append_config_change(env: &Env, key: Symbol, old: i128, new: i128) {
+        Self::push_config_change(env, key, old, new, None, None);
+    }
+
+    /// Appends a change to an address-valued key to `ConfigHistory`.
+    fn append_address_change(
+        env: &Env,
+        key: Symbol,
+        old_address: Option<Address>,
+        new_address: Option<Address>,
+    ) {
+        Self::push_config_change(env, key, 0, 0, old_address, new_address);
+    }
+
+    /// Appends a `ConfigChange` to `ConfigHistory`, dropping the oldest
+    /// entry once `MAX_CONFIG_HISTORY` are kept.
+    fn push_config_change(
+        env: &Env,
+        key: Symbol,
+        old: i128,
+        new: i128,
+        old_address: Option<Address>,
+        new_address: Option<Address>,
+    ) {
+        let mut history: Vec<ConfigChange> = env
+            .storage()
+            .persistent()
+            .get(&ConfigKey::ConfigHistory)
+            .unwrap_or(Vec::new(env));
+        if history.len() >= MAX_CONFIG_HISTORY {
+            history.pop_front();
+        }
+        history.push_back(ConfigChange {
+            key,
+            old,
+            new,
+            timestamp: env.ledger().timestamp(),
+            actor: Self::get_owner(env.clone()),
+            old_address,
+            new_address,
+        });
+        env.storage()
+            .persistent()
+            .set(&ConfigKey::ConfigHistory, &history);
+    }
+
+    /// Appends the current vault totals to `AssetsHistory`, dropping the
+    /// oldest sample once `MAX_HISTORY_SAMPLES` are stored.
+    fn record_assets_sample(env: &Env) {
+        let mut history = Self::get_assets_history(env.clone());
+        if history.len() >= MAX_HISTORY_SAMPLES {
+            history.pop_front();
+        }
+        history.push_back(AssetsSample {
+            timestamp: env.ledger().timestamp(),
+            total_assets: Self::get_total_deposits(env.clone()),
+            total_shares: Self::stored_total_shares(env),
+            share_price: Self::get_share_price(env.clone()),
+        });
+        env.storage()
+            .persistent()
+            .set(&DataKey::AssetsHistory, &history);
+    }
+
+    /// Finds the share price growth over a lookback window.
+    ///
+    /// # Returns
+    /// `(price_then, price_now, elapsed_seconds)` against the newest sample
+    /// at least `lookback` seconds old (or the oldest sample), or `None` if
+    /// no sample with shares predates the current ledger
+    fn price_growth(env: &Env, lookback: u64) -> Option<(i128, i128, u64)> {
+        let now = env.ledger().timestamp();
+        let cutoff = now.saturating_sub(lookback);
+        let history = Self::get_assets_history(env.clone());
+        let mut reference = history.first()?;
+        for sample in history.iter() {
+            if sample.timestamp <= cutoff {
+                reference = sample;
+            }
+        }
+        if reference.total_shares == 0 || reference.timestamp >= now {
+            return None;
+        }
+        let price_then = reference.share_price;
+        if price_then == 0 {
+            return None;
+        }
+        let price_now = Self::get_share_price(env.clone());
+        Some((price_then, price_now, now - reference.timestamp))
+    }
+
+    /// Returns the number of snapshots taken so far.
+    #[inline]
+    fn snapshot_count(env: &Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&DataKey::SnapshotCount)
+            .unwrap_or(0)
+    }
+
+    /// Records the current vault totals as a new snapshot.
+    ///
+    /// # Returns
+    /// The new snapshot id (ids start at 1)
+    ///
+    /// # Events
+    /// Emits `SnapshotTakenEvent` with the id and recorded totals
+    fn record_snapshot(env: &Env) -> u32 {
+        let snapshot_id = Self::snapshot_count(env) + 1;
+        let total_assets = Self::get_total_deposits(env.clone());
+        let total_shares = Self::stored_total_shares(env);
+        env.storage().persistent().set(
+            &DataKey::Snapshot(snapshot_id),
+            &VaultSnapshot {
+                total_assets,
+                total_shares,
+                timestamp: env.ledger().timestamp(),
+            },
+        );
+        env.storage()
+            .instance()
+            .set(&DataKey::SnapshotCount, &snapshot_id);
+
+        env.events().publish(
+            (symbol_short!("snapshot"),),
+            SnapshotTakenEvent {
+                snapshot_id,
+                total_assets,
+                total_shares,
+            },
+        );
+
+        snapshot_id
+    }
+
+    /// Returns a user's share balance as of a snapshot.
+    fn shares_at_snapshot(env: &Env, user: &Address, snapshot_id: u32) -> i128 {
+        let checkpoints: Vec<ShareCheckpoint> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::ShareCheckpoints(user.clone()))
+            .unwrap_or(Vec::new(env));
+        for checkpoint in checkpoints.iter().rev() {
+            if checkpoint.epoch < snapshot_id {
+                return checkpoint.shares;
+            }
+        }
+        0
+    }
+
+    /// Sums a user's portion of the airdrop rounds starting at `from`.
+    fn airdrop_amount(env: &Env, user: &Address, rounds: &Vec<AirdropRound>, from: u32) -> i128 {
+        let mut amount = 0_i128;
+        for index in from..rounds.len() {
+            let round = rounds.get(index).unwrap();
+            let shares = Self::shares_at_snapshot(env, user, round.snapshot_id);
+            amount += Self::mul_div(shares, round.total_amount, round.eligible_shares);
+        }
+        amount
+    }
+
+    /// Converts a USDC amount into shares, rounding up.
+    ///
+    /// Used when burning shares for a withdrawal so the withdrawer always
+    /// covers the full value of the assets they receive. No amount the vault
+    /// holds costs more than the whole supply, which the virtual offset would
+    /// otherwise price above it.
+    ///
+    /// # Panics
+    /// - If the implied share price is outside the configured sanity band
+    fn convert_to_shares_ceil(env: &Env, amount: i128) -> i128 {
+        let total_shares = Self::stored_total_shares(env);
+        let total_assets = Self::get_total_deposits(env.clone());
+        if total_shares == 0 || total_assets == 0 {
+            let ratio = Self::empty_vault_ratio(env);
+            return Self::mul_div_ceil(amount, ratio, SHARE_PRICE_SCALE);
+        }
+        Self::require_share_price_in_bounds(env, total_assets, total_shares);
+        let (offset_assets, offset_shares) = Self::offset_totals(env, total_assets, total_shares);
+        let shares = Self::mul_div_ceil(amount, offset_shares, offset_assets);
+        if amount <= total_assets {
+            shares.min(total_shares)
+        } else {
+            shares
+        }
+    }
+
+    /// Returns total assets and total shares with `VIRTUAL_ASSETS` and the
+    /// virtual shares they are worth at the initial share ratio added, as
+    /// conversions price them.
+    #[inline]
+    fn offset_totals(env: &Env, total_assets: i128, total_shares: i128) -> (i128, i128) {
+        let virtual_shares = Self::mul_div(
+            VIRTUAL_ASSETS,
+            Self::empty_vault_ratio(env),
+            SHARE_PRICE_SCALE,
+        );
+        (total_assets + VIRTUAL_ASSETS, total_shares + virtual_shares)
+    }
+
+    /// Returns share units per USDC unit implied by the share decimals.
+    #[inline]
+    fn share_unit(env: &Env) -> i128 {
+        10_i128.pow(Self::decimals(env.clone()) - USDC_DECIMALS)
+    }
+
+    /// Returns share units minted per USDC unit in an empty vault, scaled by
+    /// `SHARE_PRICE_SCALE`.
+    #[inline]
+    fn empty_vault_ratio(env: &Env) -> i128 {
+        Self::get_initial_share_ratio(env.clone()) * Self::share_unit(env)
+    }
+
+    /// Computes `a * b / c`, rounding down.
+    ///
+    /// # Panics
+    /// - If `a * b` overflows `i128`
+    #[inline]
+    fn mul_div(a: i128, b: i128, c: i128) -> i128 {
+        a.checked_mul(b).expect("Arithmetic overflow") / c
+    }
+
+    /// Computes `a * b / c`, rounding up.
+    ///
+    /// # Panics
+    /// - If `a * b` overflows `i128`
+    #[inline]
+    fn mul_div_ceil(a: i128, b: i128, c: i128) -> i128 {
+        let product = a.checked_mul(b).expect("Arithmetic overflow");
+        let quotient = product / c;
+        if product % c == 0 {
+            quotient
+        } else {
+            quotient + 1
+        }
+    }
+
+    /// Validates that the implied share price sits inside the sanity band.
+    ///
+    /// # Panics
+    /// - If the price is below `MinSharePrice` or above `MaxSharePrice`
+    #[inline]
+    fn require_share_price_in_bounds(env: &Env, total_assets: i128, total_shares: i128) {
+        let (min_price, max_price) = Self::get_share_price_bounds(env.clone());
+        if min_price == 0 && max_price == 0 {
+            return;
+        }
+        let price = total_assets * SHARE_PRICE_SCALE * Self::share_unit(env) / total_shares;
+        assert!(
+            (min_price == 0 || price >= min_price) && (max_price == 0 || price <= max_price),
+            "Share price out of bounds"
+        );
+    }
+
+    /// Validates that a new depositor fits under the depositor cap.
+    ///
+    /// Users already holding shares, and users with a live reservation, are
+    /// always admitted. Other users' live reservations occupy slots.
+    ///
+    /// # Panics
+    /// - If the user holds no shares and the depositor cap is reached
+    #[inline]
+    fn require_depositor_slot(env: &Env, user: &Address) {
+        let max = Self::get_max_depositors(env.clone());
+        if max > 0 && Self::get_shares(env.clone(), user.clone()) == 0 {
+            let now = env.ledger().timestamp();
+            let mut reserved = 0;
+            for reservation in Self::get_pre_registrations(env.clone()).iter() {
+                if reservation.expires_at > now {
+                    if reservation.user == *user {
+                        return;
+                    }
+                    reserved += 1;
+                }
+            }
+            assert!(
+                Self::get_depositor_count(env.clone()) + reserved < max,
+                "Depositor limit reached"
+            );
+        }
+    }
+
+    /// Consumes a user's depositor slot reservation once they hold shares.
+    fn clear_pre_registration(env: &Env, user: &Address) {
+        let mut reservations = Self::get_pre_registrations(env.clone());
+        if let Some(index) = reservations.iter().position(|r| r.user == *user) {
+            reservations.remove(index as u32);
+            env.storage()
+                .persistent()
+                .set(&DataKey::PreRegistrations, &reservations);
+        }
+    }
+
+    /// Validates that `shares` can leave a position without touching locked shares.
+    ///
+    /// # Panics
+    /// - If fewer than `shares` of the user's `user_shares` are unlocked
+    #[inline]
+    fn require_unlocked_shares(env: &Env, user: &Address, user_shares: i128, shares: i128) {
+        let locked = Self::total_locked_shares(env, user);
+        assert!(user_shares - locked >= shares, "Shares are locked");
+    }
+
+    /// Returns a user's shares pledged to lockers plus those under an active boost.
+    fn total_locked_shares(env: &Env, user: &Address) -> i128 {
+        let boosted = Self::active_boost(env, user).map_or(0, |boost| boost.shares);
+        Self::get_locked_shares(env.clone(), user.clone()) + boosted
+    }
+
+    /// Returns a user's shares not pledged to lockers or under an active boost.
+    #[inline]
+    fn unlocked_shares(env: &Env, user: &Address) -> i128 {
+        let shares = Self::get_shares(env.clone(), user.clone());
+        (shares - Self::total_locked_shares(env, user)).max(0)
+    }
+
+    /// Validates that a share count does not exceed the shares outstanding.
+    ///
+    /// A user balance above `TotalShares` can only come from corrupted
+    /// state, so share-denominated exits refuse it rather than underflowing
+    /// the total.
+    ///
+    /// # Panics
+    /// - If `shares` exceeds `TotalShares`
+    #[inline]
+    fn require_within_total_shares(env: &Env, shares: i128) {
+        assert!(
+            shares <= Self::stored_total_shares(env),
+            "Exceeds total shares"
+        );
+    }
+
+    /// Returns the shares a user has locked to a specific locker.
+    fn get_locked_shares_by(env: &Env, user: &Address, locker: &Address) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::LockedShares(user.clone(), locker.clone()))
+            .unwrap_or(0)
+    }
+
+    /// Validates that the user's deposit cooldown has elapsed.
+    ///
+    /// # Panics
+    /// - If the user acted less than `DepositCooldownSeconds` seconds ago
+    #[inline]
+    fn require_deposit_cooldown_elapsed(env: &Env, user: &Address) {
+        let cooldown = Self::get_deposit_cooldown(env.clone());
+        if cooldown == 0 {
+            return;
+        }
+        if let Some(last) = Self::get_last_action(env.clone(), user.clone()) {
+            assert!(
+                env.ledger().timestamp() >= last + cooldown,
+                "Deposit cooldown."
+            );
+        }
+    }
+
+    /// Writes the initial vault configuration shared by both initializers.
+    ///
+    /// # Panics
+    /// - If the vault has already been initialized (Agent key already exists)
+    fn init_vault(env: &Env, agent: Address, usdc_token: Address, permissions: u32) {
+        if env.storage().instance().has(&DataKey::Agent) {
+            panic!("Already initialized");
+        }
+        assert!(
+            usdc_token != env.current_contract_address(),
+            "Address is the vault contract"
+        );
+        Self::require_valid_role_address(env, &agent, &usdc_token);
+
+        let tvl_cap = 100_000_000_000_i128; // 100M USDC default
+
+        env.storage().instance().set(&DataKey::Agent, &agent);
+        env.storage()
+            .instance()
+            .set(&DataKey::UsdcToken, &usdc_token);
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalDeposits, &0_i128);
+        env.storage().instance().set(&DataKey::Paused, &false);
+        env.storage().instance().set(&DataKey::Owner, &agent);
+        env.storage().instance().set(&DataKey::TvLCap, &tvl_cap);
+        env.storage()
+            .instance()
+            .set(&DataKey::UserDepositCap, &10_000_000_000_i128); // 10K USDC default
+        env.storage().instance().set(&DataKey::Version, &1_u32);
+        env.storage()
+            .instance()
+            .set(&ConfigKey::AgentPermissions, &permissions);
+        env.storage()
+            .persistent()
+            .set(&PositionKey::PositionLayout, &POSITION_LAYOUT_VERSION);
+
+        env.events().publish(
+            (symbol_short!("v_init"),),
+            VaultInitializedEvent {
+                agent: agent.clone(),
+                usdc_token: usdc_token.clone(),
+                tvl_cap,
+            },
+        );
+        Self::warn_if_roles_conflated(env);
+    }
+
+    /// Emits `RoleConflationEvent` if the owner is also the agent.
+    fn warn_if_roles_conflated(env: &Env) {
+        if Self::roles_conflated(env.clone()) {
+            env.events().publish(
+                (symbol_short!("roles_cnf"),),
+                RoleConflationEvent {
+                    address: Self::get_owner(env.clone()),
+                },
+            );
+        }
+    }
+
+    /// Validates that an optional feature is enabled.
+    ///
+    /// # Panics
+    /// - If `feature` is disabled in the feature flags
+    #[inline]
+    fn require_feature_enabled(env: &Env, feature: u32) {
+        assert!(
+            Self::get_feature_flags(env.clone()) & feature == feature,
+            "Feature disabled"
+        );
+    }
+
+    /// Returns whether `addr` may pause the vault: the owner or the pauser.
+    fn can_pause(env: &Env, addr: &Address) -> bool {
+        *addr == Self::get_owner(env.clone()) || Self::get_pauser(env.clone()) == Some(addr.clone())
+    }
+
+    /// Validates that the caller is the agent and holds `permission`.
+    ///
+    /// # Panics
+    /// - If the agent has not authorized the call
+    /// - If the agent's permission mask lacks `permission`
+    #[inline]
+    fn require_agent_permission(env: &Env, permission: u32) {
+        Self::require_is_agent(env);
+        assert!(
+            Self::get_agent_permissions(env.clone()) & permission == permission,
+            "Agent lacks permission"
+        );
+    }
+
+    /// Validates that an address can hold the agent or owner role.
+    ///
+    /// Assigning either role to the token or to the vault itself would leave
+    /// no key able to sign for it, bricking administration.
+    ///
+    /// # Panics
+    /// - If the address is the vault contract
+    /// - If the address is the USDC token
+    #[inline]
+    fn require_valid_role_address(env: &Env, address: &Address, usdc_token: &Address) {
+        assert!(
+            *address != env.current_contract_address(),
+            "Address is the vault contract"
+        );
+        assert!(address != usdc_token, "Address is the USDC token");
+    }
+
+    /// Validates that a permission mask only contains known `PERM_*` bits.
+    ///
+    /// # Panics
+    /// - If the mask contains unknown bits
+    #[inline]
+    fn require_valid_permissions(permissions: u32) {
+        assert!(
+            permissions & !ALL_AGENT_PERMISSIONS == 0,
+            "Invalid permissions"
+        );
+    }
+
+    /// Validates that a withdrawal is within the per-transaction cap.
+    ///
+    /// # Panics
+    /// - If `MaxWithdrawPerTx` is set and amount exceeds it
+    #[inline]
+    fn require_within_withdraw_cap(env: &Env, amount: i128) {
+        let cap = Self::get_max_withdraw_per_tx(env.clone());
+        assert!(
+            cap == 0 || amount <= cap,
+            "Exceeds max withdrawal per transaction"
+        );
+    }
+
+    /// Validates that the current share price is within tolerance of a quote.
+    ///
+    /// # Panics
+    /// - If `quoted_price` is not positive
+    /// - If the price deviates from the quote by more than `MaxSlippageBps`
+    fn require_within_slippage(env: &Env, quoted_price: i128) {
+        assert!(quoted_price > 0, "Invalid quoted price");
+        let price = Self::get_share_price(env.clone());
+        let deviation = (price - quoted_price).abs();
+        assert!(
+            deviation * 10_000 <= quoted_price * Self::get_max_slippage_bps(env.clone()),
+            "Slippage exceeded"
+        );
+    }
+
+    /// Validates an `update_total_assets` call against the configured rate limits.
+    ///
+    /// # Panics
+    /// - If the previous update was less than `AssetsUpdateCooldown` seconds ago
+    /// - If the change exceeds `MaxAssetsDeltaBps` of `old_total`
+    /// - If an assets oracle is set and `new_total` deviates from its figure
+    ///   by more than `AssetsOracleToleranceBps`
+    fn require_assets_update_allowed(env: &Env, old_total: i128, new_total: i128) {
+        let cooldown = Self::get_assets_update_cooldown(env.clone());
+        if cooldown > 0 {
+            let last: Option<u64> = env.storage().instance().get(&DataKey::LastAssetsUpdate);
+            if let Some(last) = last {
+                assert!(
+                    env.ledger().timestamp() >= last + cooldown,
+                    "Assets update cooldown"
+                );
+            }
+        }
+
+        let max_delta_bps = Self::get_max_assets_delta_bps(env.clone());
+        if max_delta_bps > 0 && old_total > 0 {
+            let delta = (new_total - old_total).abs();
+            assert!(
+                delta * 10_000 <= old_total * max_delta_bps,
+                "Assets delta too large"
+            );
+        }
+
+        if let Some(oracle) = Self::get_assets_oracle(env.clone()) {
+            let reference =
+                AssetsOracleClient::new(env, &oracle).total_assets(&env.current_contract_address());
+            let deviation = (new_total - reference).abs();
+            assert!(
+                deviation * 10_000
+                    <= reference * Self::get_assets_oracle_tolerance_bps(env.clone()),
+                "Assets deviate from oracle"
+            );
+        }
+    }
+
+    /// Burns the shares backing `amount` and pays the USDC to `user` once
+    /// the caller has authorized the withdrawal.
+    fn process_withdrawal(env: &Env, user: Address, amount: i128) {
+        Self::require_positive_amount(amount);
+        Self::accrue_management_fee(env);
+
+        let shares = Self::convert_to_shares_ceil(env, amount);
+        let shares = Self::apply_favorable_rounding(env, amount, shares);
+        let user_shares = Self::get_shares(env.clone(), user.clone());
+
+        assert!(user_shares >= shares, "Insufficient balance");
+        Self::require_unlocked_shares(env, &user, user_shares, shares);
+        let (shares, amount) = Self::sweep_dust(env, &user, user_shares, shares, amount);
+
+        let amount = Self::process_exit(env, &user, shares, amount, &user, None, true);
+
+        Self::publish_withdraw_event(env, user, amount, shares);
+    }
+
+    /// Burns `shares` worth `amount` USDC and pays the USDC to `to`, less
+    /// any withdrawal fee, returning the amount paid.
+    ///
+    /// Every exit ends here so the pause grace, withdraw cap, fee and
+    /// bookkeeping stay identical across them. The shares come from the
+    /// user's balance, or from withdrawal-queue escrow when `escrow_basis`
+    /// carries the cost basis the request set aside. `voluntary` is `false`
+    /// only for `force_withdraw`, which skips the grace, cap and fee.
+    /// Callers validate the shares and publish their own exit event.
+    fn process_exit(
+        env: &Env,
+        user: &Address,
+        shares: i128,
+        amount: i128,
+        to: &Address,
+        escrow_basis: Option<i128>,
+        voluntary: bool,
+    ) -> i128 {
+        if voluntary {
+            Self::require_not_paused_or_in_grace(env, user);
+            Self::require_within_withdraw_cap(env, amount);
+        }
+        let reserve_was_low = Self::reserve_shortfall(env).2 > 0;
+        let price_before = Self::get_share_price(env.clone());
+
+        let cost_basis = match escrow_basis {
+            Some(cost_basis) => {
+                Self::burn_escrowed_shares(env, user, shares);
+                cost_basis
+            }
+            None => Self::burn_shares(env, user, shares),
+        };
+        Self::reduce_protected_principal(env, user, amount);
+        let amount = if voluntary {
+            amount - Self::charge_withdrawal_fee(env, user, amount)
+        } else {
+            amount
+        };
+        Self::record_realized_pnl(env, user, amount - cost_basis);
+
+        let total: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::TotalDeposits)
+            .unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalDeposits, &(total - amount));
+        Self::exclude_exit_from_high_water_mark(env, price_before);
+
+        let usdc_token: Address = env.storage().instance().get(&DataKey::UsdcToken).unwrap();
+        let token_client = token::Client::new(env, &usdc_token);
+        Self::ensure_liquidity(env, &token_client, amount);
+        token_client.transfer(&env.current_contract_address(), to, &amount);
+
+        Self::record_user_action(env, user);
+        Self::sync_reserve(env);
+        if !reserve_was_low {
+            Self::publish_reserve_low(env);
+        }
+
+        amount
+    }
+
+    /// Moves `amount` of USDC into a registered strategy and records the
+    /// allocation, once the agent has authorized it.
+    fn deploy_to_strategy(env: &Env, strategy: Address, amount: i128) {
+        Self::require_positive_amount(amount);
+        assert!(
+            Self::get_strategies(env.clone()).contains(&strategy),
+            "Strategy not registered"
+        );
+
+        let usdc_token: Address = env.storage().instance().get(&DataKey::UsdcToken).unwrap();
+        let token_client = token::Client::new(env, &usdc_token);
+        let liquidity = token_client.balance(&env.current_contract_address())
+            + Self::get_reserve_deployed(env.clone());
+        assert!(amount <= liquidity, "Insufficient liquidity");
+        Self::ensure_liquidity(env, &token_client, amount);
+
+        let allocation = Self::get_strategy_allocation(env.clone(), strategy.clone());
+        env.storage().persistent().set(
+            &DataKey::StrategyAllocation(strategy.clone()),
+            &(allocation + amount),
+        );
+        let total_deployed = Self::get_total_deployed(env.clone());
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalDeployed, &(total_deployed + amount));
+
+        token_client.transfer(&env.current_contract_address(), &strategy, &amount);
+
+        env.events().publish(
+            (symbol_short!("rebal_fnd"),),
+            FundsRebalancedEvent { strategy, amount },
+        );
+    }
 
+    /// Sets total assets to `new_total` once the caller has authorized the
+    /// update, settling fees and recording the sample as
+    /// `update_total_assets` documents.
+    fn apply_total_assets(env: &Env, new_total: i128) {
         let old_total = env
             .storage()
             .instance()
             .get(&DataKey::TotalDeposits)
             .unwrap_or(0);
+        Self::require_assets_update_allowed(env, old_total, new_total);
+        env.storage()
+            .instance()
+            .set(&DataKey::LastAssetsUpdate, &env.ledger().timestamp());
+        if new_total > old_total {
+            let old_shares = Self::stored_total_shares(env);
+            Self::attribute_strategy_yield(env, new_total - old_total);
+            Self::exclude_recent_deposits(env, old_total, new_total);
+            Self::distribute_boosted_yield(env, old_total, new_total, old_shares);
+        }
+        if new_total < old_total && env.storage().instance().has(&DataKey::StableSince) {
+            env.storage()
+                .instance()
+                .set(&DataKey::StableSince, &env.ledger().timestamp());
+        }
 
         env.storage()
             .instance()
             .set(&DataKey::TotalDeposits, &new_total);
+        Self::accrue_management_fee(env);
+        Self::accrue_performance_fee(env);
 
         env.events().publish(
             (symbol_short!("assets_up"),),
@@ -895,233 +10823,307 @@ impl NeuroWealthVault {
                 new_total,
             },
         );
-    }
-
-    // ==========================================================================
-    // READ FUNCTIONS
-    // ==========================================================================
 
-    /// Returns the USDC balance of a specific user.
-    ///
-    /// This is the amount of USDC the user has deposited, excluding any
-    /// yield that may have been earned through the AI agent's strategies.
-    /// Yield is tracked separately by the AI agent off-chain.
-    ///
-    /// # Arguments
-    /// * `env` - The Soroban environment
-    /// * `user` - The user address to query
-    ///
-    /// # Returns
-    /// The user's USDC balance in raw units (7 decimal places)
-    ///
-    /// # Panics
-    /// None
-    ///
-    /// # Events
-    /// None
-    pub fn get_balance(env: Env, user: Address) -> i128 {
-        env.storage()
-            .persistent()
-            .get(&DataKey::Balance(user))
-            .unwrap_or(0)
+        Self::record_assets_sample(env);
+        Self::update_underwater_state(env);
+        Self::check_price_milestone(env);
     }
 
-    /// Returns the total USDC deposited in the vault.
-    ///
-    /// This is the sum of all user balances. It represents the on-chain
-    /// TVL. Funds deployed to external yield protocols are not included
-    /// in this figure (tracked separately by the AI agent).
+    /// Prices, receives and records a deposit worth `amount` USDC once the
+    /// caller has authorized it, returning the shares minted.
     ///
-    /// # Arguments
-    /// * `env` - The Soroban environment
-    ///
-    /// # Returns
-    /// Total USDC deposits in raw units (7 decimal places)
-    ///
-    /// # Panics
-    /// None
-    ///
-    /// # Events
-    /// None
-    pub fn get_total_deposits(env: Env) -> i128 {
-        env.storage()
+    /// Every deposit entry point ends here so the limit checks, bookkeeping
+    /// and reserve rebalance stay identical across them. `shares` fixes the
+    /// share count for `mint`; otherwise shares are priced from `amount`.
+    /// `funding` says how the backing tokens arrive. Callers publish their
+    /// own deposit event.
+    fn process_deposit(
+        env: &Env,
+        user: &Address,
+        amount: i128,
+        shares: Option<i128>,
+        funding: DepositFunding,
+    ) -> i128 {
+        Self::require_not_paused(env);
+        Self::require_not_winding_down(env);
+        Self::accrue_management_fee(env);
+        Self::require_positive_amount(amount);
+        Self::require_minimum_deposit(amount);
+        Self::require_within_deposit_cap(env, user, amount);
+        Self::require_within_tvl_cap(env, amount);
+        Self::consume_ledger_deposit_capacity(env, amount);
+        Self::require_depositor_slot(env, user);
+        Self::require_deposit_cooldown_elapsed(env, user);
+
+        let shares = shares.unwrap_or_else(|| Self::convert_to_shares(env.clone(), amount));
+        assert!(shares > 0, "Deposit too small to mint shares");
+
+        let usdc_token: Address = env.storage().instance().get(&DataKey::UsdcToken).unwrap();
+        match funding {
+            DepositFunding::Transfer => Self::receive_tokens(env, &usdc_token, user, amount),
+            DepositFunding::Allowance => {
+                Self::receive_tokens_via_allowance(env, &usdc_token, user, amount)
+            }
+            DepositFunding::Asset(asset, asset_amount) => {
+                Self::receive_tokens(env, &asset, user, asset_amount)
+            }
+            DepositFunding::Received => {}
+        }
+
+        Self::mint_shares(env, user, shares);
+        Self::add_cost_basis(env, user, amount);
+        Self::add_protected_principal(env, user, amount);
+
+        let total: i128 = env
+            .storage()
             .instance()
             .get(&DataKey::TotalDeposits)
-            .unwrap_or(0)
+            .unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalDeposits, &(total + amount));
+
+        Self::record_user_action(env, user);
+        Self::sync_reserve(env);
+
+        shares
     }
 
-    /// Returns the authorized AI agent address.
-    ///
-    /// This is the only address that can call rebalance() to move funds
-    /// between yield strategies.
-    ///
-    /// # Arguments
-    /// * `env` - The Soroban environment
+    /// Pulls `amount` of `token` from `from` into the vault and verifies receipt.
     ///
-    /// # Returns
-    /// The agent's Address
+    /// Checks the vault's balance delta rather than trusting the transfer,
+    /// so a token that delivers less than requested (or a refactor that sends
+    /// funds elsewhere) cannot mint unbacked shares.
     ///
     /// # Panics
-    /// None
-    ///
-    /// # Events
-    /// None
-    pub fn get_agent(env: Env) -> Address {
-        env.storage().instance().get(&DataKey::Agent).unwrap()
+    /// - If the transfer fails
+    /// - If the vault's balance did not rise by exactly `amount`
+    fn receive_tokens(env: &Env, token: &Address, from: &Address, amount: i128) {
+        let token_client = token::Client::new(env, token);
+        let vault = env.current_contract_address();
+
+        let balance_before = token_client.balance(&vault);
+        token_client.transfer(from, &vault, &amount);
+        let balance_after = token_client.balance(&vault);
+
+        assert!(
+            balance_after - balance_before == amount,
+            "Deposit transfer mismatch"
+        );
     }
 
-    /// Returns the contract owner address.
-    ///
-    /// The owner can pause/unpause the vault, set limits, and upgrade the contract.
-    ///
-    /// # Arguments
-    /// * `env` - The Soroban environment
-    ///
-    /// # Returns
-    /// The owner's Address
+    /// Pulls `amount` of `token` from `from` using the vault's allowance and
+    /// verifies receipt, as `receive_tokens` does.
     ///
     /// # Panics
-    /// None
-    ///
-    /// # Events
-    /// None
-    pub fn get_owner(env: Env) -> Address {
-        env.storage().instance().get(&DataKey::Owner).unwrap()
+    /// - If the allowance is below `amount` or the transfer fails
+    /// - If the vault's balance did not rise by exactly `amount`
+    fn receive_tokens_via_allowance(env: &Env, token: &Address, from: &Address, amount: i128) {
+        let token_client = token::Client::new(env, token);
+        let vault = env.current_contract_address();
+
+        let balance_before = token_client.balance(&vault);
+        token_client.transfer_from(&vault, from, &vault, &amount);
+        let balance_after = token_client.balance(&vault);
+
+        assert!(
+            balance_after - balance_before == amount,
+            "Deposit transfer mismatch"
+        );
     }
 
-    /// Returns whether the vault is currently paused.
-    ///
-    /// # Arguments
-    /// * `env` - The Soroban environment
-    ///
-    /// # Returns
-    /// True if paused, false otherwise
-    ///
-    /// # Panics
-    /// None
+    /// Makes at least `amount` USDC liquid in the vault, pulling any
+    /// shortfall from the reserve strategy.
     ///
-    /// # Events
-    /// None
-    pub fn is_paused(env: Env) -> bool {
-        env.storage()
-            .instance()
-            .get(&DataKey::Paused)
-            .unwrap_or(false)
+    /// Leaves the balance untouched when it already covers `amount`; if the
+    /// parked reserve cannot cover the shortfall, the caller's transfer
+    /// fails as it would without a strategy.
+    fn ensure_liquidity(env: &Env, token_client: &token::Client, amount: i128) {
+        let balance = token_client.balance(&env.current_contract_address());
+        let shortfall = (amount - balance).min(Self::get_reserve_deployed(env.clone()));
+        if shortfall > 0 {
+            Self::pull_reserve(env, token_client, shortfall);
+        }
     }
 
-    /// Returns the contract version.
-    ///
-    /// Used to track upgrades and ensure compatibility with external systems.
-    ///
-    /// # Arguments
-    /// * `env` - The Soroban environment
-    ///
-    /// # Returns
-    /// The current contract version (u32)
-    ///
-    /// # Panics
-    /// None
-    ///
-    /// # Events
-    /// None
-    pub fn get_version(env: Env) -> u32 {
-        env.storage().instance().get(&DataKey::Version).unwrap_or(1)
+    /// Parks or pulls reserve USDC so `ReserveBufferBps` of the on-chain
+    /// reserve stays liquid in the vault. Does nothing without a strategy.
+    fn sync_reserve(env: &Env) {
+        let Some(strategy) = Self::get_reserve_strategy(env.clone()) else {
+            return;
+        };
+        let usdc_token = Self::get_usdc_token(env.clone());
+        let token_client = token::Client::new(env, &usdc_token);
+        let vault = env.current_contract_address();
+
+        let balance = token_client.balance(&vault);
+        let deployed = Self::get_reserve_deployed(env.clone());
+        let target = (balance + deployed) * Self::get_reserve_buffer_bps(env.clone()) / 10_000;
+
+        if balance > target {
+            let amount = balance - target;
+            token_client.transfer(&vault, &strategy, &amount);
+            MoneyMarketClient::new(env, &strategy).supply(&vault, &amount);
+            Self::record_reserve_move(env, &strategy, amount, deployed + amount);
+        } else if balance < target {
+            Self::pull_reserve(env, &token_client, (target - balance).min(deployed));
+        }
     }
 
-    /// Returns the USDC token address.
-    ///
-    /// # Arguments
-    /// * `env` - The Soroban environment
-    ///
-    /// # Returns
-    /// The USDC token contract address
-    ///
-    /// # Panics
-    /// None
-    ///
-    /// # Events
-    /// None
-    pub fn get_usdc_token(env: Env) -> Address {
-        env.storage().instance().get(&DataKey::UsdcToken).unwrap()
+    /// Returns on-chain liquidity, the liquidity the reserve target calls
+    /// for, and the shortfall between them (0 when the target is met or
+    /// disabled).
+    fn reserve_shortfall(env: &Env) -> (i128, i128, i128) {
+        let target_bps = Self::get_reserve_target_bps(env.clone());
+        if target_bps == 0 {
+            return (0, 0, 0);
+        }
+        let usdc_token = Self::get_usdc_token(env.clone());
+        let balance = token::Client::new(env, &usdc_token).balance(&env.current_contract_address());
+        let liquidity = balance + Self::get_reserve_deployed(env.clone());
+        let target = Self::get_total_deposits(env.clone()) * target_bps / 10_000;
+        (liquidity, target, (target - liquidity).max(0))
     }
 
-    // ==========================================================================
-    // INTERNAL VALIDATION HELPERS
-    // ==========================================================================
+    /// Publishes `ReserveLowEvent` if on-chain liquidity is below the
+    /// reserve target.
+    fn publish_reserve_low(env: &Env) {
+        let (liquidity, target, shortfall) = Self::reserve_shortfall(env);
+        if shortfall > 0 {
+            env.events().publish(
+                (symbol_short!("rsv_low"),),
+                ReserveLowEvent {
+                    liquidity,
+                    target,
+                    shortfall,
+                },
+            );
+        }
+    }
 
-    /// Validates that the vault is not paused.
+    /// Redeems `amount` from the reserve strategy and verifies receipt.
     ///
     /// # Panics
-    /// - If the vault is paused
-    #[inline]
-    fn require_not_paused(env: &Env) {
-        let paused: bool = env
-            .storage()
-            .instance()
-            .get(&DataKey::Paused)
-            .unwrap_or(false);
-        assert!(!paused, "Vault is paused");
+    /// - If the vault's balance did not rise by exactly `amount`
+    fn pull_reserve(env: &Env, token_client: &token::Client, amount: i128) {
+        let strategy = Self::get_reserve_strategy(env.clone()).unwrap();
+        let vault = env.current_contract_address();
+
+        let balance_before = token_client.balance(&vault);
+        MoneyMarketClient::new(env, &strategy).redeem(&vault, &amount);
+        let balance_after = token_client.balance(&vault);
+        assert!(
+            balance_after - balance_before == amount,
+            "Reserve pull mismatch"
+        );
+
+        let deployed = Self::get_reserve_deployed(env.clone()) - amount;
+        Self::record_reserve_move(env, &strategy, -amount, deployed);
     }
 
-    /// Validates that the caller is the contract owner.
-    ///
-    /// # Panics
-    /// - If the caller is not the owner
-    #[inline]
-    fn require_is_owner(env: &Env) {
-        let owner: Address = env.storage().instance().get(&DataKey::Owner).unwrap();
-        owner.require_auth();
+    /// Stores the parked reserve after a move and publishes `ReserveMovedEvent`.
+    fn record_reserve_move(env: &Env, strategy: &Address, amount: i128, deployed: i128) {
+        env.storage()
+            .instance()
+            .set(&DataKey::ReserveDeployed, &deployed);
+        env.events().publish(
+            (symbol_short!("rsv_move"),),
+            ReserveMovedEvent {
+                strategy: strategy.clone(),
+                amount,
+                deployed,
+            },
+        );
     }
 
-    /// Validates that the caller is the AI agent.
-    ///
-    /// # Panics
-    /// - If the caller is not the agent
-    #[inline]
-    fn require_is_agent(env: &Env) {
-        let agent: Address = env.storage().instance().get(&DataKey::Agent).unwrap();
-        agent.require_auth();
+    /// Stores or clears an advisor authorization and emits `AdvisorUpdatedEvent`.
+    fn set_advisor(env: &Env, client: Address, advisor: Address, authorized: bool) {
+        let key = DataKey::Advisor(client.clone(), advisor.clone());
+        if authorized {
+            env.storage().persistent().set(&key, &true);
+        } else {
+            env.storage().persistent().remove(&key);
+        }
+
+        env.events().publish(
+            (symbol_short!("advisor"),),
+            AdvisorUpdatedEvent {
+                client,
+                advisor,
+                authorized,
+            },
+        );
     }
 
-    /// Validates that an amount is positive.
+    /// Publishes `DepositEvent`, preceded by its legacy shape when dual emission is on.
     ///
-    /// # Panics
-    /// - If amount is <= 0
-    #[inline]
-    fn require_positive_amount(amount: i128) {
-        assert!(amount > 0, "Amount must be positive");
+    /// A deposit with a referrer publishes `ReferredDepositEvent` in place of
+    /// the slim `DepositEvent`.
+    fn publish_deposit_event(
+        env: &Env,
+        user: Address,
+        amount: i128,
+        shares: i128,
+        referrer: Option<Address>,
+    ) {
+        if Self::get_dual_emit(env.clone()) {
+            env.events().publish(
+                (symbol_short!("deposit"), symbol_short!("legacy")),
+                LegacyDepositEvent {
+                    user: user.clone(),
+                    amount,
+                },
+            );
+        }
+        match referrer {
+            Some(referrer) => env.events().publish(
+                (symbol_short!("deposit"), symbol_short!("referral")),
+                ReferredDepositEvent {
+                    user,
+                    amount,
+                    shares,
+                    referrer,
+                },
+            ),
+            None => env.events().publish(
+                (symbol_short!("deposit"),),
+                DepositEvent {
+                    user,
+                    amount,
+                    shares,
+                },
+            ),
+        }
     }
 
-    /// Validates that a deposit meets the minimum requirement.
-    ///
-    /// Minimum deposit is 1 USDC (1_000_000 in 7-decimal units).
-    ///
-    /// # Panics
-    /// - If amount < minimum deposit
-    #[inline]
-    fn require_minimum_deposit(amount: i128) {
-        assert!(amount >= 1_000_000, "Minimum deposit is 1 USDC");
+    /// Publishes `WithdrawEvent`, preceded by its legacy shape when dual emission is on.
+    fn publish_withdraw_event(env: &Env, user: Address, amount: i128, shares: i128) {
+        if Self::get_dual_emit(env.clone()) {
+            env.events().publish(
+                (symbol_short!("withdraw"), symbol_short!("legacy")),
+                LegacyWithdrawEvent {
+                    user: user.clone(),
+                    amount,
+                },
+            );
+        }
+        env.events().publish(
+            (symbol_short!("withdraw"),),
+            WithdrawEvent {
+                user,
+                amount,
+                shares,
+            },
+        );
     }
 
-    /// Validates that a deposit is within the user's cap.
-    ///
-    /// # Panics
-    /// - If user's new balance would exceed the deposit cap
+    /// Records the current ledger timestamp as the user's last action.
     #[inline]
-    fn require_within_deposit_cap(env: &Env, user: &Address, amount: i128) {
-        let cap: i128 = env
-            .storage()
-            .instance()
-            .get(&DataKey::UserDepositCap)
-            .unwrap_or(0);
-        if cap > 0 {
-            let current_balance: i128 = env
-                .storage()
-                .persistent()
-                .get(&DataKey::Balance(user.clone()))
-                .unwrap_or(0);
-            assert!(current_balance + amount <= cap, "Exceeds user deposit cap");
-        }
+    fn record_user_action(env: &Env, user: &Address) {
+        env.storage().persistent().set(
+            &DataKey::LastAction(user.clone()),
+            &env.ledger().timestamp(),
+        );
     }
 
     /// Validates that a deposit is within the TVL cap.
@@ -1140,6 +11142,24 @@ impl NeuroWealthVault {
             assert!(total + amount <= cap, "Exceeds TVL cap");
         }
     }
+
+    /// Validates that a deposit fits under the per-ledger cap and counts it
+    /// toward the current ledger's total.
+    ///
+    /// # Panics
+    /// - If deposits in this ledger would exceed `MaxDepositPerLedger`
+    #[inline]
+    fn consume_ledger_deposit_capacity(env: &Env, amount: i128) {
+        let cap = Self::get_max_deposit_per_ledger(env.clone());
+        if cap > 0 {
+            let deposited = cap - Self::get_ledger_deposit_remaining(env.clone());
+            assert!(deposited + amount <= cap, "Exceeds ledger deposit cap");
+            env.storage().instance().set(
+                &DataKey::LedgerDeposits,
+                &(env.ledger().sequence(), deposited + amount),
+            );
+        }
+    }
 }
 
 #[cfg(test)]
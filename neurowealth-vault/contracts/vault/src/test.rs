@@ -1,7 +1,10 @@
 use super::*;
+use soroban_env_host::storage::AccessType;
 use soroban_sdk::{
-    testutils::{Address as _, Events},
-    Address, Env,
+    contract, contractimpl,
+    testutils::{Address as _, Events, Ledger, MockAuth, MockAuthInvoke},
+    token::StellarAssetClient,
+    xdr, Address, Env, IntoVal,
 };
 
 fn setup_vault(env: &Env) -> (Address, Address, Address) {
@@ -17,6 +20,36 @@ fn setup_vault(env: &Env) -> (Address, Address, Address) {
     (contract_id, agent, owner)
 }
 
+/// Sets up a vault backed by a real Stellar asset contract acting as USDC.
+///
+/// Returns `(contract_id, usdc_token, agent)`; the agent is also the owner.
+fn setup_vault_with_usdc(env: &Env) -> (Address, Address, Address) {
+    let contract_id = env.register_contract(None, NeuroWealthVault);
+    let client = NeuroWealthVaultClient::new(env, &contract_id);
+
+    let agent = Address::generate(env);
+    let token_admin = Address::generate(env);
+    let usdc_token = env
+        .register_stellar_asset_contract_v2(token_admin)
+        .address();
+
+    client.initialize(&agent, &usdc_token);
+
+    (contract_id, usdc_token, agent)
+}
+
+fn write_legacy_balance(env: &Env, contract_id: &Address, user: &Address, amount: i128) {
+    env.as_contract(contract_id, || {
+        env.storage()
+            .persistent()
+            .set(&DataKey::Balance(user.clone()), &amount);
+    });
+}
+
+fn mint_token(env: &Env, token: &Address, to: &Address, amount: i128) {
+    StellarAssetClient::new(env, token).mint(to, &amount);
+}
+
 #[test]
 fn test_vault_initialized_event() {
     let env = Env::default();
@@ -484,3 +517,7328 @@ fn test_only_owner_can_unpause() {
     client.unpause(&agent);
     assert!(!client.is_paused());
 }
+
+// ============================================================================
+// SHARE ACCOUNTING TESTS
+// ============================================================================
+
+#[test]
+fn test_first_deposit_mints_shares_one_to_one() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 5_000_000);
+
+    client.deposit(&user, &5_000_000);
+
+    assert_eq!(client.get_shares(&user), 5_000_000);
+    assert_eq!(client.get_total_shares(), 5_000_000);
+    assert_eq!(client.get_balance(&user), 5_000_000);
+    assert_eq!(client.get_share_price(), SHARE_PRICE_SCALE);
+}
+
+#[test]
+fn test_yield_raises_balance_and_withdraw_burns_shares_rounded_up() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 3_000_000);
+    client.deposit(&user, &3_000_000);
+
+    // 1 USDC of yield lands in the vault and is reported
+    mint_token(&env, &usdc_token, &contract_id, 1_000_000);
+    client.update_total_assets(&4_000_000);
+    assert_eq!(client.get_balance(&user), 4_000_000);
+
+    // 1 USDC at a price just under 4/3 costs 750_623.4 shares, rounded up
+    client.withdraw(&user, &1_000_000);
+    assert_eq!(client.get_shares(&user), 2_249_376);
+
+    // 1 unit costs 0.75 shares, rounded up to a full share
+    client.withdraw(&user, &1);
+    assert_eq!(client.get_shares(&user), 2_249_375);
+    assert_eq!(client.get_total_deposits(), 2_999_999);
+}
+
+#[test]
+fn test_second_depositor_priced_against_current_share_price() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user1 = Address::generate(&env);
+    let user2 = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user1, 2_000_000);
+    mint_token(&env, &usdc_token, &user2, 2_000_000);
+
+    client.deposit(&user1, &2_000_000);
+    client.update_total_assets(&4_000_000);
+    client.deposit(&user2, &2_000_000);
+
+    // The virtual offset keeps a sliver of user1's yield
+    assert_eq!(client.get_shares(&user2), 1_002_493);
+    assert_eq!(client.get_balance(&user1), 3_990_050);
+    assert_eq!(client.get_balance(&user2), 1_999_998);
+}
+
+#[test]
+#[should_panic(expected = "Insufficient balance")]
+fn test_withdraw_more_than_share_value_fails() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 2_000_000);
+    client.deposit(&user, &2_000_000);
+
+    client.withdraw(&user, &2_000_001);
+}
+
+// ============================================================================
+// SHARE PRICE BOUNDS TESTS
+// ============================================================================
+
+#[test]
+fn test_share_price_bounds_allow_in_band_conversions() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 2_000_000);
+    client.deposit(&user, &1_000_000);
+
+    client.set_share_price_bounds(&(SHARE_PRICE_SCALE / 2), &(SHARE_PRICE_SCALE * 2));
+    assert_eq!(
+        client.get_share_price_bounds(),
+        (SHARE_PRICE_SCALE / 2, SHARE_PRICE_SCALE * 2)
+    );
+
+    client.deposit(&user, &1_000_000);
+    assert_eq!(client.convert_to_assets(&2_000_000), 2_000_000);
+}
+
+#[test]
+#[should_panic(expected = "Share price out of bounds")]
+fn test_share_price_above_max_bound_trips_guard() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 1_000_000);
+    client.deposit(&user, &1_000_000);
+    client.set_share_price_bounds(&0, &(SHARE_PRICE_SCALE * 2));
+
+    // Corrupt total assets directly to imply a 10x share price
+    env.as_contract(&contract_id, || {
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalDeposits, &10_000_000_i128);
+    });
+
+    client.convert_to_assets(&1_000_000);
+}
+
+#[test]
+#[should_panic(expected = "Share price out of bounds")]
+fn test_share_price_below_min_bound_trips_guard_on_deposit() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 2_000_000);
+    client.deposit(&user, &1_000_000);
+    client.set_share_price_bounds(&(SHARE_PRICE_SCALE / 2), &0);
+
+    // Inflate total shares directly to imply a 0.1x share price
+    env.as_contract(&contract_id, || {
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalShares, &10_000_000_i128);
+    });
+
+    client.deposit(&user, &1_000_000);
+}
+
+#[test]
+#[should_panic(expected = "Invalid share price bounds")]
+fn test_share_price_bounds_reject_inverted_band() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    client.set_share_price_bounds(&(SHARE_PRICE_SCALE * 2), &SHARE_PRICE_SCALE);
+}
+
+// ============================================================================
+// AIRDROP TESTS
+// ============================================================================
+
+#[test]
+fn test_airdrop_claims_are_proportional_to_snapshot_shares() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let reward_token = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    let reward_client = token::Client::new(&env, &reward_token);
+
+    let user1 = Address::generate(&env);
+    let user2 = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user1, 3_000_000);
+    mint_token(&env, &usdc_token, &user2, 1_000_000);
+    client.deposit(&user1, &3_000_000);
+    client.deposit(&user2, &1_000_000);
+
+    // Partner sends 1000 reward units to the vault
+    mint_token(&env, &reward_token, &contract_id, 1_000);
+    let snapshot_id = client.distribute_airdrop(&reward_token, &1_000);
+    assert_eq!(snapshot_id, 1);
+
+    // Share changes after the snapshot do not affect the airdrop
+    client.withdraw(&user1, &3_000_000);
+
+    assert_eq!(client.get_claimable_airdrop(&user1, &reward_token), 750);
+    assert_eq!(client.claim_airdrop(&user1, &reward_token), 750);
+    assert_eq!(client.claim_airdrop(&user2, &reward_token), 250);
+    assert_eq!(reward_client.balance(&user1), 750);
+    assert_eq!(reward_client.balance(&user2), 250);
+    assert_eq!(client.get_claimable_airdrop(&user1, &reward_token), 0);
+}
+
+#[test]
+#[should_panic(expected = "No airdrop to claim")]
+fn test_airdrop_cannot_be_claimed_twice() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let reward_token = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 1_000_000);
+    client.deposit(&user, &1_000_000);
+
+    mint_token(&env, &reward_token, &contract_id, 500);
+    client.distribute_airdrop(&reward_token, &500);
+
+    client.claim_airdrop(&user, &reward_token);
+    client.claim_airdrop(&user, &reward_token);
+}
+
+#[test]
+fn test_later_depositor_excluded_from_earlier_airdrop() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let reward_token = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+
+    let user1 = Address::generate(&env);
+    let user2 = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user1, 1_000_000);
+    mint_token(&env, &usdc_token, &user2, 1_000_000);
+    client.deposit(&user1, &1_000_000);
+
+    mint_token(&env, &reward_token, &contract_id, 1_000);
+    client.distribute_airdrop(&reward_token, &600);
+    client.deposit(&user2, &1_000_000);
+    client.distribute_airdrop(&reward_token, &400);
+
+    assert_eq!(client.claim_airdrop(&user1, &reward_token), 800);
+    assert_eq!(client.claim_airdrop(&user2, &reward_token), 200);
+}
+
+#[test]
+#[should_panic(expected = "Insufficient airdrop balance")]
+fn test_airdrop_requires_reward_tokens_in_vault() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let reward_token = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 1_000_000);
+    client.deposit(&user, &1_000_000);
+
+    mint_token(&env, &reward_token, &contract_id, 500);
+    client.distribute_airdrop(&reward_token, &500);
+    client.distribute_airdrop(&reward_token, &1);
+}
+
+// ============================================================================
+// MINT TESTS
+// ============================================================================
+
+#[test]
+fn test_preview_mint_matches_assets_pulled_by_mint() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let usdc = token::Client::new(&env, &usdc_token);
+
+    let user1 = Address::generate(&env);
+    let user2 = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user1, 3_000_000);
+    mint_token(&env, &usdc_token, &user2, 10_000_000);
+    client.deposit(&user1, &3_000_000);
+    client.update_total_assets(&4_000_000);
+
+    let shares = 1_500_001_i128;
+    let preview = client.preview_mint(&shares);
+    let before = usdc.balance(&user2);
+    let pulled = client.mint(&user2, &shares);
+
+    assert_eq!(pulled, preview);
+    assert_eq!(before - usdc.balance(&user2), preview);
+    assert_eq!(client.get_shares(&user2), shares);
+}
+
+#[test]
+fn test_preview_mint_rounds_up() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 3_000_000);
+    client.deposit(&user, &3_000_000);
+
+    // Empty-vault style 1:1 pricing before yield
+    assert_eq!(client.preview_mint(&7), 7);
+
+    // At a price of 4/3, one share is worth 1.33 units: converting down
+    // yields 1 but minting it must cost 2
+    client.update_total_assets(&4_000_000);
+    assert_eq!(client.convert_to_assets(&1), 1);
+    assert_eq!(client.preview_mint(&1), 2);
+    assert_eq!(client.preview_mint(&3), 4);
+}
+
+// ============================================================================
+// SNAPSHOT TESTS
+// ============================================================================
+
+#[test]
+fn test_assets_at_values_shares_at_historical_snapshots() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 4_000_000);
+    client.deposit(&user, &2_000_000);
+    let snap1 = client.take_snapshot();
+
+    client.update_total_assets(&3_000_000);
+    let snap2 = client.take_snapshot();
+
+    client.deposit(&user, &1_500_000);
+    client.update_total_assets(&9_000_000);
+    let snap3 = client.take_snapshot();
+
+    assert_eq!(client.assets_at(&user, &snap1), 2_000_000);
+    assert_eq!(client.assets_at(&user, &snap2), 3_000_000);
+    assert_eq!(client.assets_at(&user, &snap3), 9_000_000);
+    assert_eq!(client.get_snapshot(&snap2).total_shares, 2_000_000);
+    assert_eq!(client.get_snapshot(&snap3).total_shares, 3_001_661);
+}
+
+#[test]
+fn test_assets_at_ignores_changes_after_snapshot() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user1 = Address::generate(&env);
+    let user2 = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user1, 1_000_000);
+    mint_token(&env, &usdc_token, &user2, 1_000_000);
+    client.deposit(&user1, &1_000_000);
+    let snapshot_id = client.take_snapshot();
+
+    client.deposit(&user2, &1_000_000);
+    client.withdraw(&user1, &1_000_000);
+
+    assert_eq!(client.assets_at(&user1, &snapshot_id), 1_000_000);
+    assert_eq!(client.assets_at(&user2, &snapshot_id), 0);
+}
+
+#[test]
+#[should_panic(expected = "Snapshot not found")]
+fn test_assets_at_unknown_snapshot_fails() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    client.assets_at(&Address::generate(&env), &1);
+}
+
+#[test]
+fn test_total_supply_at_tracks_each_snapshot() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    mint_token(&env, &usdc_token, &alice, 4_000_000);
+    mint_token(&env, &usdc_token, &bob, 2_000_000);
+
+    let empty = client.take_snapshot();
+    client.deposit(&alice, &4_000_000);
+    let after_alice = client.take_snapshot();
+    client.deposit(&bob, &2_000_000);
+    let after_bob = client.take_snapshot();
+    client.withdraw(&alice, &3_000_000);
+    let after_withdraw = client.take_snapshot();
+
+    assert_eq!(client.total_supply_at(&empty), 0);
+    assert_eq!(client.total_supply_at(&after_alice), 4_000_000);
+    assert_eq!(client.total_supply_at(&after_bob), 6_000_000);
+    assert_eq!(client.total_supply_at(&after_withdraw), 3_000_000);
+    assert_eq!(client.get_total_shares(), 3_000_000);
+}
+
+#[test]
+#[should_panic(expected = "Snapshot not found")]
+fn test_total_supply_at_unknown_snapshot_fails() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    client.total_supply_at(&1);
+}
+
+// ============================================================================
+// DEPOSITOR LIMIT TESTS
+// ============================================================================
+
+#[test]
+fn test_depositor_cap_admits_up_to_cap_and_allows_top_ups() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    client.set_max_depositors(&2);
+    assert_eq!(client.get_max_depositors(), 2);
+
+    let user1 = Address::generate(&env);
+    let user2 = Address::generate(&env);
+    let user3 = Address::generate(&env);
+    for user in [&user1, &user2, &user3] {
+        mint_token(&env, &usdc_token, user, 2_000_000);
+    }
+
+    client.deposit(&user1, &1_000_000);
+    client.deposit(&user2, &1_000_000);
+    assert_eq!(client.get_depositor_count(), 2);
+
+    // Existing depositors can still top up at the cap
+    client.deposit(&user1, &1_000_000);
+    assert_eq!(client.get_depositor_count(), 2);
+
+    // A full exit frees a slot for a new address
+    client.withdraw(&user2, &1_000_000);
+    assert_eq!(client.get_depositor_count(), 1);
+    client.deposit(&user3, &1_000_000);
+    assert_eq!(client.get_depositor_count(), 2);
+}
+
+#[test]
+#[should_panic(expected = "Depositor limit reached")]
+fn test_depositor_cap_rejects_new_address() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    client.set_max_depositors(&1);
+
+    let user1 = Address::generate(&env);
+    let user2 = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user1, 1_000_000);
+    mint_token(&env, &usdc_token, &user2, 1_000_000);
+
+    client.deposit(&user1, &1_000_000);
+    client.deposit(&user2, &1_000_000);
+}
+
+// ============================================================================
+// WIND-DOWN TESTS
+// ============================================================================
+
+#[test]
+fn test_force_withdraw_during_wind_down_zeroes_user_shares() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let usdc = token::Client::new(&env, &usdc_token);
+
+    let user = Address::generate(&env);
+    let custodian = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 3_000_000);
+    client.deposit(&user, &3_000_000);
+
+    client.start_wind_down();
+    assert!(client.is_winding_down());
+
+    let amount = client.force_withdraw(&user, &custodian);
+
+    assert_eq!(amount, 3_000_000);
+    assert_eq!(client.get_shares(&user), 0);
+    assert_eq!(client.get_total_shares(), 0);
+    assert_eq!(client.get_total_deposits(), 0);
+    assert_eq!(usdc.balance(&custodian), 3_000_000);
+    assert_eq!(client.get_depositor_count(), 0);
+}
+
+#[test]
+fn test_force_withdraw_cancels_queued_requests() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let usdc = token::Client::new(&env, &usdc_token);
+    client.set_withdrawal_cooldown(&3_600);
+
+    let user = Address::generate(&env);
+    let other = Address::generate(&env);
+    let custodian = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 3_000_000);
+    mint_token(&env, &usdc_token, &other, 2_000_000);
+    client.deposit(&user, &3_000_000);
+    client.deposit(&other, &2_000_000);
+    client.request_withdrawal(&user, &1_000_000);
+    let kept = client.request_withdrawal(&other, &1_000_000);
+    client.request_withdrawal(&user, &1_000_000);
+
+    client.start_wind_down();
+    env.ledger().with_mut(|li| li.timestamp += 100);
+    assert_eq!(client.force_withdraw(&user, &custodian), 3_000_000);
+
+    assert_eq!(usdc.balance(&custodian), 3_000_000);
+    assert_eq!(client.get_total_shares(), 2_000_000);
+    assert_eq!(client.get_total_deposits(), 2_000_000);
+    assert_eq!(client.get_cost_basis(&user), 0);
+    assert_eq!(
+        client.get_last_action(&user),
+        Some(env.ledger().timestamp())
+    );
+    assert_eq!(client.get_withdrawable_breakdown(&user).queued_assets, 0);
+
+    // Only the other user's request is left in the queue
+    assert_eq!(
+        client.get_total_pending_withdrawals(),
+        (1_000_000, 1_000_000)
+    );
+    env.ledger().with_mut(|li| li.timestamp += 3_600);
+    assert_eq!(
+        client.process_withdrawals(&10),
+        Vec::from_array(&env, [kept])
+    );
+}
+
+#[test]
+#[should_panic(expected = "Vault is not winding down")]
+fn test_force_withdraw_outside_wind_down_fails() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 1_000_000);
+    client.deposit(&user, &1_000_000);
+
+    client.force_withdraw(&user, &Address::generate(&env));
+}
+
+#[test]
+#[should_panic(expected = "Vault is winding down")]
+fn test_deposit_rejected_during_wind_down() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 2_000_000);
+    client.deposit(&user, &1_000_000);
+
+    client.start_wind_down();
+    // Withdrawals still work during wind-down
+    client.withdraw(&user, &500_000);
+    client.deposit(&user, &1_000_000);
+}
+
+// ============================================================================
+// PRICE ORACLE TESTS
+// ============================================================================
+
+#[contract]
+pub struct MockPriceOracle;
+
+#[contractimpl]
+impl MockPriceOracle {
+    pub fn set_price(env: Env, asset: Address, price: i128, timestamp: u64) {
+        env.storage()
+            .instance()
+            .set(&asset, &PriceData { price, timestamp });
+    }
+
+    pub fn price(env: Env, asset: Address) -> PriceData {
+        env.storage().instance().get(&asset).unwrap()
+    }
+}
+
+fn setup_mock_oracle(env: &Env, asset: &Address, price: i128) -> Address {
+    let oracle = env.register_contract(None, MockPriceOracle);
+    MockPriceOracleClient::new(env, &oracle).set_price(asset, &price, &env.ledger().timestamp());
+    oracle
+}
+
+#[test]
+fn test_deposit_asset_mints_shares_on_oracle_value() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let eurc = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+
+    // 1 EURC = 1.08 USDC
+    let oracle = setup_mock_oracle(&env, &eurc, 10_800_000);
+    client.set_price_oracle(&oracle);
+    assert_eq!(client.get_price_oracle(), Some(oracle));
+
+    let user1 = Address::generate(&env);
+    let user2 = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user1, 2_000_000);
+    mint_token(&env, &eurc, &user2, 5_000_000);
+    client.deposit(&user1, &2_000_000);
+
+    let shares = client.deposit_asset(&user2, &eurc, &5_000_000);
+
+    assert_eq!(shares, 5_400_000);
+    assert_eq!(client.get_shares(&user2), 5_400_000);
+    assert_eq!(client.get_total_deposits(), 7_400_000);
+    assert_eq!(
+        token::Client::new(&env, &eurc).balance(&contract_id),
+        5_000_000
+    );
+}
+
+#[test]
+#[should_panic(expected = "Stale oracle price")]
+fn test_deposit_asset_rejects_stale_price() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let eurc = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+
+    let oracle = setup_mock_oracle(&env, &eurc, 10_800_000);
+    client.set_price_oracle(&oracle);
+    client.set_max_oracle_age(&60);
+
+    env.ledger().with_mut(|li| li.timestamp += 61);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &eurc, &user, 5_000_000);
+    client.deposit_asset(&user, &eurc, &5_000_000);
+}
+
+#[test]
+#[should_panic(expected = "Price oracle not set")]
+fn test_deposit_asset_requires_oracle() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let eurc = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+
+    let user = Address::generate(&env);
+    mint_token(&env, &eurc, &user, 5_000_000);
+    client.deposit_asset(&user, &eurc, &5_000_000);
+}
+
+// ============================================================================
+// WITHDRAWAL QUEUE TESTS
+// ============================================================================
+
+#[test]
+fn test_pending_withdrawal_aggregates() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    client.set_withdrawal_cooldown(&3_600);
+
+    let user1 = Address::generate(&env);
+    let user2 = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user1, 4_000_000);
+    mint_token(&env, &usdc_token, &user2, 4_000_000);
+    client.deposit(&user1, &4_000_000);
+    client.deposit(&user2, &4_000_000);
+
+    assert_eq!(client.get_total_pending_withdrawals(), (0, 0));
+    assert_eq!(client.get_next_claimable_timestamp(), 0);
+
+    let start = env.ledger().timestamp();
+    client.request_withdrawal(&user1, &1_000_000);
+    env.ledger().with_mut(|li| li.timestamp += 600);
+    client.request_withdrawal(&user2, &2_000_000);
+    client.request_withdrawal(&user1, &1_000_000);
+
+    assert_eq!(client.get_shares(&user1), 2_000_000);
+    assert_eq!(
+        client.get_total_pending_withdrawals(),
+        (4_000_000, 4_000_000)
+    );
+    assert_eq!(client.get_next_claimable_timestamp(), start + 3_600);
+
+    // Pending shares keep earning yield until claimed, less the sliver the
+    // virtual offset keeps
+    mint_token(&env, &usdc_token, &contract_id, 8_000_000);
+    client.update_total_assets(&16_000_000);
+    assert_eq!(
+        client.get_total_pending_withdrawals(),
+        (4_000_000, 7_995_006)
+    );
+}
+
+#[test]
+fn test_claim_withdrawal_after_cooldown() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let usdc = token::Client::new(&env, &usdc_token);
+    client.set_withdrawal_cooldown(&3_600);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 2_000_000);
+    client.deposit(&user, &2_000_000);
+
+    let first = client.request_withdrawal(&user, &1_000_000);
+    env.ledger().with_mut(|li| li.timestamp += 100);
+    let second = client.request_withdrawal(&user, &1_000_000);
+
+    env.ledger().with_mut(|li| li.timestamp += 3_500);
+    assert_eq!(client.claim_withdrawal(&user, &first), 1_000_000);
+
+    assert_eq!(usdc.balance(&user), 1_000_000);
+    assert_eq!(client.get_total_shares(), 1_000_000);
+    assert_eq!(
+        client.get_total_pending_withdrawals(),
+        (1_000_000, 1_000_000)
+    );
+    assert_eq!(
+        client.get_next_claimable_timestamp(),
+        client.get_withdrawal_request(&second).unlock_time
+    );
+}
+
+#[test]
+#[should_panic(expected = "Withdrawal still in cooldown")]
+fn test_claim_withdrawal_before_cooldown_fails() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    client.set_withdrawal_cooldown(&3_600);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 1_000_000);
+    client.deposit(&user, &1_000_000);
+
+    let request_id = client.request_withdrawal(&user, &1_000_000);
+    env.ledger().with_mut(|li| li.timestamp += 3_599);
+    client.claim_withdrawal(&user, &request_id);
+}
+
+// ============================================================================
+// DEPOSIT COOLDOWN TESTS
+// ============================================================================
+
+#[test]
+fn test_deposit_succeeds_after_cooldown() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    client.set_deposit_cooldown(&300);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 2_000_000);
+    client.deposit(&user, &1_000_000);
+    assert_eq!(
+        client.get_last_action(&user),
+        Some(env.ledger().timestamp())
+    );
+
+    env.ledger().with_mut(|li| li.timestamp += 300);
+    client.deposit(&user, &1_000_000);
+    assert_eq!(client.get_shares(&user), 2_000_000);
+}
+
+#[test]
+#[should_panic(expected = "Deposit cooldown.")]
+fn test_rapid_second_deposit_reverts() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    client.set_deposit_cooldown(&300);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 2_000_000);
+    client.deposit(&user, &1_000_000);
+
+    env.ledger().with_mut(|li| li.timestamp += 299);
+    client.deposit(&user, &1_000_000);
+}
+
+#[test]
+fn test_zero_deposit_cooldown_allows_back_to_back_deposits() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    assert_eq!(client.get_deposit_cooldown(), 0);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 2_000_000);
+    client.deposit(&user, &1_000_000);
+    client.deposit(&user, &1_000_000);
+    assert_eq!(client.get_shares(&user), 2_000_000);
+}
+
+// ============================================================================
+// LEGACY BALANCE TESTS
+// ============================================================================
+
+#[test]
+fn test_get_legacy_balance_reads_deprecated_key() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    assert_eq!(client.get_legacy_balance(&user), 0);
+
+    write_legacy_balance(&env, &contract_id, &user, 4_200_000);
+    assert_eq!(client.get_legacy_balance(&user), 4_200_000);
+    // Legacy balances are not share balances
+    assert_eq!(client.get_shares(&user), 0);
+}
+
+// ============================================================================
+// BATCH WITHDRAWAL PROCESSING TESTS
+// ============================================================================
+
+#[test]
+fn test_process_withdrawals_breaks_unlock_time_ties_by_request_id() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    client.set_withdrawal_cooldown(&3_600);
+
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    mint_token(&env, &usdc_token, &alice, 2_000_000);
+    mint_token(&env, &usdc_token, &bob, 1_000_000);
+    client.deposit(&alice, &2_000_000);
+    client.deposit(&bob, &1_000_000);
+
+    // Three requests in the same ledger share an unlock time
+    let first = client.request_withdrawal(&bob, &1_000_000);
+    let second = client.request_withdrawal(&alice, &1_000_000);
+    let third = client.request_withdrawal(&alice, &1_000_000);
+
+    env.ledger().with_mut(|li| li.timestamp += 3_600);
+    let processed = client.process_withdrawals(&2);
+    assert_eq!(processed, Vec::from_array(&env, [first, second]));
+
+    let processed = client.process_withdrawals(&10);
+    assert_eq!(processed, Vec::from_array(&env, [third]));
+    assert_eq!(client.get_total_pending_withdrawals(), (0, 0));
+}
+
+#[test]
+fn test_process_withdrawals_orders_by_unlock_time_then_id() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    client.set_withdrawal_cooldown(&3_600);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 3_000_000);
+    client.deposit(&user, &3_000_000);
+
+    let slow = client.request_withdrawal(&user, &1_000_000);
+    client.set_withdrawal_cooldown(&0);
+    let fast_a = client.request_withdrawal(&user, &1_000_000);
+    let fast_b = client.request_withdrawal(&user, &1_000_000);
+    assert_eq!(
+        client.get_next_claimable_timestamp(),
+        env.ledger().timestamp()
+    );
+
+    // Only the zero-cooldown requests have matured
+    let processed = client.process_withdrawals(&10);
+    assert_eq!(processed, Vec::from_array(&env, [fast_a, fast_b]));
+
+    env.ledger().with_mut(|li| li.timestamp += 3_600);
+    let processed = client.process_withdrawals(&10);
+    assert_eq!(processed, Vec::from_array(&env, [slow]));
+}
+
+// ============================================================================
+// CONVERSION PRECISION TESTS
+// ============================================================================
+
+#[test]
+fn test_conversions_are_exact_floor_at_fractional_price() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let seed = Address::generate(&env);
+    mint_token(&env, &usdc_token, &seed, 3_000_000);
+    client.deposit(&seed, &3_000_000);
+    client.update_total_assets(&7_000_001);
+
+    // Conversions price against the totals plus the virtual offset, one
+    // virtual share per virtual unit at the default ratio
+    let total_shares = client.get_total_shares() + VIRTUAL_ASSETS;
+    let total_assets = client.get_total_deposits() + VIRTUAL_ASSETS;
+    for amount in [1_i128, 7, 999_999, 1_234_567, 10_000_001] {
+        // Same result as a 1e9-scaled intermediate would produce
+        let scaled = amount * 1_000_000_000 * total_shares / total_assets / 1_000_000_000;
+        assert_eq!(client.convert_to_shares(&amount), scaled);
+        assert_eq!(
+            client.convert_to_shares(&amount),
+            amount * total_shares / total_assets
+        );
+    }
+}
+
+#[test]
+fn test_dust_over_long_sequence_is_bounded_and_vault_favoring() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let usdc = token::Client::new(&env, &usdc_token);
+
+    // Seed the vault and push the share price to an awkward ratio
+    let seed = Address::generate(&env);
+    mint_token(&env, &usdc_token, &seed, 3_000_000);
+    client.deposit(&seed, &3_000_000);
+    mint_token(&env, &usdc_token, &contract_id, 1_000_001);
+    client.update_total_assets(&4_000_001);
+
+    let user = Address::generate(&env);
+    let start = 100_000_000;
+    mint_token(&env, &usdc_token, &user, start);
+
+    let rounds = 40;
+    for i in 0..rounds {
+        client.deposit(&user, &(1_000_003 + i * 7_919));
+        let withdrawable = client.get_balance(&user);
+        client.withdraw(&user, &withdrawable);
+    }
+
+    // Each deposit and withdrawal loses at most one unit of rounding dust,
+    // and that dust always stays with the vault
+    let lost = start - usdc.balance(&user);
+    assert!(lost >= 0);
+    assert!(lost <= 2 * rounds);
+    assert!(usdc.balance(&contract_id) >= client.get_total_deposits());
+}
+
+/// Runs 40 deposit and full-withdrawal rounds at an awkward share price in a
+/// vault with `decimals` share decimals, returning the units the user lost.
+fn round_trip_dust(decimals: u32) -> i128 {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let usdc = token::Client::new(&env, &usdc_token);
+    client.set_share_decimals(&decimals);
+
+    let seed = Address::generate(&env);
+    mint_token(&env, &usdc_token, &seed, 3_000_000);
+    client.deposit(&seed, &3_000_000);
+    mint_token(&env, &usdc_token, &contract_id, 1_000_001);
+    client.update_total_assets(&4_000_001);
+
+    let user = Address::generate(&env);
+    let start = 100_000_000;
+    mint_token(&env, &usdc_token, &user, start);
+    for i in 0..40 {
+        env.budget().reset_default();
+        client.deposit(&user, &(1_000_003 + i * 7_919));
+        let withdrawable = client.get_balance(&user);
+        client.withdraw(&user, &withdrawable);
+    }
+    start - usdc.balance(&user)
+}
+
+#[test]
+fn test_higher_share_precision_reduces_accumulated_dust() {
+    // Finer share units round away less of each deposit, so far less dust
+    // accumulates than at the default precision; either way it stays with
+    // the vault
+    let coarse = round_trip_dust(USDC_DECIMALS);
+    let fine = round_trip_dust(MAX_SHARE_DECIMALS);
+    assert!(fine >= 0);
+    assert!(fine * 10 < coarse);
+}
+
+// ============================================================================
+// FEE HOLIDAY TESTS
+// ============================================================================
+
+#[test]
+fn test_fee_holiday_window() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    assert_eq!(client.get_fee_holiday(), 0);
+
+    let until = env.ledger().timestamp() + 86_400;
+    client.set_fee_holiday(&until);
+    assert_eq!(client.get_fee_holiday(), until);
+    assert!(client.is_fee_holiday());
+
+    env.ledger().with_mut(|li| li.timestamp = until);
+    assert!(!client.is_fee_holiday());
+}
+
+// ============================================================================
+// CONFIG VIEW TESTS
+// ============================================================================
+
+#[test]
+fn test_get_config_matches_init_and_config_state() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let config = client.get_config();
+    assert_eq!(config.agent, agent);
+    assert_eq!(config.owner, agent);
+    assert_eq!(config.usdc_token, usdc_token);
+    assert_eq!(config.tvl_cap, client.get_tvl_cap());
+    assert_eq!(config.user_cap, 10_000_000_000);
+    assert_eq!(config.min_deposit, MIN_DEPOSIT);
+    assert!(!config.paused);
+    assert_eq!(config.version, 1);
+
+    client.set_tvl_cap(&50_000_000_000);
+    client.set_user_deposit_cap(&5_000_000_000);
+    client.pause(&agent);
+
+    let config = client.get_config();
+    assert_eq!(config.tvl_cap, 50_000_000_000);
+    assert_eq!(config.user_cap, 5_000_000_000);
+    assert!(config.paused);
+}
+
+// ============================================================================
+// DEPOSIT RECEIPT TESTS
+// ============================================================================
+
+/// Token that skims one unit from every transfer, like a fee-on-transfer token.
+#[contract]
+pub struct MockSkimmingToken;
+
+#[contractimpl]
+impl MockSkimmingToken {
+    pub fn mint(env: Env, to: Address, amount: i128) {
+        let balance = Self::balance(env.clone(), to.clone());
+        env.storage().instance().set(&to, &(balance + amount));
+    }
+
+    pub fn balance(env: Env, id: Address) -> i128 {
+        env.storage().instance().get(&id).unwrap_or(0)
+    }
+
+    pub fn transfer(env: Env, from: Address, to: Address, amount: i128) {
+        let from_balance = Self::balance(env.clone(), from.clone());
+        env.storage()
+            .instance()
+            .set(&from, &(from_balance - amount));
+        let to_balance = Self::balance(env.clone(), to.clone());
+        env.storage()
+            .instance()
+            .set(&to, &(to_balance + amount - 1));
+    }
+}
+
+#[test]
+fn test_deposit_raises_vault_balance_by_received_amount() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let usdc = token::Client::new(&env, &usdc_token);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 3_000_000);
+
+    let before = usdc.balance(&contract_id);
+    client.deposit(&user, &3_000_000);
+    assert_eq!(usdc.balance(&contract_id) - before, 3_000_000);
+    assert_eq!(client.get_total_deposits(), 3_000_000);
+}
+
+#[test]
+#[should_panic(expected = "Deposit transfer mismatch")]
+fn test_deposit_rejects_short_transfer() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let token_id = env.register_contract(None, MockSkimmingToken);
+    let contract_id = env.register_contract(None, NeuroWealthVault);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let agent = Address::generate(&env);
+    client.initialize(&agent, &token_id);
+
+    let user = Address::generate(&env);
+    MockSkimmingTokenClient::new(&env, &token_id).mint(&user, &2_000_000);
+    client.deposit(&user, &2_000_000);
+}
+
+// ============================================================================
+// ADVISOR TESTS
+// ============================================================================
+
+#[test]
+fn test_authorized_advisor_can_read_client_position() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let advisor = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 2_000_000);
+    client.deposit(&user, &2_000_000);
+
+    client.authorize_advisor(&user, &advisor);
+    let position = client.get_client_position(&advisor, &user);
+    assert_eq!(
+        position,
+        UserData {
+            shares: 2_000_000,
+            balance: 2_000_000,
+        }
+    );
+}
+
+#[test]
+#[should_panic(expected = "Advisor not authorized")]
+fn test_unauthorized_advisor_is_rejected() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let advisor = Address::generate(&env);
+    client.get_client_position(&advisor, &user);
+}
+
+#[test]
+#[should_panic(expected = "Advisor not authorized")]
+fn test_revoked_advisor_is_rejected() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let advisor = Address::generate(&env);
+    client.authorize_advisor(&user, &advisor);
+    client.revoke_advisor(&user, &advisor);
+    client.get_client_position(&advisor, &user);
+}
+
+// ============================================================================
+// FUNDS REBALANCE TESTS
+// ============================================================================
+
+fn setup_vault_with_strategy(env: &Env) -> (Address, Address, Address) {
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(env);
+    let client = NeuroWealthVaultClient::new(env, &contract_id);
+    let strategy = Address::generate(env);
+    client.register_strategy(&strategy);
+
+    let user = Address::generate(env);
+    mint_token(env, &usdc_token, &user, 5_000_000);
+    client.deposit(&user, &5_000_000);
+
+    (contract_id, usdc_token, strategy)
+}
+
+#[test]
+fn test_rebalance_funds_moves_usdc_to_strategy() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, strategy) = setup_vault_with_strategy(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let usdc = token::Client::new(&env, &usdc_token);
+
+    client.rebalance_funds(&strategy, &2_000_000);
+    assert_eq!(usdc.balance(&strategy), 2_000_000);
+    assert_eq!(usdc.balance(&contract_id), 3_000_000);
+    assert_eq!(client.get_strategy_allocation(&strategy), 2_000_000);
+    assert_eq!(client.get_total_deployed(), 2_000_000);
+    // Deployed funds still back the shares
+    assert_eq!(client.get_total_deposits(), 5_000_000);
+
+    // The full remaining liquidity can be deployed
+    client.rebalance_funds(&strategy, &3_000_000);
+    assert_eq!(client.get_total_deployed(), 5_000_000);
+}
+
+#[test]
+#[should_panic(expected = "Amount must be positive")]
+fn test_rebalance_funds_rejects_zero_amount() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _usdc_token, strategy) = setup_vault_with_strategy(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    client.rebalance_funds(&strategy, &0);
+}
+
+#[test]
+#[should_panic(expected = "Insufficient liquidity")]
+fn test_rebalance_funds_rejects_amount_above_liquidity() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _usdc_token, strategy) = setup_vault_with_strategy(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    client.rebalance_funds(&strategy, &5_000_001);
+}
+
+#[test]
+#[should_panic(expected = "Strategy not registered")]
+fn test_rebalance_funds_rejects_unregistered_strategy() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _usdc_token, _strategy) = setup_vault_with_strategy(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    client.rebalance_funds(&Address::generate(&env), &1_000_000);
+}
+
+// ============================================================================
+// BATCH CONVERSION TESTS
+// ============================================================================
+
+#[test]
+fn test_convert_to_assets_batch_matches_single_conversions() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 3_000_000);
+    client.deposit(&user, &3_000_000);
+    client.update_total_assets(&4_100_000);
+
+    let shares = Vec::from_array(&env, [1_i128, 333_333, 1_000_000, 2_999_999]);
+    let assets = client.convert_to_assets_batch(&shares);
+    assert_eq!(assets.len(), shares.len());
+    for (i, amount) in shares.iter().enumerate() {
+        assert_eq!(
+            assets.get(i as u32).unwrap(),
+            client.convert_to_assets(&amount)
+        );
+    }
+}
+
+#[test]
+#[should_panic(expected = "Batch too large")]
+fn test_convert_to_assets_batch_rejects_oversized_input() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let mut shares = Vec::new(&env);
+    for i in 0..=MAX_BATCH_SIZE {
+        shares.push_back(i as i128);
+    }
+    client.convert_to_assets_batch(&shares);
+}
+
+// ============================================================================
+// UNDERWATER TESTS
+// ============================================================================
+
+#[test]
+fn test_cost_basis_tracks_deposits_and_withdrawals() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 4_000_000);
+    client.deposit(&user, &4_000_000);
+    assert_eq!(client.get_cost_basis(&user), 4_000_000);
+
+    client.withdraw(&user, &1_000_000);
+    assert_eq!(client.get_cost_basis(&user), 3_000_000);
+    assert_eq!(client.get_total_cost_basis(), 3_000_000);
+
+    client.withdraw(&user, &3_000_000);
+    assert_eq!(client.get_cost_basis(&user), 0);
+    assert_eq!(client.get_total_cost_basis(), 0);
+}
+
+#[test]
+fn test_underwater_state_crossing_in_and_out() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 4_000_000);
+    client.deposit(&user, &4_000_000);
+    assert!(!client.is_underwater());
+
+    client.update_total_assets(&3_000_000);
+    assert!(client.is_underwater());
+    let last = env.events().all().last().unwrap();
+    let event: UnderwaterEvent = last.2.into_val(&env);
+    assert!(event.underwater);
+    assert_eq!(event.total_assets, 3_000_000);
+    assert_eq!(event.total_cost_basis, 4_000_000);
+
+    // Withdrawals remain open at the reduced share price
+    client.withdraw(&user, &1_500_000);
+    assert!(client.is_underwater());
+    assert_eq!(client.get_total_cost_basis(), 2_001_661);
+
+    client.update_total_assets(&2_001_661);
+    assert!(!client.is_underwater());
+}
+
+// ============================================================================
+// POSITION SPLIT TESTS
+// ============================================================================
+
+#[test]
+fn test_split_position_apportions_shares_and_cost_basis() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let heir = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 3_000_000);
+    client.deposit(&user, &3_000_000);
+    // Yield raises the share price without changing the cost basis
+    client.update_total_assets(&6_000_000);
+
+    client.split_position(&user, &heir, &1_000_000);
+    assert_eq!(client.get_shares(&user), 2_000_000);
+    assert_eq!(client.get_shares(&heir), 1_000_000);
+    assert_eq!(client.get_cost_basis(&user), 2_000_000);
+    assert_eq!(client.get_cost_basis(&heir), 1_000_000);
+    assert_eq!(client.get_balance(&heir), 1_996_677);
+    assert_eq!(client.get_total_shares(), 3_000_000);
+    assert_eq!(client.get_depositor_count(), 2);
+
+    let last = env.events().all().last().unwrap();
+    let event: SplitEvent = last.2.into_val(&env);
+    assert_eq!(event.from, user);
+    assert_eq!(event.to, heir);
+    assert_eq!(event.shares, 1_000_000);
+    assert_eq!(event.cost_basis, 1_000_000);
+}
+
+#[test]
+fn test_split_position_preserves_combined_cost_basis() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let other = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 1_000_000);
+    client.deposit(&user, &1_000_000);
+    mint_token(&env, &usdc_token, &other, 2_000_000);
+    client.deposit(&other, &2_000_000);
+
+    // A split that does not divide the basis evenly still loses nothing
+    client.split_position(&user, &other, &333_333);
+    assert_eq!(
+        client.get_cost_basis(&user) + client.get_cost_basis(&other),
+        3_000_000
+    );
+    assert_eq!(client.get_total_cost_basis(), 3_000_000);
+
+    // Splitting off the rest moves the whole remaining basis
+    client.split_position(&user, &other, &client.get_shares(&user));
+    assert_eq!(client.get_cost_basis(&user), 0);
+    assert_eq!(client.get_cost_basis(&other), 3_000_000);
+    assert_eq!(client.get_total_cost_basis(), 3_000_000);
+    assert_eq!(client.get_depositor_count(), 1);
+}
+
+#[test]
+#[should_panic(expected = "Insufficient balance")]
+fn test_split_position_exceeding_shares_fails() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 1_000_000);
+    client.deposit(&user, &1_000_000);
+
+    client.split_position(&user, &Address::generate(&env), &1_000_001);
+}
+
+// ============================================================================
+// ASSETS UPDATE RATE LIMIT TESTS
+// ============================================================================
+
+#[test]
+fn test_gradual_assets_updates_within_limits_succeed() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    client.update_total_assets(&100_000_000);
+
+    client.set_assets_update_cooldown(&3_600);
+    client.set_max_assets_delta_bps(&500);
+    assert_eq!(client.get_assets_update_cooldown(), 3_600);
+    assert_eq!(client.get_max_assets_delta_bps(), 500);
+
+    env.ledger().with_mut(|li| li.timestamp += 3_600);
+    client.update_total_assets(&105_000_000);
+    env.ledger().with_mut(|li| li.timestamp += 3_600);
+    client.update_total_assets(&100_000_000);
+    assert_eq!(client.get_total_deposits(), 100_000_000);
+}
+
+#[test]
+#[should_panic(expected = "Assets update cooldown")]
+fn test_assets_update_within_cooldown_fails() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    client.set_assets_update_cooldown(&3_600);
+
+    client.update_total_assets(&100_000_000);
+    env.ledger().with_mut(|li| li.timestamp += 3_599);
+    client.update_total_assets(&100_000_001);
+}
+
+#[test]
+#[should_panic(expected = "Assets delta too large")]
+fn test_assets_update_above_delta_cap_fails() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    client.update_total_assets(&100_000_000);
+    client.set_max_assets_delta_bps(&500);
+
+    client.update_total_assets(&94_999_999);
+}
+
+// ============================================================================
+// BACKING RATIO TESTS
+// ============================================================================
+
+#[test]
+fn test_backing_ratio_counts_on_chain_and_deployed_funds() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, strategy) = setup_vault_with_strategy(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    assert_eq!(client.get_backing_ratio_bps(), 10_000);
+
+    // Deploying funds moves them but keeps them backing the shares
+    client.rebalance_funds(&strategy, &2_000_000);
+    assert_eq!(client.get_backing_ratio_bps(), 10_000);
+
+    // Over-reported assets leave the vault under-backed
+    client.update_total_assets(&6_000_000);
+    assert_eq!(client.get_backing_ratio_bps(), 8_333);
+
+    // Extra USDC in the vault over-backs it
+    mint_token(&env, &usdc_token, &contract_id, 3_000_000);
+    assert_eq!(client.get_backing_ratio_bps(), 13_333);
+}
+
+#[test]
+fn test_backing_ratio_of_empty_vault_is_full() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    assert_eq!(client.get_backing_ratio_bps(), 10_000);
+}
+
+// ============================================================================
+// PROOF OF RESERVES TESTS
+// ============================================================================
+
+#[test]
+fn test_proof_of_reserves_reconciles_balance_and_attestation() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _usdc_token, strategy) = setup_vault_with_strategy(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    // Without an attestation only the on-chain balance counts
+    let proof = client.proof_of_reserves();
+    assert_eq!(proof.on_chain_balance, 5_000_000);
+    assert_eq!(proof.attested_deployed, 0);
+    assert_eq!(proof.attested_at, 0);
+    assert_eq!(proof.total_assets, 5_000_000);
+    assert!(proof.reconciled);
+
+    // Deployed funds leave the vault until the agent attests them
+    client.rebalance_funds(&strategy, &2_000_000);
+    assert!(!client.proof_of_reserves().reconciled);
+
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    client.attest_reserves(&2_000_000);
+    assert_eq!(
+        client.proof_of_reserves(),
+        ProofOfReserves {
+            on_chain_balance: 3_000_000,
+            attested_deployed: 2_000_000,
+            attested_at: 1_000,
+            total_assets: 5_000_000,
+            reserve_deployed: 0,
+            reconciled: true,
+        }
+    );
+
+    let events = env.events().all();
+    let (_, _, data) = events.last().unwrap();
+    let event: ReservesAttestedEvent = data.into_val(&env);
+    assert_eq!(event.amount, 2_000_000);
+    assert_eq!(event.timestamp, 1_000);
+}
+
+#[test]
+fn test_proof_of_reserves_flags_over_reported_assets() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _usdc_token, _strategy) = setup_vault_with_strategy(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    client.update_total_assets(&6_000_000);
+    let proof = client.proof_of_reserves();
+    assert_eq!(proof.on_chain_balance, 5_000_000);
+    assert_eq!(proof.total_assets, 6_000_000);
+    assert!(!proof.reconciled);
+}
+
+#[test]
+#[should_panic(expected = "Amount must be non-negative")]
+fn test_attest_negative_reserves_fails() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    client.attest_reserves(&-1);
+}
+
+// ============================================================================
+// DUAL EMIT TESTS
+// ============================================================================
+
+fn count_events_with_topics(
+    env: &Env,
+    contract_id: &Address,
+    topics: soroban_sdk::Vec<soroban_sdk::Val>,
+) -> u32 {
+    let mut count = 0;
+    for (emitter, event_topics, _data) in env.events().all().iter() {
+        if &emitter == contract_id && event_topics == topics {
+            count += 1;
+        }
+    }
+    count
+}
+
+#[test]
+fn test_dual_emit_publishes_legacy_and_new_shapes() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    client.set_dual_emit(&true);
+    assert!(client.get_dual_emit());
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 2_000_000);
+
+    client.deposit(&user, &2_000_000);
+    let legacy = (symbol_short!("deposit"), symbol_short!("legacy")).into_val(&env);
+    let current = (symbol_short!("deposit"),).into_val(&env);
+    assert_eq!(count_events_with_topics(&env, &contract_id, legacy), 1);
+    assert_eq!(count_events_with_topics(&env, &contract_id, current), 1);
+
+    let (_, _, data) = env
+        .events()
+        .all()
+        .iter()
+        .find(|(_, topics, _)| topics.len() == 2)
+        .unwrap();
+    let event: LegacyDepositEvent = data.into_val(&env);
+    assert_eq!(event.user, user);
+    assert_eq!(event.amount, 2_000_000);
+
+    client.withdraw(&user, &1_000_000);
+    let legacy = (symbol_short!("withdraw"), symbol_short!("legacy")).into_val(&env);
+    let current = (symbol_short!("withdraw"),).into_val(&env);
+    assert_eq!(count_events_with_topics(&env, &contract_id, legacy), 1);
+    assert_eq!(count_events_with_topics(&env, &contract_id, current), 1);
+}
+
+#[test]
+fn test_dual_emit_off_publishes_only_new_shape() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    client.set_dual_emit(&true);
+    client.set_dual_emit(&false);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 2_000_000);
+
+    client.deposit(&user, &2_000_000);
+    let legacy = (symbol_short!("deposit"), symbol_short!("legacy")).into_val(&env);
+    let current = (symbol_short!("deposit"),).into_val(&env);
+    assert_eq!(count_events_with_topics(&env, &contract_id, legacy), 0);
+    assert_eq!(count_events_with_topics(&env, &contract_id, current), 1);
+}
+
+// ============================================================================
+// MIN DEPOSIT FOR SHARES TESTS
+// ============================================================================
+
+#[test]
+fn test_min_deposit_for_shares_mints_nonzero_at_high_price() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    assert_eq!(client.min_deposit_for_shares(), MIN_DEPOSIT);
+
+    let seed = Address::generate(&env);
+    mint_token(&env, &usdc_token, &seed, 1_000_000);
+    client.deposit(&seed, &1_000_000);
+
+    // Share price near 3 USDC per raw share unit, slightly diluted by the
+    // virtual offset
+    client.set_tvl_cap(&10_000_000_000_000);
+    mint_token(&env, &usdc_token, &contract_id, 3_000_000_000_000);
+    client.update_total_assets(&3_000_001_000_001);
+
+    let minimum = client.min_deposit_for_shares();
+    assert_eq!(minimum, 2_970_299);
+    assert_eq!(client.convert_to_shares(&(minimum - 1)), 0);
+    assert_eq!(client.convert_to_shares(&minimum), 1);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, minimum);
+    client.deposit(&user, &minimum);
+    assert_eq!(client.get_shares(&user), 1);
+}
+
+// ============================================================================
+// SLIPPAGE TESTS
+// ============================================================================
+
+#[test]
+fn test_deposit_with_quote_within_tolerance_succeeds() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    client.set_max_slippage_bps(&100);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 4_000_000);
+    client.deposit(&user, &2_000_000);
+
+    let quote = client.get_share_price();
+    mint_token(&env, &usdc_token, &contract_id, 20_000);
+    client.update_total_assets(&2_020_000); // +1%
+
+    client.deposit_with_quote(&user, &1_000_000, &quote);
+    client.withdraw_with_quote(&user, &1_000_000, &quote);
+}
+
+#[test]
+#[should_panic(expected = "Slippage exceeded")]
+fn test_deposit_with_quote_beyond_tolerance_reverts() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    client.set_max_slippage_bps(&100);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 4_000_000);
+    client.deposit(&user, &2_000_000);
+
+    let quote = client.get_share_price();
+    client.update_total_assets(&2_020_001); // just over 1%
+
+    client.deposit_with_quote(&user, &1_000_000, &quote);
+}
+
+#[test]
+#[should_panic(expected = "Slippage exceeded")]
+fn test_withdraw_with_quote_beyond_tolerance_reverts() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 2_000_000);
+    client.deposit(&user, &2_000_000);
+
+    let quote = client.get_share_price();
+    client.update_total_assets(&1_900_000);
+
+    client.withdraw_with_quote(&user, &1_000_000, &quote);
+}
+
+// ============================================================================
+// SHARE VELOCITY TESTS
+// ============================================================================
+
+#[test]
+fn test_share_velocity_accumulates_mints_and_burns() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 10_000_000);
+    assert_eq!(client.get_share_velocity(&user), (0, 0));
+
+    client.deposit(&user, &3_000_000);
+    client.withdraw(&user, &1_000_000);
+    client.mint(&user, &2_000_000);
+    client.withdraw(&user, &2_500_000);
+
+    let request_id = client.request_withdrawal(&user, &1_000_000);
+    // Escrowed shares are not burned until the request settles
+    assert_eq!(client.get_share_velocity(&user), (5_000_000, 3_500_000));
+
+    client.claim_withdrawal(&user, &request_id);
+    assert_eq!(client.get_share_velocity(&user), (5_000_000, 4_500_000));
+    assert_eq!(client.get_shares(&user), 500_000);
+}
+
+// ============================================================================
+// NATIVE DEPOSIT TESTS
+// ============================================================================
+
+/// Router that pays out `amount_in * rate / 1e7` of `token_out` from its own balance.
+///
+/// It deliberately ignores `min_out` so tests exercise the vault's own check.
+#[contract]
+pub struct MockSwapRouter;
+
+#[contractimpl]
+impl MockSwapRouter {
+    pub fn set_rate(env: Env, rate: i128) {
+        env.storage().instance().set(&symbol_short!("rate"), &rate);
+    }
+
+    pub fn swap_exact_in(
+        env: Env,
+        _token_in: Address,
+        token_out: Address,
+        amount_in: i128,
+        _min_out: i128,
+        to: Address,
+    ) -> i128 {
+        let rate: i128 = env
+            .storage()
+            .instance()
+            .get(&symbol_short!("rate"))
+            .unwrap();
+        let amount_out = amount_in * rate / 10_000_000;
+        token::Client::new(&env, &token_out).transfer(
+            &env.current_contract_address(),
+            &to,
+            &amount_out,
+        );
+        amount_out
+    }
+}
+
+/// Registers a funded mock router swapping XLM to USDC at `rate` and configures the vault.
+fn setup_native_deposits(
+    env: &Env,
+    contract_id: &Address,
+    usdc_token: &Address,
+    rate: i128,
+) -> Address {
+    let client = NeuroWealthVaultClient::new(env, contract_id);
+    let native_token = env
+        .register_stellar_asset_contract_v2(Address::generate(env))
+        .address();
+    let router = env.register_contract(None, MockSwapRouter);
+    MockSwapRouterClient::new(env, &router).set_rate(&rate);
+    mint_token(env, usdc_token, &router, 1_000_000_000);
+    client.set_swap_router(&router, &native_token);
+    native_token
+}
+
+#[test]
+fn test_deposit_native_mints_shares_on_swapped_usdc() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    // 1 XLM = 0.12 USDC
+    let native_token = setup_native_deposits(&env, &contract_id, &usdc_token, 1_200_000);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &native_token, &user, 100_000_000);
+
+    let shares = client.deposit_native(&user, &100_000_000, &11_500_000);
+    assert_eq!(shares, 12_000_000);
+    assert_eq!(client.get_shares(&user), 12_000_000);
+    assert_eq!(client.get_total_deposits(), 12_000_000);
+    assert_eq!(
+        token::Client::new(&env, &usdc_token).balance(&contract_id),
+        12_000_000
+    );
+    assert_eq!(token::Client::new(&env, &native_token).balance(&user), 0);
+}
+
+#[test]
+#[should_panic(expected = "Swap slippage exceeded")]
+fn test_deposit_native_rejects_swap_below_minimum() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let native_token = setup_native_deposits(&env, &contract_id, &usdc_token, 1_200_000);
+
+    // The router ignores min_out; the vault still rejects the short swap
+    let user = Address::generate(&env);
+    mint_token(&env, &native_token, &user, 100_000_000);
+    client.deposit_native(&user, &100_000_000, &12_000_001);
+}
+
+#[test]
+#[should_panic(expected = "Swap router not set")]
+fn test_deposit_native_requires_router() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    client.deposit_native(&user, &100_000_000, &0);
+}
+
+// ============================================================================
+// INITIAL SHARE RATIO TESTS
+// ============================================================================
+
+#[test]
+fn test_first_deposit_mints_at_initial_share_ratio() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    assert_eq!(client.get_initial_share_ratio(), SHARE_PRICE_SCALE);
+
+    // Seed price of 0.5 USDC per share: 2 shares per USDC unit
+    client.set_initial_share_ratio(&(2 * SHARE_PRICE_SCALE));
+    assert_eq!(client.get_share_price(), SHARE_PRICE_SCALE / 2);
+    assert_eq!(client.convert_to_shares(&1_000_000), 2_000_000);
+
+    let first = Address::generate(&env);
+    let second = Address::generate(&env);
+    mint_token(&env, &usdc_token, &first, 3_000_000);
+    mint_token(&env, &usdc_token, &second, 1_000_000);
+
+    client.deposit(&first, &3_000_000);
+    assert_eq!(client.get_shares(&first), 6_000_000);
+    assert_eq!(client.get_balance(&first), 3_000_000);
+
+    // Later deposits are priced off vault state as usual
+    client.deposit(&second, &1_000_000);
+    assert_eq!(client.get_shares(&second), 2_000_000);
+}
+
+#[test]
+#[should_panic(expected = "Vault already has deposits")]
+fn test_initial_share_ratio_cannot_change_after_deposit() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 1_000_000);
+    client.deposit(&user, &1_000_000);
+
+    client.set_initial_share_ratio(&(2 * SHARE_PRICE_SCALE));
+}
+
+// ============================================================================
+// SHARE DECIMALS TESTS
+// ============================================================================
+
+#[test]
+fn test_share_decimals_scale_minted_shares_and_round_trip() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    assert_eq!(client.decimals(), USDC_DECIMALS);
+
+    client.set_share_decimals(&18);
+    assert_eq!(client.decimals(), 18);
+
+    // 1 USDC (7 decimals) mints 1 share at 18 decimals
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 20_000_000);
+    client.deposit(&user, &10_000_000);
+    let one_share = 1_000_000_000_000_000_000;
+    assert_eq!(client.get_shares(&user), one_share);
+    assert_eq!(client.convert_to_assets(&one_share), 10_000_000);
+    assert_eq!(client.get_balance(&user), 10_000_000);
+    // Prices stay quoted per whole share
+    assert_eq!(client.get_share_price(), SHARE_PRICE_SCALE);
+
+    // Subsequent deposits and previews keep the same scaling
+    client.deposit(&user, &10_000_000);
+    assert_eq!(client.get_shares(&user), 2 * one_share);
+    assert_eq!(client.preview_mint(&one_share), 10_000_000);
+
+    client.withdraw(&user, &5_000_000);
+    assert_eq!(client.get_shares(&user), 3 * one_share / 2);
+    assert_eq!(client.get_balance(&user), 15_000_000);
+}
+
+#[test]
+#[should_panic(expected = "Invalid share decimals")]
+fn test_share_decimals_below_asset_decimals_fails() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    client.set_share_decimals(&6);
+}
+
+#[test]
+#[should_panic(expected = "Vault already has deposits")]
+fn test_share_decimals_cannot_change_after_deposit() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 1_000_000);
+    client.deposit(&user, &1_000_000);
+
+    client.set_share_decimals(&18);
+}
+
+// ============================================================================
+// YIELD STALENESS TESTS
+// ============================================================================
+
+#[test]
+fn test_seconds_since_yield_tracks_last_update() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    assert_eq!(client.get_seconds_since_yield(), u64::MAX);
+    assert_eq!(client.get_days_since_yield(), u64::MAX);
+
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    client.update_total_assets(&1_000_000);
+    assert_eq!(client.get_seconds_since_yield(), 0);
+
+    env.ledger().with_mut(|li| li.timestamp += 3 * 86_400 + 600);
+    assert_eq!(client.get_seconds_since_yield(), 3 * 86_400 + 600);
+    assert_eq!(client.get_days_since_yield(), 3);
+}
+
+// ============================================================================
+// WITHDRAWAL CAP TESTS
+// ============================================================================
+
+#[test]
+fn test_withdrawal_split_into_capped_chunks_succeeds() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    client.set_max_withdraw_per_tx(&2_000_000);
+    assert_eq!(client.get_max_withdraw_per_tx(), 2_000_000);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 5_000_000);
+    client.deposit(&user, &5_000_000);
+
+    client.withdraw(&user, &2_000_000);
+    client.withdraw(&user, &2_000_000);
+    client.withdraw(&user, &1_000_000);
+    assert_eq!(client.get_shares(&user), 0);
+    assert_eq!(
+        token::Client::new(&env, &usdc_token).balance(&user),
+        5_000_000
+    );
+}
+
+#[test]
+#[should_panic(expected = "Exceeds max withdrawal per transaction")]
+fn test_withdrawal_over_cap_reverts() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    client.set_max_withdraw_per_tx(&2_000_000);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 5_000_000);
+    client.deposit(&user, &5_000_000);
+
+    client.withdraw(&user, &2_000_001);
+}
+
+// ============================================================================
+// AGENT PERMISSION TESTS
+// ============================================================================
+
+#[test]
+fn test_initialize_grants_all_agent_permissions() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    assert_eq!(client.get_agent_permissions(), ALL_AGENT_PERMISSIONS);
+
+    client.rebalance(&symbol_short!("balanced"), &850);
+    assert_eq!(client.process_withdrawals(&10).len(), 0);
+}
+
+#[test]
+fn test_initialize_with_permissions_restricts_agent() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, NeuroWealthVault);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let agent = Address::generate(&env);
+    client.initialize_with_permissions(&agent, &Address::generate(&env), &PERM_REBALANCE);
+    assert_eq!(client.get_agent_permissions(), PERM_REBALANCE);
+
+    client.rebalance(&symbol_short!("balanced"), &850);
+    assert!(client.try_process_withdrawals(&10).is_err());
+
+    client.set_agent_permissions(&(PERM_REBALANCE | PERM_PROCESS_WITHDRAWALS));
+    assert_eq!(client.process_withdrawals(&10).len(), 0);
+}
+
+#[test]
+#[should_panic(expected = "Agent lacks permission")]
+fn test_restricted_agent_cannot_deploy_funds() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _usdc_token, strategy) = setup_vault_with_strategy(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    client.set_agent_permissions(&PERM_REBALANCE);
+
+    client.rebalance_funds(&strategy, &1_000_000);
+}
+
+#[test]
+#[should_panic(expected = "Invalid permissions")]
+fn test_initialize_with_unknown_permission_bits_fails() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, NeuroWealthVault);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    client.initialize_with_permissions(
+        &Address::generate(&env),
+        &Address::generate(&env),
+        &(ALL_AGENT_PERMISSIONS + 1),
+    );
+}
+
+// ============================================================================
+// TOTAL SHARES RECOMPUTE TESTS
+// ============================================================================
+
+#[test]
+fn test_depositors_enumeration_tracks_holders() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    mint_token(&env, &usdc_token, &alice, 2_000_000);
+    mint_token(&env, &usdc_token, &bob, 1_000_000);
+    client.deposit(&alice, &1_000_000);
+    client.deposit(&bob, &1_000_000);
+    client.deposit(&alice, &1_000_000);
+    assert_eq!(
+        client.get_depositors(),
+        Vec::from_array(&env, [alice.clone(), bob.clone()])
+    );
+
+    client.withdraw(&alice, &2_000_000);
+    assert_eq!(client.get_depositors(), Vec::from_array(&env, [bob]));
+}
+
+#[test]
+fn test_recompute_total_shares_fixes_desynced_total() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    mint_token(&env, &usdc_token, &alice, 3_000_000);
+    mint_token(&env, &usdc_token, &bob, 2_000_000);
+    client.deposit(&alice, &3_000_000);
+    client.deposit(&bob, &2_000_000);
+    // Escrowed shares still count towards the total
+    client.request_withdrawal(&bob, &1_000_000);
+
+    env.as_contract(&contract_id, || {
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalShares, &9_999_999_i128);
+    });
+
+    client.pause(&agent);
+    assert_eq!(client.recompute_total_shares(), 5_000_000);
+    assert_eq!(client.get_total_shares(), 5_000_000);
+
+    let last = env.events().all().last().unwrap();
+    let event: TotalSharesRecomputedEvent = last.2.into_val(&env);
+    assert_eq!(event.old_total, 9_999_999);
+    assert_eq!(event.new_total, 5_000_000);
+}
+
+#[test]
+#[should_panic(expected = "Vault is not paused")]
+fn test_recompute_total_shares_requires_pause() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    client.recompute_total_shares();
+}
+
+// ============================================================================
+// TARGET PRICE TESTS
+// ============================================================================
+
+#[test]
+fn test_assets_for_target_price_reaches_target() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 3_000_000);
+    client.deposit(&user, &3_000_000);
+
+    // 1.05 USDC per share over 3 shares
+    let target = 10_500_000;
+    let assets = client.assets_for_target_price(&target);
+    assert_eq!(assets, 3_150_000);
+    client.update_total_assets(&assets);
+    assert_eq!(client.get_share_price(), target);
+
+    // A target between representable prices rounds up to the next one
+    let target = 12_345_679;
+    let assets = client.assets_for_target_price(&target);
+    client.update_total_assets(&assets);
+    assert!(client.get_share_price() >= target);
+    client.update_total_assets(&(assets - 1));
+    assert!(client.get_share_price() < target);
+}
+
+#[test]
+#[should_panic(expected = "No shares outstanding")]
+fn test_assets_for_target_price_in_empty_vault_fails() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    client.assets_for_target_price(&SHARE_PRICE_SCALE);
+}
+
+// ============================================================================
+// EFFECTIVE DEPOSIT PRICE TESTS
+// ============================================================================
+
+#[test]
+fn test_effective_deposit_price_matches_offset_price_without_rounding() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    assert_eq!(
+        client.effective_deposit_price(&1_000_000),
+        client.get_share_price()
+    );
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 2_000_000);
+    client.deposit(&user, &2_000_000);
+    // Deposits price against the totals plus the virtual offset, here
+    // (3_005_000 + 10_000) / (2_000_000 + 10_000) = 1.5 exactly
+    client.update_total_assets(&3_005_000);
+    assert_eq!(client.get_share_price(), 15_025_000);
+    assert_eq!(client.effective_deposit_price(&3_000_000), 15_000_000);
+}
+
+#[test]
+fn test_effective_deposit_price_reflects_rounding_loss() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 3_000_000);
+    client.deposit(&user, &3_000_000);
+    client.update_total_assets(&7_000_000);
+
+    // 10 units mint floor(10 * 3 / 7) = 4 shares instead of 4.28
+    let spot = client.get_share_price();
+    assert_eq!(spot, 23_333_333);
+    assert_eq!(client.effective_deposit_price(&10), 25_000_000);
+    assert!(client.effective_deposit_price(&10) > spot);
+}
+
+#[test]
+#[should_panic(expected = "Deposit mints no shares")]
+fn test_effective_deposit_price_of_dust_fails() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 1_000_000);
+    client.deposit(&user, &1_000_000);
+    client.update_total_assets(&3_000_000);
+
+    client.effective_deposit_price(&2);
+}
+
+// ============================================================================
+// MULTI-SIG APPROVAL TESTS
+// ============================================================================
+
+/// Configures a 2-of-3 signer set and returns the signers.
+fn setup_multisig(env: &Env, client: &NeuroWealthVaultClient) -> Vec<Address> {
+    let signers = Vec::from_array(
+        env,
+        [
+            Address::generate(env),
+            Address::generate(env),
+            Address::generate(env),
+        ],
+    );
+    client.set_owners(&signers, &2);
+    signers
+}
+
+#[test]
+fn test_critical_action_requires_threshold_approvals() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let signers = setup_multisig(&env, &client);
+    assert_eq!(client.get_owners(), signers);
+    assert_eq!(client.get_threshold(), 2);
+
+    let new_agent = Address::generate(&env);
+    let hash = client.get_action_hash(&symbol_short!("agent"), &new_agent.into_val(&env));
+
+    // No approvals, then one of two, both block execution
+    assert!(client.try_update_agent(&new_agent).is_err());
+    assert_eq!(client.approve_action(&signers.get(0).unwrap(), &hash), 1);
+    assert!(client.try_update_agent(&new_agent).is_err());
+    assert_eq!(client.get_agent(), agent);
+
+    // Meeting the threshold executes and consumes the approvals
+    assert_eq!(client.approve_action(&signers.get(2).unwrap(), &hash), 2);
+    client.update_agent(&new_agent);
+    assert_eq!(client.get_agent(), new_agent);
+    assert_eq!(client.get_approvals(&hash), 0);
+    assert!(client.try_update_agent(&new_agent).is_err());
+}
+
+#[test]
+fn test_approvals_are_bound_to_action_arguments() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let signers = setup_multisig(&env, &client);
+
+    let approved_agent = Address::generate(&env);
+    let hash = client.get_action_hash(&symbol_short!("agent"), &approved_agent.into_val(&env));
+    client.approve_action(&signers.get(0).unwrap(), &hash);
+    client.approve_action(&signers.get(1).unwrap(), &hash);
+
+    // Approvals for one agent do not authorize a different one
+    assert!(client.try_update_agent(&Address::generate(&env)).is_err());
+
+    // Reverting to a single owner is itself approved by the signers
+    let empty: Vec<Address> = Vec::new(&env);
+    let reset = client.get_action_hash(
+        &symbol_short!("owners"),
+        &(empty.clone(), 0_u32).into_val(&env),
+    );
+    client.approve_action(&signers.get(1).unwrap(), &reset);
+    client.approve_action(&signers.get(2).unwrap(), &reset);
+    client.set_owners(&empty, &0);
+    assert_eq!(client.get_threshold(), 0);
+}
+
+#[test]
+#[should_panic(expected = "Not an owner signer")]
+fn test_approve_action_by_non_signer_fails() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    setup_multisig(&env, &client);
+
+    let hash = client.get_action_hash(
+        &symbol_short!("agent"),
+        &Address::generate(&env).into_val(&env),
+    );
+    client.approve_action(&Address::generate(&env), &hash);
+}
+
+#[test]
+#[should_panic(expected = "Already approved")]
+fn test_double_approval_fails() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let signers = setup_multisig(&env, &client);
+
+    let hash = client.get_action_hash(
+        &symbol_short!("agent"),
+        &Address::generate(&env).into_val(&env),
+    );
+    client.approve_action(&signers.get(0).unwrap(), &hash);
+    client.approve_action(&signers.get(0).unwrap(), &hash);
+}
+
+#[test]
+#[should_panic(expected = "Invalid threshold")]
+fn test_set_owners_threshold_above_signers_fails() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    client.set_owners(&Vec::from_array(&env, [Address::generate(&env)]), &2);
+}
+
+// ============================================================================
+// WITHDRAWABLE BREAKDOWN TESTS
+// ============================================================================
+
+#[test]
+fn test_withdrawable_breakdown_separates_liquid_and_queued() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    client.set_withdrawal_cooldown(&3_600);
+
+    let user = Address::generate(&env);
+    let other = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 4_000_000);
+    mint_token(&env, &usdc_token, &other, 4_000_000);
+    client.deposit(&user, &4_000_000);
+    client.deposit(&other, &4_000_000);
+
+    client.request_withdrawal(&user, &1_000_000);
+    client.request_withdrawal(&user, &1_500_000);
+    // Other users' requests are not attributed to this user
+    client.request_withdrawal(&other, &2_000_000);
+
+    assert_eq!(
+        client.get_withdrawable_breakdown(&user),
+        Withdrawable {
+            liquid_assets: 1_500_000,
+            locked_assets: 0,
+            queued_assets: 2_500_000,
+        }
+    );
+
+    // Both values follow the share price
+    client.update_total_assets(&16_000_000);
+    let breakdown = client.get_withdrawable_breakdown(&user);
+    assert_eq!(breakdown.liquid_assets, 2_998_127);
+    assert_eq!(breakdown.queued_assets, 4_996_878);
+
+    // Settled requests leave the queued value
+    mint_token(&env, &usdc_token, &contract_id, 8_000_000);
+    env.ledger().with_mut(|li| li.timestamp += 3_600);
+    client.process_withdrawals(&1);
+    assert_eq!(
+        client.get_withdrawable_breakdown(&user).queued_assets,
+        2_998_127
+    );
+    client.process_withdrawals(&1);
+    assert_eq!(client.get_withdrawable_breakdown(&user).queued_assets, 0);
+}
+
+// ============================================================================
+// SAME-TRANSACTION DEPOSIT TESTS
+// ============================================================================
+
+/// Calls the vault twice within a single invocation, as a router or batching
+/// contract would.
+#[contract]
+pub struct DoubleDepositor;
+
+#[contractimpl]
+impl DoubleDepositor {
+    /// Makes two deposits and returns the vault's total shares observed
+    /// between them.
+    pub fn deposit_twice(
+        env: Env,
+        vault: Address,
+        first: Address,
+        second: Address,
+        amount: i128,
+    ) -> i128 {
+        let vault = NeuroWealthVaultClient::new(&env, &vault);
+        vault.deposit(&first, &amount);
+        let total_between = vault.get_total_shares();
+        vault.deposit(&second, &amount);
+        total_between
+    }
+}
+
+#[test]
+fn test_same_transaction_deposits_are_priced_sequentially() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    // Price of 7/3 USDC per share, so every deposit rounds
+    let seed = Address::generate(&env);
+    mint_token(&env, &usdc_token, &seed, 3_000_000);
+    client.deposit(&seed, &3_000_000);
+    client.update_total_assets(&7_000_000);
+
+    let first = Address::generate(&env);
+    let second = Address::generate(&env);
+    mint_token(&env, &usdc_token, &first, 7_000_001);
+    mint_token(&env, &usdc_token, &second, 7_000_001);
+
+    let helper = env.register_contract(None, DoubleDepositor);
+    let total_between = DoubleDepositorClient::new(&env, &helper).deposit_twice(
+        &contract_id,
+        &first,
+        &second,
+        &7_000_001,
+    );
+
+    // The first deposit is priced against the opening totals plus the
+    // virtual offset
+    let first_shares = 7_000_001 * (3_000_000 + VIRTUAL_ASSETS) / (7_000_000 + VIRTUAL_ASSETS);
+    assert_eq!(client.get_shares(&first), first_shares);
+    assert_eq!(total_between, 3_000_000 + first_shares);
+
+    // The second sees the totals the first one left behind
+    let second_shares =
+        7_000_001 * (total_between + VIRTUAL_ASSETS) / (7_000_000 + 7_000_001 + VIRTUAL_ASSETS);
+    assert_eq!(client.get_shares(&second), second_shares);
+    assert_eq!(
+        client.get_total_shares(),
+        3_000_000 + first_shares + second_shares
+    );
+    assert_eq!(client.get_total_deposits(), 7_000_000 + 2 * 7_000_001);
+}
+
+// ============================================================================
+// ASSETS ORACLE TESTS
+// ============================================================================
+
+#[contract]
+pub struct MockAssetsOracle;
+
+#[contractimpl]
+impl MockAssetsOracle {
+    pub fn set_total(env: Env, total: i128) {
+        env.storage()
+            .instance()
+            .set(&symbol_short!("total"), &total);
+    }
+
+    pub fn total_assets(env: Env, _vault: Address) -> i128 {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("total"))
+            .unwrap()
+    }
+}
+
+/// Wires a mock assets oracle reporting `total` with the given tolerance.
+fn setup_assets_oracle(env: &Env, client: &NeuroWealthVaultClient, total: i128, bps: i128) {
+    let oracle = env.register_contract(None, MockAssetsOracle);
+    MockAssetsOracleClient::new(env, &oracle).set_total(&total);
+    client.set_assets_oracle(&oracle);
+    client.set_assets_oracle_tolerance_bps(&bps);
+    assert_eq!(client.get_assets_oracle(), Some(oracle));
+}
+
+#[test]
+fn test_assets_update_within_oracle_tolerance_succeeds() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    setup_assets_oracle(&env, &client, 10_000_000, 100);
+
+    // 1% either side of the oracle's figure is accepted
+    client.update_total_assets(&10_100_000);
+    client.update_total_assets(&9_900_000);
+    assert_eq!(client.get_total_deposits(), 9_900_000);
+}
+
+#[test]
+#[should_panic(expected = "Assets deviate from oracle")]
+fn test_assets_update_beyond_oracle_tolerance_reverts() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    setup_assets_oracle(&env, &client, 10_000_000, 100);
+
+    client.update_total_assets(&10_100_001);
+}
+
+#[test]
+#[should_panic(expected = "Invalid oracle tolerance")]
+fn test_assets_oracle_tolerance_above_full_range_fails() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    client.set_assets_oracle_tolerance_bps(&10_001);
+}
+
+// ============================================================================
+// YIELD RATE TESTS
+// ============================================================================
+
+/// Deposits 10 USDC at t = 0 and reports `final_total` one year later.
+fn setup_vault_with_yearly_yield(env: &Env, final_total: i128) -> Address {
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(env);
+    let client = NeuroWealthVaultClient::new(env, &contract_id);
+
+    let user = Address::generate(env);
+    mint_token(env, &usdc_token, &user, 10_000_000);
+    client.deposit(&user, &10_000_000);
+    client.update_total_assets(&10_000_000);
+
+    env.ledger().with_mut(|li| li.timestamp += SECONDS_PER_YEAR);
+    client.update_total_assets(&final_total);
+    contract_id
+}
+
+#[test]
+fn test_time_to_double_at_known_apy() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    // 10% simple growth over one year
+    let contract_id = setup_vault_with_yearly_yield(&env, 11_000_000);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    assert_eq!(client.get_assets_history().len(), 2);
+    assert_eq!(client.get_implied_apy_bps(&SECONDS_PER_YEAR), 1_000);
+
+    // ln(2) / ln(1.1) = 7.27 years; the rule of 72 gives 7.2 years
+    let doubling = client.get_time_to_double_seconds(&SECONDS_PER_YEAR);
+    let exact = 727 * SECONDS_PER_YEAR / 100;
+    assert!(doubling.abs_diff(exact) * 100 <= exact * 2);
+}
+
+#[test]
+fn test_time_to_double_without_growth_is_sentinel() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    // A loss over the window
+    let contract_id = setup_vault_with_yearly_yield(&env, 9_000_000);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    assert_eq!(client.get_implied_apy_bps(&SECONDS_PER_YEAR), -1_000);
+    assert_eq!(
+        client.get_time_to_double_seconds(&SECONDS_PER_YEAR),
+        u64::MAX
+    );
+
+    // No history at all
+    let (contract_id, _agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    assert_eq!(client.get_time_to_double_seconds(&86_400), u64::MAX);
+}
+
+#[test]
+fn test_assets_history_is_bounded() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    for i in 0..(MAX_HISTORY_SAMPLES + 3) {
+        env.ledger().with_mut(|li| li.timestamp = i as u64);
+        client.update_total_assets(&(1_000_000 + i as i128));
+    }
+
+    let history = client.get_assets_history();
+    assert_eq!(history.len(), MAX_HISTORY_SAMPLES);
+    assert_eq!(history.first().unwrap().timestamp, 3);
+    assert_eq!(
+        history.last().unwrap().total_assets,
+        1_000_000 + (MAX_HISTORY_SAMPLES + 2) as i128
+    );
+}
+
+// ============================================================================
+// SHARE LOCK TESTS
+// ============================================================================
+
+#[test]
+fn test_locked_shares_block_withdrawal_until_released() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let lender = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 4_000_000);
+    client.deposit(&user, &4_000_000);
+
+    client.lock_shares(&user, &3_000_000, &lender);
+    assert_eq!(client.get_locked_shares(&user), 3_000_000);
+    // Locked shares stay with the user
+    assert_eq!(client.get_shares(&user), 4_000_000);
+    assert_eq!(
+        client.get_withdrawable_breakdown(&user),
+        Withdrawable {
+            liquid_assets: 1_000_000,
+            locked_assets: 3_000_000,
+            queued_assets: 0,
+        }
+    );
+
+    // Only the unlocked portion can leave the position
+    assert!(client.try_withdraw(&user, &1_000_001).is_err());
+    assert!(client.try_request_withdrawal(&user, &1_000_001).is_err());
+    assert!(client
+        .try_split_position(&user, &Address::generate(&env), &1_000_001)
+        .is_err());
+    client.withdraw(&user, &1_000_000);
+
+    client.unlock_shares(&lender, &user, &3_000_000);
+    assert_eq!(client.get_locked_shares(&user), 0);
+    client.withdraw(&user, &3_000_000);
+    assert_eq!(client.get_shares(&user), 0);
+}
+
+#[test]
+#[should_panic(expected = "Exceeds locked shares")]
+fn test_only_the_locker_can_release_shares() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 2_000_000);
+    client.deposit(&user, &2_000_000);
+    client.lock_shares(&user, &2_000_000, &Address::generate(&env));
+
+    client.unlock_shares(&Address::generate(&env), &user, &1);
+}
+
+#[test]
+#[should_panic(expected = "Shares are locked")]
+fn test_lock_more_than_unlocked_shares_fails() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 2_000_000);
+    client.deposit(&user, &2_000_000);
+    client.lock_shares(&user, &1_500_000, &Address::generate(&env));
+
+    client.lock_shares(&user, &600_000, &Address::generate(&env));
+}
+
+// ============================================================================
+// PNL TESTS
+// ============================================================================
+
+#[test]
+fn test_pnl_separates_realized_and_unrealized_gains() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 4_000_000);
+    client.deposit(&user, &4_000_000);
+    assert_eq!(
+        client.get_pnl(&user),
+        Pnl {
+            realized: 0,
+            unrealized: 0,
+        }
+    );
+
+    // Yield doubles the share price
+    mint_token(&env, &usdc_token, &contract_id, 4_000_000);
+    client.update_total_assets(&8_000_000);
+    assert_eq!(client.get_pnl(&user).unrealized, 4_000_000);
+
+    // Withdrawing a quarter of the position realizes a quarter of the gain
+    client.withdraw(&user, &2_000_000);
+    assert_eq!(
+        client.get_pnl(&user),
+        Pnl {
+            realized: 998_751,
+            unrealized: 3_001_249,
+        }
+    );
+}
+
+#[test]
+fn test_queued_withdrawal_realizes_pnl_at_claim() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 2_000_000);
+    client.deposit(&user, &2_000_000);
+
+    let request_id = client.request_withdrawal(&user, &1_000_000);
+    assert_eq!(
+        client.get_withdrawal_request(&request_id).cost_basis,
+        1_000_000
+    );
+
+    // A loss before the claim is realized by it
+    client.update_total_assets(&1_500_000);
+    client.claim_withdrawal(&user, &request_id);
+    // The claim prices against the virtual offset, which cushions it; the
+    // remaining shares are worth at most the assets left
+    assert_eq!(
+        client.get_pnl(&user),
+        Pnl {
+            realized: -248_757,
+            unrealized: -251_243,
+        }
+    );
+}
+
+// ============================================================================
+// ROLE ADDRESS VALIDATION TESTS
+// ============================================================================
+
+#[test]
+#[should_panic(expected = "Address is the USDC token")]
+fn test_initialize_with_token_as_agent_fails() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, NeuroWealthVault);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let usdc_token = Address::generate(&env);
+    client.initialize(&usdc_token, &usdc_token);
+}
+
+#[test]
+#[should_panic(expected = "Address is the vault contract")]
+fn test_initialize_with_vault_as_agent_fails() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, NeuroWealthVault);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    client.initialize(&contract_id, &Address::generate(&env));
+}
+
+#[test]
+#[should_panic(expected = "Address is the vault contract")]
+fn test_initialize_with_vault_as_token_fails() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, NeuroWealthVault);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    client.initialize(&Address::generate(&env), &contract_id);
+}
+
+#[test]
+fn test_update_agent_rejects_token_and_vault() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    assert!(client.try_update_agent(&usdc_token).is_err());
+    assert!(client.try_update_agent(&contract_id).is_err());
+    assert_eq!(client.get_agent(), agent);
+}
+
+// ============================================================================
+// OPERATION COST HINT TESTS
+// ============================================================================
+
+/// Runs `call` and returns the vault ledger entries it touched as
+/// `OperationCost` counts: every entry in the recorded footprint is a read
+/// and the read-write ones are also writes. Auth nonces and entries of other
+/// contracts are left out, as in `operation_cost_hint`.
+fn measure_vault_footprint(env: &Env, contract_id: &Address, call: impl FnOnce()) -> (u32, u32) {
+    env.host()
+        .with_mut_storage(|storage| {
+            storage.footprint = Default::default();
+            Ok(())
+        })
+        .unwrap();
+    call();
+    let footprint = env
+        .host()
+        .with_mut_storage(|storage| Ok(storage.footprint.clone()))
+        .unwrap();
+    let vault = xdr::ScAddress::from(contract_id);
+    let (mut reads, mut writes) = (0, 0);
+    for (key, access) in footprint.0 {
+        if let xdr::LedgerKey::ContractData(data) = &*key {
+            if data.contract == vault && data.durability == xdr::ContractDataDurability::Persistent
+            {
+                reads += 1;
+                if access == AccessType::ReadWrite {
+                    writes += 1;
+                }
+            }
+        }
+    }
+    (reads, writes)
+}
+
+/// Asserts that an operation's hint matches its measured footprint, naming the
+/// measured counts so a drifting hint can be corrected from the failure.
+fn assert_cost_hint(op: &str, hint: &OperationCost, measured: (u32, u32)) {
+    assert_eq!(
+        (hint.ledger_reads, hint.ledger_writes),
+        measured,
+        "operation_cost_hint({op}) is stale: measured (reads, writes) = {measured:?}"
+    );
+}
+
+#[test]
+fn test_cost_hints_match_deposit_and_withdraw_profiles() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let usdc = token::Client::new(&env, &usdc_token);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 4_000_000);
+
+    // A reserved first deposit also consumes its PreRegistrations entry
+    client.set_max_depositors(&10);
+    client.pre_register(&user);
+    let deposit = client.operation_cost_hint(&symbol_short!("deposit"));
+    let measured = measure_vault_footprint(&env, &contract_id, || {
+        client.deposit(&user, &2_000_000);
+    });
+    assert_cost_hint("deposit", &deposit, measured);
+    assert!(deposit.cross_contract);
+    assert_eq!(usdc.balance(&contract_id), 2_000_000);
+
+    // A full exit additionally writes RealizedPnl
+    let withdraw = client.operation_cost_hint(&symbol_short!("withdraw"));
+    let measured = measure_vault_footprint(&env, &contract_id, || {
+        client.withdraw(&user, &2_000_000);
+    });
+    assert_cost_hint("withdraw", &withdraw, measured);
+    assert!(withdraw.cross_contract);
+    assert_eq!(usdc.balance(&user), 4_000_000);
+
+    client.deposit(&user, &2_000_000);
+    let wd_req = client.operation_cost_hint(&symbol_short!("wd_req"));
+    let measured = measure_vault_footprint(&env, &contract_id, || {
+        client.request_withdrawal(&user, &client.get_shares(&user));
+    });
+    assert_cost_hint("wd_req", &wd_req, measured);
+    assert!(!wd_req.cross_contract);
+
+    let rebalance = client.operation_cost_hint(&symbol_short!("rebalance"));
+    let measured = measure_vault_footprint(&env, &contract_id, || {
+        client.rebalance(&symbol_short!("balanced"), &0);
+    });
+    assert_cost_hint("rebalance", &rebalance, measured);
+    assert!(!rebalance.cross_contract);
+    assert_eq!(client.get_agent(), agent);
+}
+
+#[test]
+#[should_panic(expected = "Unknown operation")]
+fn test_cost_hint_for_unknown_operation_fails() {
+    let env = Env::default();
+    let (contract_id, _agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    client.operation_cost_hint(&symbol_short!("teleport"));
+}
+
+// ============================================================================
+// PRE-REGISTRATION TESTS
+// ============================================================================
+
+#[test]
+fn test_pre_registration_reserves_depositor_slot() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let early = Address::generate(&env);
+    let reserved = Address::generate(&env);
+    let latecomer = Address::generate(&env);
+    for user in [&early, &reserved, &latecomer] {
+        mint_token(&env, &usdc_token, user, 10_000_000);
+    }
+
+    client.set_max_depositors(&2);
+    client.deposit(&early, &10_000_000);
+
+    let expires_at = client.pre_register(&reserved);
+    assert_eq!(expires_at, env.ledger().timestamp() + PRE_REGISTRATION_TTL);
+    assert!(client.is_pre_registered(&reserved));
+    assert!(!client.is_pre_registered(&latecomer));
+
+    // The reserved slot is not available to anyone else
+    assert!(client.try_deposit(&latecomer, &10_000_000).is_err());
+    assert!(client.try_pre_register(&latecomer).is_err());
+
+    client.deposit(&reserved, &10_000_000);
+    assert_eq!(client.get_depositor_count(), 2);
+    assert!(!client.is_pre_registered(&reserved));
+    assert_eq!(client.get_pre_registrations().len(), 0);
+}
+
+#[test]
+fn test_expired_pre_registration_is_reclaimed() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let reserved = Address::generate(&env);
+    let latecomer = Address::generate(&env);
+    mint_token(&env, &usdc_token, &reserved, 10_000_000);
+    mint_token(&env, &usdc_token, &latecomer, 10_000_000);
+
+    client.set_max_depositors(&1);
+    client.pre_register(&reserved);
+    assert!(client.try_deposit(&latecomer, &10_000_000).is_err());
+
+    env.ledger()
+        .with_mut(|li| li.timestamp += PRE_REGISTRATION_TTL);
+    assert!(!client.is_pre_registered(&reserved));
+
+    // The unused reservation no longer holds the slot
+    client.deposit(&latecomer, &10_000_000);
+    assert_eq!(client.get_depositor_count(), 1);
+    assert!(client.try_deposit(&reserved, &10_000_000).is_err());
+}
+
+#[test]
+#[should_panic(expected = "Already a depositor")]
+fn test_pre_register_existing_depositor_fails() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 10_000_000);
+    client.deposit(&user, &10_000_000);
+    client.pre_register(&user);
+}
+
+// ============================================================================
+// FEATURE FLAG TESTS
+// ============================================================================
+
+#[test]
+fn test_feature_flags_toggle_lockups() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let locker = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 10_000_000);
+    client.deposit(&user, &10_000_000);
+    assert_eq!(client.get_feature_flags(), ALL_FEATURES);
+
+    client.lock_shares(&user, &1_000_000, &locker);
+
+    client.set_feature_flags(&(ALL_FEATURES & !FEATURE_LOCKUPS));
+    assert_eq!(client.get_feature_flags() & FEATURE_LOCKUPS, 0);
+    assert!(client.try_lock_shares(&user, &1_000_000, &locker).is_err());
+
+    // Existing locks can still be released, and other features are unaffected
+    client.unlock_shares(&locker, &user, &1_000_000);
+    assert_eq!(client.get_locked_shares(&user), 0);
+    client.split_position(&user, &locker, &1_000_000);
+
+    client.set_feature_flags(&ALL_FEATURES);
+    client.lock_shares(&user, &1_000_000, &locker);
+    assert_eq!(client.get_locked_shares(&user), 1_000_000);
+}
+
+#[test]
+fn test_feature_flags_gate_splits_and_pre_registration() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let other = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 10_000_000);
+    client.deposit(&user, &10_000_000);
+
+    client.set_feature_flags(&0);
+    assert!(client
+        .try_split_position(&user, &other, &1_000_000)
+        .is_err());
+    assert!(client.try_pre_register(&other).is_err());
+
+    client.set_feature_flags(&(FEATURE_SPLITS | FEATURE_PRE_REGISTRATION));
+    client.split_position(&user, &other, &1_000_000);
+    client.pre_register(&Address::generate(&env));
+}
+
+#[test]
+#[should_panic(expected = "Invalid feature flags")]
+fn test_set_unknown_feature_flag_fails() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    client.set_feature_flags(&(ALL_FEATURES + 1));
+}
+
+// ============================================================================
+// SHARE DELTA TESTS
+// ============================================================================
+
+#[test]
+fn test_shares_delta_since_ledger() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 100_000_000);
+
+    env.ledger().with_mut(|li| li.sequence_number = 100);
+    client.deposit(&user, &10_000_000);
+    let shares_at_100 = client.get_shares(&user);
+
+    env.ledger().with_mut(|li| li.sequence_number = 110);
+    client.deposit(&user, &20_000_000);
+    client.deposit(&user, &5_000_000);
+    let shares_at_110 = client.get_shares(&user);
+
+    env.ledger().with_mut(|li| li.sequence_number = 120);
+    client.withdraw(&user, &15_000_000);
+    let shares_now = client.get_shares(&user);
+
+    assert_eq!(client.shares_delta_since(&user, &100), shares_now);
+    assert_eq!(
+        client.shares_delta_since(&user, &105),
+        shares_now - shares_at_100
+    );
+    assert_eq!(
+        client.shares_delta_since(&user, &110),
+        shares_now - shares_at_100
+    );
+    assert_eq!(
+        client.shares_delta_since(&user, &111),
+        shares_now - shares_at_110
+    );
+    assert!(client.shares_delta_since(&user, &120) < 0);
+    assert_eq!(client.shares_delta_since(&user, &121), 0);
+}
+
+// ============================================================================
+// GLOBAL SHARE GUARD TESTS
+// ============================================================================
+
+#[test]
+#[should_panic(expected = "Exceeds total shares")]
+fn test_split_with_corrupted_share_balance_fails() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 10_000_000);
+    client.deposit(&user, &10_000_000);
+
+    // Inflate the user's balance past what TotalShares records
+    let total_shares = client.get_total_shares();
+    env.as_contract(&contract_id, || {
+        env.storage()
+            .persistent()
+            .set(&DataKey::Shares(user.clone()), &(total_shares * 3));
+    });
+
+    client.split_position(&user, &Address::generate(&env), &(total_shares * 2));
+}
+
+// ============================================================================
+// RESERVE STRATEGY TESTS
+// ============================================================================
+
+#[contract]
+pub struct MockMoneyMarket;
+
+#[contractimpl]
+impl MockMoneyMarket {
+    pub fn set_token(env: Env, token: Address) {
+        env.storage()
+            .instance()
+            .set(&symbol_short!("token"), &token);
+    }
+
+    /// Makes every redemption deliver `haircut` less than requested.
+    pub fn set_haircut(env: Env, haircut: i128) {
+        env.storage()
+            .instance()
+            .set(&symbol_short!("haircut"), &haircut);
+    }
+
+    pub fn supplied(env: Env, account: Address) -> i128 {
+        env.storage().instance().get(&account).unwrap_or(0)
+    }
+
+    pub fn supply(env: Env, from: Address, amount: i128) {
+        let supplied = Self::supplied(env.clone(), from.clone());
+        env.storage().instance().set(&from, &(supplied + amount));
+    }
+
+    pub fn redeem(env: Env, to: Address, amount: i128) {
+        let supplied = Self::supplied(env.clone(), to.clone());
+        env.storage().instance().set(&to, &(supplied - amount));
+        let token: Address = env
+            .storage()
+            .instance()
+            .get(&symbol_short!("token"))
+            .unwrap();
+        let haircut: i128 = env
+            .storage()
+            .instance()
+            .get(&symbol_short!("haircut"))
+            .unwrap_or(0);
+        token::Client::new(&env, &token).transfer(
+            &env.current_contract_address(),
+            &to,
+            &(amount - haircut),
+        );
+    }
+}
+
+fn setup_money_market(env: &Env, usdc_token: &Address) -> Address {
+    let market = env.register_contract(None, MockMoneyMarket);
+    MockMoneyMarketClient::new(env, &market).set_token(usdc_token);
+    market
+}
+
+#[test]
+fn test_reserve_is_parked_and_pulled_around_withdrawals() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let usdc = token::Client::new(&env, &usdc_token);
+    let market = setup_money_market(&env, &usdc_token);
+    let market_client = MockMoneyMarketClient::new(&env, &market);
+
+    client.set_reserve_buffer_bps(&2_000);
+    client.set_reserve_strategy(&Some(market.clone()));
+    assert_eq!(client.get_reserve_strategy(), Some(market.clone()));
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 10_000_000);
+    client.deposit(&user, &10_000_000);
+
+    // 20% of the reserve stays liquid, the rest is parked
+    assert_eq!(usdc.balance(&contract_id), 2_000_000);
+    assert_eq!(usdc.balance(&market), 8_000_000);
+    assert_eq!(client.get_reserve_deployed(), 8_000_000);
+    assert_eq!(market_client.supplied(&contract_id), 8_000_000);
+    assert!(client.proof_of_reserves().reconciled);
+
+    // The shortfall is pulled for the withdrawal, then the buffer is restored
+    client.withdraw(&user, &5_000_000);
+    assert_eq!(usdc.balance(&user), 5_000_000);
+    assert_eq!(usdc.balance(&contract_id), 1_000_000);
+    assert_eq!(client.get_reserve_deployed(), 4_000_000);
+    assert_eq!(market_client.supplied(&contract_id), 4_000_000);
+    assert_eq!(client.get_backing_ratio_bps(), 10_000);
+
+    // The full position can still exit
+    client.withdraw(&user, &5_000_000);
+    assert_eq!(usdc.balance(&user), 10_000_000);
+    assert_eq!(client.get_reserve_deployed(), 0);
+}
+
+#[test]
+fn test_clearing_reserve_strategy_recalls_parked_funds() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let usdc = token::Client::new(&env, &usdc_token);
+    let market = setup_money_market(&env, &usdc_token);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 10_000_000);
+    client.deposit(&user, &10_000_000);
+
+    // Setting the strategy parks the existing idle reserve
+    client.set_reserve_strategy(&Some(market.clone()));
+    assert_eq!(usdc.balance(&contract_id), 0);
+    assert_eq!(client.get_reserve_deployed(), 10_000_000);
+
+    client.set_reserve_strategy(&None);
+    assert_eq!(client.get_reserve_strategy(), None);
+    assert_eq!(usdc.balance(&contract_id), 10_000_000);
+    assert_eq!(usdc.balance(&market), 0);
+    assert_eq!(client.get_reserve_deployed(), 0);
+}
+
+#[test]
+#[should_panic(expected = "Reserve pull mismatch")]
+fn test_short_reserve_pull_fails() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let market = setup_money_market(&env, &usdc_token);
+    client.set_reserve_strategy(&Some(market.clone()));
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 10_000_000);
+    client.deposit(&user, &10_000_000);
+
+    MockMoneyMarketClient::new(&env, &market).set_haircut(&1);
+    client.withdraw(&user, &5_000_000);
+}
+
+// ============================================================================
+// SUMMARY TESTS
+// ============================================================================
+
+#[test]
+fn test_summary_matches_standalone_getters() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, strategy) = setup_vault_with_strategy(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 10_000_000);
+    client.deposit(&user, &10_000_000);
+    client.rebalance_funds(&strategy, &3_000_000);
+    client.request_withdrawal(&user, &1_000_000);
+    env.ledger().with_mut(|li| li.timestamp += 120);
+    client.update_total_assets(&16_000_000);
+    env.ledger().with_mut(|li| li.timestamp += 30);
+
+    let summary = client.get_summary();
+    assert_eq!(summary.config, client.get_config());
+
+    let stats = summary.stats;
+    assert_eq!(stats.total_assets, client.get_total_deposits());
+    assert_eq!(stats.total_shares, client.get_total_shares());
+    assert_eq!(stats.share_price, client.get_share_price());
+    assert_eq!(stats.depositor_count, client.get_depositor_count());
+    assert_eq!(stats.total_deployed, client.get_total_deployed());
+    assert_eq!(stats.reserve_deployed, client.get_reserve_deployed());
+    assert_eq!(
+        (
+            stats.pending_withdrawal_shares,
+            stats.pending_withdrawal_assets
+        ),
+        client.get_total_pending_withdrawals()
+    );
+    assert_eq!(stats.total_assets, 16_000_000);
+    assert_eq!(stats.total_deployed, 3_000_000);
+    assert_eq!(stats.pending_withdrawal_shares, 1_000_000);
+
+    let health = summary.health;
+    assert_eq!(health.backing_ratio_bps, client.get_backing_ratio_bps());
+    assert_eq!(health.underwater, client.is_underwater());
+    assert_eq!(health.winding_down, client.is_winding_down());
+    assert_eq!(health.seconds_since_yield, client.get_seconds_since_yield());
+    assert_eq!(health.seconds_since_yield, 30);
+}
+
+// ============================================================================
+// ACCRUAL DELAY TESTS
+// ============================================================================
+
+/// Deposits 10 USDC for an early and a just-in-time user, `gap` seconds
+/// before a 10 USDC gain is reported.
+fn setup_gain_after_late_deposit(
+    env: &Env,
+    delay: u64,
+    gap: u64,
+) -> (NeuroWealthVaultClient<'_>, Address, Address) {
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(env);
+    let client = NeuroWealthVaultClient::new(env, &contract_id);
+    client.set_accrual_delay(&delay);
+
+    let early = Address::generate(env);
+    let late = Address::generate(env);
+    mint_token(env, &usdc_token, &early, 10_000_000);
+    mint_token(env, &usdc_token, &late, 10_000_000);
+
+    client.deposit(&early, &10_000_000);
+    env.ledger().with_mut(|li| li.timestamp += 10_000);
+    client.deposit(&late, &10_000_000);
+    env.ledger().with_mut(|li| li.timestamp += gap);
+    client.update_total_assets(&30_000_000);
+
+    (client, early, late)
+}
+
+#[test]
+fn test_just_in_time_deposit_does_not_capture_gain() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, early, late) = setup_gain_after_late_deposit(&env, 3_600, 10);
+    assert_eq!(client.get_accrual_delay(), 3_600);
+
+    // The late deposit keeps its value, less rounding; the early one receives
+    // the gain the virtual offset does not keep
+    assert_eq!(client.get_balance(&late), 9_999_998);
+    assert_eq!(client.get_balance(&early), 19_990_010);
+    assert_eq!(client.get_total_deposits(), 30_000_000);
+    assert_eq!(
+        client.get_shares(&early) + client.get_shares(&late),
+        client.get_total_shares()
+    );
+
+    let events = env.events().all();
+    let excluded = events.iter().find_map(|(_, topics, data)| {
+        let topic: Symbol = topics.get(0)?.into_val(&env);
+        (topic == symbol_short!("acr_excl")).then(|| {
+            let event: AccrualExcludedEvent = data.into_val(&env);
+            event
+        })
+    });
+    let excluded = excluded.unwrap();
+    assert_eq!(excluded.excluded_shares, 10_000_000);
+    assert_eq!(excluded.burned_shares, 4_997_502);
+
+    // Later gains are shared pro rata again
+    env.ledger().with_mut(|li| li.timestamp += 3_600);
+    client.update_total_assets(&45_000_000);
+    assert_eq!(client.get_balance(&late), 14_998_332);
+    assert_eq!(client.get_balance(&early), 29_981_685);
+}
+
+#[test]
+fn test_deposit_outside_accrual_delay_shares_gain() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, early, late) = setup_gain_after_late_deposit(&env, 3_600, 3_600);
+    assert_eq!(client.get_balance(&late), 14_997_501);
+    assert_eq!(client.get_balance(&early), 14_997_501);
+}
+
+#[test]
+fn test_accrual_delay_disabled_by_default() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, early, late) = setup_gain_after_late_deposit(&env, 0, 10);
+    assert_eq!(client.get_balance(&late), 14_997_501);
+    assert_eq!(client.get_balance(&early), 14_997_501);
+}
+
+// ============================================================================
+// ROLE TESTS
+// ============================================================================
+
+#[test]
+fn test_is_role_identifies_each_configured_address() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _agent, owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let agent = Address::generate(&env);
+    let pauser = Address::generate(&env);
+    let user = Address::generate(&env);
+    client.update_agent(&agent);
+    client.set_pauser(&Some(pauser.clone()));
+    assert_eq!(client.get_pauser(), Some(pauser.clone()));
+
+    let roles = [
+        symbol_short!("owner"),
+        symbol_short!("agent"),
+        symbol_short!("pauser"),
+        symbol_short!("user"),
+    ];
+    let expected = [
+        (&owner, [true, false, false, false]),
+        (&agent, [false, true, false, false]),
+        (&pauser, [false, false, true, false]),
+        (&user, [false, false, false, true]),
+    ];
+    for (addr, flags) in expected {
+        for (role, flag) in roles.iter().zip(flags) {
+            assert_eq!(client.is_role(addr, role), flag);
+        }
+    }
+
+    // Multi-sig signers are owners too
+    let signer = Address::generate(&env);
+    client.set_owners(&Vec::from_array(&env, [owner.clone(), signer.clone()]), &1);
+    assert!(client.is_role(&signer, &symbol_short!("owner")));
+
+    client.set_pauser(&None);
+    assert!(client.is_role(&pauser, &symbol_short!("user")));
+}
+
+#[test]
+fn test_pauser_can_pause_but_not_unpause() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _agent, owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let pauser = Address::generate(&env);
+    assert!(client.try_pause(&pauser).is_err());
+
+    client.set_pauser(&Some(pauser.clone()));
+    client.pause(&pauser);
+    assert!(client.is_paused());
+    assert!(client.try_unpause(&pauser).is_err());
+
+    client.unpause(&owner);
+    client.emergency_pause(&pauser);
+    assert!(client.is_paused());
+}
+
+#[test]
+#[should_panic(expected = "Unknown role")]
+fn test_is_role_with_unknown_role_fails() {
+    let env = Env::default();
+    let (contract_id, agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    client.is_role(&agent, &symbol_short!("admin"));
+}
+
+// ============================================================================
+// DUST SWEEP TESTS
+// ============================================================================
+
+#[test]
+fn test_withdraw_leaving_dust_sweeps_full_position() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let usdc = token::Client::new(&env, &usdc_token);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 10_000_000);
+    client.deposit(&user, &10_000_000);
+    client.set_dust_share_threshold(&500_000);
+    assert_eq!(client.get_dust_share_threshold(), 500_000);
+
+    // Leaving 400_000 shares falls under the threshold
+    client.withdraw(&user, &9_600_000);
+    assert_eq!(client.get_shares(&user), 0);
+    assert_eq!(usdc.balance(&user), 10_000_000);
+    assert_eq!(client.get_total_shares(), 0);
+    assert_eq!(client.get_total_deposits(), 0);
+    assert_eq!(client.get_depositor_count(), 0);
+
+    let events = env.events().all();
+    let (_, _, data) = events.last().unwrap();
+    let event: WithdrawEvent = data.into_val(&env);
+    assert_eq!(event.amount, 10_000_000);
+    assert_eq!(event.shares, 10_000_000);
+}
+
+#[test]
+fn test_withdraw_leaving_shares_above_threshold_does_not_sweep() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let usdc = token::Client::new(&env, &usdc_token);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 10_000_000);
+    client.deposit(&user, &10_000_000);
+    client.set_dust_share_threshold(&500_000);
+
+    client.withdraw(&user, &9_500_000);
+    assert_eq!(client.get_shares(&user), 500_000);
+    assert_eq!(usdc.balance(&user), 9_500_000);
+
+    // Locked remainders are never swept
+    client.lock_shares(&user, &100_000, &Address::generate(&env));
+    client.withdraw(&user, &100_000);
+    assert_eq!(client.get_shares(&user), 400_000);
+}
+
+// ============================================================================
+// YIELD EARNED TESTS
+// ============================================================================
+
+#[test]
+fn test_yield_earned_tracks_assets_update() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    mint_token(&env, &usdc_token, &alice, 6_000_000);
+    mint_token(&env, &usdc_token, &bob, 2_000_000);
+    client.deposit(&alice, &6_000_000);
+    client.deposit(&bob, &2_000_000);
+    assert_eq!(client.get_yield_earned(&alice), 0);
+
+    // A 1 USDC gain splits 3:1 with the holdings, after the virtual offset
+    // keeps its sliver
+    client.update_total_assets(&9_000_000);
+    assert_eq!(client.get_yield_earned(&alice), 749_063);
+    assert_eq!(client.get_yield_earned(&bob), 249_687);
+    assert_eq!(
+        client.get_yield_earned(&alice),
+        client.get_pnl(&alice).unrealized
+    );
+}
+
+#[test]
+fn test_yield_earned_is_zero_while_underwater() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 4_000_000);
+    client.deposit(&user, &4_000_000);
+
+    client.update_total_assets(&3_000_000);
+    assert!(client.is_underwater());
+    assert_eq!(client.get_pnl(&user).unrealized, -1_000_000);
+    assert_eq!(client.get_yield_earned(&user), 0);
+}
+
+// ============================================================================
+// BATCH BALANCE TESTS
+// ============================================================================
+
+#[test]
+fn test_get_balances_matches_single_balances() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    let carol = Address::generate(&env);
+    mint_token(&env, &usdc_token, &alice, 3_000_000);
+    mint_token(&env, &usdc_token, &carol, 1_000_000);
+    client.deposit(&alice, &3_000_000);
+    client.deposit(&carol, &1_000_000);
+    client.update_total_assets(&4_100_000);
+
+    let users = Vec::from_array(&env, [alice.clone(), bob.clone(), carol, alice]);
+    let balances = client.get_balances(&users);
+    assert_eq!(balances.len(), users.len());
+    for (i, user) in users.iter().enumerate() {
+        assert_eq!(balances.get(i as u32).unwrap(), client.get_balance(&user));
+    }
+    assert_eq!(balances.get(1).unwrap(), 0);
+}
+
+#[test]
+#[should_panic(expected = "Batch too large")]
+fn test_get_balances_rejects_oversized_input() {
+    let env = Env::default();
+    let (contract_id, _agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let mut users = Vec::new(&env);
+    for _ in 0..=MAX_BATCH_SIZE {
+        users.push_back(Address::generate(&env));
+    }
+    client.get_balances(&users);
+}
+
+// ============================================================================
+// TOTAL PRINCIPAL TESTS
+// ============================================================================
+
+#[test]
+fn test_total_principal_tracks_net_deposits_not_yield() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    mint_token(&env, &usdc_token, &alice, 6_000_000);
+    mint_token(&env, &usdc_token, &bob, 2_000_000);
+    client.deposit(&alice, &6_000_000);
+    client.deposit(&bob, &2_000_000);
+    assert_eq!(client.get_total_principal(), 8_000_000);
+    assert_eq!(client.get_total_deposits(), 8_000_000);
+
+    // Yield grows total assets but not principal
+    mint_token(&env, &usdc_token, &contract_id, 8_000_000);
+    client.update_total_assets(&16_000_000);
+    assert_eq!(client.get_total_principal(), 8_000_000);
+    assert_eq!(client.get_total_deposits(), 16_000_000);
+
+    // Withdrawing 4 USDC at a nearly doubled price removes just over 2 USDC
+    // of principal
+    client.withdraw(&alice, &4_000_000);
+    assert_eq!(client.get_total_principal(), 5_998_750);
+    assert_eq!(client.get_total_deposits(), 12_000_000);
+
+    // A queued withdrawal leaves principal when it is requested
+    client.request_withdrawal(&bob, &1_000_000);
+    assert_eq!(client.get_total_principal(), 4_998_750);
+}
+
+// ============================================================================
+// PER-LEDGER DEPOSIT CAP TESTS
+// ============================================================================
+
+#[test]
+fn test_ledger_deposit_cap_resets_each_ledger() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    mint_token(&env, &usdc_token, &alice, 20_000_000);
+    mint_token(&env, &usdc_token, &bob, 20_000_000);
+
+    assert_eq!(client.get_ledger_deposit_remaining(), i128::MAX);
+    client.set_max_deposit_per_ledger(&10_000_000);
+    assert_eq!(client.get_ledger_deposit_remaining(), 10_000_000);
+
+    // Deposits from different users share the same ledger budget
+    client.deposit(&alice, &6_000_000);
+    client.deposit(&bob, &4_000_000);
+    assert_eq!(client.get_ledger_deposit_remaining(), 0);
+    assert!(client.try_deposit(&alice, &1_000_000).is_err());
+
+    env.ledger().with_mut(|li| li.sequence_number += 1);
+    assert_eq!(client.get_ledger_deposit_remaining(), 10_000_000);
+    client.deposit(&alice, &10_000_000);
+    assert_eq!(client.get_total_deposits(), 20_000_000);
+}
+
+#[test]
+#[should_panic(expected = "Exceeds ledger deposit cap")]
+fn test_deposit_above_ledger_cap_fails() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 20_000_000);
+    client.set_max_deposit_per_ledger(&10_000_000);
+    client.deposit(&user, &10_000_001);
+}
+
+// ============================================================================
+// WITHDRAWAL WAIT ESTIMATE TESTS
+// ============================================================================
+
+#[test]
+fn test_estimate_wait_grows_with_queue_depth() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, strategy) = setup_vault_with_strategy(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    client.set_withdrawal_cooldown(&3_600);
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+
+    let users = [
+        Address::generate(&env),
+        Address::generate(&env),
+        Address::generate(&env),
+    ];
+    for user in users.iter() {
+        mint_token(&env, &usdc_token, user, 5_000_000);
+        client.deposit(user, &5_000_000);
+    }
+    // 20 USDC of assets, 8 USDC of it liquid
+    client.rebalance_funds(&strategy, &12_000_000);
+
+    // An empty queue only waits out the cooldown
+    assert_eq!(client.estimate_wait(&2_000_000), 4_600);
+
+    // Each queued request ahead consumes liquidity for later requests
+    client.request_withdrawal(&users[0], &3_000_000);
+    assert_eq!(client.estimate_wait(&2_000_000), 4_600);
+    client.request_withdrawal(&users[1], &3_000_000);
+    assert_eq!(client.estimate_wait(&2_000_000), 4_600);
+    client.request_withdrawal(&users[2], &1_000_000);
+    assert_eq!(client.estimate_wait(&2_000_000), u64::MAX);
+    assert_eq!(client.estimate_wait(&1_000_000), 4_600);
+
+    // Settling the queue ahead restores the cooldown-only estimate
+    env.ledger().with_mut(|li| li.timestamp = 4_600);
+    client.process_withdrawals(&3);
+    assert_eq!(client.estimate_wait(&1_000_000), 8_200);
+}
+
+// ============================================================================
+// PRICE MILESTONE TESTS
+// ============================================================================
+
+/// Returns the `PriceMilestoneEvent`s recorded in the environment's event log.
+fn price_milestones(env: &Env) -> Vec<PriceMilestoneEvent> {
+    let mut milestones = Vec::new(env);
+    for (_, topics, data) in env.events().all().iter() {
+        let topic: Symbol = topics.get(0).unwrap().into_val(env);
+        if topic == symbol_short!("price_ms") {
+            milestones.push_back(data.into_val(env));
+        }
+    }
+    milestones
+}
+
+#[test]
+fn test_price_milestones_fire_only_on_step_crossings() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 10_000_000);
+    client.deposit(&user, &10_000_000);
+    client.set_price_step_bps(&500);
+    assert_eq!(client.get_price_step_bps(), 500);
+    assert_eq!(client.get_last_price_milestone(), 10_000_000);
+
+    // A 3% move stays inside the 5% step
+    client.update_total_assets(&10_300_000);
+    assert_eq!(price_milestones(&env).len(), 0);
+
+    // Crossing 1.05 emits a single milestone
+    client.update_total_assets(&10_600_000);
+    let events = price_milestones(&env);
+    assert_eq!(events.len(), 1);
+    let event = events.get(0).unwrap();
+    assert_eq!(event.previous_milestone, 10_000_000);
+    assert_eq!(event.milestone, 10_500_000);
+    assert_eq!(event.share_price, 10_600_000);
+    assert_eq!(client.get_last_price_milestone(), 10_500_000);
+
+    // The next step is measured from the new milestone
+    client.update_total_assets(&10_900_000);
+    assert_eq!(price_milestones(&env).len(), 1);
+
+    // A drop over several steps lands on the furthest boundary crossed
+    client.update_total_assets(&9_000_000);
+    let events = price_milestones(&env);
+    assert_eq!(events.len(), 2);
+    assert_eq!(events.get(1).unwrap().milestone, 9_450_000);
+    assert_eq!(client.get_last_price_milestone(), 9_450_000);
+}
+
+#[test]
+fn test_price_milestones_disabled_by_default() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 10_000_000);
+    client.deposit(&user, &10_000_000);
+
+    client.update_total_assets(&20_000_000);
+    assert_eq!(price_milestones(&env).len(), 0);
+}
+
+// ============================================================================
+// ROLE CONFLATION TESTS
+// ============================================================================
+
+/// Returns the `RoleConflationEvent`s recorded in the environment's event log.
+fn role_conflations(env: &Env) -> Vec<RoleConflationEvent> {
+    let mut conflations = Vec::new(env);
+    for (_, topics, data) in env.events().all().iter() {
+        let topic: Symbol = topics.get(0).unwrap().into_val(env);
+        if topic == symbol_short!("roles_cnf") {
+            conflations.push_back(data.into_val(env));
+        }
+    }
+    conflations
+}
+
+#[test]
+fn test_roles_conflated_flagged_at_init_and_cleared_by_split() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, agent, owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    assert!(client.roles_conflated());
+    let events = role_conflations(&env);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events.get(0).unwrap().address, agent);
+
+    client.update_agent(&Address::generate(&env));
+    assert!(!client.roles_conflated());
+    assert_eq!(role_conflations(&env).len(), 1);
+
+    // Handing the agent role back to the owner warns again
+    client.update_agent(&owner);
+    assert!(client.roles_conflated());
+    assert_eq!(role_conflations(&env).len(), 2);
+}
+
+// ============================================================================
+// REDEEM TO STRATEGY TESTS
+// ============================================================================
+
+#[contract]
+pub struct MockPartnerStrategy;
+
+#[contractimpl]
+impl MockPartnerStrategy {
+    pub fn position(env: Env, beneficiary: Address) -> i128 {
+        env.storage().instance().get(&beneficiary).unwrap_or(0)
+    }
+
+    /// Credits two strategy units per USDC unit received.
+    pub fn deposit_for(env: Env, beneficiary: Address, amount: i128) -> i128 {
+        let units = amount * 2;
+        let position = Self::position(env.clone(), beneficiary.clone());
+        env.storage()
+            .instance()
+            .set(&beneficiary, &(position + units));
+        units
+    }
+}
+
+#[test]
+fn test_redeem_to_strategy_burns_shares_and_funds_strategy() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let usdc = token::Client::new(&env, &usdc_token);
+    let strategy = env.register_contract(None, MockPartnerStrategy);
+    let strategy_client = MockPartnerStrategyClient::new(&env, &strategy);
+    client.register_strategy(&strategy);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 10_000_000);
+    client.deposit(&user, &10_000_000);
+    let shares = client.get_shares(&user);
+
+    let receipt = client.redeem_to_strategy(&user, &(shares / 4), &strategy);
+    assert_eq!(receipt.strategy, strategy);
+    assert_eq!(receipt.shares, shares / 4);
+    assert_eq!(receipt.amount, 2_500_000);
+    assert_eq!(receipt.strategy_units, 5_000_000);
+
+    assert_eq!(client.get_shares(&user), shares - shares / 4);
+    assert_eq!(client.get_total_shares(), shares - shares / 4);
+    assert_eq!(client.get_total_deposits(), 7_500_000);
+    assert_eq!(usdc.balance(&strategy), 2_500_000);
+    assert_eq!(usdc.balance(&user), 0);
+    assert_eq!(strategy_client.position(&user), 5_000_000);
+}
+
+#[test]
+#[should_panic(expected = "Strategy not registered")]
+fn test_redeem_to_strategy_rejects_unregistered_strategy() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let strategy = env.register_contract(None, MockPartnerStrategy);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 10_000_000);
+    client.deposit(&user, &10_000_000);
+
+    client.redeem_to_strategy(&user, &1_000, &strategy);
+}
+
+#[test]
+fn test_redeem_to_strategy_respects_withdraw_cap_and_pause_grace() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, owner) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let strategy = env.register_contract(None, MockPartnerStrategy);
+    client.register_strategy(&strategy);
+    client.set_max_withdraw_per_tx(&2_000_000);
+    client.set_pause_grace_seconds(&600);
+
+    let early = Address::generate(&env);
+    let late = Address::generate(&env);
+    mint_token(&env, &usdc_token, &early, 10_000_000);
+    mint_token(&env, &usdc_token, &late, 10_000_000);
+    env.ledger().with_mut(|l| l.timestamp = 1_000);
+    client.deposit(&early, &10_000_000);
+    env.ledger().with_mut(|l| l.timestamp = 5_000);
+    client.deposit(&late, &10_000_000);
+
+    let over_cap = client.try_redeem_to_strategy(&late, &3_000_000, &strategy);
+    assert!(over_cap.is_err());
+
+    // A recent depositor can still exit to a strategy during the grace
+    client.pause(&owner);
+    client.redeem_to_strategy(&late, &2_000_000, &strategy);
+    assert!(client
+        .try_redeem_to_strategy(&early, &1_000_000, &strategy)
+        .is_err());
+}
+
+// ============================================================================
+// CONCENTRATION TESTS
+// ============================================================================
+
+#[test]
+fn test_largest_holder_and_concentration() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    assert_eq!(client.get_concentration_bps(), 0);
+
+    let small = Address::generate(&env);
+    let whale = Address::generate(&env);
+    let medium = Address::generate(&env);
+    mint_token(&env, &usdc_token, &small, 1_000_000);
+    mint_token(&env, &usdc_token, &whale, 6_000_000);
+    mint_token(&env, &usdc_token, &medium, 3_000_000);
+    client.deposit(&small, &1_000_000);
+    client.deposit(&whale, &6_000_000);
+    client.deposit(&medium, &3_000_000);
+
+    let (holder, shares) = client.get_largest_holder();
+    assert_eq!(holder, whale);
+    assert_eq!(shares, client.get_shares(&whale));
+    assert_eq!(client.get_concentration_bps(), 6_000);
+
+    // The whale exits most of its position; the medium holder takes the lead
+    client.withdraw(&whale, &5_000_000);
+    let (holder, _) = client.get_largest_holder();
+    assert_eq!(holder, medium);
+    assert_eq!(client.get_concentration_bps(), 6_000);
+}
+
+#[test]
+#[should_panic(expected = "No shares outstanding")]
+fn test_largest_holder_panics_on_empty_vault() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    client.get_largest_holder();
+}
+
+// ============================================================================
+// AUTO-UNPAUSE TESTS
+// ============================================================================
+
+#[test]
+fn test_auto_unpause_waits_for_loss_free_window() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, owner) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 10_000_000);
+    client.deposit(&user, &10_000_000);
+
+    client.set_auto_unpause_window(&3_600);
+    assert_eq!(client.get_auto_unpause_window(), 3_600);
+
+    env.ledger().with_mut(|l| l.timestamp = 1_000);
+    client.pause(&owner);
+
+    env.ledger().with_mut(|l| l.timestamp = 3_000);
+    assert!(!client.try_auto_unpause());
+
+    // A loss restarts the window
+    client.update_total_assets(&9_000_000);
+    env.ledger().with_mut(|l| l.timestamp = 4_600);
+    assert!(!client.try_auto_unpause());
+    assert!(client.is_paused());
+
+    env.ledger().with_mut(|l| l.timestamp = 6_600);
+    assert!(client.try_auto_unpause());
+    assert!(!client.is_paused());
+    assert!(!client.try_auto_unpause());
+}
+
+#[test]
+fn test_auto_unpause_ignores_emergency_pause_and_disabled_window() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, owner, _) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    // Disabled by default
+    client.pause(&owner);
+    env.ledger().with_mut(|l| l.timestamp = 100_000);
+    assert!(!client.try_auto_unpause());
+
+    client.set_auto_unpause_window(&60);
+    client.emergency_pause(&owner);
+    env.ledger().with_mut(|l| l.timestamp = 200_000);
+    assert!(!client.try_auto_unpause());
+    assert!(client.is_paused());
+}
+
+// ============================================================================
+// PROTECTED PRINCIPAL TESTS
+// ============================================================================
+
+#[test]
+fn test_protected_principal_tracks_net_deposits_through_yield_swings() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 20_000_000);
+    mint_token(&env, &usdc_token, &contract_id, 20_000_000);
+    client.deposit(&user, &10_000_000);
+    assert_eq!(client.get_protected_principal(&user), 10_000_000);
+
+    // Yield and losses leave the principal untouched
+    client.update_total_assets(&15_000_000);
+    assert_eq!(client.get_protected_principal(&user), 10_000_000);
+    client.update_total_assets(&8_000_000);
+    assert_eq!(client.get_protected_principal(&user), 10_000_000);
+
+    client.deposit(&user, &4_000_000);
+    assert_eq!(client.get_protected_principal(&user), 14_000_000);
+
+    client.withdraw(&user, &5_000_000);
+    assert_eq!(client.get_protected_principal(&user), 9_000_000);
+
+    // Withdrawing gains beyond the principal floors it at zero
+    client.update_total_assets(&20_000_000);
+    client.withdraw(&user, &client.get_balance(&user));
+    assert_eq!(client.get_protected_principal(&user), 0);
+}
+
+#[test]
+fn test_protected_principal_moves_with_split() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let heir = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 10_000_000);
+    client.deposit(&user, &10_000_000);
+
+    client.split_position(&user, &heir, &(client.get_shares(&user) / 4));
+    assert_eq!(client.get_protected_principal(&user), 7_500_000);
+    assert_eq!(client.get_protected_principal(&heir), 2_500_000);
+}
+
+// ============================================================================
+// GUARDIAN RECOVERY TESTS
+// ============================================================================
+
+/// Registers a vault initialized with a guardian and a one day recovery delay.
+///
+/// Returns (contract_id, usdc_token, owner, guardian, recovery_address).
+fn setup_vault_with_guardian(env: &Env) -> (Address, Address, Address, Address, Address) {
+    let contract_id = env.register_contract(None, NeuroWealthVault);
+    let client = NeuroWealthVaultClient::new(env, &contract_id);
+
+    let agent = Address::generate(env);
+    let guardian = Address::generate(env);
+    let recovery_address = Address::generate(env);
+    let token_admin = Address::generate(env);
+    let usdc_token = env
+        .register_stellar_asset_contract_v2(token_admin)
+        .address();
+
+    client.initialize_with_guardian(&agent, &usdc_token, &guardian, &recovery_address, &86_400);
+
+    (contract_id, usdc_token, agent, guardian, recovery_address)
+}
+
+#[test]
+fn test_guardian_recovery_executes_after_timelock() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _owner, guardian, recovery_address) =
+        setup_vault_with_guardian(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let usdc = token::Client::new(&env, &usdc_token);
+    assert_eq!(client.get_guardian(), Some(guardian.clone()));
+    assert_eq!(
+        client.get_recovery_address(),
+        Some(recovery_address.clone())
+    );
+    assert_eq!(client.get_recovery_delay(), 86_400);
+    assert!(client.is_role(&guardian, &symbol_short!("guardian")));
+    assert!(!client.is_role(&guardian, &symbol_short!("user")));
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 10_000_000);
+    client.deposit(&user, &10_000_000);
+
+    env.ledger().with_mut(|l| l.timestamp = 1_000);
+    let ready_at = client.guardian_recover(&recovery_address);
+    assert_eq!(ready_at, 87_400);
+    assert_eq!(client.get_pending_recovery(), Some(87_400));
+
+    env.ledger().with_mut(|l| l.timestamp = 87_400);
+    assert_eq!(client.execute_guardian_recovery(), 10_000_000);
+    assert_eq!(usdc.balance(&recovery_address), 10_000_000);
+    assert_eq!(usdc.balance(&contract_id), 0);
+    assert!(client.is_paused());
+    assert_eq!(client.get_pending_recovery(), None);
+    // Share balances remain as the record of what users are owed
+    assert_eq!(client.get_shares(&user), 10_000_000);
+}
+
+#[test]
+#[should_panic(expected = "Recovery timelock active")]
+fn test_guardian_recovery_cannot_execute_early() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _usdc_token, _owner, _guardian, recovery_address) =
+        setup_vault_with_guardian(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    client.guardian_recover(&recovery_address);
+    env.ledger().with_mut(|l| l.timestamp += 86_399);
+    client.execute_guardian_recovery();
+}
+
+#[test]
+fn test_only_guardian_can_start_recovery() {
+    let env = Env::default();
+
+    let (contract_id, _usdc_token, owner, guardian, recovery_address) =
+        setup_vault_with_guardian(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let result = client
+        .mock_auths(&[MockAuth {
+            address: &owner,
+            invoke: &MockAuthInvoke {
+                contract: &contract_id,
+                fn_name: "guardian_recover",
+                args: (recovery_address.clone(),).into_val(&env),
+                sub_invokes: &[],
+            },
+        }])
+        .try_guardian_recover(&recovery_address);
+    assert!(result.is_err());
+
+    client
+        .mock_auths(&[MockAuth {
+            address: &guardian,
+            invoke: &MockAuthInvoke {
+                contract: &contract_id,
+                fn_name: "guardian_recover",
+                args: (recovery_address.clone(),).into_val(&env),
+                sub_invokes: &[],
+            },
+        }])
+        .guardian_recover(&recovery_address);
+    assert!(client.get_pending_recovery().is_some());
+}
+
+#[test]
+#[should_panic(expected = "Not the recovery address")]
+fn test_guardian_recovery_only_to_fixed_address() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _usdc_token, _owner, _guardian, _recovery_address) =
+        setup_vault_with_guardian(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    client.guardian_recover(&Address::generate(&env));
+}
+
+#[test]
+fn test_owner_can_veto_recovery_within_window() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _usdc_token, _owner, _guardian, recovery_address) =
+        setup_vault_with_guardian(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    client.guardian_recover(&recovery_address);
+    env.ledger().with_mut(|l| l.timestamp += 86_399);
+    client.veto_recovery();
+    assert_eq!(client.get_pending_recovery(), None);
+    assert!(client.try_execute_guardian_recovery().is_err());
+
+    // Once the window has passed the owner can no longer block it
+    client.guardian_recover(&recovery_address);
+    env.ledger().with_mut(|l| l.timestamp += 86_400);
+    assert!(client.try_veto_recovery().is_err());
+    client.execute_guardian_recovery();
+}
+
+// ============================================================================
+// STRATEGY YIELD TESTS
+// ============================================================================
+
+#[test]
+fn test_strategy_yield_attributed_pro_rata_to_allocations() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _usdc_token, first) = setup_vault_with_strategy(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let second = Address::generate(&env);
+    client.register_strategy(&second);
+
+    // No allocation, no attribution
+    client.update_total_assets(&5_100_000);
+    assert_eq!(client.get_strategy_yield(&first), 0);
+
+    client.rebalance_funds(&first, &3_000_000);
+    client.rebalance_funds(&second, &1_000_000);
+
+    client.update_total_assets(&5_500_000);
+    assert_eq!(client.get_strategy_yield(&first), 300_000);
+    assert_eq!(client.get_strategy_yield(&second), 100_000);
+
+    // Losses are not attributed; later gains accumulate
+    client.update_total_assets(&5_300_000);
+    client.update_total_assets(&6_100_000);
+    assert_eq!(client.get_strategy_yield(&first), 900_000);
+    assert_eq!(client.get_strategy_yield(&second), 300_000);
+}
+
+// ============================================================================
+// PAUSE GRACE TESTS
+// ============================================================================
+
+#[test]
+fn test_recent_depositor_can_withdraw_during_pause_grace() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, owner) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let usdc = token::Client::new(&env, &usdc_token);
+
+    client.set_pause_grace_seconds(&600);
+    assert_eq!(client.get_pause_grace_seconds(), 600);
+
+    let early = Address::generate(&env);
+    let late = Address::generate(&env);
+    mint_token(&env, &usdc_token, &early, 10_000_000);
+    mint_token(&env, &usdc_token, &late, 10_000_000);
+
+    env.ledger().with_mut(|l| l.timestamp = 1_000);
+    client.deposit(&early, &10_000_000);
+    env.ledger().with_mut(|l| l.timestamp = 5_000);
+    client.deposit(&late, &10_000_000);
+    client.pause(&owner);
+
+    env.ledger().with_mut(|l| l.timestamp = 5_300);
+    client.withdraw(&late, &4_000_000);
+    assert_eq!(usdc.balance(&late), 4_000_000);
+    assert!(client.try_withdraw(&early, &1_000_000).is_err());
+
+    // Once the grace elapses the pause applies to everyone
+    env.ledger().with_mut(|l| l.timestamp = 5_601);
+    assert!(client.try_withdraw(&late, &1_000_000).is_err());
+}
+
+#[test]
+#[should_panic(expected = "Vault is paused")]
+fn test_pause_grace_disabled_by_default() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, owner) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 10_000_000);
+    client.deposit(&user, &10_000_000);
+    client.pause(&owner);
+
+    client.withdraw(&user, &1_000_000);
+}
+
+// ============================================================================
+// DEPOSIT SIMULATION TESTS
+// ============================================================================
+
+#[test]
+fn test_simulate_deposit_matches_real_deposit() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let early = Address::generate(&env);
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &early, 10_000_000);
+    mint_token(&env, &usdc_token, &user, 7_000_000);
+    client.deposit(&early, &10_000_000);
+    client.update_total_assets(&13_000_000);
+
+    let (shares, stats) = client.simulate_deposit(&user, &7_000_000);
+    // Simulating changes nothing
+    assert_eq!(client.get_shares(&user), 0);
+    assert_eq!(client.get_total_deposits(), 13_000_000);
+
+    client.deposit(&user, &7_000_000);
+    assert_eq!(client.get_shares(&user), shares);
+    assert_eq!(client.get_summary().stats, stats);
+    assert_eq!(stats.depositor_count, 2);
+}
+
+#[test]
+fn test_simulate_deposit_projects_reserve_parking() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let market = setup_money_market(&env, &usdc_token);
+    client.set_reserve_buffer_bps(&2_500);
+    client.set_reserve_strategy(&Some(market));
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 12_000_000);
+    client.deposit(&user, &4_000_000);
+
+    let (_, stats) = client.simulate_deposit(&user, &8_000_000);
+    client.deposit(&user, &8_000_000);
+    assert_eq!(stats.reserve_deployed, 9_000_000);
+    assert_eq!(client.get_summary().stats, stats);
+}
+
+// ============================================================================
+// FAVORABLE ROUNDING TESTS
+// ============================================================================
+
+#[test]
+fn test_small_withdrawals_round_in_users_favor_within_budget() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let usdc = token::Client::new(&env, &usdc_token);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 10_000_000);
+    mint_token(&env, &usdc_token, &contract_id, 3_000_000);
+    client.deposit(&user, &10_000_000);
+    client.update_total_assets(&13_000_000);
+
+    client.set_favorable_rounding_threshold(&2_000_000);
+    client.set_favorable_rounding_budget(&1);
+    assert_eq!(client.get_favorable_rounding_threshold(), 2_000_000);
+
+    // 1_000_001 USDC units are worth 769_408.9 shares: rounded down for the user
+    let before = client.get_shares(&user);
+    client.withdraw(&user, &1_000_001);
+    assert_eq!(before - client.get_shares(&user), 769_408);
+    assert_eq!(client.get_favorable_rounding_budget(), 0);
+
+    // Budget spent: the same withdrawal rounds up again
+    let before = client.get_shares(&user);
+    client.withdraw(&user, &1_000_001);
+    assert_eq!(before - client.get_shares(&user), 769_409);
+
+    // The vault still holds every unit its shares are worth
+    let total_assets = client.get_total_deposits();
+    assert!(client.convert_to_assets(&client.get_total_shares()) <= total_assets);
+    assert_eq!(usdc.balance(&contract_id), total_assets);
+}
+
+#[test]
+fn test_large_withdrawals_ignore_favorable_rounding() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 10_000_000);
+    mint_token(&env, &usdc_token, &contract_id, 3_000_000);
+    client.deposit(&user, &10_000_000);
+    client.update_total_assets(&13_000_000);
+
+    client.set_favorable_rounding_threshold(&2_000_000);
+    client.set_favorable_rounding_budget(&100);
+
+    // 2_600_001 USDC units are worth 2_000_461.9 shares: rounded up as usual
+    let before = client.get_shares(&user);
+    client.withdraw(&user, &2_600_001);
+    assert_eq!(before - client.get_shares(&user), 2_000_462);
+    assert_eq!(client.get_favorable_rounding_budget(), 100);
+}
+
+// ============================================================================
+// PREVIEW REDEEM TESTS
+// ============================================================================
+
+#[test]
+fn test_preview_redeem_matches_redemption_and_rounds_down() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let strategy = env.register_contract(None, MockPartnerStrategy);
+    client.register_strategy(&strategy);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 10_000_000);
+    mint_token(&env, &usdc_token, &contract_id, 3_000_000);
+    client.deposit(&user, &10_000_000);
+    client.update_total_assets(&13_000_000);
+
+    // 3 shares are worth 3.9 units at a price just under 1.3
+    assert_eq!(client.preview_redeem(&3), 3);
+
+    let preview = client.preview_redeem(&1_234_567);
+    let receipt = client.redeem_to_strategy(&user, &1_234_567, &strategy);
+    assert_eq!(receipt.amount, preview);
+    assert_eq!(preview, 1_604_567);
+}
+
+// ============================================================================
+// BOOSTED DEPOSIT TESTS
+// ============================================================================
+
+#[test]
+fn test_boosted_deposits_earn_multiplied_yield_share() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let plain = Address::generate(&env);
+    let long = Address::generate(&env);
+    let short = Address::generate(&env);
+    for user in [&plain, &long, &short] {
+        mint_token(&env, &usdc_token, user, 10_000_000);
+    }
+
+    client.deposit(&plain, &10_000_000);
+    assert_eq!(
+        client.deposit_boosted(&long, &10_000_000, &MAX_BOOST_LOCK),
+        MAX_BOOST_MULTIPLIER
+    );
+    assert_eq!(
+        client.deposit_boosted(&short, &10_000_000, &(MAX_BOOST_LOCK / 2)),
+        15_000
+    );
+    assert_eq!(client.get_boost_multiplier(&plain), BOOST_SCALE);
+    assert_eq!(client.get_boost_multiplier(&long), MAX_BOOST_MULTIPLIER);
+
+    // A 3 USDC gain split by weight 1 : 2 : 1.5, less the sliver the virtual
+    // offset keeps
+    client.update_total_assets(&33_000_000);
+    let plain_balance = client.get_balance(&plain);
+    let long_balance = client.get_balance(&long);
+    let short_balance = client.get_balance(&short);
+    assert!((plain_balance - 10_666_451).abs() <= 2);
+    assert!((long_balance - 11_333_104).abs() <= 2);
+    assert!((short_balance - 10_999_777).abs() <= 2);
+    assert!(plain_balance + long_balance + short_balance <= 33_000_000);
+}
+
+#[test]
+fn test_boosted_shares_are_locked_until_expiry() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 11_000_000);
+    client.deposit_boosted(&user, &10_000_000, &86_400);
+    assert!(client.try_withdraw(&user, &1_000_000).is_err());
+    assert!(client.try_deposit_boosted(&user, &1_000_000, &60).is_err());
+
+    env.ledger().with_mut(|l| l.timestamp += 86_400);
+    assert_eq!(client.get_boost_multiplier(&user), BOOST_SCALE);
+    client.withdraw(&user, &10_000_000);
+    assert_eq!(client.get_shares(&user), 0);
+}
+
+// ============================================================================
+// DRIFT CHECK TESTS
+// ============================================================================
+
+/// Returns the `DriftDetectedEvent`s recorded in the environment's event log.
+fn drift_events(env: &Env) -> Vec<DriftDetectedEvent> {
+    let mut events = Vec::new(env);
+    for (_, topics, data) in env.events().all().iter() {
+        let topic: Symbol = topics.get(0).unwrap().into_val(env);
+        if topic == symbol_short!("drift") {
+            events.push_back(data.into_val(env));
+        }
+    }
+    events
+}
+
+/// Overwrites `TotalShares` directly, simulating storage corruption.
+fn inject_total_shares(env: &Env, contract_id: &Address, total_shares: i128) {
+    env.as_contract(contract_id, || {
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalShares, &total_shares);
+    });
+}
+
+#[test]
+fn test_drift_check_flags_injected_drift_without_reverting() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 10_000_000);
+    client.deposit(&user, &6_000_000);
+
+    // Enabling seeds the checksum from existing balances
+    client.set_drift_check(&true);
+    assert!(client.get_drift_check());
+    client.deposit(&user, &4_000_000);
+    client.request_withdrawal(&user, &1_000_000);
+    assert_eq!(client.get_total_shares(), 10_000_000);
+    assert_eq!(drift_events(&env).len(), 0);
+
+    inject_total_shares(&env, &contract_id, 10_000_500);
+    assert_eq!(client.get_total_shares(), 10_000_500);
+    let events = drift_events(&env);
+    assert_eq!(events.len(), 1);
+    let event = events.get(0).unwrap();
+    assert_eq!(event.total_shares, 10_000_500);
+    assert_eq!(event.checksum, 10_000_000);
+}
+
+#[test]
+fn test_drift_check_off_by_default() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 10_000_000);
+    client.deposit(&user, &10_000_000);
+
+    inject_total_shares(&env, &contract_id, 1);
+    assert_eq!(client.get_total_shares(), 1);
+    assert_eq!(drift_events(&env).len(), 0);
+}
+
+// ============================================================================
+// STRATEGY CAP TESTS
+// ============================================================================
+
+#[test]
+fn test_register_strategies_up_to_cap() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    assert_eq!(client.get_max_strategies(), 0);
+    client.set_max_strategies(&2);
+    assert_eq!(client.get_max_strategies(), 2);
+
+    client.register_strategy(&Address::generate(&env));
+    client.register_strategy(&Address::generate(&env));
+    assert_eq!(client.get_strategy_count(), 2);
+}
+
+#[test]
+#[should_panic(expected = "Strategy limit reached")]
+fn test_register_strategy_beyond_cap_rejected() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    client.set_max_strategies(&2);
+    client.register_strategy(&Address::generate(&env));
+    client.register_strategy(&Address::generate(&env));
+    client.register_strategy(&Address::generate(&env));
+}
+
+#[test]
+fn test_strategy_count_uncapped_by_default() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    assert_eq!(client.get_strategy_count(), 0);
+    for _ in 0..5 {
+        client.register_strategy(&Address::generate(&env));
+    }
+    assert_eq!(client.get_strategy_count(), 5);
+}
+
+// ============================================================================
+// PERSONALIZED APY TESTS
+// ============================================================================
+
+#[test]
+fn test_user_apy_differs_by_holding_period() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let early = Address::generate(&env);
+    let late = Address::generate(&env);
+    mint_token(&env, &usdc_token, &early, 10_000_000);
+    mint_token(&env, &usdc_token, &late, 11_000_000);
+
+    client.deposit(&early, &10_000_000);
+
+    // 10% over the first half year, then the late user buys in at 1.1
+    env.ledger()
+        .with_mut(|li| li.timestamp += SECONDS_PER_YEAR / 2);
+    client.update_total_assets(&11_000_000);
+    client.deposit(&late, &11_000_000);
+
+    // Another 10% over the second half year
+    env.ledger()
+        .with_mut(|li| li.timestamp += SECONDS_PER_YEAR / 2);
+    client.update_total_assets(&24_200_000);
+
+    // Early: 10 -> 12.1 over a year; late: 11 -> 12.1 over half a year, each
+    // less the sliver the virtual offset keeps
+    assert_eq!(client.get_user_apy(&early, &0), 2_098);
+    assert_eq!(client.get_user_apy(&late, &0), 1_999);
+
+    // Capped at half a year, the early user sees only the recent growth
+    assert_eq!(client.get_user_apy(&early, &(SECONDS_PER_YEAR / 2)), 1_999);
+}
+
+#[test]
+fn test_user_apy_without_position_is_zero() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    assert_eq!(client.get_user_apy(&user, &0), 0);
+
+    // Bought in the current ledger
+    mint_token(&env, &usdc_token, &user, 10_000_000);
+    client.deposit(&user, &10_000_000);
+    assert_eq!(client.get_user_apy(&user, &0), 0);
+}
+
+// ============================================================================
+// DEPOSIT AND WITHDRAW PREVIEW TESTS
+// ============================================================================
+
+#[test]
+fn test_previews_on_empty_vault() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    assert_eq!(client.preview_deposit(&5_000_000), 5_000_000);
+    assert_eq!(client.preview_withdraw(&5_000_000), 5_000_000);
+    assert_eq!(client.preview_withdraw(&0), 0);
+}
+
+#[test]
+fn test_previews_match_deposit_and_withdraw_rounding() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 10_000_000);
+    client.deposit(&user, &3_000_000);
+    mint_token(&env, &usdc_token, &contract_id, 1_000_000);
+    client.update_total_assets(&4_000_000);
+
+    // Deposits round down: 2.000001 USDC at just under 4/3 is 1.5012476 shares
+    let preview = client.preview_deposit(&2_000_001);
+    assert_eq!(preview, 1_501_247);
+    let before = client.get_shares(&user);
+    client.deposit(&user, &2_000_001);
+    assert_eq!(client.get_shares(&user) - before, preview);
+
+    // Withdrawals round up, so even one unit burns a share
+    assert_eq!(client.preview_withdraw(&1), 1);
+    let preview = client.preview_withdraw(&1_000_001);
+    assert_eq!(preview, 750_625);
+    let before = client.get_shares(&user);
+    client.withdraw(&user, &1_000_001);
+    assert_eq!(before - client.get_shares(&user), preview);
+}
+
+// ============================================================================
+// REBALANCE PAUSE TESTS
+// ============================================================================
+
+#[test]
+fn test_user_flows_stay_open_while_rebalancing_paused() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, strategy) = setup_vault_with_strategy(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let pauser = Address::generate(&env);
+    client.set_pauser(&Some(pauser.clone()));
+    assert!(!client.is_rebalance_paused());
+    client.pause_rebalancing(&pauser);
+    assert!(client.is_rebalance_paused());
+    assert!(!client.is_paused());
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 3_000_000);
+    client.deposit(&user, &3_000_000);
+    client.withdraw(&user, &1_000_000);
+    assert_eq!(client.get_balance(&user), 2_000_000);
+
+    client.unpause_rebalancing(&client.get_owner());
+    assert!(!client.is_rebalance_paused());
+    client.rebalance_funds(&strategy, &1_000_000);
+    assert_eq!(client.get_strategy_allocation(&strategy), 1_000_000);
+}
+
+#[test]
+#[should_panic(expected = "Rebalancing is paused")]
+fn test_rebalance_funds_rejected_while_rebalancing_paused() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _usdc_token, strategy) = setup_vault_with_strategy(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    client.pause_rebalancing(&client.get_owner());
+    client.rebalance_funds(&strategy, &1_000_000);
+}
+
+#[test]
+#[should_panic(expected = "Rebalancing is paused")]
+fn test_assets_update_rejected_while_rebalancing_paused() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _usdc_token, _strategy) = setup_vault_with_strategy(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    client.pause_rebalancing(&client.get_owner());
+    client.update_total_assets(&5_100_000);
+}
+
+#[test]
+#[should_panic(expected = "Only owner can unpause rebalancing")]
+fn test_pauser_cannot_unpause_rebalancing() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let pauser = Address::generate(&env);
+    client.set_pauser(&Some(pauser.clone()));
+    client.pause_rebalancing(&pauser);
+    client.unpause_rebalancing(&pauser);
+}
+
+// ============================================================================
+// CONVERSION BOUNDARY TESTS
+// ============================================================================
+
+/// Overwrites the vault's total assets and total shares directly.
+fn inject_vault_totals(env: &Env, contract_id: &Address, total_assets: i128, total_shares: i128) {
+    env.as_contract(contract_id, || {
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalDeposits, &total_assets);
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalShares, &total_shares);
+    });
+}
+
+/// Sets the stored totals so that conversions, which add `VIRTUAL_ASSETS` and
+/// the matching virtual shares, price against exactly these totals.
+fn inject_offset_totals(env: &Env, contract_id: &Address, total_assets: i128, total_shares: i128) {
+    // The default ratio mints one share per unit, so the virtual shares equal
+    // `VIRTUAL_ASSETS`
+    inject_vault_totals(
+        env,
+        contract_id,
+        total_assets - VIRTUAL_ASSETS,
+        total_shares - VIRTUAL_ASSETS,
+    );
+}
+
+/// Asserts that a round trip through shares never pays out more than went in.
+fn assert_round_trips_favor_vault(client: &NeuroWealthVaultClient, amounts: &[i128]) {
+    for &amount in amounts {
+        let shares = client.convert_to_shares(&amount);
+        assert!(client.convert_to_assets(&shares) <= amount);
+        assert!(client.preview_mint(&shares) <= amount);
+        assert!(client.preview_withdraw(&amount) >= shares);
+    }
+}
+
+#[test]
+fn test_conversions_near_i128_limits() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    // Products stay just below i128::MAX when the whole vault converts
+    let total_assets = 7_000_000_000_000_000_000;
+    inject_offset_totals(&env, &contract_id, total_assets, total_assets * 3);
+
+    let amount = total_assets - VIRTUAL_ASSETS;
+    let shares = client.convert_to_shares(&amount);
+    assert_eq!(shares, amount * 3);
+    assert_eq!(client.convert_to_assets(&shares), amount);
+    assert_round_trips_favor_vault(&client, &[1, 7, 1_000_000_007, amount]);
+}
+
+#[test]
+#[should_panic(expected = "Arithmetic overflow")]
+fn test_conversion_overflow_panics_cleanly() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let big = 100_000_000_000_000_000_000;
+    inject_vault_totals(&env, &contract_id, big, big);
+    client.convert_to_shares(&big);
+}
+
+#[test]
+#[should_panic(expected = "Arithmetic overflow")]
+fn test_ceil_conversion_overflow_panics_cleanly() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let big = 100_000_000_000_000_000_000;
+    inject_vault_totals(&env, &contract_id, big, big);
+    client.preview_mint(&big);
+}
+
+#[test]
+fn test_conversions_at_skewed_ratios() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    // One share worth 10^12 units: dust deposits mint nothing
+    let total_shares = VIRTUAL_ASSETS + 2;
+    inject_offset_totals(
+        &env,
+        &contract_id,
+        1_000_000_000_000 * total_shares,
+        total_shares,
+    );
+    assert_eq!(client.convert_to_shares(&1), 0);
+    assert_eq!(client.convert_to_shares(&999_999_999_999), 0);
+    assert_eq!(client.convert_to_assets(&1), 1_000_000_000_000);
+    assert_eq!(client.preview_mint(&1), 1_000_000_000_000);
+    assert_eq!(client.preview_withdraw(&1), 1);
+    assert_round_trips_favor_vault(&client, &[1, 999_999_999_999, 1_000_000_000_001]);
+
+    // One unit backing 10^30 shares: dust shares are worth nothing
+    let shares = 1_000_000_000_000_000_000_000_000_000_000;
+    let total_assets = VIRTUAL_ASSETS + 1;
+    inject_offset_totals(&env, &contract_id, total_assets, shares * total_assets);
+    assert_eq!(client.convert_to_assets(&(shares - 1)), 0);
+    assert_eq!(client.convert_to_assets(&shares), 1);
+    assert_eq!(client.convert_to_shares(&1), shares);
+    assert_eq!(client.preview_withdraw(&1), shares);
+    assert_round_trips_favor_vault(&client, &[1, 7, 100]);
+}
+
+// ============================================================================
+// WITHDRAW AND CLAIM TESTS
+// ============================================================================
+
+#[test]
+fn test_withdraw_and_claim_claims_airdrops_then_withdraws() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let usdc = token::Client::new(&env, &usdc_token);
+    let reward_token = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    let idle_token = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    let reward_client = token::Client::new(&env, &reward_token);
+
+    let user = Address::generate(&env);
+    let other = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 3_000_000);
+    mint_token(&env, &usdc_token, &other, 1_000_000);
+    client.deposit(&user, &3_000_000);
+    client.deposit(&other, &1_000_000);
+
+    mint_token(&env, &reward_token, &contract_id, 1_000);
+    client.distribute_airdrop(&reward_token, &1_000);
+
+    let claimed = client.withdraw_and_claim(
+        &user,
+        &3_000_000,
+        &Vec::from_array(&env, [reward_token.clone(), idle_token]),
+    );
+    assert_eq!(claimed, Vec::from_array(&env, [750, 0]));
+    assert_eq!(reward_client.balance(&user), 750);
+    assert_eq!(usdc.balance(&user), 3_000_000);
+    assert_eq!(client.get_shares(&user), 0);
+    assert_eq!(client.get_claimable_airdrop(&user, &reward_token), 0);
+}
+
+#[test]
+fn test_withdraw_and_claim_reverts_claims_when_withdraw_fails() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let reward_token = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    let reward_client = token::Client::new(&env, &reward_token);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 1_000_000);
+    client.deposit(&user, &1_000_000);
+    mint_token(&env, &reward_token, &contract_id, 1_000);
+    client.distribute_airdrop(&reward_token, &1_000);
+
+    let result = client.try_withdraw_and_claim(
+        &user,
+        &2_000_000,
+        &Vec::from_array(&env, [reward_token.clone()]),
+    );
+    assert!(result.is_err());
+    assert_eq!(reward_client.balance(&user), 0);
+    assert_eq!(client.get_claimable_airdrop(&user, &reward_token), 1_000);
+    assert_eq!(client.get_shares(&user), 1_000_000);
+}
+
+// ============================================================================
+// REDEEM TESTS
+// ============================================================================
+
+#[test]
+fn test_redeem_burns_exact_shares_and_closes_position() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let usdc = token::Client::new(&env, &usdc_token);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 10_000_000);
+    mint_token(&env, &usdc_token, &contract_id, 3_000_000);
+    client.deposit(&user, &10_000_000);
+    client.update_total_assets(&13_000_000);
+
+    let preview = client.preview_redeem(&1_234_567);
+    assert_eq!(client.redeem(&user, &1_234_567), preview);
+    assert_eq!(preview, 1_604_567);
+    assert_eq!(client.get_shares(&user), 8_765_433);
+    assert_eq!(usdc.balance(&user), preview);
+
+    let events = env.events().all();
+    let (_, _, data) = events.last().unwrap();
+    let event: WithdrawEvent = data.into_val(&env);
+    assert_eq!(event.amount, preview);
+    assert_eq!(event.shares, 1_234_567);
+
+    // Redeeming the remaining balance leaves no dust shares behind
+    assert_eq!(client.redeem(&user, &8_765_433), 11_395_433);
+    assert_eq!(client.get_shares(&user), 0);
+    assert_eq!(client.get_total_shares(), 0);
+    assert_eq!(client.get_total_deposits(), 0);
+    assert_eq!(client.get_protected_principal(&user), 0);
+    assert_eq!(usdc.balance(&user), 13_000_000);
+}
+
+#[test]
+#[should_panic(expected = "Insufficient balance")]
+fn test_redeem_more_than_held_fails() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 1_000_000);
+    client.deposit(&user, &1_000_000);
+    client.redeem(&user, &1_000_001);
+}
+
+// ============================================================================
+// MAX WITHDRAW AND REDEEM TESTS
+// ============================================================================
+
+#[test]
+fn test_max_withdraw_and_redeem_on_empty_vault() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    assert_eq!(client.max_withdraw(&user), 0);
+    assert_eq!(client.max_redeem(&user), 0);
+}
+
+#[test]
+fn test_max_withdraw_exits_fully_at_fractional_price() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let other = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 3_000_000);
+    mint_token(&env, &usdc_token, &other, 1_000_000);
+    mint_token(&env, &usdc_token, &contract_id, 1_333_335);
+    client.deposit(&user, &3_000_000);
+    client.deposit(&other, &1_000_000);
+    client.update_total_assets(&5_333_335);
+
+    assert_eq!(client.max_redeem(&user), 3_000_000);
+    let max = client.max_withdraw(&user);
+    assert_eq!(max, 3_997_507);
+    client.withdraw(&user, &max);
+    assert_eq!(client.get_shares(&user), 0);
+    assert_eq!(client.max_withdraw(&user), 0);
+}
+
+#[test]
+fn test_max_withdraw_and_redeem_respect_locks_and_cap() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 3_000_000);
+    client.deposit(&user, &3_000_000);
+    client.lock_shares(&user, &1_000_000, &Address::generate(&env));
+    assert_eq!(client.max_withdraw(&user), 2_000_000);
+    assert_eq!(client.max_redeem(&user), 2_000_000);
+
+    client.set_max_withdraw_per_tx(&1_500_000);
+    assert_eq!(client.max_withdraw(&user), 1_500_000);
+    assert_eq!(client.max_redeem(&user), 1_500_000);
+    client.redeem(&user, &client.max_redeem(&user));
+    assert_eq!(client.get_shares(&user), 1_500_000);
+    assert_eq!(client.max_redeem(&user), 500_000);
+}
+
+// ============================================================================
+// CONFIG CHANGELOG TESTS
+// ============================================================================
+
+#[test]
+fn test_config_history_records_changes_in_order() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _agent, owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    assert_eq!(client.get_config_history(&10).len(), 0);
+    let initial_tvl_cap = client.get_tvl_cap();
+    let initial_user_cap = client.get_user_deposit_cap();
+
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    client.set_max_strategies(&3);
+    env.ledger().with_mut(|li| li.timestamp = 2_000);
+    client.set_tvl_cap(&50_000_000);
+    env.ledger().with_mut(|li| li.timestamp = 3_000);
+    client.set_limits(&1_000_000, &60_000_000);
+    client.set_user_deposit_cap(&2_000_000);
+    client.update_agent(&Address::generate(&env));
+
+    let history = client.get_config_history(&10);
+    assert_eq!(history.len(), 6);
+    let expected = [
+        (symbol_short!("max_strat"), 0, 3, 1_000),
+        (symbol_short!("tvl_cap"), initial_tvl_cap, 50_000_000, 2_000),
+        (
+            symbol_short!("user_cap"),
+            initial_user_cap,
+            1_000_000,
+            3_000,
+        ),
+        (symbol_short!("tvl_cap"), 50_000_000, 60_000_000, 3_000),
+        (symbol_short!("user_cap"), 1_000_000, 2_000_000, 3_000),
+        (symbol_short!("agent"), 0, 0, 3_000),
+    ];
+    for (change, (key, old, new, timestamp)) in history.iter().zip(expected) {
+        assert_eq!(change.key, key);
+        assert_eq!(change.old, old);
+        assert_eq!(change.new, new);
+        assert_eq!(change.timestamp, timestamp);
+        assert_eq!(change.actor, owner);
+    }
+
+    // A limit returns the newest entries, still oldest first
+    let recent = client.get_config_history(&2);
+    assert_eq!(recent.len(), 2);
+    assert_eq!(recent.get(0).unwrap().key, symbol_short!("user_cap"));
+    assert_eq!(recent.get(1).unwrap().key, symbol_short!("agent"));
+}
+
+#[test]
+fn test_config_history_records_address_setters() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    client.set_fee_recipient(&Some(Address::generate(&env)));
+    client.set_assets_oracle(&Address::generate(&env));
+    client.set_reserve_strategy(&None);
+
+    let history = client.get_config_history(&10);
+    assert_eq!(history.len(), 3);
+    let expected = [
+        symbol_short!("fee_rcpt"),
+        symbol_short!("ast_orc"),
+        symbol_short!("rsv_strat"),
+    ];
+    for (change, key) in history.iter().zip(expected) {
+        assert_eq!(change.key, key);
+        assert_eq!((change.old, change.new), (0, 0));
+    }
+}
+
+#[test]
+fn test_config_history_is_bounded() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    for max in 1..=MAX_CONFIG_HISTORY + 5 {
+        client.set_max_strategies(&max);
+    }
+    let history = client.get_config_history(&u32::MAX);
+    assert_eq!(history.len(), MAX_CONFIG_HISTORY);
+    assert_eq!(history.get(0).unwrap().new, 6);
+    assert_eq!(
+        history.last().unwrap().new,
+        (MAX_CONFIG_HISTORY + 5) as i128
+    );
+}
+
+// ============================================================================
+// DRAWDOWN TESTS
+// ============================================================================
+
+#[test]
+fn test_loss_after_high_water_mark_records_drawdown() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 10_000_000);
+    client.deposit(&user, &10_000_000);
+    assert_eq!(client.get_max_drawdown_bps(&user), 0);
+    assert_eq!(client.get_max_drawdown_bps(&stranger), 0);
+
+    // A keeper records the 1.2 peak before the loss
+    client.update_total_assets(&12_000_000);
+    assert_eq!(client.checkpoint_drawdown(&user), 0);
+
+    client.update_total_assets(&9_000_000);
+    assert_eq!(client.get_max_drawdown_bps(&user), 2_500);
+
+    // Recovering does not erase the worst drawdown once recorded
+    client.checkpoint_drawdown(&user);
+    client.update_total_assets(&10_000_000);
+    assert_eq!(client.get_max_drawdown_bps(&user), 2_500);
+}
+
+#[test]
+fn test_user_operations_record_the_peak() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 20_000_000);
+    client.deposit(&user, &10_000_000);
+
+    // Topping up at a 2.0 price records it as the peak
+    mint_token(&env, &usdc_token, &contract_id, 10_000_000);
+    client.update_total_assets(&20_000_000);
+    client.deposit(&user, &10_000_000);
+    client.update_total_assets(&27_000_000);
+    assert_eq!(client.get_max_drawdown_bps(&user), 1_001);
+}
+
+#[test]
+#[should_panic(expected = "No shares held")]
+fn test_checkpoint_drawdown_without_shares_fails() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    client.checkpoint_drawdown(&Address::generate(&env));
+}
+
+// ============================================================================
+// INFLATION ATTACK TESTS
+// ============================================================================
+
+#[test]
+fn test_inflation_attack_cannot_dilute_next_depositor() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let attacker = Address::generate(&env);
+    let victim = Address::generate(&env);
+    mint_token(&env, &usdc_token, &attacker, 1_000_000);
+    mint_token(&env, &usdc_token, &victim, 100_000_000);
+
+    // The attacker shrinks the supply to a single share
+    client.deposit(&attacker, &1_000_000);
+    client.redeem(&attacker, &999_999);
+    assert_eq!(client.get_total_shares(), 1);
+
+    // Tokens sent straight to the vault do not move the price
+    let price = client.get_share_price();
+    mint_token(&env, &usdc_token, &contract_id, 1_000_000_000);
+    assert_eq!(client.get_share_price(), price);
+
+    // Reporting the donation as a 100 USDC gain pumps the spot price
+    client.update_total_assets(&1_000_000_001);
+    client.deposit(&victim, &100_000_000);
+
+    // The victim loses under 1 bps, while the virtual shares kept nearly
+    // all of the pumped gain from the attacker
+    let balance = client.get_balance(&victim);
+    assert!(balance <= 100_000_000);
+    assert!(balance * 10_000 >= 100_000_000 * 9_999);
+    assert!(client.get_balance(&attacker) < 1_000_000);
+}
+
+#[test]
+fn test_exit_may_leave_a_single_share() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 1_000_000);
+    client.deposit(&user, &1_000_000);
+    client.redeem(&user, &999_999);
+    assert_eq!(client.get_total_shares(), 1);
+    assert_eq!(client.get_balance(&user), 1);
+}
+
+#[test]
+fn test_last_holder_exits_fully() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let usdc = token::Client::new(&env, &usdc_token);
+
+    let user = Address::generate(&env);
+    let last = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 1_000_000);
+    mint_token(&env, &usdc_token, &last, 1_000_000);
+    client.deposit(&user, &1_000_000);
+    client.deposit(&last, &1_000_000);
+    client.withdraw(&user, &1_000_000);
+
+    // A holder left with a sliver of the supply can still leave
+    client.withdraw(&last, &1_000_000);
+    assert_eq!(client.get_total_shares(), 0);
+    assert_eq!(usdc.balance(&last), 1_000_000);
+}
+
+// ============================================================================
+// MANAGEMENT FEE TESTS
+// ============================================================================
+
+/// Returns every `FeeAccruedEvent` published so far.
+fn fee_accrued_events(env: &Env) -> Vec<FeeAccruedEvent> {
+    let mut events = Vec::new(env);
+    for (_, topics, data) in env.events().all().iter() {
+        let topic: Symbol = topics.get(0).unwrap().into_val(env);
+        if topic == symbol_short!("fee_acr") {
+            events.push_back(data.into_val(env));
+        }
+    }
+    events
+}
+
+#[test]
+fn test_management_fee_mints_owner_shares_over_time() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, owner) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 10_000_000);
+    client.deposit(&user, &10_000_000);
+    client.set_management_fee_bps(&200);
+    assert_eq!(client.get_management_fee_bps(), 200);
+    assert_eq!(client.get_config().management_fee_bps, 200);
+
+    // 2% of 10 USDC over a year, paid by diluting the holder and the virtual
+    // shares
+    env.ledger().with_mut(|li| li.timestamp += SECONDS_PER_YEAR);
+    client.update_total_assets(&10_000_000);
+    assert_eq!(client.get_shares(&owner), 204_077);
+    assert_eq!(client.get_total_shares(), 10_204_077);
+    assert_eq!(client.get_total_deposits(), 10_000_000);
+    assert_eq!(client.get_balance(&owner), 199_999);
+    assert_eq!(client.get_balance(&user), 9_800_200);
+    assert_eq!(client.get_fees_collected().management, 200_000);
+
+    let events = fee_accrued_events(&env);
+    assert_eq!(events.len(), 1);
+    let event = events.get(0).unwrap();
+    assert_eq!(event.shares_minted, 204_077);
+    assert_eq!(event.fee_bps, 200);
+    assert_eq!(event.period_seconds, SECONDS_PER_YEAR);
+
+    // An immediate second update owes nothing
+    client.update_total_assets(&10_000_000);
+    assert_eq!(client.get_total_shares(), 10_204_077);
+}
+
+#[test]
+fn test_management_fee_waived_during_fee_holiday() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, owner) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 10_000_000);
+    client.deposit(&user, &10_000_000);
+    client.set_management_fee_bps(&500);
+    client.set_fee_holiday(&(env.ledger().timestamp() + SECONDS_PER_YEAR + 1));
+
+    env.ledger().with_mut(|li| li.timestamp += SECONDS_PER_YEAR);
+    client.update_total_assets(&10_000_000);
+    assert_eq!(client.get_shares(&owner), 0);
+    assert_eq!(client.get_fees_collected(), FeesCollected::default());
+    assert_eq!(fee_accrued_events(&env).len(), 0);
+}
+
+#[test]
+#[should_panic(expected = "Invalid management fee")]
+fn test_management_fee_above_cap_rejected() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    client.set_management_fee_bps(&(MAX_MANAGEMENT_FEE_BPS + 1));
+}
+
+// ============================================================================
+// DEPOSIT PERMITS
+// ============================================================================
+
+fn permit_signer(env: &Env, contract_id: &Address, user: &Address) -> ed25519_dalek::SigningKey {
+    let signer = ed25519_dalek::SigningKey::from_bytes(&[7u8; 32]);
+    let client = NeuroWealthVaultClient::new(env, contract_id);
+    client.set_permit_key(
+        user,
+        &BytesN::from_array(env, &signer.verifying_key().to_bytes()),
+    );
+    signer
+}
+
+fn sign_permit(
+    env: &Env,
+    contract_id: &Address,
+    signer: &ed25519_dalek::SigningKey,
+    user: &Address,
+    amount: i128,
+    deadline: u64,
+) -> BytesN<64> {
+    use ed25519_dalek::Signer;
+
+    let client = NeuroWealthVaultClient::new(env, contract_id);
+    let payload = client.get_permit_payload(user, &amount, &deadline);
+    let digest = env.crypto().sha256(&payload).to_bytes().to_array();
+    BytesN::from_array(env, &signer.sign(&digest).to_bytes())
+}
+
+fn approve_vault(env: &Env, token: &Address, user: &Address, contract_id: &Address, amount: i128) {
+    let expiration = env.ledger().sequence() + 1_000;
+    token::Client::new(env, token).approve(user, contract_id, &amount, &expiration);
+}
+
+#[test]
+fn test_deposit_with_permit_mints_shares() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 10_000_000);
+    approve_vault(&env, &usdc_token, &user, &contract_id, 10_000_000);
+    let signer = permit_signer(&env, &contract_id, &user);
+
+    let deadline = env.ledger().timestamp() + 60;
+    let signature = sign_permit(&env, &contract_id, &signer, &user, 10_000_000, deadline);
+    client.deposit_with_permit(&user, &10_000_000, &deadline, &signature);
+
+    assert_eq!(client.get_shares(&user), 10_000_000);
+    assert_eq!(client.get_total_deposits(), 10_000_000);
+    assert_eq!(token::Client::new(&env, &usdc_token).balance(&user), 0);
+    assert_eq!(client.get_permit_nonce(&user), 1);
+}
+
+#[test]
+#[should_panic(expected = "Error(Crypto, InvalidInput)")]
+fn test_deposit_with_permit_replay_rejected() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 20_000_000);
+    approve_vault(&env, &usdc_token, &user, &contract_id, 20_000_000);
+    let signer = permit_signer(&env, &contract_id, &user);
+
+    let deadline = env.ledger().timestamp() + 60;
+    let signature = sign_permit(&env, &contract_id, &signer, &user, 10_000_000, deadline);
+    client.deposit_with_permit(&user, &10_000_000, &deadline, &signature);
+
+    // The nonce moved on, so the same signature no longer verifies
+    client.deposit_with_permit(&user, &10_000_000, &deadline, &signature);
+}
+
+#[test]
+#[should_panic(expected = "Permit expired")]
+fn test_deposit_with_permit_expired_rejected() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 10_000_000);
+    approve_vault(&env, &usdc_token, &user, &contract_id, 10_000_000);
+    let signer = permit_signer(&env, &contract_id, &user);
+
+    let deadline = env.ledger().timestamp() + 60;
+    let signature = sign_permit(&env, &contract_id, &signer, &user, 10_000_000, deadline);
+    env.ledger().with_mut(|li| li.timestamp = deadline + 1);
+    client.deposit_with_permit(&user, &10_000_000, &deadline, &signature);
+}
+
+#[test]
+#[should_panic(expected = "Error(Crypto, InvalidInput)")]
+fn test_deposit_with_permit_wrong_amount_rejected() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 20_000_000);
+    approve_vault(&env, &usdc_token, &user, &contract_id, 20_000_000);
+    let signer = permit_signer(&env, &contract_id, &user);
+
+    let deadline = env.ledger().timestamp() + 60;
+    let signature = sign_permit(&env, &contract_id, &signer, &user, 10_000_000, deadline);
+    client.deposit_with_permit(&user, &20_000_000, &deadline, &signature);
+}
+
+// ============================================================================
+// RESERVE LOW TRIGGER
+// ============================================================================
+
+fn reserve_low_events(env: &Env) -> Vec<ReserveLowEvent> {
+    let mut events = Vec::new(env);
+    for (_, topics, data) in env.events().all().iter() {
+        let topic: Symbol = topics.get(0).unwrap().into_val(env);
+        if topic == symbol_short!("rsv_low") {
+            events.push_back(data.into_val(env));
+        }
+    }
+    events
+}
+
+#[test]
+fn test_large_withdrawal_emits_reserve_low_with_shortfall() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let strategy = Address::generate(&env);
+    client.register_strategy(&strategy);
+    client.set_reserve_target_bps(&1_000);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 10_000_000);
+    client.deposit(&user, &10_000_000);
+    client.rebalance_funds(&strategy, &8_000_000);
+
+    // 2 USDC liquid against a 1 USDC target: no signal
+    client.withdraw(&user, &500_000);
+    assert_eq!(reserve_low_events(&env).len(), 0);
+
+    // 0.5 USDC liquid against 10% of 8.5 USDC
+    client.withdraw(&user, &1_000_000);
+    let events = reserve_low_events(&env);
+    assert_eq!(events.len(), 1);
+    let event = events.get(0).unwrap();
+    assert_eq!(event.liquidity, 500_000);
+    assert_eq!(event.target, 850_000);
+    assert_eq!(event.shortfall, 350_000);
+
+    // Already below target: later withdrawals do not repeat the signal
+    client.withdraw(&user, &100_000);
+    assert_eq!(reserve_low_events(&env).len(), 1);
+}
+
+#[test]
+fn test_reserve_low_disabled_by_default() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let strategy = Address::generate(&env);
+    client.register_strategy(&strategy);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 10_000_000);
+    client.deposit(&user, &10_000_000);
+    client.rebalance_funds(&strategy, &9_000_000);
+
+    client.withdraw(&user, &1_000_000);
+    assert_eq!(client.get_reserve_target_bps(), 0);
+    assert_eq!(reserve_low_events(&env).len(), 0);
+}
+
+#[test]
+#[should_panic(expected = "Invalid reserve target")]
+fn test_reserve_target_above_full_rejected() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    client.set_reserve_target_bps(&10_001);
+}
+
+// ============================================================================
+// PERFORMANCE FEE
+// ============================================================================
+
+#[test]
+fn test_performance_fee_minted_above_high_water_mark() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _owner) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let recipient = Address::generate(&env);
+    client.set_fee_recipient(&Some(recipient.clone()));
+    client.set_performance_fee_bps(&2_000);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 10_000_000);
+    client.deposit(&user, &10_000_000);
+    assert_eq!(client.get_high_water_mark(), SHARE_PRICE_SCALE);
+
+    // 20% of the 2 USDC gain, paid as 0.4 USDC of shares
+    client.update_total_assets(&12_000_000);
+    assert_eq!(client.get_shares(&recipient), 344_875);
+    assert_eq!(client.get_total_shares(), 10_344_875);
+    assert_eq!(client.get_fees_collected().performance, 400_000);
+    assert_eq!(client.get_high_water_mark(), 11_599_946);
+    assert_eq!(client.get_share_price(), 11_599_946);
+}
+
+#[test]
+fn test_performance_fee_not_charged_on_recovery_to_prior_peak() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _owner) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let recipient = Address::generate(&env);
+    client.set_fee_recipient(&Some(recipient.clone()));
+    client.set_performance_fee_bps(&2_000);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 10_000_000);
+    client.deposit(&user, &10_000_000);
+    client.update_total_assets(&12_000_000);
+
+    // A loss leaves the mark in place
+    client.update_total_assets(&10_000_000);
+    assert_eq!(client.get_high_water_mark(), 11_599_946);
+
+    // Recovering to the peak owes nothing
+    client.update_total_assets(&12_000_000);
+    assert_eq!(client.get_shares(&recipient), 344_875);
+    assert_eq!(client.get_fees_collected().performance, 400_000);
+
+    // Only the gain above the peak is charged
+    client.update_total_assets(&13_000_000);
+    assert_eq!(client.get_shares(&recipient), 344_875 + 161_668);
+    assert_eq!(client.get_fees_collected().performance, 600_000);
+    assert_eq!(client.get_high_water_mark(), 12_373_242);
+}
+
+#[test]
+fn test_performance_fee_waived_during_fee_holiday() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, owner) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    client.set_performance_fee_bps(&2_000);
+    client.set_fee_holiday(&(env.ledger().timestamp() + 100));
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 10_000_000);
+    client.deposit(&user, &10_000_000);
+
+    // The holiday gain raises the mark without a fee
+    client.update_total_assets(&12_000_000);
+    assert_eq!(client.get_shares(&owner), 0);
+    assert_eq!(client.get_high_water_mark(), 12_000_000);
+
+    env.ledger().with_mut(|li| li.timestamp += 100);
+    client.update_total_assets(&12_000_000);
+    assert_eq!(client.get_fees_collected(), FeesCollected::default());
+}
+
+#[test]
+fn test_fee_recipient_defaults_to_owner() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _agent, owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    assert_eq!(client.get_fee_recipient(), owner);
+
+    let recipient = Address::generate(&env);
+    client.set_fee_recipient(&Some(recipient.clone()));
+    assert_eq!(client.get_fee_recipient(), recipient);
+    client.set_fee_recipient(&None);
+    assert_eq!(client.get_fee_recipient(), owner);
+}
+
+#[test]
+#[should_panic(expected = "Invalid performance fee")]
+fn test_performance_fee_above_cap_rejected() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    client.set_performance_fee_bps(&(MAX_PERFORMANCE_FEE_BPS + 1));
+}
+
+// ============================================================================
+// SHARE PRICE HISTORY
+// ============================================================================
+
+#[test]
+fn test_share_price_history_records_each_update_in_order() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 10_000_000);
+    client.deposit(&user, &10_000_000);
+
+    for (timestamp, total) in [(100, 11_000_000), (200, 12_000_000), (300, 10_500_000)] {
+        env.ledger().with_mut(|li| li.timestamp = timestamp);
+        client.update_total_assets(&total);
+    }
+
+    let prices = client.get_share_price_history();
+    assert_eq!(prices.len(), 3);
+    assert_eq!(prices.get(0).unwrap(), (100, 11_000_000));
+    assert_eq!(prices.get(1).unwrap(), (200, 12_000_000));
+    assert_eq!(prices.get(2).unwrap(), (300, 10_500_000));
+    assert_eq!(
+        client.get_assets_history().last().unwrap().share_price,
+        client.get_share_price()
+    );
+}
+
+#[test]
+fn test_share_price_history_is_bounded() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 10_000_000);
+    client.deposit(&user, &10_000_000);
+    for i in 0..(MAX_HISTORY_SAMPLES + 3) {
+        env.ledger().with_mut(|li| li.timestamp = i as u64);
+        client.update_total_assets(&(10_000_000 + i as i128));
+    }
+
+    let prices = client.get_share_price_history();
+    assert_eq!(prices.len(), MAX_HISTORY_SAMPLES);
+    assert_eq!(prices.first().unwrap(), (3, 10_000_003));
+    assert_eq!(
+        prices.last().unwrap(),
+        (
+            (MAX_HISTORY_SAMPLES + 2) as u64,
+            10_000_000 + (MAX_HISTORY_SAMPLES + 2) as i128
+        )
+    );
+}
+
+// ============================================================================
+// WITHDRAWAL FEE
+// ============================================================================
+
+fn withdrawal_fee_events(env: &Env) -> Vec<WithdrawalFeeEvent> {
+    let mut events = Vec::new(env);
+    for (_, topics, data) in env.events().all().iter() {
+        let topic: Symbol = topics.get(0).unwrap().into_val(env);
+        if topic == symbol_short!("wd_fee") {
+            events.push_back(data.into_val(env));
+        }
+    }
+    events
+}
+
+#[test]
+fn test_withdrawal_fee_stays_in_vault_for_remaining_holders() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let usdc = token::Client::new(&env, &usdc_token);
+    client.set_withdrawal_fee_bps(&100);
+
+    let leaver = Address::generate(&env);
+    let stayer = Address::generate(&env);
+    mint_token(&env, &usdc_token, &leaver, 10_000_000);
+    mint_token(&env, &usdc_token, &stayer, 10_000_000);
+    client.deposit(&leaver, &10_000_000);
+    client.deposit(&stayer, &10_000_000);
+
+    client.withdraw(&leaver, &10_000_000);
+    assert_eq!(usdc.balance(&leaver), 9_900_000);
+    assert_eq!(client.get_shares(&leaver), 0);
+    assert_eq!(client.get_total_deposits(), 10_100_000);
+    assert_eq!(client.get_balance(&stayer), 10_100_000);
+    assert_eq!(client.get_fees_collected().withdrawal, 100_000);
+
+    let events = withdrawal_fee_events(&env);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events.get(0).unwrap().user, leaver);
+    assert_eq!(events.get(0).unwrap().fee, 100_000);
+}
+
+#[test]
+fn test_redeem_deducts_withdrawal_fee() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let usdc = token::Client::new(&env, &usdc_token);
+    client.set_withdrawal_fee_bps(&50);
+
+    let leaver = Address::generate(&env);
+    let stayer = Address::generate(&env);
+    mint_token(&env, &usdc_token, &leaver, 10_000_000);
+    mint_token(&env, &usdc_token, &stayer, 10_000_000);
+    client.deposit(&leaver, &10_000_000);
+    client.deposit(&stayer, &10_000_000);
+
+    assert_eq!(client.redeem(&leaver, &4_000_000), 3_980_000);
+    assert_eq!(usdc.balance(&leaver), 3_980_000);
+    assert_eq!(client.get_total_deposits(), 16_020_000);
+    assert_eq!(client.get_fees_collected().withdrawal, 20_000);
+}
+
+/// Exits 4_000_000 of 20_000_000 shares at a 50 bps fee through `redeem`
+/// (0), a queued claim (1) or `redeem_to_strategy` (2), returning the
+/// payout, fee collected and the vault's remaining assets.
+fn exit_with_withdrawal_fee(exit: u32) -> (i128, i128, i128) {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let strategy = env.register_contract(None, MockPartnerStrategy);
+    client.register_strategy(&strategy);
+    client.set_withdrawal_fee_bps(&50);
+
+    let leaver = Address::generate(&env);
+    let stayer = Address::generate(&env);
+    mint_token(&env, &usdc_token, &leaver, 10_000_000);
+    mint_token(&env, &usdc_token, &stayer, 10_000_000);
+    client.deposit(&leaver, &10_000_000);
+    client.deposit(&stayer, &10_000_000);
+
+    let paid = match exit {
+        0 => client.redeem(&leaver, &4_000_000),
+        1 => {
+            let request_id = client.request_withdrawal(&leaver, &4_000_000);
+            client.claim_withdrawal(&leaver, &request_id)
+        }
+        _ => {
+            client
+                .redeem_to_strategy(&leaver, &4_000_000, &strategy)
+                .amount
+        }
+    };
+    (
+        paid,
+        client.get_fees_collected().withdrawal,
+        client.get_total_deposits(),
+    )
+}
+
+#[test]
+fn test_every_exit_pays_the_same_withdrawal_fee() {
+    let redeemed = exit_with_withdrawal_fee(0);
+    assert_eq!(redeemed, (3_980_000, 20_000, 16_020_000));
+    assert_eq!(exit_with_withdrawal_fee(1), redeemed);
+    assert_eq!(exit_with_withdrawal_fee(2), redeemed);
+}
+
+#[test]
+fn test_process_withdrawals_deducts_withdrawal_fee() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let usdc = token::Client::new(&env, &usdc_token);
+    client.set_withdrawal_fee_bps(&100);
+
+    let leaver = Address::generate(&env);
+    let stayer = Address::generate(&env);
+    mint_token(&env, &usdc_token, &leaver, 10_000_000);
+    mint_token(&env, &usdc_token, &stayer, 10_000_000);
+    client.deposit(&leaver, &10_000_000);
+    client.deposit(&stayer, &10_000_000);
+
+    client.request_withdrawal(&leaver, &10_000_000);
+    client.process_withdrawals(&10);
+    assert_eq!(usdc.balance(&leaver), 9_900_000);
+    assert_eq!(client.get_total_deposits(), 10_100_000);
+    assert_eq!(client.get_balance(&stayer), 10_100_000);
+    assert_eq!(withdrawal_fee_events(&env).len(), 1);
+}
+
+#[test]
+fn test_zero_withdrawal_fee_behaves_as_before() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let usdc = token::Client::new(&env, &usdc_token);
+
+    let user = Address::generate(&env);
+    let other = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 10_000_000);
+    mint_token(&env, &usdc_token, &other, 10_000_000);
+    client.deposit(&user, &10_000_000);
+    client.deposit(&other, &10_000_000);
+
+    client.withdraw(&user, &4_000_000);
+    assert_eq!(usdc.balance(&user), 4_000_000);
+    assert_eq!(client.get_total_deposits(), 16_000_000);
+    assert_eq!(client.get_fees_collected(), FeesCollected::default());
+    assert_eq!(withdrawal_fee_events(&env).len(), 0);
+}
+
+#[test]
+fn test_withdrawal_fee_waived_for_last_holder() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let usdc = token::Client::new(&env, &usdc_token);
+    client.set_withdrawal_fee_bps(&100);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 10_000_000);
+    client.deposit(&user, &10_000_000);
+
+    client.withdraw(&user, &10_000_000);
+    assert_eq!(usdc.balance(&user), 10_000_000);
+    assert_eq!(client.get_total_deposits(), 0);
+    assert_eq!(withdrawal_fee_events(&env).len(), 0);
+}
+
+#[test]
+#[should_panic(expected = "Invalid withdrawal fee")]
+fn test_withdrawal_fee_above_cap_rejected() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    client.set_withdrawal_fee_bps(&(MAX_WITHDRAWAL_FEE_BPS + 1));
+}
+
+// ============================================================================
+// WITHDRAW MANAGER
+// ============================================================================
+
+fn setup_managed_account(env: &Env) -> (Address, Address, Address, Address) {
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(env);
+    let client = NeuroWealthVaultClient::new(env, &contract_id);
+
+    let user = Address::generate(env);
+    let manager = Address::generate(env);
+    mint_token(env, &usdc_token, &user, 10_000_000);
+    client.deposit(&user, &10_000_000);
+    client.set_withdraw_manager(&user, &Some(manager.clone()));
+
+    (contract_id, usdc_token, user, manager)
+}
+
+#[test]
+fn test_withdraw_manager_withdraws_to_user() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, user, manager) = setup_managed_account(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let usdc = token::Client::new(&env, &usdc_token);
+    assert_eq!(client.get_withdraw_manager(&user), Some(manager.clone()));
+
+    // Only the manager's authorization is needed
+    client
+        .mock_auths(&[MockAuth {
+            address: &manager,
+            invoke: &MockAuthInvoke {
+                contract: &contract_id,
+                fn_name: "withdraw_for",
+                args: (manager.clone(), user.clone(), 4_000_000_i128, user.clone()).into_val(&env),
+                sub_invokes: &[],
+            },
+        }])
+        .withdraw_for(&manager, &user, &4_000_000, &user);
+
+    assert_eq!(usdc.balance(&user), 4_000_000);
+    assert_eq!(usdc.balance(&manager), 0);
+    assert_eq!(client.get_shares(&user), 6_000_000);
+}
+
+#[test]
+#[should_panic(expected = "Destination must be the user")]
+fn test_withdraw_manager_cannot_redirect_funds() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _usdc_token, user, manager) = setup_managed_account(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    client.withdraw_for(&manager, &user, &4_000_000, &manager);
+}
+
+#[test]
+#[should_panic(expected = "Not the withdraw manager")]
+fn test_withdraw_for_rejects_other_callers() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _usdc_token, user, _manager) = setup_managed_account(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let stranger = Address::generate(&env);
+    client.withdraw_for(&stranger, &user, &4_000_000, &user);
+}
+
+#[test]
+#[should_panic(expected = "Not the withdraw manager")]
+fn test_revoked_withdraw_manager_rejected() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _usdc_token, user, manager) = setup_managed_account(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    client.set_withdraw_manager(&user, &None);
+    assert_eq!(client.get_withdraw_manager(&user), None);
+    client.withdraw_for(&manager, &user, &4_000_000, &user);
+}
+
+// ============================================================================
+// TWO-STEP OWNERSHIP TRANSFER
+// ============================================================================
+
+fn accept_ownership_as(env: &Env, contract_id: &Address, caller: &Address) -> bool {
+    NeuroWealthVaultClient::new(env, contract_id)
+        .mock_auths(&[MockAuth {
+            address: caller,
+            invoke: &MockAuthInvoke {
+                contract: contract_id,
+                fn_name: "accept_ownership",
+                args: ().into_val(env),
+                sub_invokes: &[],
+            },
+        }])
+        .try_accept_ownership()
+        .is_ok()
+}
+
+#[test]
+fn test_ownership_moves_only_when_pending_owner_accepts() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _agent, owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let new_owner = Address::generate(&env);
+
+    client.transfer_ownership(&new_owner);
+    assert_eq!(client.get_pending_owner(), Some(new_owner.clone()));
+    assert_eq!(client.get_owner(), owner);
+
+    // Nobody else can complete the transfer
+    let stranger = Address::generate(&env);
+    assert!(!accept_ownership_as(&env, &contract_id, &stranger));
+    assert!(!accept_ownership_as(&env, &contract_id, &owner));
+
+    assert!(accept_ownership_as(&env, &contract_id, &new_owner));
+    assert_eq!(client.get_owner(), new_owner);
+    assert_eq!(client.get_pending_owner(), None);
+    assert!(!client.roles_conflated());
+}
+
+#[test]
+fn test_pending_owner_can_be_replaced_before_acceptance() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let mistyped = Address::generate(&env);
+    let intended = Address::generate(&env);
+
+    client.transfer_ownership(&mistyped);
+    client.transfer_ownership(&intended);
+    assert_eq!(client.get_pending_owner(), Some(intended.clone()));
+
+    assert!(!accept_ownership_as(&env, &contract_id, &mistyped));
+    assert!(accept_ownership_as(&env, &contract_id, &intended));
+    assert_eq!(client.get_owner(), intended);
+}
+
+#[test]
+#[should_panic(expected = "No pending owner")]
+fn test_accept_ownership_without_transfer_rejected() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    client.accept_ownership();
+}
+
+#[test]
+#[should_panic(expected = "Address is the vault contract")]
+fn test_transfer_ownership_to_vault_rejected() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    client.transfer_ownership(&contract_id);
+}
+
+#[test]
+#[should_panic(expected = "Guardian must be distinct from owner")]
+fn test_transfer_ownership_to_guardian_rejected() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, NeuroWealthVault);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let agent = Address::generate(&env);
+    let guardian = Address::generate(&env);
+    let recovery_address = Address::generate(&env);
+    client.initialize_with_guardian(
+        &agent,
+        &Address::generate(&env),
+        &guardian,
+        &recovery_address,
+        &86_400,
+    );
+
+    client.transfer_ownership(&guardian);
+}
+
+// ============================================================================
+// ATOMIC REBALANCE AND UPDATE
+// ============================================================================
+
+fn setup_funded_strategy_vault(env: &Env) -> (Address, Address, Address) {
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(env);
+    let client = NeuroWealthVaultClient::new(env, &contract_id);
+    let strategy = Address::generate(env);
+    client.register_strategy(&strategy);
+
+    let user = Address::generate(env);
+    mint_token(env, &usdc_token, &user, 10_000_000);
+    client.deposit(&user, &10_000_000);
+
+    (contract_id, usdc_token, strategy)
+}
+
+#[test]
+fn test_rebalance_and_update_moves_funds_and_reports_assets() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, strategy) = setup_funded_strategy_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let usdc = token::Client::new(&env, &usdc_token);
+
+    client.rebalance_and_update(
+        &symbol_short!("growth"),
+        &850,
+        &strategy,
+        &4_000_000,
+        &10_500_000,
+    );
+
+    assert_eq!(usdc.balance(&strategy), 4_000_000);
+    assert_eq!(client.get_strategy_allocation(&strategy), 4_000_000);
+    assert_eq!(client.get_total_deployed(), 4_000_000);
+    assert_eq!(client.get_total_deposits(), 10_500_000);
+    assert_eq!(client.get_strategy_yield(&strategy), 500_000);
+
+    let mut topics = Vec::new(&env);
+    for (_, event_topics, _) in env.events().all().iter() {
+        let topic: Symbol = event_topics.get(0).unwrap().into_val(&env);
+        topics.push_back(topic);
+    }
+    assert!(topics.contains(symbol_short!("rebal_fnd")));
+    assert!(topics.contains(symbol_short!("rebalance")));
+    assert!(topics.contains(symbol_short!("assets_up")));
+}
+
+#[test]
+fn test_rebalance_and_update_rolls_back_on_rejected_assets_update() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, strategy) = setup_funded_strategy_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let usdc = token::Client::new(&env, &usdc_token);
+    client.set_max_assets_delta_bps(&100);
+
+    let result = client.try_rebalance_and_update(
+        &symbol_short!("growth"),
+        &850,
+        &strategy,
+        &4_000_000,
+        &12_000_000,
+    );
+    assert!(result.is_err());
+
+    // The fund movement is undone along with the report
+    assert_eq!(usdc.balance(&strategy), 0);
+    assert_eq!(usdc.balance(&contract_id), 10_000_000);
+    assert_eq!(client.get_strategy_allocation(&strategy), 0);
+    assert_eq!(client.get_total_deposits(), 10_000_000);
+}
+
+#[test]
+fn test_rebalance_and_update_rolls_back_on_failed_fund_movement() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _usdc_token, strategy) = setup_funded_strategy_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let result = client.try_rebalance_and_update(
+        &symbol_short!("growth"),
+        &850,
+        &strategy,
+        &20_000_000,
+        &10_500_000,
+    );
+    assert!(result.is_err());
+    assert_eq!(client.get_total_deposits(), 10_000_000);
+    assert_eq!(client.get_assets_history().len(), 0);
+}
+
+#[test]
+fn test_rebalance_and_update_needs_owner_when_agent_is_separate() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _usdc_token, strategy) = setup_funded_strategy_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let agent = Address::generate(&env);
+    client.update_agent(&agent);
+
+    let args = (
+        symbol_short!("growth"),
+        850_i128,
+        strategy.clone(),
+        4_000_000_i128,
+        10_500_000_i128,
+    );
+    let result = client
+        .mock_auths(&[MockAuth {
+            address: &agent,
+            invoke: &MockAuthInvoke {
+                contract: &contract_id,
+                fn_name: "rebalance_and_update",
+                args: args.into_val(&env),
+                sub_invokes: &[],
+            },
+        }])
+        .try_rebalance_and_update(
+            &symbol_short!("growth"),
+            &850,
+            &strategy,
+            &4_000_000,
+            &10_500_000,
+        );
+    assert!(result.is_err());
+    assert_eq!(client.get_total_deposits(), 10_000_000);
+
+    // With the owner co-signing, the same call goes through
+    env.mock_all_auths();
+    client.rebalance_and_update(
+        &symbol_short!("growth"),
+        &850,
+        &strategy,
+        &4_000_000,
+        &10_500_000,
+    );
+    assert_eq!(client.get_total_deposits(), 10_500_000);
+}
+
+#[test]
+#[should_panic(expected = "Agent lacks permission")]
+fn test_rebalance_and_update_requires_deploy_permission() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, _usdc_token, strategy) = setup_funded_strategy_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    client.set_agent_permissions(&PERM_REBALANCE);
+
+    client.rebalance_and_update(
+        &symbol_short!("growth"),
+        &850,
+        &strategy,
+        &4_000_000,
+        &10_500_000,
+    );
+}
+
+// ============================================================================
+// WITHDRAWAL QUEUE BOUNDS
+// ============================================================================
+
+#[test]
+#[should_panic(expected = "Withdrawal request too small")]
+fn test_withdrawal_request_below_minimum_rejected() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 2_000_000);
+    client.deposit(&user, &2_000_000);
+    client.request_withdrawal(&user, &(MIN_DEPOSIT - 1));
+}
+
+#[test]
+fn test_withdrawal_queue_length_is_capped() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    client.set_withdrawal_cooldown(&3_600);
+
+    let user = Address::generate(&env);
+    let amount = MIN_DEPOSIT * (MAX_PENDING_REQUESTS as i128 + 2);
+    mint_token(&env, &usdc_token, &user, amount);
+    client.deposit(&user, &amount);
+    for _ in 0..MAX_PENDING_REQUESTS {
+        // Each request is its own transaction with a fresh budget.
+        env.budget().reset_default();
+        client.request_withdrawal(&user, &MIN_DEPOSIT);
+    }
+    env.budget().reset_default();
+    assert!(client.try_request_withdrawal(&user, &MIN_DEPOSIT).is_err());
+
+    // Settling a request frees a slot
+    env.ledger().with_mut(|li| li.timestamp += 3_600);
+    client.claim_withdrawal(&user, &1);
+    client.request_withdrawal(&user, &MIN_DEPOSIT);
+}
+
+// ============================================================================
+// AUTO-COMPOUND TESTS
+// ============================================================================
+
+/// Withdraws 4_000_000 of a 10_000_000 deposit at a 100 bps fee, next to two
+/// other 10_000_000 holders, returning the user's remaining shares, the
+/// payout, another holder's balance and the share price.
+fn withdraw_with_auto_compound(opt_in: bool) -> (i128, i128, i128, i128) {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let usdc = token::Client::new(&env, &usdc_token);
+    client.set_withdrawal_fee_bps(&100);
+
+    let user = Address::generate(&env);
+    let stayer = Address::generate(&env);
+    let other = Address::generate(&env);
+    for depositor in [&user, &stayer, &other] {
+        mint_token(&env, &usdc_token, depositor, 10_000_000);
+        client.deposit(depositor, &10_000_000);
+    }
+    client.set_auto_compound(&user, &opt_in);
+    assert_eq!(client.get_auto_compound(&user), opt_in);
+
+    client.withdraw(&user, &4_000_000);
+    let holders = client.get_shares(&user) + client.get_shares(&stayer) + client.get_shares(&other);
+    assert_eq!(client.get_total_shares(), holders);
+    (
+        client.get_shares(&user),
+        usdc.balance(&user),
+        client.get_balance(&stayer),
+        client.get_share_price(),
+    )
+}
+
+#[test]
+fn test_auto_compound_remints_withdrawal_fee_as_shares() {
+    let (out_shares, out_paid, out_stayer, out_price) = withdraw_with_auto_compound(false);
+    let (in_shares, in_paid, in_stayer, in_price) = withdraw_with_auto_compound(true);
+
+    // Both receive the same USDC; the opted-in user keeps the fee as shares
+    assert_eq!(out_paid, 3_960_000);
+    assert_eq!(in_paid, 3_960_000);
+    assert_eq!(out_shares, 6_000_000);
+    assert_eq!(in_shares, 6_040_000);
+
+    // The opted-out fee accrues to other holders; the opted-in one does not
+    assert!(out_stayer > 10_000_000);
+    assert!(out_price > in_price);
+    assert_eq!(in_stayer, 10_000_000);
+}
+
+#[test]
+fn test_auto_compound_skips_fees_collected() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    client.set_withdrawal_fee_bps(&100);
+
+    let user = Address::generate(&env);
+    let stayer = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 10_000_000);
+    mint_token(&env, &usdc_token, &stayer, 10_000_000);
+    client.deposit(&user, &10_000_000);
+    client.deposit(&stayer, &10_000_000);
+    client.set_auto_compound(&user, &true);
+
+    client.redeem(&user, &4_000_000);
+    assert_eq!(client.get_fees_collected().withdrawal, 0);
+    assert_eq!(withdrawal_fee_events(&env).len(), 0);
+    assert_eq!(client.get_total_deposits(), 16_040_000);
+
+    // Opting out restores the normal fee
+    client.set_auto_compound(&user, &false);
+    assert!(!client.get_auto_compound(&user));
+    client.redeem(&user, &1_000_000);
+    assert_eq!(withdrawal_fee_events(&env).len(), 1);
+}
+
+// ============================================================================
+// DEPOSIT ENTRY POINTS
+// ============================================================================
+
+#[test]
+fn test_every_deposit_path_rebalances_reserve() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let usdc = token::Client::new(&env, &usdc_token);
+    let market = setup_money_market(&env, &usdc_token);
+    client.set_reserve_buffer_bps(&2_000);
+    client.set_reserve_strategy(&Some(market.clone()));
+    // 1 XLM = 1 USDC
+    let native_token = setup_native_deposits(&env, &contract_id, &usdc_token, 10_000_000);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 10_000_000);
+    mint_token(&env, &native_token, &user, 10_000_000);
+
+    client.mint(&user, &10_000_000);
+    assert_eq!(usdc.balance(&contract_id), 2_000_000);
+    assert_eq!(client.get_reserve_deployed(), 8_000_000);
+
+    client.deposit_native(&user, &10_000_000, &10_000_000);
+    assert_eq!(usdc.balance(&contract_id), 4_000_000);
+    assert_eq!(client.get_reserve_deployed(), 16_000_000);
+    assert_eq!(client.get_shares(&user), 20_000_000);
+    assert!(client.proof_of_reserves().reconciled);
+}
+
+// ============================================================================
+// QUEUED CLAIM LIMIT TESTS
+// ============================================================================
+
+#[test]
+fn test_queued_claims_respect_withdraw_cap() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    client.set_withdrawal_cooldown(&0);
+    client.set_max_withdraw_per_tx(&2_000_000);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 10_000_000);
+    client.deposit(&user, &10_000_000);
+
+    assert!(client.try_request_withdrawal(&user, &3_000_000).is_err());
+    let large = client.request_withdrawal(&user, &2_000_000);
+    let small = client.request_withdrawal(&user, &1_000_000);
+
+    // Lowering the cap strands the large request without blocking the small one
+    client.set_max_withdraw_per_tx(&1_000_000);
+    assert!(client.try_claim_withdrawal(&user, &large).is_err());
+    let processed = client.process_withdrawals(&10);
+    assert_eq!(processed, Vec::from_array(&env, [small]));
+    assert_eq!(client.get_withdrawal_request(&large).shares, 2_000_000);
+
+    client.set_max_withdraw_per_tx(&2_000_000);
+    assert_eq!(client.claim_withdrawal(&user, &large), 2_000_000);
+}
+
+#[test]
+fn test_queued_claims_respect_pause_grace() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, owner) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    client.set_withdrawal_cooldown(&0);
+    client.set_pause_grace_seconds(&600);
+
+    let early = Address::generate(&env);
+    let late = Address::generate(&env);
+    mint_token(&env, &usdc_token, &early, 10_000_000);
+    mint_token(&env, &usdc_token, &late, 10_000_000);
+    env.ledger().with_mut(|l| l.timestamp = 1_000);
+    client.deposit(&early, &10_000_000);
+    let early_request = client.request_withdrawal(&early, &2_000_000);
+    env.ledger().with_mut(|l| l.timestamp = 5_000);
+    client.deposit(&late, &10_000_000);
+    let late_request = client.request_withdrawal(&late, &2_000_000);
+
+    // Only the depositor still inside the grace can claim while paused
+    client.pause(&owner);
+    assert_eq!(client.claim_withdrawal(&late, &late_request), 2_000_000);
+    assert!(client.try_claim_withdrawal(&early, &early_request).is_err());
+}
+
+// ============================================================================
+// EXIT PATH TESTS
+// ============================================================================
+
+#[test]
+fn test_every_exit_path_rebalances_reserve() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let usdc = token::Client::new(&env, &usdc_token);
+    let market = setup_money_market(&env, &usdc_token);
+    client.set_reserve_buffer_bps(&2_000);
+    client.set_reserve_strategy(&Some(market.clone()));
+    client.set_withdrawal_cooldown(&0);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 20_000_000);
+    client.deposit(&user, &20_000_000);
+    assert_eq!(client.get_reserve_deployed(), 16_000_000);
+
+    client.redeem(&user, &5_000_000);
+    assert_eq!(usdc.balance(&contract_id), 3_000_000);
+    assert_eq!(client.get_reserve_deployed(), 12_000_000);
+
+    let request = client.request_withdrawal(&user, &5_000_000);
+    client.claim_withdrawal(&user, &request);
+    assert_eq!(usdc.balance(&contract_id), 2_000_000);
+    assert_eq!(client.get_reserve_deployed(), 8_000_000);
+    assert_eq!(usdc.balance(&user), 10_000_000);
+    assert!(client.proof_of_reserves().reconciled);
+}
+
+#[test]
+fn test_auto_compound_full_exit_pays_fee_in_usdc() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let usdc = token::Client::new(&env, &usdc_token);
+    client.set_withdrawal_fee_bps(&100);
+    client.set_withdrawal_cooldown(&0);
+
+    let user = Address::generate(&env);
+    let stayer = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 10_000_000);
+    mint_token(&env, &usdc_token, &stayer, 10_000_000);
+    client.deposit(&user, &10_000_000);
+    client.deposit(&stayer, &10_000_000);
+    client.set_auto_compound(&user, &true);
+
+    // A partial exit compounds the fee and protects it as principal
+    client.redeem(&user, &4_000_000);
+    assert_eq!(client.get_shares(&user), 6_040_000);
+    assert_eq!(client.get_protected_principal(&user), 6_040_000);
+
+    // Exiting the rest leaves no dust shares behind
+    let request = client.request_withdrawal(&user, &6_040_000);
+    assert_eq!(client.claim_withdrawal(&user, &request), 6_040_000);
+    assert_eq!(client.get_shares(&user), 0);
+    assert_eq!(usdc.balance(&user), 10_000_000);
+    assert_eq!(client.get_total_shares(), client.get_shares(&stayer));
+}
+
+// ============================================================================
+// MANAGEMENT FEE ACCRUAL TESTS
+// ============================================================================
+
+#[test]
+fn test_management_fee_accrues_on_deposit_and_withdraw() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, owner) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let late = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 10_000_000);
+    mint_token(&env, &usdc_token, &late, 10_000_000);
+    client.deposit(&user, &10_000_000);
+    client.set_management_fee_bps(&200);
+
+    // buys in at the diluted price and does not pay for the past year, less
+    // rounding
+    // buys in at the diluted price and does not pay for the past year
+    env.ledger().with_mut(|li| li.timestamp += SECONDS_PER_YEAR);
+    client.deposit(&late, &10_000_000);
+    assert_eq!(client.get_shares(&owner), 204_077);
+    assert_eq!(client.get_fees_collected().management, 200_000);
+    assert_eq!(client.get_balance(&late), 9_999_999);
+
+    // A withdrawal settles the fee owed since the deposit
+    env.ledger().with_mut(|li| li.timestamp += SECONDS_PER_YEAR);
+    client.withdraw(&user, &1_000_000);
+    assert_eq!(client.get_fees_collected().management, 600_000);
+    assert_eq!(fee_accrued_events(&env).len(), 2);
+}
+
+// ============================================================================
+// PERFORMANCE FEE AND WITHDRAWAL FEE TESTS
+// ============================================================================
+
+#[test]
+fn test_withdrawal_fees_never_mint_performance_fee_shares() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _owner) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let recipient = Address::generate(&env);
+    client.set_fee_recipient(&Some(recipient.clone()));
+    client.set_performance_fee_bps(&2_000);
+    client.set_withdrawal_fee_bps(&100);
+
+    let users = [
+        Address::generate(&env),
+        Address::generate(&env),
+        Address::generate(&env),
+    ];
+    for user in users.iter() {
+        mint_token(&env, &usdc_token, user, 10_000_000);
+        client.deposit(user, &10_000_000);
+    }
+    for user in users.iter() {
+        client.withdraw(user, &3_000_000);
+        client.redeem(user, &2_000_000);
+    }
+    assert!(client.get_share_price() > SHARE_PRICE_SCALE);
+    assert!(client.get_fees_collected().withdrawal > 0);
+
+    // Reporting unchanged assets settles fees on the retained withdrawal
+    // fees alone, which owe nothing
+    client.update_total_assets(&client.get_total_deposits());
+    assert_eq!(client.get_shares(&recipient), 0);
+    assert_eq!(client.get_fees_collected().performance, 0);
+    assert_eq!(client.get_high_water_mark(), client.get_share_price());
+}
+
+// ============================================================================
+// AIRDROP ESCROW TESTS
+// ============================================================================
+
+#[test]
+fn test_airdrop_splits_whole_round_when_shares_are_queued() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let reward_token = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+
+    let holder = Address::generate(&env);
+    let leaver = Address::generate(&env);
+    mint_token(&env, &usdc_token, &holder, 3_000_000);
+    mint_token(&env, &usdc_token, &leaver, 1_000_000);
+    client.deposit(&holder, &3_000_000);
+    client.deposit(&leaver, &1_000_000);
+    client.request_withdrawal(&leaver, &1_000_000);
+
+    // The escrowed shares are not owed a portion, so none of it is stranded
+    mint_token(&env, &reward_token, &contract_id, 1_000);
+    client.distribute_airdrop(&reward_token, &1_000);
+    assert_eq!(client.get_claimable_airdrop(&leaver, &reward_token), 0);
+    assert_eq!(client.claim_airdrop(&holder, &reward_token), 1_000);
+}
+
+// ============================================================================
+// MULTI-SIG COVERAGE TESTS
+// ============================================================================
+
+#[test]
+fn test_removed_signer_approval_no_longer_counts() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let signers = setup_multisig(&env, &client);
+
+    let new_agent = Address::generate(&env);
+    let hash = client.get_action_hash(&symbol_short!("agent"), &new_agent.into_val(&env));
+    client.approve_action(&signers.get(0).unwrap(), &hash);
+    assert_eq!(client.get_approvals(&hash), 1);
+
+    // Rotate the first signer out
+    let rotated = Vec::from_array(
+        &env,
+        [
+            signers.get(1).unwrap(),
+            signers.get(2).unwrap(),
+            Address::generate(&env),
+        ],
+    );
+    let rotate = client.get_action_hash(
+        &symbol_short!("owners"),
+        &(rotated.clone(), 2_u32).into_val(&env),
+    );
+    client.approve_action(&signers.get(1).unwrap(), &rotate);
+    client.approve_action(&signers.get(2).unwrap(), &rotate);
+    client.set_owners(&rotated, &2);
+    assert_eq!(client.get_approvals(&hash), 0);
+
+    // One remaining signer is no longer enough together with the removed one
+    client.approve_action(&rotated.get(0).unwrap(), &hash);
+    assert_eq!(client.get_approvals(&hash), 1);
+    assert!(client.try_update_agent(&new_agent).is_err());
+    assert_eq!(client.get_agent(), agent);
+
+    client.approve_action(&rotated.get(2).unwrap(), &hash);
+    client.update_agent(&new_agent);
+    assert_eq!(client.get_agent(), new_agent);
+}
+
+#[test]
+fn test_multisig_gates_fee_strategy_and_assets_actions() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 10_000_000);
+    client.deposit(&user, &10_000_000);
+    let signers = setup_multisig(&env, &client);
+    let approve = |action: Symbol, args: Val| {
+        let hash = client.get_action_hash(&action, &args);
+        client.approve_action(&signers.get(0).unwrap(), &hash);
+        client.approve_action(&signers.get(1).unwrap(), &hash);
+    };
+
+    let recipient = Address::generate(&env);
+    let strategy = Address::generate(&env);
+    assert!(client.try_update_total_assets(&11_000_000).is_err());
+    assert!(client.try_set_withdrawal_fee_bps(&50).is_err());
+    assert!(client.try_set_management_fee_bps(&100).is_err());
+    assert!(client.try_set_performance_fee_bps(&1_000).is_err());
+    assert!(client.try_set_fee_holiday(&1_000).is_err());
+    assert!(client
+        .try_set_fee_recipient(&Some(recipient.clone()))
+        .is_err());
+    assert!(client.try_register_strategy(&strategy).is_err());
+    assert!(client.try_set_reserve_strategy(&None).is_err());
+
+    approve(symbol_short!("assets"), 11_000_000_i128.into_val(&env));
+    client.update_total_assets(&11_000_000);
+    assert_eq!(client.get_total_deposits(), 11_000_000);
+
+    approve(symbol_short!("wd_fee"), 50_i128.into_val(&env));
+    client.set_withdrawal_fee_bps(&50);
+    assert_eq!(client.get_withdrawal_fee_bps(), 50);
+
+    let recipient_arg = Some(recipient.clone());
+    approve(symbol_short!("fee_rcpt"), recipient_arg.into_val(&env));
+    client.set_fee_recipient(&Some(recipient.clone()));
+    assert_eq!(client.get_fee_recipient(), recipient);
+
+    approve(symbol_short!("strategy"), strategy.into_val(&env));
+    client.register_strategy(&strategy);
+    assert!(client.get_strategies().contains(&strategy));
+}
+
+// ============================================================================
+// ADDRESS CHANGELOG TESTS
+// ============================================================================
+
+#[test]
+fn test_config_history_identifies_old_and_new_addresses() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, agent, owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let recipient = Address::generate(&env);
+    let new_agent = Address::generate(&env);
+    let new_owner = Address::generate(&env);
+    client.set_fee_recipient(&Some(recipient.clone()));
+    client.set_fee_recipient(&None);
+    client.update_agent(&new_agent);
+    client.transfer_ownership(&new_owner);
+    client.accept_ownership();
+
+    let history = client.get_config_history(&10);
+    assert_eq!(history.len(), 4);
+    let expected = [
+        (symbol_short!("fee_rcpt"), None, Some(recipient.clone())),
+        (symbol_short!("fee_rcpt"), Some(recipient), None),
+        (symbol_short!("agent"), Some(agent), Some(new_agent)),
+        (symbol_short!("owner"), Some(owner), Some(new_owner)),
+    ];
+    for (change, (key, old_address, new_address)) in history.iter().zip(expected) {
+        assert_eq!(change.key, key);
+        assert_eq!(change.old_address, old_address);
+        assert_eq!(change.new_address, new_address);
+    }
+}
+
+// ============================================================================
+// STORED VS ACCRUED TOTAL ASSETS TESTS
+// ============================================================================
+
+#[test]
+fn test_accrued_total_assets_deduct_unminted_management_fee() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _owner) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 10_000_000);
+    client.deposit(&user, &10_000_000);
+    client.set_management_fee_bps(&200);
+    assert_eq!(client.get_stored_total_assets(), 10_000_000);
+    assert_eq!(client.get_total_assets(), 10_000_000);
+
+    // Half a year at 2% accrues 0.1 USDC of fee that is not yet minted
+    env.ledger()
+        .with_mut(|li| li.timestamp += SECONDS_PER_YEAR / 2);
+    assert_eq!(client.get_stored_total_assets(), 10_000_000);
+    assert_eq!(client.get_total_assets(), 9_900_000);
+
+    // An explicit update mints the fee and the two agree again
+    client.update_total_assets(&10_000_000);
+    assert_eq!(client.get_stored_total_assets(), 10_000_000);
+    assert_eq!(client.get_total_assets(), 10_000_000);
+    assert_eq!(client.get_fees_collected().management, 100_000);
+}
+
+// ============================================================================
+// POSITION LAYOUT MIGRATION TESTS
+// ============================================================================
+
+/// Rewrites the depositor index the way a vault that predates versioned
+/// positions stored it, listing `extra` after the live depositors.
+fn downgrade_position_layout(env: &Env, contract_id: &Address, extra: &[Address]) {
+    env.as_contract(contract_id, || {
+        let storage = env.storage().persistent();
+        let key = PositionKey::DepositorIndex(POSITION_LAYOUT_VERSION);
+        let mut depositors: Vec<Address> = storage.get(&key).unwrap();
+        for user in extra {
+            depositors.push_back(user.clone());
+        }
+        storage.remove(&key);
+        storage.remove(&PositionKey::PositionLayout);
+        storage.set(&DataKey::Depositors, &depositors);
+    });
+}
+
+#[test]
+fn test_migrate_positions_reindexes_legacy_layout() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    assert_eq!(client.get_position_layout(), POSITION_LAYOUT_VERSION);
+
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    let gone = Address::generate(&env);
+    for user in [&alice, &bob] {
+        mint_token(&env, &usdc_token, user, 2_000_000);
+        client.deposit(user, &2_000_000);
+    }
+
+    // The old layout is still read, stale and duplicate entries included
+    downgrade_position_layout(&env, &contract_id, &[gone, alice.clone()]);
+    assert_eq!(client.get_position_layout(), 0);
+    assert_eq!(client.get_depositors().len(), 4);
+
+    assert_eq!(client.migrate_positions(), 2);
+    assert_eq!(client.get_position_layout(), POSITION_LAYOUT_VERSION);
+    assert_eq!(
+        client.get_depositors(),
+        Vec::from_array(&env, [alice.clone(), bob.clone()])
+    );
+    assert_eq!(client.get_depositor_count(), 2);
+    assert_eq!(client.get_largest_holder(), (alice.clone(), 2_000_000));
+    env.as_contract(&contract_id, || {
+        assert!(!env.storage().persistent().has(&DataKey::Depositors));
+    });
+
+    // Positions keep updating under the new layout
+    client.withdraw(&alice, &2_000_000);
+    assert_eq!(
+        client.get_depositors(),
+        Vec::from_array(&env, [bob.clone()])
+    );
+    assert_eq!(client.get_depositor_count(), 1);
+}
+
+#[test]
+fn test_migrate_positions_is_idempotent() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 2_000_000);
+    client.deposit(&user, &2_000_000);
+    downgrade_position_layout(&env, &contract_id, &[]);
+
+    assert_eq!(client.migrate_positions(), 1);
+    let history_len = client.get_config_history(&100).len();
+    assert_eq!(client.migrate_positions(), 1);
+    assert_eq!(client.get_config_history(&100).len(), history_len);
+    assert_eq!(client.get_depositors(), Vec::from_array(&env, [user]));
+    assert_eq!(client.get_depositor_count(), 1);
+}
+
+#[test]
+fn test_migrate_positions_requires_owner() {
+    let env = Env::default();
+
+    let (contract_id, _agent, _owner) = setup_vault(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+    assert!(client.try_migrate_positions().is_err());
+}
+
+// ============================================================================
+// REFERRED DEPOSIT EVENT TESTS
+// ============================================================================
+
+#[test]
+fn test_plain_deposit_emits_slim_event() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 2_000_000);
+    client.deposit(&user, &2_000_000);
+
+    let slim = (symbol_short!("deposit"),).into_val(&env);
+    let rich = (symbol_short!("deposit"), symbol_short!("referral")).into_val(&env);
+    assert_eq!(count_events_with_topics(&env, &contract_id, slim), 1);
+    assert_eq!(count_events_with_topics(&env, &contract_id, rich), 0);
+}
+
+#[test]
+fn test_referred_deposit_emits_rich_event() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let referrer = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 2_000_000);
+    client.deposit_with_referral(&user, &2_000_000, &referrer);
+
+    let slim: Vec<soroban_sdk::Val> = (symbol_short!("deposit"),).into_val(&env);
+    let rich: Vec<soroban_sdk::Val> =
+        (symbol_short!("deposit"), symbol_short!("referral")).into_val(&env);
+    assert_eq!(count_events_with_topics(&env, &contract_id, slim), 0);
+    assert_eq!(
+        count_events_with_topics(&env, &contract_id, rich.clone()),
+        1
+    );
+
+    let (_, _, data) = env
+        .events()
+        .all()
+        .iter()
+        .find(|(_, topics, _)| *topics == rich)
+        .unwrap();
+    let event: ReferredDepositEvent = data.into_val(&env);
+    assert_eq!(event.user, user);
+    assert_eq!(event.amount, 2_000_000);
+    assert_eq!(event.shares, client.get_shares(&user));
+    assert_eq!(event.referrer, referrer);
+    assert_eq!(client.get_balance(&user), 2_000_000);
+}
+
+#[test]
+#[should_panic(expected = "Cannot refer yourself")]
+fn test_deposit_with_referral_rejects_self_referral() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (contract_id, usdc_token, _agent) = setup_vault_with_usdc(&env);
+    let client = NeuroWealthVaultClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    mint_token(&env, &usdc_token, &user, 2_000_000);
+    client.deposit_with_referral(&user, &2_000_000, &user);
+}